@@ -1,7 +1,7 @@
 use std::num::Bounded;
 use std::rc::Rc;
 use std::cell::RefCell;
-use nalgebra::na::Translation;
+use nalgebra::na::{Translation, Transform, AbsoluteRotate};
 use nalgebra::na;
 use bounding_volume::{HasBoundingVolume, BoundingVolume, LooseBoundingVolume};
 use math::{Scalar, Vect, Matrix};
@@ -63,6 +63,15 @@ impl AABB {
         &'a self.maxs
     }
 
+    /// The AABB of this AABB transformed by `m`.
+    #[inline]
+    pub fn transformed(&self, m: &Matrix) -> AABB {
+        let _0_5: Scalar = na::cast(0.5);
+        let center       = m.transform(&((self.mins + self.maxs) * _0_5));
+        let half_extents = m.absolute_rotate(&((self.maxs - self.mins) * _0_5));
+
+        AABB::new(center - half_extents, center + half_extents)
+    }
 }
 
 impl BoundingVolume for AABB {
@@ -0,0 +1,27 @@
+use nalgebra::na::{Translation, Transform, AbsoluteRotate};
+use nalgebra::na;
+use bounding_volume::{AABB, HasAABB};
+use geom::TetMesh;
+use math::{Scalar, Matrix};
+
+impl HasAABB for TetMesh {
+    #[inline]
+    fn aabb(&self, m: &Matrix) -> AABB {
+        match self.bvt().root_bounding_volume() {
+            Some(bv) => {
+                let ls_center       = bv.translation();
+                let center          = m.transform(&ls_center);
+                let half_extents    = (bv.maxs() - *bv.mins()) / na::cast::<f64, Scalar>(2.0);
+                let ws_half_extents = m.absolute_rotate(&half_extents);
+
+                AABB::new(center - ws_half_extents, center + ws_half_extents)
+            },
+            None => {
+                // a mesh without tetrahedra is reduced to its origin.
+                let center = m.translation();
+
+                AABB::new(center.clone(), center)
+            }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use bounding_volume::{HasAABB, AABB};
+use bounding_volume;
+use geom::Tetrahedron;
+use math::Matrix;
+
+impl HasAABB for Tetrahedron {
+    fn aabb(&self, m: &Matrix) -> AABB {
+        // FIXME: optimize that
+        bounding_volume::implicit_shape_aabb(m, self)
+    }
+}
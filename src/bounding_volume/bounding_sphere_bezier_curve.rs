@@ -0,0 +1,13 @@
+use nalgebra::na::Transform;
+use bounding_volume::{BoundingSphere, HasBoundingSphere};
+use bounding_volume;
+use geom::BezierCurve;
+use math::Matrix;
+
+impl HasBoundingSphere for BezierCurve {
+    fn bounding_sphere(&self, m: &Matrix) -> BoundingSphere {
+        let (center, radius) = bounding_volume::bounding_sphere(self.control_points());
+
+        BoundingSphere::new(m.transform(&center), radius)
+    }
+}
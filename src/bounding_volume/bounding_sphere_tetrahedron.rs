@@ -0,0 +1,14 @@
+use nalgebra::na::Transform;
+use bounding_volume::{BoundingSphere, HasBoundingSphere};
+use bounding_volume;
+use geom::Tetrahedron;
+use math::Matrix;
+
+impl HasBoundingSphere for Tetrahedron {
+    fn bounding_sphere(&self, m: &Matrix) -> BoundingSphere {
+        let pts = [ self.a().clone(), self.b().clone(), self.c().clone(), self.d().clone() ];
+        let (center, radius) = bounding_volume::bounding_sphere(pts.as_slice());
+
+        BoundingSphere::new(m.transform(&center), radius + self.margin())
+    }
+}
@@ -26,9 +26,13 @@ mod aabb_plane;
 mod aabb_convex;
 mod aabb_compound;
 mod aabb_triangle;
+mod aabb_tetrahedron;
 mod aabb_segment;
 mod aabb_mesh;
+#[cfg(dim3)]
+mod aabb_tet_mesh;
 mod aabb_bezier_surface;
+mod aabb_bezier_curve;
 mod aabb_utils;
 
 mod bounding_sphere;
@@ -41,7 +45,11 @@ mod bounding_sphere_plane;
 mod bounding_sphere_convex;
 mod bounding_sphere_compound;
 mod bounding_sphere_triangle;
+mod bounding_sphere_tetrahedron;
 mod bounding_sphere_segment;
 mod bounding_sphere_mesh;
+#[cfg(dim3)]
+mod bounding_sphere_tet_mesh;
 mod bounding_sphere_bezier_surface;
+mod bounding_sphere_bezier_curve;
 mod bounding_sphere_utils;
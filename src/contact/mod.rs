@@ -1,7 +1,40 @@
 //! Main data structure for contacts.
 
 use std::mem;
-use math::{Scalar, Vect};
+use std::num::{Zero, ToPrimitive};
+use nalgebra::na::{Transform, Indexable};
+use nalgebra::na;
+use util::hash;
+use math::{Scalar, Vect, Matrix};
+
+/// Identifier of a feature of a geometry.
+///
+/// The meaning of the index depends on the geometry. For concave geometries, it identifies a
+/// feature of the part given by `Contact::part1` or `Contact::part2`.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub enum FeatureId {
+    /// The feature is not known.
+    UnknownFeature,
+    /// The i-th vertex of the geometry.
+    VertexFeature(uint),
+    /// The i-th edge of the geometry.
+    EdgeFeature(uint),
+    /// The i-th face of the geometry.
+    FaceFeature(uint)
+}
+
+impl FeatureId {
+    /// Encodes this feature identifier into a single integer.
+    #[inline]
+    pub fn key(&self) -> uint {
+        match *self {
+            UnknownFeature   => 0,
+            VertexFeature(i) => i * 4 + 1,
+            EdgeFeature(i)   => i * 4 + 2,
+            FaceFeature(i)   => i * 4 + 3
+        }
+    }
+}
 
 /// Geometric description of a contact.
 #[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
@@ -16,26 +49,165 @@ pub struct Contact {
     pub normal: Vect,
 
     /// Penetration depth
-    pub depth:  Scalar
+    pub depth:  Scalar,
+
+    /// Feature of the first object the contact lies on.
+    pub feature1: FeatureId,
+
+    /// Feature of the second object the contact lies on.
+    pub feature2: FeatureId,
+
+    /// Index of the part of the first object the contact lies on, if it is a concave geometry.
+    pub part1: Option<uint>,
+
+    /// Index of the part of the second object the contact lies on, if it is a concave geometry.
+    pub part2: Option<uint>,
+
+    /// Identifier of the contact.
+    ///
+    /// It remains the same as long as the contact is generated by the same features or, for
+    /// contact manifold generators keeping track of the contacts, as long as the contact persists.
+    pub id: uint
 }
 
 impl Contact {
-    /// Creates a new contact.
+    /// Creates a new contact with unknown features.
     #[inline]
     pub fn new(world1: Vect, world2: Vect, normal: Vect, depth: Scalar) -> Contact {
+        Contact::new_with_features(world1, world2, normal, depth, UnknownFeature, UnknownFeature)
+    }
+
+    /// Creates a new contact between two features.
+    ///
+    /// The contact identifier is deduced from the features.
+    #[inline]
+    pub fn new_with_features(world1:   Vect,
+                             world2:   Vect,
+                             normal:   Vect,
+                             depth:    Scalar,
+                             feature1: FeatureId,
+                             feature2: FeatureId)
+                             -> Contact {
+        let id = hash::key_from_pair(feature1.key(), feature2.key());
+
         Contact {
-            world1: world1,
-            world2: world2,
-            normal: normal,
-            depth:  depth
+            world1:   world1,
+            world2:   world2,
+            normal:   normal,
+            depth:    depth,
+            feature1: feature1,
+            feature2: feature2,
+            part1:    None,
+            part2:    None,
+            id:       id
         }
     }
 }
 
 impl Contact {
-    /// Reverts the contact normal and swaps `world1` and `world2`.
+    /// Reverts the contact normal and swaps the informations relative to each object.
+    ///
+    /// The contact identifier is left unchanged.
     pub fn flip(&mut self) {
         mem::swap(&mut self.world1, &mut self.world2);
+        mem::swap(&mut self.feature1, &mut self.feature2);
+        mem::swap(&mut self.part1, &mut self.part2);
         self.normal = -self.normal;
     }
+
+    /// Sets the features this contact lies on, and updates the contact identifier accordingly.
+    pub fn set_features(&mut self, feature1: FeatureId, feature2: FeatureId) {
+        self.id       = hash::key_from_pair(feature1.key(), feature2.key());
+        self.feature1 = feature1;
+        self.feature2 = feature2;
+    }
+
+    /// Sets the contact identifier from the position of one of its points.
+    ///
+    /// This is used by collision detectors which do not know the features the contact lies on.
+    /// The point must be expressed in the local space of the geometry it lies on, and is quantized
+    /// on a grid with 64 cells per `scale`, where `scale` is the size of this geometry. Thus, the
+    /// identifier stays the same as long as the contact does not move too much on the geometry.
+    pub fn set_id_from_point(&mut self, pt: &Vect, scale: &Scalar) {
+        let cell    = *scale / na::cast(64.0f64);
+        let mut key = 0u;
+
+        for i in range(0u, na::dim::<Vect>()) {
+            let q = if cell.is_zero() { 0 } else { (pt.at(i) / cell).floor().to_i64().unwrap_or(0) };
+
+            key = hash::tomas_wang_hash(hash::key_from_pair(key, q as uint));
+        }
+
+        self.id = key;
+    }
+
+    /// Sets the part of one of the objects this contact lies on, and updates the contact
+    /// identifier accordingly.
+    ///
+    /// This is used by collision detectors involving concave geometries.
+    pub fn set_part(&mut self, first: bool, part: uint) {
+        if first {
+            self.part1 = Some(part);
+        }
+        else {
+            self.part2 = Some(part);
+        }
+
+        self.id = hash::tomas_wang_hash(hash::key_from_pair(part, self.id));
+    }
+}
+
+/// A contact together with its local-space points.
+///
+/// This contains what contact solvers usually need to compute friction anchors. The tangent basis
+/// is computed on demand by `tangents` so that no memory is allocated per contact.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct ContactWLocals {
+    /// Position of the contact on the first object, expressed in its local space.
+    pub local1:  Vect,
+
+    /// Position of the contact on the second object, expressed in its local space.
+    pub local2:  Vect,
+
+    /// The contact itself, with its world-space data.
+    pub contact: Contact
+}
+
+impl ContactWLocals {
+    /// Extends a contact between two objects with the transforms `m1` and `m2`.
+    pub fn new_with_contact(contact: Contact, m1: &Matrix, m2: &Matrix) -> ContactWLocals {
+        ContactWLocals {
+            local1:  m1.inv_transform(&contact.world1),
+            local2:  m2.inv_transform(&contact.world2),
+            contact: contact
+        }
+    }
+
+    /// Applies `f` to each vector of an orthonormal basis of the space orthogonal to the contact
+    /// normal.
+    ///
+    /// The iteration stops as soon as `f` returns `false`. The basis depends only on the normal so
+    /// it is the same for every call.
+    #[inline]
+    pub fn tangents(&self, f: |Vect| -> bool) {
+        na::orthonormal_subspace_basis(&self.contact.normal, f)
+    }
+
+    /// Index of the part of the first object the contact lies on, if it is a concave geometry.
+    #[inline]
+    pub fn part1(&self) -> Option<uint> {
+        self.contact.part1
+    }
+
+    /// Index of the part of the second object the contact lies on, if it is a concave geometry.
+    #[inline]
+    pub fn part2(&self) -> Option<uint> {
+        self.contact.part2
+    }
+
+    /// The point halfway between the contact points on both objects, in world space.
+    #[inline]
+    pub fn center(&self) -> Vect {
+        (self.contact.world1 + self.contact.world2) * na::cast::<f32, Scalar>(0.5)
+    }
 }
@@ -0,0 +1,105 @@
+//! Capabilities of the geometries supported by `ncollide`.
+
+use geom::{GeomCapabilities, GeomCapability, ImplicitCapability, PlaneCapability,
+           ConcaveCapability, Ball, Plane, Cuboid, Capsule, Cone, Cylinder, Convex,
+           Compound, Mesh, Segment, Triangle, Tetrahedron, BezierSurface, BezierCurve};
+use narrow::concave_geom_any_detector;
+
+#[cfg(dim3)]
+use geom::TetMesh;
+
+impl GeomCapabilities for Ball {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ImplicitCapability(self)
+    }
+}
+
+impl GeomCapabilities for Cuboid {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ImplicitCapability(self)
+    }
+}
+
+impl GeomCapabilities for Capsule {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ImplicitCapability(self)
+    }
+}
+
+impl GeomCapabilities for Cone {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ImplicitCapability(self)
+    }
+}
+
+impl GeomCapabilities for Cylinder {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ImplicitCapability(self)
+    }
+}
+
+impl GeomCapabilities for Convex {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ImplicitCapability(self)
+    }
+}
+
+impl GeomCapabilities for Segment {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ImplicitCapability(self)
+    }
+}
+
+impl GeomCapabilities for Triangle {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ImplicitCapability(self)
+    }
+}
+
+impl GeomCapabilities for Tetrahedron {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ImplicitCapability(self)
+    }
+}
+
+impl GeomCapabilities for Plane {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        PlaneCapability(self)
+    }
+}
+
+impl GeomCapabilities for Compound {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ConcaveCapability(concave_geom_any_detector::<Compound>)
+    }
+}
+
+impl GeomCapabilities for Mesh {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ConcaveCapability(concave_geom_any_detector::<Mesh>)
+    }
+}
+
+#[cfg(dim3)]
+impl GeomCapabilities for TetMesh {
+    #[inline]
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        ConcaveCapability(concave_geom_any_detector::<TetMesh>)
+    }
+}
+
+impl GeomCapabilities for BezierSurface { }
+
+impl GeomCapabilities for BezierCurve { }
@@ -106,4 +106,9 @@ impl ConcaveGeom for Compound {
     fn aabb_at<'a>(&'a self, i: uint) -> &'a AABB {
         self.bvs.get(i)
     }
+
+    #[inline]
+    fn bvt<'a>(&'a self) -> Option<&'a BVT<uint, AABB>> {
+        Some(&'a self.bvt)
+    }
 }
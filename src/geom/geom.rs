@@ -7,6 +7,10 @@ use std::any::{Any, AnyRefExt};
 use ray::{Ray, RayCast};
 use volumetric::Volumetric;
 use bounding_volume::{HasBoundingSphere, HasAABB, AABB};
+use partitioning::BVT;
+use implicit::ImplicitGeom;
+use narrow::GeomGeomCollisionDetector;
+use geom::Plane;
 use math::Matrix;
 
 /// Trait (that should be) implemented by each geometry supported by `ncollide`.
@@ -14,6 +18,7 @@ pub trait Geom : Volumetric        +
                  HasAABB           +
                  HasBoundingSphere +
                  RayCast           +
+                 GeomCapabilities  +
                  Any {
     /// Duplicates (clones) this geometry.
     fn duplicate(&self) -> Box<Geom:Send>;
@@ -38,9 +43,44 @@ pub trait ConcaveGeom : Geom {
     // FIXME: kind of ad-hoc…
     /// Gets the AABB of the geometry identified by the index `i`.
     fn aabb_at<'a>(&'a self, i: uint) -> &'a AABB;
+    /// The acceleration structure containing the local-space AABB of each sub-geometry, if any.
+    ///
+    /// This is used by the queries traversing the parts of a concave geometry (distance,
+    /// concave-concave collision detection). By default, there is none and those queries fall
+    /// back to `approx_interferences_with_aabb`.
+    fn bvt<'a>(&'a self) -> Option<&'a BVT<uint, AABB>> {
+        None
+    }
+}
+
+/// The capability of a geometry, i.e., the kind of generic algorithm able to handle it.
+pub enum GeomCapability<'a> {
+    /// The geometry has a support mapping function.
+    ImplicitCapability(&'a ImplicitGeom),
+    /// The geometry behaves like a plane.
+    PlaneCapability(&'a Plane),
+    /// The geometry is concave. The function builds a collision detector between this geometry
+    /// and any other one. Its argument is `true` if this geometry is the second one of the pair.
+    ConcaveCapability(fn(bool) -> Box<GeomGeomCollisionDetector>),
+    /// The geometry cannot be handled by any generic algorithm.
+    NoCapability
+}
+
+/// Trait implemented by geometries to advertise their capability.
+///
+/// This is used by the `GeomGeomDispatcher` to build a generic collision detector for pairs of
+/// geometries which do not have a registered one.
+pub trait GeomCapabilities {
+    /// The capability of this geometry.
+    ///
+    /// By default, the geometry cannot be handled by any generic algorithm.
+    fn capability<'a>(&'a self) -> GeomCapability<'a> {
+        NoCapability
+    }
 }
 
-impl<T: 'static + Send + Clone + Volumetric + HasAABB + HasBoundingSphere + RayCast + Any>
+impl<T: 'static + Send + Clone + Volumetric + HasAABB + HasBoundingSphere + RayCast +
+        GeomCapabilities + Any>
 Geom for T {
     fn duplicate(&self) -> Box<Geom:Send> {
         (box self.clone()) as Box<Geom:Send>
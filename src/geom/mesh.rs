@@ -16,8 +16,8 @@ use math::{Scalar, Vect, Matrix};
 use geom::Segment;
 #[cfg(dim3)]
 use geom::Triangle;
-#[cfg(dim4)] // XXX: this is wrong
-use geom::Triangle;
+#[cfg(dim4)]
+use geom::Tetrahedron;
 
 /// Trait implemented by elements usable on the Mesh.
 ///
@@ -38,9 +38,9 @@ pub type MeshPrimitive = Segment;
 pub type MeshPrimitive = Triangle;
 
 #[cfg(dim4)]
-pub type MeshPrimitive = Triangle; // XXX: this is wrong
+pub type MeshPrimitive = Tetrahedron;
 
-/// Geometry commonly known as a 2d line strip or a 3d triangle mesh.
+/// Geometry commonly known as a 2d line strip, a 3d triangle mesh, or a 4d tetrahedral mesh.
 pub struct Mesh {
     bvt:      BVT<uint, AABB>,
     bvs:      Vec<AABB>,
@@ -207,4 +207,9 @@ impl ConcaveGeom for Mesh {
     fn aabb_at<'a>(&'a self, i: uint) -> &'a AABB {
         self.bvs.get(i)
     }
+
+    #[inline]
+    fn bvt<'a>(&'a self) -> Option<&'a BVT<uint, AABB>> {
+        Some(&'a self.bvt)
+    }
 }
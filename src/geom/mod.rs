@@ -15,7 +15,11 @@ pub use geom::geom_with_margin::GeomWithMargin;
 pub use geom::mesh::{Mesh, MeshElement, MeshPrimitive};
 pub use geom::segment::Segment;
 pub use geom::triangle::Triangle;
-pub use geom::geom::{Geom, ConcaveGeom};
+pub use geom::tetrahedron::Tetrahedron;
+#[cfg(dim3)]
+pub use geom::tet_mesh::{TetMesh, TetrahedralizationError, VolumeMismatch};
+pub use geom::geom::{Geom, ConcaveGeom, GeomCapabilities, GeomCapability, ImplicitCapability,
+                     PlaneCapability, ConcaveCapability, NoCapability};
 pub use geom::bezier_surface::{BezierSurface, BezierSurfaceEvaluationCache};
 pub use geom::bezier_curve::{BezierCurve, BezierCurveEvaluationCache};
 
@@ -33,7 +37,11 @@ mod reflection;
 mod compound;
 mod segment;
 mod triangle;
+mod tetrahedron;
 mod geom_with_margin;
 mod mesh;
+#[cfg(dim3)]
+mod tet_mesh;
 mod bezier_curve;
 mod bezier_surface;
+mod capabilities;
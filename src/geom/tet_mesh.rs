@@ -0,0 +1,616 @@
+//!
+//! 3d solid mesh made of tetrahedra.
+//!
+
+use std::num::Bounded;
+use sync::Arc;
+use nalgebra::na::{Vec3, Transform};
+use nalgebra::na;
+use ray::Ray;
+use ray;
+use partitioning::BVT;
+use bounding_volume::{HasAABB, AABB, LooseBoundingVolume};
+use partitioning::{BoundingVolumeInterferencesCollector, RayInterferencesCollector};
+use geom::{Geom, ConcaveGeom, Mesh, MeshElement, Tetrahedron};
+use volumetric;
+use math::{Scalar, Vect, Matrix};
+
+/// Error returned when a triangle mesh cannot be filled with tetrahedra.
+#[deriving(Show, PartialEq, Clone)]
+pub enum TetrahedralizationError {
+    /// The volume of the tetrahedra differs from the volume enclosed by the triangle mesh.
+    ///
+    /// This happens when the triangle mesh is not closed or not conforming to the Delaunay
+    /// tetrahedralization of its vertices, e.g., some non-convex meshes.
+    VolumeMismatch
+}
+
+/// A solid geometry made of tetrahedra.
+///
+/// Contrary to the `Mesh` which is hollow, each part of a `TetMesh` is a solid tetrahedron: an
+/// object fully inside of it is in contact with it.
+pub struct TetMesh {
+    bvt:      BVT<uint, AABB>,
+    bvs:      Vec<AABB>,
+    margin:   Scalar,
+    vertices: Arc<Vec<Vect>>,
+    indices:  Arc<Vec<uint>>
+}
+
+impl Clone for TetMesh {
+    fn clone(&self) -> TetMesh {
+        TetMesh {
+            bvt:      self.bvt.clone(),
+            bvs:      self.bvs.clone(),
+            margin:   self.margin.clone(),
+            vertices: self.vertices.clone(),
+            indices:  self.indices.clone()
+        }
+    }
+}
+
+impl TetMesh {
+    /// Builds a new tetrahedral mesh with a default margin of 0.04.
+    ///
+    /// Each group of four consecutive indices identifies the vertices of one tetrahedron.
+    pub fn new(vertices: Arc<Vec<Vect>>, indices: Arc<Vec<uint>>) -> TetMesh {
+        TetMesh::new_with_margin(vertices, indices, na::cast(0.04))
+    }
+
+    /// Builds a new tetrahedral mesh with a custom margin.
+    pub fn new_with_margin(vertices: Arc<Vec<Vect>>, indices: Arc<Vec<uint>>, margin: Scalar)
+                           -> TetMesh {
+        assert!(indices.len() % 4 == 0);
+
+        let mut leaves = Vec::new();
+        let mut bvs    = Vec::new();
+
+        {
+            let vs: &[Vect] = vertices.as_slice();
+
+            for (i, is) in indices.as_slice().chunks(4).enumerate() {
+                let element: Tetrahedron = MeshElement::new_with_vertices_and_indices(vs, is, margin.clone());
+                // loosen for better persistancy
+                let id = na::one();
+                let bv = element.aabb(&id).loosened(margin);
+                leaves.push((i, bv.clone()));
+                bvs.push(bv);
+            }
+        }
+
+        let bvt = BVT::new_kdtree(leaves);
+
+        TetMesh {
+            bvt:      bvt,
+            bvs:      bvs,
+            margin:   margin,
+            vertices: vertices,
+            indices:  indices
+        }
+    }
+
+    /// Builds a new tetrahedral mesh filling the volume enclosed by a triangle mesh.
+    ///
+    /// The triangle mesh must be closed, manifold, and consistently oriented. The tetrahedra are
+    /// those of the Delaunay tetrahedralization of its vertices having their center inside of the
+    /// triangle mesh.
+    ///
+    /// This is not a constrained nor a conforming tetrahedralization: no Steiner point is added
+    /// and the boundary of the triangle mesh is not recovered. Thus, every face of the triangle
+    /// mesh must already be a face of the Delaunay tetrahedralization of its vertices. This is
+    /// always the case for convex meshes but not for every non-convex one (e.g. the Schönhardt
+    /// polyhedron, which cannot be tetrahedralized at all without Steiner points). Otherwise, some
+    /// tetrahedra cross the boundary of the triangle mesh. This is detected by comparing the
+    /// volume of the tetrahedra with the volume enclosed by the triangle mesh, and a
+    /// `VolumeMismatch` error is returned.
+    ///
+    /// The result has the same margin as `mesh`.
+    pub fn new_from_mesh(mesh: &Mesh) -> Result<TetMesh, TetrahedralizationError> {
+        let vertices    = mesh.vertices().clone();
+        let mut indices = Vec::new();
+        let mut volume  = na::zero::<Scalar>();
+
+        {
+            let vs: &[Vect] = vertices.as_slice();
+            let size        = diameter(vs);
+            let _rel_tol: Scalar = na::cast(1.0e-7);
+            let min_volume  = size * size * size * _rel_tol;
+
+            for tet in delaunay_tetrahedralization(vs).iter() {
+                let (a, b, c, d) = (&vs[tet[0]], &vs[tet[1]], &vs[tet[2]], &vs[tet[3]]);
+                let tet_volume   = volumetric::tetrahedron_volume(a, b, c, d);
+                let center       = (*a + *b + *c + *d) / na::cast::<f32, Scalar>(4.0);
+
+                // Flat tetrahedra are discarded.
+                if tet_volume > min_volume && mesh_contains_point(mesh, &center) {
+                    indices.push_all(tet.as_slice());
+                    volume = volume + tet_volume;
+                }
+            }
+
+            let _rel_tol: Scalar = na::cast(1.0e-5);
+            let enclosed         = enclosed_volume(mesh);
+
+            if (volume - enclosed).abs() > enclosed * _rel_tol {
+                return Err(VolumeMismatch)
+            }
+        }
+
+        Ok(TetMesh::new_with_margin(vertices, Arc::new(indices), mesh.margin()))
+    }
+}
+
+impl TetMesh {
+    /// The vertices of this mesh.
+    #[inline]
+    pub fn vertices<'a>(&'a self) -> &'a Arc<Vec<Vect>> {
+        &'a self.vertices
+    }
+
+    /// Bounding volumes of the tetrahedra.
+    #[inline]
+    pub fn bounding_volumes<'a>(&'a self) -> &'a [AABB] {
+        self.bvs.as_slice()
+    }
+
+    /// The indices of this mesh.
+    #[inline]
+    pub fn indices<'a>(&'a self) -> &'a Arc<Vec<uint>> {
+        &'a self.indices
+    }
+
+    /// The acceleration structure used for efficient collision detection and ray casting.
+    #[inline]
+    pub fn bvt<'a>(&'a self) -> &'a BVT<uint, AABB> {
+        &'a self.bvt
+    }
+
+    /// The collision margin used by this mesh.
+    #[inline]
+    pub fn margin(&self) -> Scalar {
+        self.margin.clone()
+    }
+}
+
+impl TetMesh {
+    /// Gets the i-th tetrahedron.
+    #[inline(always)]
+    pub fn element_at(&self, i: uint) -> Tetrahedron {
+        let vs: &[Vect] = self.vertices.as_slice();
+        let is = self.indices.slice(i * 4, i * 4 + 4);
+
+        MeshElement::new_with_vertices_and_indices(vs, is, self.margin.clone())
+    }
+
+    /// Tests if a point is inside of this mesh transformed by `m`.
+    ///
+    /// The margin is ignored.
+    pub fn contains_point(&self, m: &Matrix, pt: &Vect) -> bool {
+        let ls_pt = m.inv_transform(pt);
+        let aabb  = AABB::new(ls_pt.clone(), ls_pt.clone());
+        let mut interferences = Vec::new();
+
+        self.approx_interferences_with_aabb(&aabb, &mut interferences);
+
+        interferences.iter().any(|i| {
+            let t = self.element_at(*i);
+
+            tetrahedron_contains_point(t.a(), t.b(), t.c(), t.d(), &ls_pt)
+        })
+    }
+}
+
+impl ConcaveGeom for TetMesh {
+    #[inline(always)]
+    fn map_part_at<T>(&self, i: uint, f: |&Matrix, &Geom| -> T) -> T {
+        let one: Matrix = na::one();
+
+        self.map_transformed_part_at(&one, i, f)
+    }
+
+    #[inline(always)]
+    fn map_transformed_part_at<T>(&self, m: &Matrix, i: uint, f: |&Matrix, &Geom| -> T) -> T{
+        let element = self.element_at(i);
+
+        f(m, &element as &Geom)
+    }
+
+    #[inline]
+    fn approx_interferences_with_aabb(&self, aabb: &AABB, out: &mut Vec<uint>) {
+        let mut visitor = BoundingVolumeInterferencesCollector::new(aabb, out);
+        self.bvt.visit(&mut visitor);
+    }
+
+    #[inline]
+    fn approx_interferences_with_ray(&self, ray: &Ray, out: &mut Vec<uint>) {
+        let mut visitor = RayInterferencesCollector::new(ray, out);
+        self.bvt.visit(&mut visitor);
+    }
+
+    #[inline]
+    fn aabb_at<'a>(&'a self, i: uint) -> &'a AABB {
+        self.bvs.get(i)
+    }
+
+    #[inline]
+    fn bvt<'a>(&'a self) -> Option<&'a BVT<uint, AABB>> {
+        Some(&'a self.bvt)
+    }
+}
+
+// Six times the signed volume of the tetrahedron `abcd`.
+#[inline]
+fn signed_volume6(a: &Vect, b: &Vect, c: &Vect, d: &Vect) -> Scalar {
+    na::dot(&(*b - *a), &na::cross(&(*c - *a), &(*d - *a)))
+}
+
+fn tetrahedron_contains_point(a: &Vect, b: &Vect, c: &Vect, d: &Vect, pt: &Vect) -> bool {
+    let _0: Scalar = na::zero();
+    let vol = signed_volume6(a, b, c, d);
+
+    if vol == _0 {
+        return false;
+    }
+
+    let v1 = signed_volume6(pt, b, c, d) / vol;
+    let v2 = signed_volume6(a, pt, c, d) / vol;
+    let v3 = signed_volume6(a, b, pt, d) / vol;
+    let v4 = signed_volume6(a, b, c, pt) / vol;
+
+    v1 >= _0 && v2 >= _0 && v3 >= _0 && v4 >= _0
+}
+
+// Tests if a point is inside of a closed triangle mesh, by counting the intersections of a ray
+// with its triangles.
+fn mesh_contains_point(mesh: &Mesh, pt: &Vect) -> bool {
+    // Use a direction unlikely to be aligned with the mesh edges.
+    let dir = na::normalize(&Vec3::new(na::cast(0.8271), na::cast(0.4312), na::cast(0.3597)));
+    let ray = Ray::new(pt.clone(), dir);
+    let vs: &[Vect] = mesh.vertices().as_slice();
+    let is: &[uint] = mesh.indices().as_slice();
+    let mut interferences = Vec::new();
+
+    mesh.approx_interferences_with_ray(&ray, &mut interferences);
+
+    let mut nhits = 0u;
+
+    for i in interferences.iter() {
+        let i = *i * 3;
+
+        if ray::triangle_ray_intersection(&vs[is[i]], &vs[is[i + 1]], &vs[is[i + 2]], &ray).is_some() {
+            nhits = nhits + 1;
+        }
+    }
+
+    nhits % 2 == 1
+}
+
+// The volume enclosed by a closed triangle mesh, computed with the divergence theorem.
+fn enclosed_volume(mesh: &Mesh) -> Scalar {
+    let vs: &[Vect] = mesh.vertices().as_slice();
+    let is: &[uint] = mesh.indices().as_slice();
+
+    if vs.len() == 0 {
+        return na::zero()
+    }
+
+    // Use a vertex as the reference point to limit the rounding errors.
+    let origin = &vs[0];
+    let mut volume6 = na::zero::<Scalar>();
+
+    for t in is.chunks(3) {
+        volume6 = volume6 + signed_volume6(origin, &vs[t[0]], &vs[t[1]], &vs[t[2]]);
+    }
+
+    volume6.abs() / na::cast(6.0)
+}
+
+// The length of the diagonal of the AABB of a set of points.
+fn diameter(pts: &[Vect]) -> Scalar {
+    if pts.len() == 0 {
+        return na::zero()
+    }
+
+    let mut mins = pts[0].clone();
+    let mut maxs = pts[0].clone();
+
+    for pt in pts.iter() {
+        mins = na::inf(&mins, pt);
+        maxs = na::sup(&maxs, pt);
+    }
+
+    na::norm(&(maxs - mins))
+}
+
+// Computes the circumscribed sphere of a tetrahedron. Returns its center and its squared radius.
+//
+// A degenerate tetrahedron has an infinite circumscribed sphere.
+fn circumsphere(a: &Vect, b: &Vect, c: &Vect, d: &Vect) -> (Vect, Scalar) {
+    let u = *b - *a;
+    let v = *c - *a;
+    let w = *d - *a;
+
+    let det = na::dot(&u, &na::cross(&v, &w)) * na::cast(2.0);
+
+    // The tolerance is relative to the size of the tetrahedron.
+    let _rel_tol: Scalar = na::cast(1.0e-10);
+    let size = na::norm(&u) * na::norm(&v) * na::norm(&w);
+
+    if det.abs() <= size * _rel_tol {
+        return (a.clone(), Bounded::max_value());
+    }
+
+    let shift = (na::cross(&v, &w) * na::sqnorm(&u) +
+                 na::cross(&w, &u) * na::sqnorm(&v) +
+                 na::cross(&u, &v) * na::sqnorm(&w)) / det;
+
+    (*a + shift, na::sqnorm(&shift))
+}
+
+#[inline]
+fn sorted_face(a: uint, b: uint, c: uint) -> (uint, uint, uint) {
+    let (a, b) = if a < b { (a, b) } else { (b, a) };
+    let (b, c) = if b < c { (b, c) } else { (c, b) };
+    let (a, b) = if a < b { (a, b) } else { (b, a) };
+
+    (a, b, c)
+}
+
+// Computes the Delaunay tetrahedralization of a set of points, using the Bowyer-Watson algorithm.
+fn delaunay_tetrahedralization(pts: &[Vect]) -> Vec<[uint, ..4]> {
+    let npts = pts.len();
+
+    if npts < 4 {
+        return Vec::new();
+    }
+
+    // Build a tetrahedron containing every points.
+    let mut mins = pts[0].clone();
+    let mut maxs = pts[0].clone();
+
+    for pt in pts.iter() {
+        mins = na::inf(&mins, pt);
+        maxs = na::sup(&maxs, pt);
+    }
+
+    let center = (mins + maxs) / na::cast::<f32, Scalar>(2.0);
+    let radius = na::norm(&(maxs - mins)) + na::one();
+    let scale: Scalar = radius * na::cast(10.0);
+
+    let mut vs: Vec<Vect> = pts.iter().map(|pt| pt.clone()).collect();
+
+    vs.push(center + Vec3::new(scale, scale, scale));
+    vs.push(center + Vec3::new(scale, -scale, -scale));
+    vs.push(center + Vec3::new(-scale, scale, -scale));
+    vs.push(center + Vec3::new(-scale, -scale, scale));
+
+    let first = [ npts, npts + 1, npts + 2, npts + 3 ];
+    let mut tets: Vec<([uint, ..4], Vect, Scalar)> = Vec::new();
+    let (c, r) = circumsphere(vs.get(npts), vs.get(npts + 1), vs.get(npts + 2), vs.get(npts + 3));
+
+    tets.push((first, c, r));
+
+    for i in range(0u, npts) {
+        let pt = vs.get(i).clone();
+
+        // Remove every tetrahedron which circumscribed sphere contains the new point, and collect
+        // the faces on the boundary of the resulting cavity.
+        let mut faces: Vec<(uint, uint, uint)> = Vec::new();
+        let mut j = 0;
+
+        while j < tets.len() {
+            let is_bad = {
+                let &(_, ref c, ref r) = tets.get(j);
+                na::sqnorm(&(pt - *c)) < *r
+            };
+
+            if is_bad {
+                let (t, _, _) = tets.swap_remove(j).unwrap();
+                let tfaces = [
+                    sorted_face(t[0], t[1], t[2]),
+                    sorted_face(t[0], t[1], t[3]),
+                    sorted_face(t[0], t[2], t[3]),
+                    sorted_face(t[1], t[2], t[3])
+                ];
+
+                for f in tfaces.iter() {
+                    match faces.iter().position(|f2| f2 == f) {
+                        Some(k) => { let _ = faces.swap_remove(k); },
+                        None    => faces.push(f.clone())
+                    }
+                }
+            }
+            else {
+                j = j + 1;
+            }
+        }
+
+        // Fill the cavity with the new point.
+        for &(a, b, c) in faces.iter() {
+            let (center, sqradius) = circumsphere(vs.get(a), vs.get(b), vs.get(c), &pt);
+
+            tets.push(([ a, b, c, i ], center, sqradius));
+        }
+    }
+
+    // Remove the tetrahedra attached to the enclosing tetrahedron.
+    tets.move_iter()
+        .map(|(t, _, _)| t)
+        .filter(|t| t.iter().all(|i| *i < npts))
+        .collect()
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use bounding_volume::HasAABB;
+    use geom::{Geom, Ball, Mesh, TetMesh, VolumeMismatch};
+    use volumetric::Volumetric;
+    use narrow::{GeomGeomTOIDispatcher, GeomGeomDistanceDispatcher, GeomGeomProximityDispatcher,
+                 Impact, WithinMargin, Disjoint};
+
+    fn cube_mesh() -> Mesh {
+        let vs = vec!(
+            Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(1.0, 1.0, -1.0),   Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),  Vec3::new(1.0, -1.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),    Vec3::new(-1.0, 1.0, 1.0)
+        );
+        let is = vec!(
+            0, 2, 1, 0, 3, 2, // bottom
+            4, 5, 6, 4, 6, 7, // top
+            0, 1, 5, 0, 5, 4, // front
+            2, 3, 7, 2, 7, 6, // back
+            1, 2, 6, 1, 6, 5, // right
+            0, 4, 7, 0, 7, 3  // left
+        );
+
+        Mesh::new(Arc::new(vs), Arc::new(is), None, None)
+    }
+
+    // An L-shaped prism: the extrusion of the L made of the squares `[0, 1] x [0, 1]`,
+    // `[1, 2] x [0, 1]` and `[0, 1] x [1, 2]` along `z`, with `z` in `[0, 1]`.
+    fn l_shape_mesh() -> Mesh {
+        let l = [ (0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (0.0, 2.0) ];
+        let mut vs = Vec::new();
+
+        for &(x, y) in l.iter() {
+            vs.push(Vec3::new(x, y, 0.0));
+        }
+
+        for &(x, y) in l.iter() {
+            vs.push(Vec3::new(x, y, 1.0));
+        }
+
+        let mut is = vec!(
+            // bottom, seen from below
+            0, 3, 1, 1, 3, 2, 0, 5, 3, 3, 5, 4,
+            // top, seen from above
+            6, 7, 9, 7, 8, 9, 6, 9, 11, 9, 10, 11
+        );
+
+        // sides
+        for i in range(0u, 6) {
+            let j = (i + 1) % 6;
+
+            is.push_all([ i, j, j + 6, i, j + 6, i + 6 ]);
+        }
+
+        Mesh::new(Arc::new(vs), Arc::new(is), None, None)
+    }
+
+    #[test]
+    fn test_tet_mesh_from_cube() {
+        let tm = TetMesh::new_from_mesh(&cube_mesh()).ok().expect("The cube should be filled.");
+        let (mass, center, _) = tm.mass_properties(&1.0);
+
+        assert!(na::approx_eq_eps(&mass, &8.0, &1.0e-5));
+        assert!(na::approx_eq_eps(&center, &na::zero(), &1.0e-5));
+    }
+
+    #[test]
+    fn test_tet_mesh_from_concave_l_shape() {
+        let tm = TetMesh::new_from_mesh(&l_shape_mesh()).ok().expect("The L should be filled.");
+        let (mass, center, _) = tm.mass_properties(&1.0);
+
+        // three unit cubes
+        assert!(na::approx_eq_eps(&mass, &3.0, &1.0e-5));
+        assert!(na::approx_eq_eps(&center, &Vec3::new(5.0 / 6.0, 5.0 / 6.0, 0.5), &1.0e-5));
+
+        let id = na::one();
+
+        assert!(tm.contains_point(&id, &Vec3::new(1.5, 0.5, 0.5)));
+        assert!(tm.contains_point(&id, &Vec3::new(0.5, 1.5, 0.5)));
+        // the notch of the L is empty
+        assert!(!tm.contains_point(&id, &Vec3::new(1.5, 1.5, 0.5)));
+        assert!(!tm.contains_point(&id, &Vec3::new(1.2, 1.1, 0.5)));
+    }
+
+    #[test]
+    fn test_tet_mesh_from_open_mesh() {
+        let cube = cube_mesh();
+        let vs   = cube.vertices().clone();
+        // remove the top of the cube
+        let is   = cube.indices().slice_to(6).to_owned() + cube.indices().slice_from(12);
+        let open = Mesh::new(vs, Arc::new(is), None, None);
+
+        assert!(TetMesh::new_from_mesh(&open).is_err());
+    }
+
+    // The Schönhardt polyhedron: a twisted triangular prism with reflex side edges.
+    fn schonhardt_mesh() -> Mesh {
+        let h  = 3.0f64.sqrt() / 2.0;
+        // The top triangle is the bottom one rotated by 30 degrees. Its vertices are not at the
+        // same height: otherwise, all the vertices are on the same sphere and the Delaunay
+        // tetrahedralization is not unique.
+        let vs = vec!(
+            Vec3::new(0.0, 1.0, 0.0), Vec3::new(-h, -0.5, 0.0), Vec3::new(h, -0.5, 0.0),
+            Vec3::new(-0.5, h, 1.0),  Vec3::new(-0.5, -h, 1.1), Vec3::new(1.0, 0.0, 1.2)
+        );
+
+        let mut is = vec!(0, 2, 1, 3, 4, 5);
+
+        // sides, split along the reflex diagonals
+        for i in range(0u, 3) {
+            let j = (i + 1) % 3;
+
+            is.push_all([ i, j, j + 3, i, j + 3, i + 3 ]);
+        }
+
+        Mesh::new(Arc::new(vs), Arc::new(is), None, None)
+    }
+
+    #[test]
+    fn test_tet_mesh_from_non_conforming_mesh() {
+        assert!(TetMesh::new_from_mesh(&schonhardt_mesh()).err() == Some(VolumeMismatch));
+    }
+
+    #[test]
+    fn test_tet_mesh_contains_point() {
+        let tm = TetMesh::new_from_mesh(&cube_mesh()).ok().expect("The cube should be filled.");
+        let id = na::one();
+
+        assert!(tm.contains_point(&id, &Vec3::new(0.1, 0.2, -0.3)));
+        assert!(!tm.contains_point(&id, &Vec3::new(1.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_tet_mesh_dispatchers() {
+        let vs = vec!(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0),
+                      Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let tm = TetMesh::new_with_margin(Arc::new(vs), Arc::new(vec!(0u, 1, 2, 3)), 0.0);
+        let b  = Ball::new(0.5f64);
+        let mt = na::one::<Iso3<f64>>();
+        let mb = Iso3::new(Vec3::new(0.2f64, 0.2, -1.0), na::zero());
+
+        // The ball is 0.5 bellow the bottom face of the tetrahedron.
+        let dir = Vec3::new(0.0f64, 0.0, 1.0);
+
+        match GeomGeomTOIDispatcher::new().toi(&mb, &dir, &10.0, &b as &Geom, &mt, &tm as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &0.5, &1.0e-5)),
+            _           => fail!("The ball should touch the tetrahedral mesh.")
+        }
+
+        match GeomGeomDistanceDispatcher::new().distance(&mt, &tm as &Geom, &mb, &b as &Geom) {
+            Some(d) => assert!(na::approx_eq_eps(&d.dist, &0.5, &1.0e-5)),
+            None    => fail!("The distance should be computable.")
+        }
+
+        let proximity = GeomGeomProximityDispatcher::new();
+
+        assert!(proximity.proximity(&mt, &tm as &Geom, &mb, &b as &Geom, &1.0) == Some(WithinMargin));
+        assert!(proximity.proximity(&mb, &b as &Geom, &mt, &tm as &Geom, &0.2) == Some(Disjoint));
+    }
+
+    #[test]
+    fn test_empty_tet_mesh_aabb() {
+        let tm   = TetMesh::new(Arc::new(vec!(Vec3::new(1.0f64, 2.0, 3.0))), Arc::new(Vec::new()));
+        let m    = Iso3::new(Vec3::new(1.0f64, 1.0, 1.0), na::zero());
+        let aabb = tm.aabb(&m);
+
+        assert!(*aabb.mins() == Vec3::new(1.0, 1.0, 1.0));
+        assert!(*aabb.maxs() == Vec3::new(1.0, 1.0, 1.0));
+    }
+}
@@ -0,0 +1,159 @@
+//! Definition of the tetrahedron geometry.
+
+use nalgebra::na;
+use geom::mesh::MeshElement;
+use math::{Scalar, Vect};
+
+/// A tetrahedron geometry.
+///
+/// In 3d, this is a solid element. In 4d, this is the boundary element of a `Mesh`.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct Tetrahedron {
+    margin: Scalar,
+    a:      Vect,
+    b:      Vect,
+    c:      Vect,
+    d:      Vect
+}
+
+impl Tetrahedron {
+    /// Creates a tetrahedron from four points.
+    ///
+    /// The tetrahedron is created with a default margin of 0.04.
+    #[inline]
+    pub fn new(a: Vect, b: Vect, c: Vect, d: Vect) -> Tetrahedron {
+        Tetrahedron::new_with_margin(a, b, c, d, na::cast(0.04))
+    }
+
+    /// Creates a tetrahedron from four points and a default margin.
+    #[inline]
+    pub fn new_with_margin(a: Vect, b: Vect, c: Vect, d: Vect, margin: Scalar) -> Tetrahedron {
+        assert!(na::dim::<Vect>() > 2);
+
+        Tetrahedron {
+            margin: margin,
+            a:      a,
+            b:      b,
+            c:      c,
+            d:      d
+        }
+    }
+}
+
+impl Tetrahedron {
+    /// The fist point of this tetrahedron.
+    #[inline]
+    pub fn a<'a>(&'a self) -> &'a Vect {
+        &'a self.a
+    }
+
+    /// The second point of this tetrahedron.
+    #[inline]
+    pub fn b<'a>(&'a self) -> &'a Vect {
+        &'a self.b
+    }
+
+    /// The third point of this tetrahedron.
+    #[inline]
+    pub fn c<'a>(&'a self) -> &'a Vect {
+        &'a self.c
+    }
+
+    /// The fourth point of this tetrahedron.
+    #[inline]
+    pub fn d<'a>(&'a self) -> &'a Vect {
+        &'a self.d
+    }
+
+    /// The margin surrounding this tetrahedron.
+    #[inline]
+    pub fn margin(&self) -> Scalar {
+        self.margin.clone()
+    }
+}
+
+impl MeshElement for Tetrahedron {
+    #[inline]
+    fn nvertices(_: Option<Tetrahedron>) -> uint {
+        4
+    }
+
+    #[inline]
+    fn new_with_vertices_and_indices(vs: &[Vect], is: &[uint], margin: Scalar) -> Tetrahedron {
+        assert!(is.len() == 4);
+
+        Tetrahedron::new_with_margin(vs[is[0]].clone(), vs[is[1]].clone(), vs[is[2]].clone(),
+                                     vs[is[3]].clone(), margin)
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Tetrahedron;
+    use implicit::Implicit;
+    use ray::{Ray, RayCast};
+
+    fn unit_tetrahedron() -> Tetrahedron {
+        Tetrahedron::new_with_margin(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0),
+                                     Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0), 0.0)
+    }
+
+    #[test]
+    fn test_tetrahedron_support_point() {
+        let t  = unit_tetrahedron();
+        let id = na::one::<Iso3<f64>>();
+
+        assert!(t.support_point(&id, &Vec3::new(1.0, 0.1, 0.0)) == Vec3::new(1.0, 0.0, 0.0));
+        assert!(t.support_point(&id, &Vec3::new(0.1, 1.0, 0.2)) == Vec3::new(0.0, 1.0, 0.0));
+        assert!(t.support_point(&id, &Vec3::new(0.0, 0.1, 1.0)) == Vec3::new(0.0, 0.0, 1.0));
+        assert!(t.support_point(&id, &Vec3::new(-1.0, -1.0, -1.0)) == Vec3::new(0.0, 0.0, 0.0));
+
+        // The rotation maps the local `x` axis to the world `y` axis.
+        let m = Iso3::new(Vec3::new(1.0f64, 2.0, 3.0), Vec3::new(0.0, 0.0, Float::frac_pi_2()));
+        let p = t.support_point(&m, &Vec3::new(0.1, 1.0, 0.0));
+
+        assert!(na::approx_eq_eps(&p, &Vec3::new(1.0, 3.0, 3.0), &1.0e-7));
+    }
+
+    #[test]
+    fn test_tetrahedron_support_point_with_margin() {
+        let t  = Tetrahedron::new_with_margin(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0),
+                                              Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0), 0.1);
+        let id = na::one::<Iso3<f64>>();
+        let p  = t.support_point(&id, &Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(na::approx_eq_eps(&p, &Vec3::new(1.1, 0.0, 0.0), &1.0e-7));
+    }
+
+    #[test]
+    fn test_tetrahedron_ray_cast() {
+        let t = unit_tetrahedron();
+
+        // hits the face `x + y + z = 1`
+        let ray   = Ray::new(Vec3::new(0.2f64, 0.2, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let inter = t.toi_and_normal_with_ray(&ray, true).expect("The ray should hit.");
+        let n     = na::normalize(&Vec3::new(1.0f64, 1.0, 1.0));
+
+        assert!(na::approx_eq_eps(&inter.toi, &4.4, &1.0e-3));
+        assert!(na::approx_eq_eps(&inter.normal, &n, &1.0e-3));
+
+        // hits the face `x = 0`
+        let ray   = Ray::new(Vec3::new(-2.0f64, 0.2, 0.3), Vec3::new(1.0, 0.0, 0.0));
+        let inter = t.toi_and_normal_with_ray(&ray, true).expect("The ray should hit.");
+
+        assert!(na::approx_eq_eps(&inter.toi, &2.0, &1.0e-3));
+        assert!(na::approx_eq_eps(&inter.normal, &Vec3::new(-1.0, 0.0, 0.0), &1.0e-3));
+
+        // a solid tetrahedron is hit immediately from its inside
+        let ray = Ray::new(Vec3::new(0.1f64, 0.1, 0.1), Vec3::new(0.0, 0.0, 1.0));
+
+        assert!(t.toi_with_ray(&ray, true) == Some(0.0));
+
+        // misses
+        let ray = Ray::new(Vec3::new(2.0f64, 2.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(t.toi_with_ray(&ray, true).is_none());
+    }
+}
@@ -0,0 +1,38 @@
+use implicit::{Implicit, HasMargin, PreferedSamplingDirections};
+use math::{Scalar, Vect, Matrix};
+
+/// Trait of geometries usable by the GJK-based algorithms through a trait object.
+///
+/// This is automatically implemented by every geometry implementing both `Implicit` and
+/// `PreferedSamplingDirections`.
+pub trait ImplicitGeom: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix> {
+}
+
+impl<T: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>> ImplicitGeom for T {
+}
+
+impl<'a> HasMargin for &'a ImplicitGeom {
+    #[inline]
+    fn margin(&self) -> Scalar {
+        (**self).margin()
+    }
+}
+
+impl<'a> Implicit<Vect, Matrix> for &'a ImplicitGeom {
+    #[inline]
+    fn support_point(&self, transform: &Matrix, dir: &Vect) -> Vect {
+        (**self).support_point(transform, dir)
+    }
+
+    #[inline]
+    fn support_point_without_margin(&self, transform: &Matrix, dir: &Vect) -> Vect {
+        (**self).support_point_without_margin(transform, dir)
+    }
+}
+
+impl<'a> PreferedSamplingDirections<Vect, Matrix> for &'a ImplicitGeom {
+    #[inline]
+    fn sample(&self, transform: &Matrix, f: |Vect| -> ()) {
+        (**self).sample(transform, f)
+    }
+}
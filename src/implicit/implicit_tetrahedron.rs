@@ -0,0 +1,51 @@
+use nalgebra::na::{Transform, Rotate};
+use nalgebra::na;
+use implicit::{Implicit, HasMargin, PreferedSamplingDirections};
+use geom::Tetrahedron;
+use math::{Scalar, Vect};
+
+impl HasMargin for Tetrahedron {
+    #[inline]
+    fn margin(&self) -> Scalar {
+        self.margin()
+    }
+}
+
+impl<_M: Transform<Vect> + Rotate<Vect>>
+Implicit<Vect, _M> for Tetrahedron {
+    #[inline]
+    fn support_point_without_margin(&self, m: &_M, dir: &Vect) -> Vect {
+        let local_dir = m.inv_rotate(dir);
+
+        let d1 = na::dot(self.a(), &local_dir);
+        let d2 = na::dot(self.b(), &local_dir);
+        let d3 = na::dot(self.c(), &local_dir);
+        let d4 = na::dot(self.d(), &local_dir);
+
+        let res =
+            if d1 > d2 {
+                if d1 > d3 {
+                    if d1 > d4 { self.a() } else { self.d() }
+                }
+                else {
+                    if d3 > d4 { self.c() } else { self.d() }
+                }
+            }
+            else {
+                if d2 > d3 {
+                    if d2 > d4 { self.b() } else { self.d() }
+                }
+                else {
+                    if d3 > d4 { self.c() } else { self.d() }
+                }
+            };
+
+        m.transform(res)
+    }
+}
+
+impl<_V, _M> PreferedSamplingDirections<_V, _M> for Tetrahedron {
+    #[inline(always)]
+    fn sample(&self, _: &_M, _: |_V| -> ()) {
+    }
+}
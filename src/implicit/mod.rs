@@ -1,6 +1,7 @@
 //! Definition of support functions.
 
 pub use implicit::implicit::{Implicit, HasMargin, PreferedSamplingDirections};
+pub use implicit::implicit_geom::ImplicitGeom;
 pub use implicit::implicit_minkowski_sum::{cso_support_point, cso_support_point_without_margin};
 
 pub mod implicit;
@@ -13,5 +14,7 @@ mod implicit_cylinder;
 mod implicit_convex;
 mod implicit_reflection;
 mod implicit_triangle;
+mod implicit_tetrahedron;
 mod implicit_segment;
 mod implicit_minkowski_sum;
+mod implicit_geom;
@@ -31,8 +31,9 @@ recursive clone:
 - collision detection between arbitrary convex objects
 - compound geometries
 - ray-casting
-- time of impact computation  for objects without rotational movement (compound vs. compound is not
-  yet implemented)
+- time of impact computation  for objects without rotational movement
+- distance and closest points computation
+- proximity queries (intersecting, within a margin, or disjoint)
 
 And various traits for collision detectors and broad phase collision detection.
 
@@ -79,6 +80,9 @@ pub mod partitioning;
 /// Data structure utilities.
 pub mod util;
 
+#[cfg(dim3, f64, test)]
+mod test_utils;
+
 // #[cfg(test)]
 // mod tests {
 //     mod geom;
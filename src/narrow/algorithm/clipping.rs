@@ -0,0 +1,74 @@
+//! Clipping of polygons and segments by half-spaces.
+
+use nalgebra::na;
+use util::hash;
+use math::{Scalar, Vect};
+
+/// Clips a polygon (or a segment) with the half-space `dot(normal, x) <= offset`.
+///
+/// Each vertex is associated with an identifier. The vertices kept by the clipping keep their
+/// identifier. A vertex created by the clipping is identified by the pair formed by `plane` and the
+/// original vertex starting the clipped edge, combined with `hash::key_from_pair`. Thus, the
+/// identifiers do not grow with the number of clipping planes. `plane` should be unique for each
+/// half-space used to clip the same polygon, and the identifiers of the original vertices must fit
+/// on half a `uint`.
+///
+/// # Arguments:
+/// * `poly`   - the vertices of the polygon, ordered along its boundary. If there are only two
+///              vertices, they are considered to be a segment.
+/// * `normal` - the half-space normal.
+/// * `offset` - the half-space offset.
+/// * `plane`  - the identifier of the half-space.
+/// * `out`    - the vector which will contain the clipped polygon.
+pub fn clip_with_half_space(poly:   &Vec<(Vect, uint)>,
+                            normal: &Vect,
+                            offset: &Scalar,
+                            plane:  uint,
+                            out:    &mut Vec<(Vect, uint)>) {
+    let num_pts   = poly.len();
+    let num_edges = if num_pts == 2 { 1 } else { num_pts };
+
+    if num_pts == 1 {
+        let &(ref p, _) = poly.get(0);
+
+        if na::dot(normal, p) <= *offset {
+            out.push(poly.get(0).clone())
+        }
+
+        return;
+    }
+
+    for e in range(0u, num_edges) {
+        let &(ref a, ida) = poly.get(e);
+        let &(ref b, _)   = poly.get((e + 1) % num_pts);
+
+        let da = na::dot(normal, a) - *offset;
+        let db = na::dot(normal, b) - *offset;
+
+        if da <= na::zero() {
+            out.push((a.clone(), ida));
+        }
+
+        if (da <= na::zero()) != (db <= na::zero()) {
+            let t = da / (da - db);
+
+            out.push((*a + (*b - *a) * t, hash::key_from_pair(original_vertex(ida), plane + 1)));
+        }
+    }
+
+    if num_pts == 2 {
+        let &(ref b, idb) = poly.get(1);
+
+        if na::dot(normal, b) <= *offset {
+            out.push((b.clone(), idb));
+        }
+    }
+}
+
+/// The identifier of the original vertex of the polygon from which the vertex identified by `id`
+/// originates.
+#[inline]
+pub fn original_vertex(id: uint) -> uint {
+    // The identifier of a vertex created by the clipping has the original vertex on its lower half.
+    hash::key_from_pair(id, 0)
+}
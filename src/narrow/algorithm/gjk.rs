@@ -93,6 +93,41 @@ pub fn closest_points_without_margin_with_max_dist<S:  Simplex<AnnotatedPoint>,
     }
 }
 
+/// Tests the proximity of two convex geometries using the GJK algorithm.
+///
+/// The algorithm exits as soon as the answer is known. It returns `Intersection` if the geometries
+/// (including their margins) intersect, `Projection` with a separating axis if they are disjoint
+/// but closer than `max_dist`, and `NoIntersection` with a separating axis otherwise. The returned
+/// axis points from the second geometry toward the first one.
+///
+/// # Arguments:
+/// * `g1`       - first geometry.
+/// * `g2`       - second geometry.
+/// * `max_dist` - the distance bellow which the geometries are considered close to each other.
+/// * `simplex`  - the simplex to be used by the GJK algorithm. It must be already initialized
+///                with at least one point on the geometries CSO. See
+///                `minkowski_sum::cso_support_point` to compute such point.
+pub fn proximity<S:  Simplex<AnnotatedPoint>,
+                 G1: Implicit<Vect, Matrix>,
+                 G2: Implicit<Vect, Matrix>>(
+                 m1:       &Matrix,
+                 g1:       &G1,
+                 m2:       &Matrix,
+                 g2:       &G2,
+                 max_dist: &Scalar,
+                 simplex:  &mut S) -> GJKResult<Vect, Vect> {
+    let mg1      = GeomWithMargin::new(g1);
+    let mg2      = GeomWithMargin::new(g2);
+    let reflect2 = Reflection::new(&mg2);
+    let cso      = AnnotatedMinkowskiSum::new(m1, &mg1, m2, &reflect2);
+
+    match project_origin_with_early_exit(&Identity::new(), &cso, max_dist, simplex) {
+        Projection(dir)     => Projection(dir.point().clone()),
+        Intersection        => Intersection,
+        NoIntersection(dir) => NoIntersection(dir.point().clone())
+    }
+}
+
 /*
  * Distance GJK
  */
@@ -202,3 +237,80 @@ pub fn project_origin_with_max_dist<S: Simplex<_V>, G: Implicit<_V, _M>, _V: Flo
         }
     }
 }
+
+/// Classifies the position of the origin wrt. a geometry using the GJK algorithm.
+///
+/// Unlike `project_origin_with_max_dist`, the algorithm does not wait for the projection to
+/// converge: it stops as soon as the origin is proven to be either inside of the geometry, closer
+/// than `max_dist` but outside of the geometry (`Projection`), or farther than `max_dist`
+/// (`NoIntersection`). In the two last cases, a separating axis is returned.
+///
+/// # Arguments:
+/// * geom - the geometry to classify the origin against.
+/// * simplex - the simplex to be used by the GJK algorithm. It must be already initialized
+///             with at least one point on the geometry boundary.
+pub fn project_origin_with_early_exit<S: Simplex<_V>, G: Implicit<_V, _M>, _V: FloatVec<Scalar>, _M>(
+                                      m:        &_M,
+                                      geom:     &G,
+                                      max_dist: &Scalar,
+                                      simplex:  &mut S)
+                                      -> GJKResult<_V, _V> {
+    let mut proj       = simplex.project_origin_and_reduce();
+    let mut sq_len_dir = na::sqnorm(&proj);
+
+    let _eps: Scalar  = Float::epsilon();
+    let _eps_tol = _eps * na::cast(100.0);
+    let _eps_rel = _eps.sqrt();
+    let _dim     = na::dim::<Vect>();
+    let sq_max_dist = *max_dist * *max_dist;
+
+    loop {
+        if simplex.dimension() == _dim || sq_len_dir <= _eps_tol {
+            return Intersection // point inside of the cso
+        }
+
+        let support_point = geom.support_point_without_margin(m, &-proj);
+
+        let dot = na::dot(&proj, &support_point);
+
+        // FIXME: find a way to avoid the sqrt here
+        if dot > *max_dist * na::norm(&proj) {
+            return NoIntersection(proj);
+        }
+
+        if dot > na::zero() && sq_len_dir <= sq_max_dist {
+            // `proj` is a separating axis and the distance is smaller than `max_dist`.
+            return Projection(proj)
+        }
+
+        if sq_len_dir - dot <= _eps_rel * sq_len_dir {
+            // the projection has converged
+            if sq_len_dir <= sq_max_dist {
+                return Projection(proj)
+            }
+            else {
+                return NoIntersection(proj)
+            }
+        }
+
+        simplex.add_point(support_point);
+
+        let old_proj = proj;
+
+        proj = simplex.project_origin_and_reduce();
+
+        let old_sq_len_dir = sq_len_dir;
+
+        sq_len_dir = na::sqnorm(&proj);
+
+        if sq_len_dir >= old_sq_len_dir {
+            // upper bounds inconsistencies
+            if old_sq_len_dir <= sq_max_dist {
+                return Projection(old_proj)
+            }
+            else {
+                return NoIntersection(old_proj)
+            }
+        }
+    }
+}
@@ -2,6 +2,8 @@
 
 use std::num::Bounded;
 use nalgebra::na::Identity;
+#[cfg(dim4)]
+use nalgebra::na::Vec4;
 use nalgebra::na;
 use geom::{Reflection, MinkowskiSum, AnnotatedPoint};
 use implicit::{Implicit, PreferedSamplingDirections};
@@ -52,7 +54,7 @@ pub fn closest_points<S:  Simplex<AnnotatedPoint>,
     });
 
     // FIXME: avoid code duplication for the closure
-    na::sample_sphere(|sample: Vect| {
+    sample_sphere(|sample: Vect| {
         let support = cso.support_point(&Identity::new(), &sample);
         let dist    = na::dot(&sample, &support);
 
@@ -121,6 +123,34 @@ pub fn closest_points<S:  Simplex<AnnotatedPoint>,
     }
 }
 
+#[cfg(not(dim4))]
+fn sample_sphere(f: |Vect| -> ()) {
+    na::sample_sphere(f)
+}
+
+// `nalgebra` cannot sample the 4D unit sphere: use the 80 directions with coordinates in
+// `{ -1, 0, 1 }` instead.
+#[cfg(dim4)]
+fn sample_sphere(f: |Vect| -> ()) {
+    let _0: Scalar = na::zero();
+    let _1: Scalar = na::one();
+    let coords     = [ -_1, _0, _1 ];
+
+    for x in coords.iter() {
+        for y in coords.iter() {
+            for z in coords.iter() {
+                for w in coords.iter() {
+                    let dir = Vec4::new(x.clone(), y.clone(), z.clone(), w.clone());
+
+                    if na::sqnorm(&dir) != _0 {
+                        f(na::normalize(&dir))
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[cfg(dim2, f32, test)]
 mod test {
     use super::closest_points;
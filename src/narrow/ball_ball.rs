@@ -2,8 +2,8 @@ use std::num::Zero;
 use nalgebra::na::Translation;
 use nalgebra::na;
 use geom::Ball;
-use narrow::CollisionDetector;
-use contact::Contact;
+use narrow::{CollisionDetector, Distance, Proximity, Intersecting, WithinMargin, Disjoint};
+use contact::{Contact, FaceFeature};
 use ray::{Ray, ball_toi_with_ray};
 use math::{Scalar, Vect, Matrix};
 
@@ -61,6 +61,11 @@ BallBall {
         }
     }
 
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
     #[inline]
     fn toi(_: Option<BallBall>, c1: &Matrix, dir: &Vect, _: &Scalar, b1: &Ball, c2: &Matrix, b2: &Ball) -> Option<Scalar> {
         toi(c1, dir, b1, c2, b2)
@@ -90,11 +95,13 @@ pub fn collide(center1: &Vect, b1: &Ball, center2: &Vect, b2: &Ball, prediction:
             })
         }
 
-        Some(Contact::new(
+        Some(Contact::new_with_features(
                 center1 + normal * r1,
                 center2 - normal * r2,
                 normal,
-                (sum_radius - sqdist.sqrt())))
+                (sum_radius - sqdist.sqrt()),
+                FaceFeature(0),
+                FaceFeature(0)))
     }
     else {
         None
@@ -113,6 +120,52 @@ pub fn closest_points(center1: &Vect, b1: &Ball, center2: &Vect, b2: &Ball) -> (
     (center1 + normal * r1, center2 - normal * r2)
 }
 
+/// Computes the distance between two balls.
+///
+/// If they are intersecting, the distance is zero and the witness points correspond to the
+/// penetration depth.
+#[inline]
+pub fn distance(center1: &Vect, b1: &Ball, center2: &Vect, b2: &Ball) -> Distance {
+    let r1        = b1.radius();
+    let r2        = b2.radius();
+    let delta_pos = center2 - *center1;
+    let sqdist    = na::sqnorm(&delta_pos);
+    let mut normal = na::normalize(&delta_pos);
+
+    if sqdist.is_zero() {
+        na::canonical_basis(|b| {
+            normal = b;
+
+            false
+        })
+    }
+
+    let dist = sqdist.sqrt() - r1 - r2;
+
+    Distance::new(
+        center1 + normal * r1,
+        center2 - normal * r2,
+        normal,
+        na::max(dist, na::zero()))
+}
+
+/// Tests whether two balls are intersecting or closer than a given margin.
+#[inline]
+pub fn proximity(center1: &Vect, b1: &Ball, center2: &Vect, b2: &Ball, margin: &Scalar) -> Proximity {
+    let sum_radius = b1.radius() + b2.radius();
+    let sqdist     = na::sqnorm(&(center2 - *center1));
+
+    if sqdist <= sum_radius * sum_radius {
+        Intersecting
+    }
+    else if sqdist <= (sum_radius + *margin) * (sum_radius + *margin) {
+        WithinMargin
+    }
+    else {
+        Disjoint
+    }
+}
+
 /// Computes the Time Of Impact of two balls.
 ///
 /// Arguments:
@@ -0,0 +1,162 @@
+use nalgebra::na::Translation;
+use geom::{Ball, Capsule};
+use narrow::{CollisionDetector, implicit_implicit, ball_segment, capsule_capsule};
+use contact::Contact;
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between a ball and a capsule.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct BallCapsule {
+    prediction: Scalar,
+    contact:    Option<Contact>
+}
+
+impl BallCapsule {
+    /// Creates a new persistent collision detector between a ball and a capsule.
+    #[inline]
+    pub fn new(prediction: Scalar) -> BallCapsule {
+        BallCapsule {
+            prediction: prediction,
+            contact:    None
+        }
+    }
+}
+
+impl CollisionDetector<Ball, Capsule> for BallCapsule {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &Capsule) {
+        self.contact = collide(&ma.translation(), a, mb, b, &self.prediction);
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        match self.contact {
+            Some(ref c) => out_colls.push(c.clone()),
+            None        => ()
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_:   Option<BallCapsule>,
+           ma:  &Matrix,
+           dir: &Vect,
+           _:   &Scalar,
+           a:   &Ball,
+           mb:  &Matrix,
+           b:   &Capsule) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Collision detector between a capsule and a ball.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct CapsuleBall {
+    prediction: Scalar,
+    contact:    Option<Contact>
+}
+
+impl CapsuleBall {
+    /// Creates a new persistent collision detector between a capsule and a ball.
+    #[inline]
+    pub fn new(prediction: Scalar) -> CapsuleBall {
+        CapsuleBall {
+            prediction: prediction,
+            contact:    None
+        }
+    }
+}
+
+impl CollisionDetector<Capsule, Ball> for CapsuleBall {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Capsule, mb: &Matrix, b: &Ball) {
+        self.contact = collide(&mb.translation(), b, ma, a, &self.prediction);
+
+        for c in self.contact.mut_iter() {
+            c.flip()
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        match self.contact {
+            Some(ref c) => out_colls.push(c.clone()),
+            None        => ()
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_:   Option<CapsuleBall>,
+           ma:  &Matrix,
+           dir: &Vect,
+           _:   &Scalar,
+           a:   &Capsule,
+           mb:  &Matrix,
+           b:   &Ball) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Computes the contact point between a ball and a capsule.
+///
+/// The contact normal points toward the capsule.
+#[inline]
+pub fn collide(center:     &Vect,
+               b:          &Ball,
+               mc:         &Matrix,
+               c:          &Capsule,
+               prediction: &Scalar)
+               -> Option<Contact> {
+    let (a, b2) = capsule_capsule::axis(mc, c);
+
+    ball_segment::collide_with_radii(center, &b.radius(), &a, &b2, &c.radius(), prediction)
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3, Translation};
+    use nalgebra::na;
+    use geom::{Ball, Capsule};
+    use test_utils::assert_same_contact_as_gjk;
+    use super::collide;
+
+    #[test]
+    fn test_ball_capsule_gjk() {
+        let b  = Ball::new(0.5f64);
+        let c  = Capsule::new(1.0f64, 0.4, 0.0);
+        let mc = Iso3::new(Vec3::new(-0.3f64, 0.2, 0.0), Vec3::new(0.0f64, 0.7, 0.3));
+
+        for center in [ Vec3::new(0.3f64, 0.6, 0.2), Vec3::new(1.2f64, 0.1, -0.6),
+                        Vec3::new(-1.6f64, 0.0, 0.3), Vec3::new(0.0f64, 0.0, 3.0) ].iter() {
+            let mb      = Iso3::new(center.clone(), na::zero());
+            let contact = collide(&mb.translation(), &b, &mc, &c, &na::zero());
+
+            assert_same_contact_as_gjk(&mb, &b, &mc, &c, contact);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::any::AnyRefExt;
+use nalgebra::na::{Translation, Transform, Rotate};
+use geom::{Ball, Triangle, Mesh, ConcaveGeom};
+use bounding_volume::ball_aabb;
+use narrow::{CollisionDetector, implicit_implicit, concave_geom_geom, ball_triangle};
+use narrow::ball_triangle::{TriangleFace, TriangleEdge, TriangleVertex};
+use contact::Contact;
+use math::{Scalar, Vect, Matrix};
+
+// Feature of the whole mesh, used to identify duplicate contacts.
+#[deriving(PartialEq, Encodable, Decodable, Clone)]
+enum MeshFeature {
+    MeshFace(uint),
+    MeshEdge(uint, uint),
+    MeshVertex(uint)
+}
+
+/// Collision detector between a ball and a triangle mesh.
+///
+/// Each triangle is handled by the same closest-feature algorithm as the `BallTriangle` collision
+/// detector. When adjacent triangles report a contact with the edge or vertex they share, only
+/// the deepest contact is kept.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct BallMesh {
+    prediction:    Scalar,
+    contacts:      Vec<Contact>,
+    features:      Vec<MeshFeature>,
+    interferences: Vec<uint>
+}
+
+impl BallMesh {
+    /// Creates a new persistent collision detector between a ball and a triangle mesh.
+    #[inline]
+    pub fn new(prediction: Scalar) -> BallMesh {
+        BallMesh {
+            prediction:    prediction,
+            contacts:      Vec::new(),
+            features:      Vec::new(),
+            interferences: Vec::new()
+        }
+    }
+
+    fn do_update(&mut self, mb: &Matrix, b: &Ball, mm: &Matrix, m: &Mesh, swap: bool) {
+        self.contacts.clear();
+        self.features.clear();
+
+        let ls_center = mm.inv_transform(&mb.translation());
+        let ls_aabb   = ball_aabb(&ls_center, &(b.radius() + self.prediction));
+
+        m.approx_interferences_with_aabb(&ls_aabb, &mut self.interferences);
+
+        let is = m.indices().as_slice();
+
+        for i in self.interferences.iter() {
+            let t = m.element_at(*i);
+
+            match ball_triangle::collide_local(&ls_center, b, &t, &self.prediction) {
+                Some((mut c, f)) => {
+                    let feature = match f {
+                        TriangleFace      => MeshFace(*i),
+                        TriangleVertex(v) => MeshVertex(is[*i * 3 + v]),
+                        TriangleEdge(e)   => {
+                            let v1 = is[*i * 3 + e];
+                            let v2 = is[*i * 3 + (e + 1) % 3];
+
+                            if v1 < v2 { MeshEdge(v1, v2) } else { MeshEdge(v2, v1) }
+                        }
+                    };
+
+                    c.world1 = mm.transform(&c.world1);
+                    c.world2 = mm.transform(&c.world2);
+                    c.normal = mm.rotate(&c.normal);
+                    c.set_part(false, *i);
+
+                    if swap {
+                        c.flip()
+                    }
+
+                    // NOTE: a ball touches only a few features at a time so a linear search is
+                    // faster than a hash map here.
+                    match self.features.iter().position(|f| *f == feature) {
+                        Some(j) => {
+                            if c.depth > self.contacts.get(j).depth {
+                                *self.contacts.get_mut(j) = c
+                            }
+                        },
+                        None => {
+                            self.features.push(feature);
+                            self.contacts.push(c);
+                        }
+                    }
+                },
+                None => { }
+            }
+        }
+
+        self.interferences.clear();
+    }
+}
+
+impl CollisionDetector<Ball, Mesh> for BallMesh {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &Mesh) {
+        self.do_update(ma, a, mb, b, false)
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        for c in self.contacts.iter() {
+            out_colls.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_:    Option<BallMesh>,
+           ma:   &Matrix,
+           dir:  &Vect,
+           dist: &Scalar,
+           a:    &Ball,
+           mb:   &Matrix,
+           b:    &Mesh) -> Option<Scalar> {
+        // The mesh moves along `-dir` relatively to the ball.
+        let ndir = -dir;
+
+        concave_geom_geom::toi_with(mb, &ndir, dist, b, ma, a, |mt, t, mb, b| {
+            implicit_implicit::toi(mt, &ndir, t.as_ref::<Triangle>().expect("Invalid geometry."), mb,
+                                   b.as_ref::<Ball>().expect("Invalid geometry."))
+        }).map(|(toi, _)| toi)
+    }
+}
+
+/// Collision detector between a triangle mesh and a ball.
+///
+/// Each triangle is handled by the same closest-feature algorithm as the `BallTriangle` collision
+/// detector. When adjacent triangles report a contact with the edge or vertex they share, only
+/// the deepest contact is kept.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct MeshBall {
+    sub_detector: BallMesh
+}
+
+impl MeshBall {
+    /// Creates a new persistent collision detector between a triangle mesh and a ball.
+    #[inline]
+    pub fn new(prediction: Scalar) -> MeshBall {
+        MeshBall {
+            sub_detector: BallMesh::new(prediction)
+        }
+    }
+}
+
+impl CollisionDetector<Mesh, Ball> for MeshBall {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Mesh, mb: &Matrix, b: &Ball) {
+        self.sub_detector.do_update(mb, b, ma, a, true)
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.sub_detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.sub_detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.sub_detector.set_prediction(prediction)
+    }
+
+    #[inline]
+    fn toi(_:    Option<MeshBall>,
+           ma:   &Matrix,
+           dir:  &Vect,
+           dist: &Scalar,
+           a:    &Mesh,
+           mb:   &Matrix,
+           b:    &Ball) -> Option<Scalar> {
+        concave_geom_geom::toi_with(ma, dir, dist, a, mb, b, |mt, t, mb, b| {
+            implicit_implicit::toi(mt, dir, t.as_ref::<Triangle>().expect("Invalid geometry."), mb,
+                                   b.as_ref::<Ball>().expect("Invalid geometry."))
+        }).map(|(toi, _)| toi)
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Ball, Mesh};
+    use contact::Contact;
+    use narrow::CollisionDetector;
+    use super::BallMesh;
+
+    fn contacts(center: Vec3<f64>, m: &Mesh) -> Vec<Contact> {
+        let mut detector = BallMesh::new(0.0);
+        let mut res      = Vec::new();
+
+        detector.update(&Iso3::new(center, na::zero()), &Ball::new(0.5f64), &na::one(), m);
+        detector.colls(&mut res);
+
+        res
+    }
+
+    #[test]
+    fn test_ball_mesh_shared_edge() {
+        // Two triangles forming a ridge along the `z` axis.
+        let vertices = vec!(Vec3::new(0.0f64, 1.0, -1.0), Vec3::new(0.0f64, 1.0, 1.0),
+                            Vec3::new(-1.0f64, 0.0, 0.0), Vec3::new(1.0f64, 0.0, 0.0));
+        let indices  = vec!(0u, 1, 2, 1, 0, 3);
+        let m        = Mesh::new(Arc::new(vertices), Arc::new(indices), None, None);
+
+        let cs = contacts(Vec3::new(0.0, 1.4, 0.2), &m);
+
+        assert!(cs.len() == 1);
+        assert!(na::approx_eq_eps(&cs.get(0).normal, &Vec3::new(0.0, -1.0, 0.0), &1.0e-7));
+    }
+
+    #[test]
+    fn test_ball_mesh_shared_vertex() {
+        // A pyramid with its apex at `(0, 1, 0)`.
+        let vertices = vec!(Vec3::new(0.0f64, 1.0, 0.0),
+                            Vec3::new(-1.0f64, 0.0, -1.0), Vec3::new(1.0f64, 0.0, -1.0),
+                            Vec3::new(1.0f64, 0.0, 1.0), Vec3::new(-1.0f64, 0.0, 1.0));
+        let indices  = vec!(0u, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 1);
+        let m        = Mesh::new(Arc::new(vertices), Arc::new(indices), None, None);
+
+        let cs = contacts(Vec3::new(0.0, 1.4, 0.0), &m);
+
+        assert!(cs.len() == 1);
+        assert!(na::approx_eq_eps(&cs.get(0).normal, &Vec3::new(0.0, -1.0, 0.0), &1.0e-7));
+
+        // The contact is the deepest of the duplicates.
+        let depth = cs.get(0).depth;
+
+        assert!(depth > 0.0);
+        assert!(contacts(Vec3::new(0.0, 1.3, 0.0), &m).get(0).depth > depth);
+    }
+}
@@ -0,0 +1,200 @@
+use nalgebra::na::{Translation, Transform};
+use nalgebra::na;
+use geom::{Ball, Segment};
+use narrow::{CollisionDetector, implicit_implicit, segment_segment};
+use contact::{Contact, FaceFeature};
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between a ball and a segment.
+///
+/// The segment margin is taken into account.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct BallSegment {
+    prediction: Scalar,
+    contact:    Option<Contact>
+}
+
+impl BallSegment {
+    /// Creates a new persistent collision detector between a ball and a segment.
+    #[inline]
+    pub fn new(prediction: Scalar) -> BallSegment {
+        BallSegment {
+            prediction: prediction,
+            contact:    None
+        }
+    }
+}
+
+impl CollisionDetector<Ball, Segment> for BallSegment {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &Segment) {
+        self.contact = collide(&ma.translation(), a, mb, b, &self.prediction);
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        match self.contact {
+            Some(ref c) => out_colls.push(c.clone()),
+            None        => ()
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_:   Option<BallSegment>,
+           ma:  &Matrix,
+           dir: &Vect,
+           _:   &Scalar,
+           a:   &Ball,
+           mb:  &Matrix,
+           b:   &Segment) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Collision detector between a segment and a ball.
+///
+/// The segment margin is taken into account.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct SegmentBall {
+    prediction: Scalar,
+    contact:    Option<Contact>
+}
+
+impl SegmentBall {
+    /// Creates a new persistent collision detector between a segment and a ball.
+    #[inline]
+    pub fn new(prediction: Scalar) -> SegmentBall {
+        SegmentBall {
+            prediction: prediction,
+            contact:    None
+        }
+    }
+}
+
+impl CollisionDetector<Segment, Ball> for SegmentBall {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Segment, mb: &Matrix, b: &Ball) {
+        self.contact = collide(&mb.translation(), b, ma, a, &self.prediction);
+
+        for c in self.contact.mut_iter() {
+            c.flip()
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        match self.contact {
+            Some(ref c) => out_colls.push(c.clone()),
+            None        => ()
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_:   Option<SegmentBall>,
+           ma:  &Matrix,
+           dir: &Vect,
+           _:   &Scalar,
+           a:   &Segment,
+           mb:  &Matrix,
+           b:   &Ball) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Computes the contact point between a ball and a segment.
+///
+/// The contact normal points toward the segment.
+#[inline]
+pub fn collide(center:     &Vect,
+               b:          &Ball,
+               ms:         &Matrix,
+               s:          &Segment,
+               prediction: &Scalar)
+               -> Option<Contact> {
+    collide_with_radii(
+        center,
+        &b.radius(),
+        &ms.transform(s.a()),
+        &ms.transform(s.b()),
+        &s.margin(),
+        prediction)
+}
+
+/// Computes the contact point between a ball and a segment enlarged by the given radius.
+///
+/// The segment is given in world space. The contact normal points toward the segment.
+pub fn collide_with_radii(center:     &Vect,
+                          radius:     &Scalar,
+                          a:          &Vect,
+                          b:          &Vect,
+                          s_radius:   &Scalar,
+                          prediction: &Scalar)
+                          -> Option<Contact> {
+    let (_, proj) = segment_segment::closest_points(center, center, a, b);
+
+    segment_segment::collide_points(center, radius, &proj, s_radius, &(b - *a), &na::zero(), prediction).map(|mut c| {
+        c.set_features(FaceFeature(0), segment_segment::segment_feature(a, b, &proj));
+        c
+    })
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3, Translation};
+    use nalgebra::na;
+    use geom::{Ball, Segment};
+    use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+    use narrow::algorithm::gjk::Projection;
+    use narrow::implicit_implicit;
+    use super::collide;
+
+    #[test]
+    fn test_ball_segment_gjk() {
+        let b = Ball::new(0.5f64);
+        let s = Segment::new_with_margin(Vec3::new(-1.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.2);
+        let ms = Iso3::new(Vec3::new(0.1f64, -0.2, 0.3), Vec3::new(0.2f64, 0.4, 0.1));
+
+        for center in [ Vec3::new(0.3f64, 0.4, 0.2), Vec3::new(1.4f64, 0.1, 0.5),
+                        Vec3::new(-1.5f64, 0.0, 0.0), Vec3::new(0.0f64, 3.0, 0.0) ].iter() {
+            let mb            = Iso3::new(center.clone(), na::zero());
+            let mut simplex   = JohnsonSimplex::new_w_tls();
+            let c             = collide(&mb.translation(), &b, &ms, &s, &na::zero());
+
+            match implicit_implicit::collide(&mb, &b, &ms, &s, &na::zero(), &mut simplex, None) {
+                Projection(gjk) => {
+                    let c = c.unwrap();
+
+                    assert!(na::approx_eq_eps(&c.depth, &gjk.depth, &1.0e-5));
+                    assert!(na::approx_eq_eps(&c.normal, &gjk.normal, &1.0e-5));
+                },
+                _ => assert!(c.is_none())
+            }
+        }
+    }
+}
@@ -0,0 +1,375 @@
+use std::num::Zero;
+use nalgebra::na::{Translation, Transform, Rotate};
+use nalgebra::na;
+use geom::{Ball, Triangle};
+use narrow::{CollisionDetector, implicit_implicit, segment_segment};
+use contact::{Contact, FeatureId, VertexFeature, EdgeFeature, FaceFeature};
+use math::{Scalar, Vect, Matrix};
+
+/// The feature of a triangle closest to a point.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub enum TriangleFeature {
+    /// The interior of the triangle.
+    TriangleFace,
+    /// The i-th edge of the triangle, i.e., `ab`, `bc` or `ca` for `i` respectively equal to 0, 1 or
+    /// 2.
+    TriangleEdge(uint),
+    /// The i-th vertex of the triangle, i.e., `a`, `b` or `c` for `i` respectively equal to 0, 1 or
+    /// 2.
+    TriangleVertex(uint)
+}
+
+impl TriangleFeature {
+    /// The generic feature identifier corresponding to this triangle feature.
+    #[inline]
+    pub fn feature_id(&self) -> FeatureId {
+        match *self {
+            TriangleFace      => FaceFeature(0),
+            TriangleEdge(i)   => EdgeFeature(i),
+            TriangleVertex(i) => VertexFeature(i)
+        }
+    }
+}
+
+/// Collision detector between a ball and a triangle.
+///
+/// The triangle margin is taken into account. The feature of the triangle the ball is in contact
+/// with is reported as well.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct BallTriangle {
+    prediction: Scalar,
+    contact:    Option<(Contact, TriangleFeature)>
+}
+
+impl BallTriangle {
+    /// Creates a new persistent collision detector between a ball and a triangle.
+    #[inline]
+    pub fn new(prediction: Scalar) -> BallTriangle {
+        BallTriangle {
+            prediction: prediction,
+            contact:    None
+        }
+    }
+
+    /// The feature of the triangle touched by the ball during the last update.
+    #[inline]
+    pub fn feature(&self) -> Option<TriangleFeature> {
+        self.contact.as_ref().map(|c| c.ref1().clone())
+    }
+}
+
+impl CollisionDetector<Ball, Triangle> for BallTriangle {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &Triangle) {
+        self.contact = collide(&ma.translation(), a, mb, b, &self.prediction);
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        match self.contact {
+            Some((ref c, _)) => out_colls.push(c.clone()),
+            None             => ()
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_:   Option<BallTriangle>,
+           ma:  &Matrix,
+           dir: &Vect,
+           _:   &Scalar,
+           a:   &Ball,
+           mb:  &Matrix,
+           b:   &Triangle) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Collision detector between a triangle and a ball.
+///
+/// The triangle margin is taken into account. The feature of the triangle the ball is in contact
+/// with is reported as well.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct TriangleBall {
+    sub_detector: BallTriangle
+}
+
+impl TriangleBall {
+    /// Creates a new persistent collision detector between a triangle and a ball.
+    #[inline]
+    pub fn new(prediction: Scalar) -> TriangleBall {
+        TriangleBall {
+            sub_detector: BallTriangle::new(prediction)
+        }
+    }
+
+    /// The feature of the triangle touched by the ball during the last update.
+    #[inline]
+    pub fn feature(&self) -> Option<TriangleFeature> {
+        self.sub_detector.feature()
+    }
+}
+
+impl CollisionDetector<Triangle, Ball> for TriangleBall {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Triangle, mb: &Matrix, b: &Ball) {
+        self.sub_detector.update(mb, b, ma, a);
+
+        for c in self.sub_detector.contact.mut_iter() {
+            c.mut0().flip()
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.sub_detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.sub_detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.sub_detector.set_prediction(prediction)
+    }
+
+    #[inline]
+    fn toi(_:   Option<TriangleBall>,
+           ma:  &Matrix,
+           dir: &Vect,
+           _:   &Scalar,
+           a:   &Triangle,
+           mb:  &Matrix,
+           b:   &Ball) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Computes the contact point between a ball and a triangle, and the triangle feature it lies on.
+///
+/// The contact normal points toward the triangle.
+#[inline]
+pub fn collide(center:     &Vect,
+               b:          &Ball,
+               mt:         &Matrix,
+               t:          &Triangle,
+               prediction: &Scalar)
+               -> Option<(Contact, TriangleFeature)> {
+    let ls_center = mt.inv_transform(center);
+
+    match collide_local(&ls_center, b, t, prediction) {
+        Some((mut c, f)) => {
+            c.world1 = mt.transform(&c.world1);
+            c.world2 = mt.transform(&c.world2);
+            c.normal = mt.rotate(&c.normal);
+
+            Some((c, f))
+        },
+        None => None
+    }
+}
+
+/// Computes the contact point between a ball and a triangle expressed in the same local space.
+///
+/// The contact is expressed in the triangle local space.
+pub fn collide_local(center:     &Vect,
+                     b:          &Ball,
+                     t:          &Triangle,
+                     prediction: &Scalar)
+                     -> Option<(Contact, TriangleFeature)> {
+    let (proj, feature) = closest_point(t, center);
+
+    let ab = t.b() - *t.a();
+    let ac = t.c() - *t.a();
+    let r1 = b.radius();
+    let r2 = t.margin();
+
+    if feature == TriangleFace && na::dim::<Vect>() == 2 && na::sqnorm(&(proj - *center)).is_zero() {
+        // In 2D, the ball center is inside of the triangle: push it out of the closest edge.
+        let mut best_dist = na::zero::<Scalar>();
+        let mut best      = None;
+
+        for i in range(0u, 3) {
+            let (e1, e2) = edge(t, i);
+            let (_, p)   = segment_segment::closest_points(center, center, e1, e2);
+            let dist     = na::norm(&(p - *center));
+
+            if best.is_none() || dist < best_dist {
+                best_dist = dist;
+                best      = Some((i, p));
+            }
+        }
+
+        let (i, p)     = best.unwrap();
+        let (e1, e2)   = edge(t, i);
+        let mut normal = segment_segment::degenerate_normal(&(e2 - *e1), &na::zero());
+        let inside     = na::dot(&(center - *e1), &normal);
+
+        if inside < na::zero() {
+            normal = -normal;
+        }
+
+        let mut c = Contact::new(center + normal * r1, p - normal * r2, normal, r1 + r2 + best_dist);
+
+        c.set_features(FaceFeature(0), EdgeFeature(i));
+
+        return Some((c, TriangleEdge(i)))
+    }
+
+    segment_segment::collide_points(center, &r1, &proj, &r2, &ab, &ac, prediction).map(|mut c| {
+        c.set_features(FaceFeature(0), feature.feature_id());
+        (c, feature)
+    })
+}
+
+/// Computes the point of a triangle closest to a given point, and the feature it lies on.
+///
+/// The triangle and the point must be expressed in the same space.
+pub fn closest_point(t: &Triangle, p: &Vect) -> (Vect, TriangleFeature) {
+    let a = t.a();
+    let b = t.b();
+    let c = t.c();
+
+    let ab = b - *a;
+    let ac = c - *a;
+    let ap = p - *a;
+
+    let d1 = na::dot(&ab, &ap);
+    let d2 = na::dot(&ac, &ap);
+
+    if d1 <= na::zero() && d2 <= na::zero() {
+        return (a.clone(), TriangleVertex(0))
+    }
+
+    let bp = p - *b;
+    let d3 = na::dot(&ab, &bp);
+    let d4 = na::dot(&ac, &bp);
+
+    if d3 >= na::zero() && d4 <= d3 {
+        return (b.clone(), TriangleVertex(1))
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+
+    if vc <= na::zero() && d1 >= na::zero() && d3 <= na::zero() {
+        let v = d1 / (d1 - d3);
+
+        return (a + ab * v, TriangleEdge(0))
+    }
+
+    let cp = p - *c;
+    let d5 = na::dot(&ab, &cp);
+    let d6 = na::dot(&ac, &cp);
+
+    if d6 >= na::zero() && d5 <= d6 {
+        return (c.clone(), TriangleVertex(2))
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+
+    if vb <= na::zero() && d2 >= na::zero() && d6 <= na::zero() {
+        let w = d2 / (d2 - d6);
+
+        return (a + ac * w, TriangleEdge(2))
+    }
+
+    let va = d3 * d6 - d5 * d4;
+
+    if va <= na::zero() && d4 - d3 >= na::zero() && d5 - d6 >= na::zero() {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+
+        return (b + (c - *b) * w, TriangleEdge(1))
+    }
+
+    let sum = va + vb + vc;
+
+    if sum.is_zero() {
+        // Degenerate triangle: find the closest edge.
+        let mut best: Option<(Scalar, Vect, uint)> = None;
+
+        for i in range(0u, 3) {
+            let (e1, e2) = edge(t, i);
+            let (_, proj) = segment_segment::closest_points(p, p, e1, e2);
+            let sqdist    = na::sqnorm(&(proj - *p));
+
+            if best.as_ref().map_or(true, |b| sqdist < *b.ref0()) {
+                best = Some((sqdist, proj, i));
+            }
+        }
+
+        let (_, proj, i) = best.unwrap();
+
+        return (proj, TriangleEdge(i))
+    }
+
+    let v = vb / sum;
+    let w = vc / sum;
+
+    (a + ab * v + ac * w, TriangleFace)
+}
+
+/// The extremities of the i-th edge of a triangle.
+#[inline]
+pub fn edge<'a>(t: &'a Triangle, i: uint) -> (&'a Vect, &'a Vect) {
+    match i {
+        0 => (t.a(), t.b()),
+        1 => (t.b(), t.c()),
+        2 => (t.c(), t.a()),
+        _ => fail!("A triangle has only three edges.")
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3, Translation};
+    use nalgebra::na;
+    use geom::{Ball, Triangle};
+    use test_utils::assert_same_contact_as_gjk;
+    use super::{collide, TriangleFace, TriangleEdge, TriangleVertex};
+
+    #[test]
+    fn test_ball_triangle_features() {
+        let b  = Ball::new(0.5f64);
+        let t  = Triangle::new_with_margin(na::zero(), Vec3::new(1.0f64, 0.0, 0.0), Vec3::new(0.0f64, 1.0, 0.0), 0.0);
+        let mt = na::one::<Iso3<f64>>();
+
+        let face   = collide(&Vec3::new(0.2f64, 0.2, 0.3), &b, &mt, &t, &na::zero());
+        let edge   = collide(&Vec3::new(0.5f64, -0.2, 0.1), &b, &mt, &t, &na::zero());
+        let vertex = collide(&Vec3::new(1.2f64, -0.1, 0.1), &b, &mt, &t, &na::zero());
+        let none   = collide(&Vec3::new(0.2f64, 0.2, 2.0), &b, &mt, &t, &na::zero());
+
+        assert!(*face.unwrap().ref1() == TriangleFace);
+        assert!(*edge.unwrap().ref1() == TriangleEdge(0));
+        assert!(*vertex.unwrap().ref1() == TriangleVertex(1));
+        assert!(none.is_none());
+    }
+
+    #[test]
+    fn test_ball_triangle_gjk() {
+        let b  = Ball::new(0.5f64);
+        let t  = Triangle::new_with_margin(na::zero(), Vec3::new(1.0f64, 0.0, 0.0), Vec3::new(0.0f64, 1.0, 0.0), 0.1);
+        let mt = Iso3::new(Vec3::new(0.1f64, 0.2, -0.3), Vec3::new(0.3f64, -0.2, 0.5));
+
+        for center in [ Vec3::new(0.2f64, 0.4, 0.3), Vec3::new(1.2f64, -0.2, 0.1),
+                        Vec3::new(-0.4f64, 0.7, -0.2), Vec3::new(0.0f64, 0.0, 3.0) ].iter() {
+            let mb      = Iso3::new(center.clone(), na::zero());
+            let contact = collide(&mb.translation(), &b, &mt, &t, &na::zero());
+
+            assert_same_contact_as_gjk(&mb, &b, &mt, &t, contact.map(|(c, _)| c));
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use nalgebra::na::{Translation, Norm};
+use nalgebra::na;
+use math::{Scalar, Vect, Matrix};
+use geom::{Ball, BezierCurve};
+use bounding_volume::HasBoundingSphere;
+use narrow::CollisionDetector;
+use narrow::bezier_curve_implicit;
+use contact::{Contact, FaceFeature, UnknownFeature};
+
+// Subdivision depth of the curve before the numerical refinement.
+static MAX_DEPTH: uint = 8;
+// Number of Gauss-Newton iterations.
+static NITER:     uint = 10;
+
+/// Collision detector between a ball and a bezier curve.
+#[deriving(Clone)]
+pub struct BallBezierCurve {
+    prediction: Scalar,
+    contacts:   Vec<Contact>,
+    points:     Vec<Vect>
+}
+
+impl BallBezierCurve {
+    /// Creates a new collision detector with the given prediction margin.
+    pub fn new(prediction: Scalar) -> BallBezierCurve {
+        BallBezierCurve {
+            prediction: prediction,
+            contacts:   Vec::new(),
+            points:     Vec::new()
+        }
+    }
+}
+
+impl CollisionDetector<Ball, BezierCurve> for BallBezierCurve {
+    fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &BezierCurve) {
+        self.points.clear();
+        self.contacts.clear();
+
+        let pt = na::inv_transform(mb, &ma.translation());
+
+        closest_points(&pt, &(a.radius() + self.prediction), b, (na::zero(), na::one()), MAX_DEPTH,
+                       &mut self.points);
+
+        let scale = b.bounding_sphere(&na::one()).radius();
+
+        for (i, local_pt) in self.points.iter().enumerate() {
+            // A closest point on the boundary of two sub-curves is found twice.
+            if self.points.slice_to(i).iter().any(|p| is_duplicate(p, local_pt, &scale)) {
+                continue;
+            }
+
+            let pt = na::transform(mb, local_pt);
+            let mut normal = pt - ma.translation();
+            let gap        = normal.normalize();
+
+            if gap <= a.radius() + self.prediction {
+                let mut c = Contact::new_with_features(ma.translation() + normal * a.radius(), pt.clone(),
+                                                       normal, a.radius() - gap, FaceFeature(0),
+                                                       UnknownFeature);
+
+                c.set_id_from_point(local_pt, &scale);
+
+                self.contacts.push(c);
+            }
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        for c in self.contacts.iter() {
+            out_colls.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_: Option<BallBezierCurve>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Ball, mb: &Matrix,
+           b: &BezierCurve) -> Option<Scalar> {
+        bezier_curve_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Collision detector between a bezier curve and a ball.
+#[deriving(Clone)]
+pub struct BezierCurveBall {
+    detector: BallBezierCurve
+}
+
+impl BezierCurveBall {
+    /// Creates a new collision detector with the given prediction margin.
+    pub fn new(prediction: Scalar) -> BezierCurveBall {
+        BezierCurveBall {
+            detector: BallBezierCurve::new(prediction)
+        }
+    }
+}
+
+impl CollisionDetector<BezierCurve, Ball> for BezierCurveBall {
+    fn update(&mut self, ma: &Matrix, a: &BezierCurve, mb: &Matrix, b: &Ball) {
+        self.detector.update(mb, b, ma, a);
+
+        for c in self.detector.contacts.mut_iter() {
+            c.flip();
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.detector.set_prediction(prediction)
+    }
+
+    #[inline]
+    fn toi(_: Option<BezierCurveBall>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &BezierCurve,
+           mb: &Matrix, b: &Ball) -> Option<Scalar> {
+        bezier_curve_implicit::toi(mb, &-*dir, b, ma, a)
+    }
+}
+
+// Collects the points of `c` locally closest to `pt`, ignoring the parts of the curve farther than
+// `max_dist`. `range` is the parametric domain of `c` on the original curve.
+fn closest_points(pt:        &Vect,
+                  max_dist:  &Scalar,
+                  c:         &BezierCurve,
+                  range:     (Scalar, Scalar),
+                  max_depth: uint,
+                  out:       &mut Vec<Vect>) {
+    let bs = c.bounding_sphere(&na::one());
+
+    if na::norm(&(*bs.center() - *pt)) > bs.radius() + *max_dist {
+        return;
+    }
+
+    let (t0, t1) = range;
+
+    if max_depth == 0 {
+        let t = closest_parameter(pt, c, NITER);
+
+        if is_valid_parameter(pt, c, &t, &range) {
+            out.push(c.at(&t, &mut BezierCurve::new_evaluation_cache()))
+        }
+    }
+    else {
+        // subdivide
+        let _0_5: Scalar = na::cast(0.5);
+        let tmid          = (t0 + t1) * _0_5;
+        let mut left      = BezierCurve::new_with_degree(c.degree());
+        let mut right     = BezierCurve::new_with_degree(c.degree());
+
+        c.subdivide_at(&_0_5, &mut left, &mut right);
+
+        closest_points(pt, max_dist, &left,  (t0, tmid), max_depth - 1, out);
+        closest_points(pt, max_dist, &right, (tmid, t1), max_depth - 1, out);
+    }
+}
+
+/// Computes the parameter of the point of a bezier curve locally closest to `pt`, using the
+/// Gauss-Newton method starting from the parametric center of the curve.
+///
+/// The result is clamped to the curve parametric domain.
+pub fn closest_parameter(pt: &Vect, c: &BezierCurve, niter: uint) -> Scalar {
+    let mut diff = BezierCurve::new_with_degree(c.degree() - 1);
+
+    c.diff(&mut diff);
+
+    let mut cache = BezierCurve::new_evaluation_cache();
+    let mut t: Scalar = na::cast(0.5);
+
+    for _ in range(0, niter) {
+        let dpt = c.at(&t, &mut cache) - *pt;
+        let dt  = diff.at(&t, &mut cache);
+        let sqn = na::sqnorm(&dt);
+
+        if sqn == na::zero() {
+            break;
+        }
+
+        t = t - na::dot(&dpt, &dt) / sqn;
+
+        if t < na::zero() {
+            t = na::zero();
+        }
+        else if t > na::one() {
+            t = na::one();
+        }
+    }
+
+    t
+}
+
+/// Tests if the parameter `t` of the point of the sub-curve `c` closest to `pt` identifies a
+/// closest point of the original curve. `range` is the parametric domain of `c` on the original
+/// curve.
+///
+/// Closest points clamped to the boundary of the sub-curve are rejected, unless they lie on the
+/// boundary of the original curve or they are stationary points of the distance to `pt`. In the
+/// latter case, the same point is found on the neighbor sub-curve: duplicates have to be merged.
+pub fn is_valid_parameter(pt: &Vect, c: &BezierCurve, t: &Scalar, range: &(Scalar, Scalar)) -> bool {
+    let _0: Scalar = na::zero();
+    let _1: Scalar = na::one();
+    let (ref t0, ref t1) = *range;
+
+    if (*t > _0 || *t0 == _0) && (*t < _1 || *t1 == _1) {
+        return true;
+    }
+
+    let mut diff  = BezierCurve::new_with_degree(c.degree() - 1);
+    let mut cache = BezierCurve::new_evaluation_cache();
+
+    c.diff(&mut diff);
+
+    let dpt = c.at(t, &mut cache) - *pt;
+    let dt  = diff.at(t, &mut cache);
+    let eps: Scalar = na::cast(1.0e-3);
+
+    na::dot(&dpt, &dt).abs() <= eps * na::norm(&dpt) * na::norm(&dt)
+}
+
+/// Tests if two closest points found on neighbor sub-curves are the same. `scale` is the size of
+/// the original curve.
+pub fn is_duplicate(p: &Vect, q: &Vect, scale: &Scalar) -> bool {
+    let _rel_tol: Scalar = na::cast(1.0e-4);
+    let eps = *scale * _rel_tol;
+
+    na::sqnorm(&(*p - *q)) <= eps * eps
+}
+
+#[cfg(dim2, f64, test)]
+mod test {
+    use nalgebra::na::{Vec2, Iso2};
+    use nalgebra::na;
+    use geom::{Ball, BezierCurve};
+    use narrow::CollisionDetector;
+    use ray::{Ray, bezier_curve_ray_intersection};
+    use super::{BallBezierCurve, BezierCurveBall};
+
+    // A straight bezier curve from (-1, 0) to (1, 0), with a non-uniform parametrization.
+    fn curve() -> BezierCurve {
+        BezierCurve::new(vec!(Vec2::new(-1.0f64, 0.0), Vec2::new(-0.5, 0.0), Vec2::new(1.0, 0.0)))
+    }
+
+    #[test]
+    fn test_ball_bezier_curve() {
+        let c  = curve();
+        let b  = Ball::new(0.5f64);
+        let mc = na::one();
+        let mut d = BallBezierCurve::new(0.1);
+
+        d.update(&Iso2::new(Vec2::new(0.3f64, 0.4), na::zero()), &b, &mc, &c);
+        assert!(d.num_colls() == 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        assert!(na::approx_eq_eps(&contacts.get(0).depth, &0.1, &1.0e-5));
+        assert!(na::approx_eq_eps(&contacts.get(0).world2, &Vec2::new(0.3, 0.0), &1.0e-5));
+
+        d.update(&Iso2::new(Vec2::new(0.3f64, 1.0), na::zero()), &b, &mc, &c);
+        assert!(d.num_colls() == 0);
+    }
+
+    #[test]
+    fn test_ball_bezier_curve_at_subdivision_boundary() {
+        let c  = curve();
+        let b  = Ball::new(0.5f64);
+        let mc = na::one();
+        let mut d = BallBezierCurve::new(0.1);
+
+        // `x(0.5) = -0.25` is on the boundary of the first two sub-curves: the closest point is
+        // found on both of them.
+        d.update(&Iso2::new(Vec2::new(-0.25f64, 0.4), na::zero()), &b, &mc, &c);
+        assert!(d.num_colls() == 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        assert!(na::approx_eq_eps(&contacts.get(0).depth, &0.1, &1.0e-5));
+        assert!(na::approx_eq_eps(&contacts.get(0).world2, &Vec2::new(-0.25, 0.0), &1.0e-5));
+    }
+
+    #[test]
+    fn test_ray_bezier_curve() {
+        let c   = curve();
+        let ray = Ray::new(Vec2::new(-0.5f64, 2.0), Vec2::new(0.0, -1.0));
+
+        let (inter, t) = bezier_curve_ray_intersection(&c, &ray).expect("The ray should hit.");
+
+        // The curve is `x(t) = -1 + t + t²`, thus `x(t) = -0.5` for `t = (sqrt(3) - 1) / 2`.
+        assert!(na::approx_eq_eps(&inter.toi, &2.0, &1.0e-5));
+        assert!(na::approx_eq_eps(&inter.normal, &Vec2::new(0.0, 1.0), &1.0e-5));
+        assert!(na::approx_eq_eps(&t, &((3.0f64.sqrt() - 1.0) / 2.0), &1.0e-5));
+    }
+
+    #[test]
+    fn test_ball_bezier_curve_toi() {
+        // An arch from (-1, 0) to (1, 0), with its top at (0, 0.5).
+        let c    = BezierCurve::new(vec!(Vec2::new(-1.0f64, 0.0), Vec2::new(0.0, 1.0), Vec2::new(1.0, 0.0)));
+        let b    = Ball::new(0.25f64);
+        let mb   = Iso2::new(Vec2::new(0.0f64, 3.0), na::zero());
+        let mc   = na::one();
+        let down = Vec2::new(0.0f64, -1.0);
+
+        let t = CollisionDetector::toi(None::<BallBezierCurve>, &mb, &down, &10.0, &b, &mc, &c);
+
+        assert!(na::approx_eq_eps(&t.expect("The ball should hit the arch."), &2.25, &1.0e-5));
+
+        let flipped = CollisionDetector::toi(None::<BezierCurveBall>, &mc, &-down, &10.0, &c, &mb, &b);
+
+        assert!(flipped == t);
+
+        // The ball passes next to the end of the arch.
+        let side = Iso2::new(Vec2::new(1.3f64, 3.0), na::zero());
+
+        assert!(CollisionDetector::toi(None::<BallBezierCurve>, &side, &down, &10.0, &b, &mc, &c).is_none());
+    }
+}
@@ -0,0 +1,404 @@
+use std::num::Zero;
+use nalgebra::na::{Translation, Rotate, Transform, Norm};
+use nalgebra::na;
+use math::{Scalar, Vect, Matrix};
+use geom::{BezierCurve, Convex};
+use implicit::Implicit;
+use bounding_volume::HasBoundingSphere;
+use narrow::CollisionDetector;
+use narrow::bezier_curve_ball;
+use narrow::implicit_implicit;
+use contact::Contact;
+
+// Subdivision depth of the curve before the numerical refinement.
+static MAX_DEPTH: uint = 8;
+// Number of refinement iterations.
+static NITER:     uint = 10;
+// Subdivision depth of the curve for the time of impact computation.
+static TOI_MAX_DEPTH: uint = 10;
+
+/// Collision detector between a geometry implementing the `Implicit` trait and a bezier curve.
+///
+/// The curve is subdivided until its parts are small enough for the closest points to be refined
+/// numerically, using the support mapping function of the implicit geometry.
+pub struct ImplicitBezierCurve<G> {
+    prediction: Scalar,
+    contacts:   Vec<Contact>
+}
+
+impl<G> Clone for ImplicitBezierCurve<G> {
+    fn clone(&self) -> ImplicitBezierCurve<G> {
+        ImplicitBezierCurve {
+            prediction: self.prediction.clone(),
+            contacts:   self.contacts.clone()
+        }
+    }
+}
+
+impl<G> ImplicitBezierCurve<G> {
+    /// Creates a new collision detector with the given prediction margin.
+    pub fn new(prediction: Scalar) -> ImplicitBezierCurve<G> {
+        ImplicitBezierCurve {
+            prediction: prediction,
+            contacts:   Vec::new()
+        }
+    }
+}
+
+impl<G: Implicit<Vect, Matrix>> CollisionDetector<G, BezierCurve> for ImplicitBezierCurve<G> {
+    fn update(&mut self, ma: &Matrix, a: &G, mb: &Matrix, b: &BezierCurve) {
+        self.contacts.clear();
+
+        let ls_ma     = na::inv(mb).expect("The transformation `mb` must be inversible.") * *ma;
+        let mut local = Vec::new();
+
+        do_collide(&ls_ma, a, b, &self.prediction, (na::zero(), na::one()), MAX_DEPTH, &mut local);
+
+        let scale = b.bounding_sphere(&na::one()).radius();
+
+        for (i, &(ref p, ref q, ref n, ref depth)) in local.iter().enumerate() {
+            // A closest point on the boundary of two sub-curves is found twice.
+            let duplicate = local.slice_to(i).iter().any(|&(_, ref fq, _, _)|
+                bezier_curve_ball::is_duplicate(fq, q, &scale)
+            );
+
+            if duplicate {
+                continue;
+            }
+
+            let mut c = Contact::new(mb.transform(p), mb.transform(q), mb.rotate(n), depth.clone());
+
+            c.set_id_from_point(q, &scale);
+
+            self.contacts.push(c);
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        for c in self.contacts.iter() {
+            out_colls.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_: Option<ImplicitBezierCurve<G>>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &G, mb: &Matrix,
+           b: &BezierCurve) -> Option<Scalar> {
+        toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Collision detector between a bezier curve and a geometry implementing the `Implicit` trait.
+pub struct BezierCurveImplicit<G> {
+    detector: ImplicitBezierCurve<G>
+}
+
+impl<G> Clone for BezierCurveImplicit<G> {
+    fn clone(&self) -> BezierCurveImplicit<G> {
+        BezierCurveImplicit {
+            detector: self.detector.clone()
+        }
+    }
+}
+
+impl<G> BezierCurveImplicit<G> {
+    /// Creates a new collision detector with the given prediction margin.
+    pub fn new(prediction: Scalar) -> BezierCurveImplicit<G> {
+        BezierCurveImplicit {
+            detector: ImplicitBezierCurve::new(prediction)
+        }
+    }
+}
+
+impl<G: Implicit<Vect, Matrix>> CollisionDetector<BezierCurve, G> for BezierCurveImplicit<G> {
+    fn update(&mut self, ma: &Matrix, a: &BezierCurve, mb: &Matrix, b: &G) {
+        self.detector.update(mb, b, ma, a);
+
+        for c in self.detector.contacts.mut_iter() {
+            c.flip();
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.detector.set_prediction(prediction)
+    }
+
+    #[inline]
+    fn toi(_: Option<BezierCurveImplicit<G>>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &BezierCurve,
+           mb: &Matrix, b: &G) -> Option<Scalar> {
+        toi(mb, &-*dir, b, ma, a)
+    }
+}
+
+/// Computes the time of impact of an implicit geometry moving along the direction `dir` with a
+/// bezier curve.
+///
+/// The curve is subdivided, and its parts are culled using the convex hull of their control
+/// points. The time of impact with the convex hull of the smallest sub-curves is returned: this is
+/// a lower bound of the exact time of impact, which gets more accurate as the sub-curves flatten.
+pub fn toi<G: Implicit<Vect, Matrix>>(m1: &Matrix, dir: &Vect, g1: &G, m2: &Matrix, c2: &BezierCurve)
+                                      -> Option<Scalar> {
+    let mut best = None;
+
+    do_toi(m1, dir, g1, m2, c2, TOI_MAX_DEPTH, &mut best);
+
+    best
+}
+
+fn do_toi<G: Implicit<Vect, Matrix>>(m1:        &Matrix,
+                                     dir:       &Vect,
+                                     g1:        &G,
+                                     m2:        &Matrix,
+                                     c:         &BezierCurve,
+                                     max_depth: uint,
+                                     best:      &mut Option<Scalar>) {
+    // The sub-curve is inside of the convex hull of its control points.
+    let hull  = Convex::new_with_margin(c.control_points().to_owned(), na::zero());
+    let start = match implicit_implicit::toi(m1, dir, g1, m2, &hull) {
+        None      => return,
+        Some(toi) => toi
+    };
+
+    match *best {
+        Some(ref best_toi) if *best_toi <= start => return,
+        _ => { }
+    }
+
+    if max_depth == 0 {
+        *best = Some(start);
+    }
+    else {
+        let mut left  = BezierCurve::new_with_degree(c.degree());
+        let mut right = BezierCurve::new_with_degree(c.degree());
+
+        c.subdivide_at(&na::cast(0.5), &mut left, &mut right);
+
+        do_toi(m1, dir, g1, m2, &left,  max_depth - 1, best);
+        do_toi(m1, dir, g1, m2, &right, max_depth - 1, best);
+    }
+}
+
+// Collects the contacts between the implicit geometry `g` transformed by `m` and the bezier curve
+// `c`. Everything is expressed in the local space of the curve. `range` is the parametric domain
+// of `c` on the original curve.
+//
+// Each contact is given as the closest point on `g`, the closest point on `c`, the normal (from `g`
+// to `c`) and the penetration depth.
+fn do_collide<G: Implicit<Vect, Matrix>>(m:          &Matrix,
+                                         g:          &G,
+                                         c:          &BezierCurve,
+                                         prediction: &Scalar,
+                                         range:      (Scalar, Scalar),
+                                         max_depth:  uint,
+                                         out:        &mut Vec<(Vect, Vect, Vect, Scalar)>) {
+    // Use the support mapping function to check if `g` is close to the curve bounding sphere.
+    let bs      = c.bounding_sphere(&na::one());
+    let center  = m.translation();
+    let mut pt  = center.clone();
+    let mut dir = *bs.center() - center;
+
+    if !dir.normalize().is_zero() {
+        pt = g.support_point(m, &dir);
+
+        if na::dot(&(*bs.center() - pt), &dir) > bs.radius() + *prediction {
+            return;
+        }
+    }
+
+    let (t0, t1) = range;
+
+    if max_depth == 0 {
+        match closest_points(m, g, c, &pt, &range) {
+            Some(res) => {
+                let (_, _, _, ref depth) = res;
+
+                if *depth >= -*prediction {
+                    out.push(res.clone())
+                }
+            },
+            None => { }
+        }
+    }
+    else {
+        // subdivide
+        let _0_5: Scalar = na::cast(0.5);
+        let tmid          = (t0 + t1) * _0_5;
+        let mut left      = BezierCurve::new_with_degree(c.degree());
+        let mut right     = BezierCurve::new_with_degree(c.degree());
+
+        c.subdivide_at(&_0_5, &mut left, &mut right);
+
+        do_collide(m, g, &left,  prediction, (t0, tmid), max_depth - 1, out);
+        do_collide(m, g, &right, prediction, (tmid, t1), max_depth - 1, out);
+    }
+}
+
+// Refines the closest points between `g` and `c` by projecting alternatively on the curve and on
+// the implicit geometry (with its support mapping function). `domain` is the parametric domain of
+// `c` on the original curve.
+fn closest_points<G: Implicit<Vect, Matrix>>(m:      &Matrix,
+                                             g:      &G,
+                                             c:      &BezierCurve,
+                                             pt:     &Vect,
+                                             domain: &(Scalar, Scalar))
+                                             -> Option<(Vect, Vect, Vect, Scalar)> {
+    let center    = m.translation();
+    let mut cache = BezierCurve::new_evaluation_cache();
+    let mut pt    = pt.clone();
+    let mut res   = None;
+
+    for _ in range(0, NITER) {
+        let t = bezier_curve_ball::closest_parameter(&pt, c, NITER);
+
+        if !bezier_curve_ball::is_valid_parameter(&pt, c, &t, domain) {
+            return None;
+        }
+
+        let q     = c.at(&t, &mut cache);
+        let mut n = q - pt;
+
+        if n.normalize().is_zero() {
+            // `pt` lies on the curve.
+            n = q - center;
+
+            if n.normalize().is_zero() {
+                return None;
+            }
+        }
+
+        // The normal must point from the interior of `g` toward the curve, even if they are
+        // penetrating.
+        if na::dot(&n, &(q - center)) < na::zero() {
+            n = -n;
+        }
+
+        let p     = g.support_point(m, &n);
+        let depth = na::dot(&(p - q), &n);
+
+        res = Some((p.clone(), q, n, depth));
+        pt  = p;
+    }
+
+    res
+}
+
+#[cfg(dim2, f64, test)]
+mod test {
+    use nalgebra::na::{Vec1, Vec2, Iso2};
+    use nalgebra::na;
+    use geom::{BezierCurve, Cuboid};
+    use narrow::CollisionDetector;
+    use super::{ImplicitBezierCurve, BezierCurveImplicit, toi};
+
+    // A straight bezier curve from (-1, 0) to (1, 0), with a non-uniform parametrization.
+    fn curve() -> BezierCurve {
+        BezierCurve::new(vec!(Vec2::new(-1.0f64, 0.0), Vec2::new(-0.5, 0.0), Vec2::new(1.0, 0.0)))
+    }
+
+    // A slightly rotated box with a single lowest corner, penetrating the curve. Returns the
+    // box position, the penetration depth and the lowest corner.
+    fn tilted_box() -> (Cuboid, Iso2<f64>, f64, Vec2<f64>) {
+        let b      = Cuboid::new_with_margin(Vec2::new(0.2f64, 0.2), 0.0);
+        let m      = Iso2::new(Vec2::new(0.3f64, 0.17), Vec1::new(0.1f64));
+        let corner = na::transform(&m, &Vec2::new(-0.2f64, -0.2));
+
+        (b, m, -corner.y, corner)
+    }
+
+    #[test]
+    fn test_implicit_bezier_curve() {
+        let c = curve();
+        let (b, mb, depth, corner) = tilted_box();
+        let mut d = ImplicitBezierCurve::<Cuboid>::new(0.1);
+
+        d.update(&mb, &b, &na::one(), &c);
+        assert!(d.num_colls() == 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        assert!(na::approx_eq_eps(&contacts.get(0).depth, &depth, &1.0e-5));
+        assert!(na::approx_eq_eps(&contacts.get(0).normal, &Vec2::new(0.0, -1.0), &1.0e-5));
+        assert!(na::approx_eq_eps(&contacts.get(0).world1, &corner, &1.0e-5));
+        assert!(na::approx_eq_eps(&contacts.get(0).world2, &Vec2::new(corner.x, 0.0), &1.0e-5));
+
+        d.update(&Iso2::new(Vec2::new(0.3f64, 1.0), na::zero()), &b, &na::one(), &c);
+        assert!(d.num_colls() == 0);
+    }
+
+    #[test]
+    fn test_bezier_curve_implicit() {
+        let c = curve();
+        let (b, mb, depth, corner) = tilted_box();
+        let mut d = BezierCurveImplicit::<Cuboid>::new(0.1);
+
+        d.update(&na::one(), &c, &mb, &b);
+        assert!(d.num_colls() == 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        assert!(na::approx_eq_eps(&contacts.get(0).depth, &depth, &1.0e-5));
+        assert!(na::approx_eq_eps(&contacts.get(0).normal, &Vec2::new(0.0, 1.0), &1.0e-5));
+        assert!(na::approx_eq_eps(&contacts.get(0).world1, &Vec2::new(corner.x, 0.0), &1.0e-5));
+        assert!(na::approx_eq_eps(&contacts.get(0).world2, &corner, &1.0e-5));
+    }
+
+    #[test]
+    fn test_implicit_bezier_curve_at_subdivision_boundary() {
+        let c = curve();
+        let b = Cuboid::new_with_margin(Vec2::new(0.2f64, 0.2), 0.0);
+        // The lowest corner of the box is right below its center. It projects on
+        // `x(0.5) = -0.25`, i.e., on the boundary of the first two sub-curves.
+        let m = Iso2::new(Vec2::new(-0.25f64, 0.2 * 2.0f64.sqrt() - 0.05), Vec1::new(Float::frac_pi_4()));
+        let mut d = ImplicitBezierCurve::<Cuboid>::new(0.1);
+
+        d.update(&m, &b, &na::one(), &c);
+        assert!(d.num_colls() == 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        assert!(na::approx_eq_eps(&contacts.get(0).depth, &0.05, &1.0e-5));
+        assert!(na::approx_eq_eps(&contacts.get(0).world2, &Vec2::new(-0.25, 0.0), &1.0e-5));
+    }
+
+    #[test]
+    fn test_implicit_bezier_curve_toi() {
+        let c    = curve();
+        let b    = Cuboid::new_with_margin(Vec2::new(0.2f64, 0.2), 0.0);
+        let mb   = Iso2::new(Vec2::new(0.3f64, 3.0), na::zero());
+        let down = Vec2::new(0.0f64, -1.0);
+
+        let t = toi(&mb, &down, &b, &na::one(), &c);
+
+        assert!(na::approx_eq_eps(&t.expect("The box should hit the curve."), &2.8, &1.0e-5));
+
+        let flipped = CollisionDetector::toi(None::<BezierCurveImplicit<Cuboid>>, &na::one(), &-down, &10.0, &c,
+                                             &mb, &b);
+
+        assert!(flipped == t);
+        assert!(toi(&mb, &-down, &b, &na::one(), &c).is_none());
+    }
+}
@@ -1,19 +1,25 @@
 use std::rand;
+use std::num::Zero;
 use sync::{Arc, RWLock};
 use nalgebra::na;
-use nalgebra::na::{Vec2, Mat2, Translation, Inv, Norm};
+use nalgebra::na::{Vec2, Mat2, Translation, Rotate, Inv, Norm};
 use math::{Scalar, Vect, Matrix};
 use geom::{Ball, BezierSurface};
+use bounding_volume::HasBoundingSphere;
+use ray::Ray;
+use ray;
 use narrow::CollisionDetector;
-use contact::Contact;
+use contact::{Contact, FaceFeature, UnknownFeature};
 use narrow::surface_selector::SurfaceSelector;
-use narrow::surface_subdivision_tree::{SurfaceSubdivisionTreeRef, SurfaceSubdivisionTree, SurfaceSubdivisionTreeCache};
+use narrow::surface_subdivision_tree::{SurfaceSubdivisionTreeRef, SurfaceSubdivisionTree, SurfaceSubdivisionTreeCache,
+                                       SubdivisionBoundary};
 
 /// Collision detector between a ball and a bezier surface.
 pub struct BallBezierSurface<S, D> {
     cache:      Arc<RWLock<SurfaceSubdivisionTreeCache<D>>>,
     tree:       Option<SurfaceSubdivisionTreeRef<D>>, // Keep this for automatic registration on the cache.
     selector:   S,
+    max_lmd:    Scalar,
     prediction: Scalar,
     contacts:   Vec<Contact>,
     points:     Vec<Vect>,
@@ -33,6 +39,7 @@ impl<S: Clone, D: Send> Clone for BallBezierSurface<S, D> {
             cache:      self.cache.clone(),
             tree:       self.tree.clone(),
             selector:   self.selector.clone(),
+            max_lmd:    self.max_lmd.clone(),
             prediction: self.prediction.clone(),
             contacts:   self.contacts.clone(),
             points:     self.points.clone(),
@@ -43,6 +50,9 @@ impl<S: Clone, D: Send> Clone for BallBezierSurface<S, D> {
 
 impl<S: SurfaceSelector<D>, D> BallBezierSurface<S, D> {
     /// Creates a new collision detector with the given prediction margin.
+    ///
+    /// The maximum local minimal distance of `selector` is further bounded by the ball radius
+    /// and the prediction margin.
     pub fn new(selector:   S,
                prediction: Scalar,
                cache:      Arc<RWLock<SurfaceSubdivisionTreeCache<D>>>)
@@ -50,6 +60,7 @@ impl<S: SurfaceSelector<D>, D> BallBezierSurface<S, D> {
         BallBezierSurface {
             cache:      cache,
             tree:       None,
+            max_lmd:    selector.max_lmd(),
             selector:   selector,
             prediction: prediction,
             contacts:   Vec::new(),
@@ -64,7 +75,7 @@ CollisionDetector<Ball, BezierSurface> for BallBezierSurface<S, D> {
     fn update(&mut self, ma: &Matrix, a: &Ball, mb: &Matrix, b: &BezierSurface) {
         self.points.clear();
         self.contacts.clear();
-        self.selector.set_max_lmd(a.radius() + self.prediction);
+        self.selector.set_max_lmd(na::min(self.max_lmd.clone(), a.radius() + self.prediction));
 
         /*
         let renew_tree = match self.tree {
@@ -104,12 +115,17 @@ CollisionDetector<Ball, BezierSurface> for BallBezierSurface<S, D> {
         */
 
 
-        for pt in self.points.iter() {
-            let pt = na::transform(mb, pt);
+        let scale = b.bounding_sphere(&na::one()).radius();
+
+        for local_pt in self.points.iter() {
+            let pt = na::transform(mb, local_pt);
             let mut normal = pt - ma.translation(); 
             let gap        = normal.normalize();
 
-            let c = Contact::new(ma.translation() + normal * a.radius(), pt.clone(), normal, a.radius() - gap);
+            let mut c = Contact::new_with_features(ma.translation() + normal * a.radius(), pt.clone(), normal,
+                                                   a.radius() - gap, FaceFeature(0), UnknownFeature);
+
+            c.set_id_from_point(local_pt, &scale);
 
             self.contacts.push(c);
         }
@@ -128,8 +144,14 @@ CollisionDetector<Ball, BezierSurface> for BallBezierSurface<S, D> {
     }
 
     #[inline]
-    fn toi(_: Option<BallBezierSurface<S, D>>, _: &Matrix, _: &Vect, _: &Scalar, _: &Ball, _: &Matrix, _: &BezierSurface) -> Option<Scalar> {
-        None
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_: Option<BallBezierSurface<S, D>>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &Ball, mb: &Matrix,
+           b: &BezierSurface) -> Option<Scalar> {
+        toi(ma, dir, a, mb, b)
     }
 }
 
@@ -179,12 +201,146 @@ CollisionDetector<BezierSurface, Ball> for BezierSurfaceBall<S, D> {
     }
 
     #[inline]
-    fn toi(_: Option<BezierSurfaceBall<S, D>>, _: &Matrix, _: &Vect, _: &Scalar, _: &BezierSurface,
-           _: &Matrix, _: &Ball) -> Option<Scalar> {
-        None
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.detector.set_prediction(prediction)
+    }
+
+    #[inline]
+    fn toi(_: Option<BezierSurfaceBall<S, D>>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &BezierSurface,
+           mb: &Matrix, b: &Ball) -> Option<Scalar> {
+        toi(mb, &-*dir, b, ma, a)
+    }
+}
+
+/// Computes the time of impact of a ball moving along the direction `dir` with a bezier surface.
+///
+/// The surface is subdivided until its parts are small enough for the time of impact to be refined
+/// numerically. The parts are culled using their bounding spheres swept by the ball.
+pub fn toi(m1: &Matrix, dir: &Vect, b1: &Ball, m2: &Matrix, b2: &BezierSurface) -> Option<Scalar> {
+    let max_depth = 10;
+    let niter     = 10;
+    let ray       = Ray::new(na::inv_transform(m2, &m1.translation()), m2.inv_rotate(dir));
+    let mut best  = None;
+
+    do_toi(&ray, &b1.radius(), b2, &SubdivisionBoundary::new_root(), niter, 0, max_depth, &mut best);
+
+    best
+}
+
+// `ray` is the trajectory of the center of the ball, expressed in the local space of the surface.
+fn do_toi(ray:       &Ray,
+          radius:    &Scalar,
+          b:         &BezierSurface,
+          boundary:  &SubdivisionBoundary,
+          niter:     uint,
+          level:     uint,
+          max_depth: uint,
+          best:      &mut Option<Scalar>) {
+    // The ball touches the bounding sphere of `b` when its center enters the bounding sphere
+    // enlarged by its radius.
+    let bs    = b.bounding_sphere(&na::one());
+    let start = match ray::ball_toi_with_ray(bs.center().clone(), bs.radius() + *radius, ray, true).val1() {
+        None      => return,
+        Some(toi) => toi
+    };
+
+    match *best {
+        Some(ref best_toi) if *best_toi <= start => return,
+        _ => { }
+    }
+
+    if level == max_depth {
+        match refine_toi(ray, radius, b, boundary, &start, niter) {
+            Some(toi) => {
+                let is_better = match *best {
+                    Some(ref best_toi) => toi < *best_toi,
+                    None               => true
+                };
+
+                if is_better {
+                    *best = Some(toi);
+                }
+            },
+            None => { }
+        }
+    }
+    else {
+        // subdivide
+        let mut left  = BezierSurface::new_with_degrees(b.degree_u(), b.degree_v());
+        let mut right = BezierSurface::new_with_degrees(b.degree_u(), b.degree_v());
+
+        if level % 2 == 1 {
+            b.subdivide_u(&na::cast(0.5), &mut left, &mut right);
+        }
+        else {
+            b.subdivide_v(&na::cast(0.5), &mut left, &mut right);
+        }
+
+        let (lbound, rbound) = boundary.children(level);
+
+        do_toi(ray, radius, &left,  &lbound, niter, level + 1, max_depth, best);
+        do_toi(ray, radius, &right, &rbound, niter, level + 1, max_depth, best);
     }
 }
 
+// Finds the time when the distance between the ball center and the sub-surface `b` equals the ball
+// radius, with the Newton method starting from the time `start`. The ball center is projected on
+// `b` or on its boundary: the time of impact with the whole surface is the smallest time of impact
+// with its sub-surfaces.
+//
+// Returns `None` if the ball moves away from the sub-surface, if the method fails to converge, or
+// if the contact point lies on a side of `b` shared with a neighbor sub-surface without being a
+// closest point of `b` itself: the neighbor then touches the ball at the same time or earlier.
+fn refine_toi(ray:      &Ray,
+              radius:   &Scalar,
+              b:        &BezierSurface,
+              boundary: &SubdivisionBoundary,
+              start:    &Scalar,
+              niter:    uint)
+              -> Option<Scalar> {
+    let eps: Scalar = na::cast(1.0e-5);
+    let mut toi     = start.clone();
+
+    for _ in range(0, niter) {
+        let pt = ray.orig + ray.dir * toi;
+        // NOTE: the projection is clamped on every side of `b` while iterating: the distance to
+        // the sub-surface may be reached on a shared side before the contact time.
+        let (q, on_b) = match project_on_sub_surface(&pt, b, boundary, niter) {
+            Some(res) => res,
+            None      => return None
+        };
+
+        let mut normal = pt - q;
+        let gap        = normal.normalize() - *radius;
+
+        if gap <= eps && (gap >= -eps || toi.is_zero()) {
+            // NOTE: the ball might already penetrate the surface at the start of its motion.
+            if on_b {
+                return Some(toi);
+            }
+            else {
+                return None;
+            }
+        }
+
+        // speed at which the ball gets closer to the surface.
+        let speed = -na::dot(&ray.dir, &normal);
+
+        if speed <= na::zero() {
+            return None;
+        }
+
+        toi = toi + gap / speed;
+
+        if toi < na::zero() {
+            toi = na::zero();
+        }
+    }
+
+    // the method did not converge.
+    None
+}
+
 fn closest_points<S: SurfaceSelector<D>, D>(pt:        &Vect,
                                             b:         &BezierSurface,
                                             niter:     uint,
@@ -400,8 +556,12 @@ fn do_cleanup_subdivision_tree<D: Send + Share>(tree: &Arc<RWLock<SurfaceSubdivi
     w_tree.set_timestamp(valid_timestamp); // to prevent overflow-related problems.
 }
 
+/// Projects a point on a bezier surface using the Newton method, starting from its parametric
+/// center.
+///
+/// Returns `None` if the projection leaves the surface parametric domain.
 // FIXME: the Newton method should be implemented on nalgebra.
-fn closest_point(pt: &Vect, b: &BezierSurface, niter: uint) -> Option<Vect> {
+pub fn closest_point(pt: &Vect, b: &BezierSurface, niter: uint) -> Option<Vect> {
     /*
      * derivatives
      */
@@ -471,3 +631,219 @@ fn closest_point(pt: &Vect, b: &BezierSurface, niter: uint) -> Option<Vect> {
 
     Some(b.at(&uv.x, &uv.y, &mut cache))
 }
+
+/// Projects a point on a bezier surface sub-surface using the Newton method, starting from its
+/// parametric center.
+///
+/// Contrary to `closest_point`, the projection is clamped to the surface parametric domain: when
+/// the Newton method leaves it, the point is projected on the corresponding boundary curves. Such
+/// a constrained projection is kept only if it lies on a side which is part of the boundary of the
+/// original surface, or if it is a stationary point of the distance function (it is then a
+/// closest point shared by two neighbor sub-surfaces).
+///
+/// Returns `None` if the method fails or if the projection should be found on a neighbor
+/// sub-surface.
+pub fn closest_point_with_boundary(pt:       &Vect,
+                                   b:        &BezierSurface,
+                                   boundary: &SubdivisionBoundary,
+                                   niter:    uint)
+                                   -> Option<Vect> {
+    match project_on_sub_surface(pt, b, boundary, niter) {
+        Some((res, true)) => Some(res),
+        _                 => None
+    }
+}
+
+// Same as `closest_point_with_boundary` but the constrained projection is returned even if it
+// should be found on a neighbor sub-surface. The returned flag is `false` in this case.
+fn project_on_sub_surface(pt:       &Vect,
+                          b:        &BezierSurface,
+                          boundary: &SubdivisionBoundary,
+                          niter:    uint)
+                          -> Option<(Vect, bool)> {
+    let mut diff_u   = BezierSurface::new_with_degrees(0, 0);
+    let mut diff_v   = BezierSurface::new_with_degrees(0, 0);
+    let mut diff_u_u = BezierSurface::new_with_degrees(0, 0);
+    let mut diff_u_v = BezierSurface::new_with_degrees(0, 0);
+    let mut diff_v_v = BezierSurface::new_with_degrees(0, 0);
+
+    b.diff_u(&mut diff_u);
+    b.diff_v(&mut diff_v);
+    diff_u.diff_u(&mut diff_u_u);
+    diff_u.diff_v(&mut diff_u_v);
+    diff_v.diff_v(&mut diff_v_v);
+
+    let _0: Scalar  = na::zero();
+    let _1: Scalar  = na::one();
+    let mut uv: Vec2<Scalar> = Vec2::new(na::cast(0.5), na::cast(0.5));
+    // whether each parameter is free, or constrained on a side of the domain.
+    let mut free_u  = true;
+    let mut free_v  = true;
+    let mut cache   = BezierSurface::new_evaluation_cache();
+
+    for _ in range(0, niter) {
+        let dpt = b.at(&uv.x, &uv.y, &mut cache) - *pt;
+        let du  = diff_u.at(&uv.x, &uv.y, &mut cache);
+        let dv  = diff_v.at(&uv.x, &uv.y, &mut cache);
+        let f_u = na::dot(&dpt, &du);
+        let f_v = na::dot(&dpt, &dv);
+
+        // release the constraints pulling the projection toward the interior of the domain.
+        if !free_u && ((uv.x <= _0 && f_u < _0) || (uv.x >= _1 && f_u > _0)) {
+            free_u = true;
+        }
+
+        if !free_v && ((uv.y <= _0 && f_v < _0) || (uv.y >= _1 && f_v > _0)) {
+            free_v = true;
+        }
+
+        if !free_u && !free_v {
+            // stuck on a corner.
+            break;
+        }
+
+        let duu   = diff_u_u.at(&uv.x, &uv.y, &mut cache);
+        let duv   = diff_u_v.at(&uv.x, &uv.y, &mut cache);
+        let dvv   = diff_v_v.at(&uv.x, &uv.y, &mut cache);
+        let df_uu = na::dot(&duu, &dpt) + na::dot(&du, &du);
+        let df_uv = na::dot(&duv, &dpt) + na::dot(&du, &dv);
+        let df_vv = na::dot(&dvv, &dpt) + na::dot(&dv, &dv);
+
+        if free_u && free_v {
+            let mut inv_j = Mat2::new(df_uu, df_uv,
+                                      df_uv, df_vv);
+
+            if !inv_j.inv() {
+                return None;
+            }
+
+            uv = uv - inv_j * Vec2::new(f_u, f_v);
+        }
+        else if free_u {
+            // Newton method on the boundary curve `v = uv.y`.
+            if df_uu.is_zero() {
+                return None;
+            }
+
+            uv.x = uv.x - f_u / df_uu;
+        }
+        else {
+            // Newton method on the boundary curve `u = uv.x`.
+            if df_vv.is_zero() {
+                return None;
+            }
+
+            uv.y = uv.y - f_v / df_vv;
+        }
+
+        if uv.x <= _0 || uv.x >= _1 {
+            uv.x   = na::clamp(uv.x, _0, _1);
+            free_u = false;
+        }
+
+        if uv.y <= _0 || uv.y >= _1 {
+            uv.y   = na::clamp(uv.y, _0, _1);
+            free_v = false;
+        }
+    }
+
+    /*
+     * Check that the constraints are satisfied.
+     */
+    let res = b.at(&uv.x, &uv.y, &mut cache);
+    let dpt = res - *pt;
+    let eps: Scalar = na::cast(1.0e-3);
+
+    if !free_u && !(if uv.x <= _0 { boundary.umin } else { boundary.umax }) {
+        let du = diff_u.at(&uv.x, &uv.y, &mut cache);
+
+        if na::dot(&dpt, &du).abs() > eps * na::norm(&dpt) * na::norm(&du) {
+            return Some((res, false));
+        }
+    }
+
+    if !free_v && !(if uv.y <= _0 { boundary.vmin } else { boundary.vmax }) {
+        let dv = diff_v.at(&uv.x, &uv.y, &mut cache);
+
+        if na::dot(&dpt, &dv).abs() > eps * na::norm(&dpt) * na::norm(&dv) {
+            return Some((res, false));
+        }
+    }
+
+    Some((res, true))
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Ball;
+    use test_utils::flat_surface;
+    use super::toi;
+
+    #[test]
+    fn test_ball_bezier_surface_toi() {
+        let s  = flat_surface();
+        let b  = Ball::new(0.5f64);
+        let mb = Iso3::new(Vec3::new(0.9f64, 1.3, 3.0), na::zero());
+        let ms = na::one();
+
+        let t = toi(&mb, &Vec3::new(0.0, 0.0, -1.0), &b, &ms, &s).expect("The ball should hit.");
+
+        assert!(na::approx_eq_eps(&t, &2.5, &1.0e-5));
+        assert!(toi(&mb, &Vec3::new(0.0, 0.0, 1.0), &b, &ms, &s).is_none());
+    }
+
+    #[test]
+    fn test_ball_bezier_surface_edge_toi() {
+        let s  = flat_surface();
+        let b  = Ball::new(0.5f64);
+        let ms = na::one();
+        // The ball center passes at the distance 0.3 from the edge `x = 2`: it touches the edge
+        // when its center is at the height 0.4.
+        let mb = Iso3::new(Vec3::new(2.3f64, 1.3, 3.0), na::zero());
+
+        let t = toi(&mb, &Vec3::new(0.0, 0.0, -1.0), &b, &ms, &s).expect("The ball should hit.");
+
+        assert!(na::approx_eq_eps(&t, &2.6, &1.0e-5));
+
+        let far = Iso3::new(Vec3::new(2.6f64, 1.3, 3.0), na::zero());
+
+        assert!(toi(&far, &Vec3::new(0.0, 0.0, -1.0), &b, &ms, &s).is_none());
+    }
+
+    #[test]
+    fn test_ball_bezier_surface_grazing_edge_toi() {
+        let s   = flat_surface();
+        let b   = Ball::new(0.5f64);
+        let ms  = na::one();
+        let dir = Vec3::new(0.0f64, 1.0, 0.0);
+
+        // The ball center moves parallel to the edge `x = 2` at the distance `sqrt(0.2925)` from
+        // it: the ball passes by the surface without touching it.
+        let graze = Iso3::new(Vec3::new(2.3f64, -3.0, 0.45), na::zero());
+
+        assert!(toi(&graze, &dir, &b, &ms, &s).is_none());
+
+        // Closer to the edge, the ball hits the corner `(2, 0, 0)` first.
+        let hit = Iso3::new(Vec3::new(2.3f64, -3.0, 0.35), na::zero());
+        let t   = toi(&hit, &dir, &b, &ms, &s).expect("The ball should hit the corner.");
+
+        assert!(na::approx_eq_eps(&t, &(3.0 - 0.0375f64.sqrt()), &1.0e-5));
+    }
+
+    #[test]
+    fn test_ball_bezier_surface_seam_toi() {
+        let s  = flat_surface();
+        let b  = Ball::new(0.5f64);
+        let ms = na::one();
+        // The ball falls next to the lines `x = 1` and `y = 1` where the surface is first
+        // subdivided: the sub-surfaces on the other side of those seams must not report an
+        // impact with their boundary.
+        let mb = Iso3::new(Vec3::new(1.01f64, 1.01, 3.0), na::zero());
+
+        let t = toi(&mb, &Vec3::new(0.0, 0.0, -1.0), &b, &ms, &s).expect("The ball should hit.");
+
+        assert!(na::approx_eq_eps(&t, &2.5, &1.0e-5));
+    }
+}
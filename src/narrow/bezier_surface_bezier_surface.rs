@@ -0,0 +1,663 @@
+use std::num::Zero;
+use sync::{Arc, RWLock};
+use nalgebra::na::{Vec4, Mat4, Rotate, Transform, Inv, Norm, Indexable};
+use nalgebra::na;
+use math::{Scalar, Vect, Matrix};
+use geom::{BezierSurface, Convex};
+use bounding_volume::HasBoundingSphere;
+use narrow::CollisionDetector;
+use narrow::bezier_surface_ball;
+use narrow::implicit_implicit;
+use narrow::surface_selector::{SurfaceSelector, TangentConesSurfaceSelector,
+                               TangentConesSurfaceSelectorTestData};
+use narrow::surface_subdivision_tree::{SurfaceSubdivisionTreeRef, SurfaceSubdivisionTree,
+                                       SurfaceSubdivisionTreeCache, SubdivisionBoundary};
+use narrow::surface_subdivision_tree;
+use contact::Contact;
+
+type Tree = Arc<RWLock<SurfaceSubdivisionTree<TangentConesSurfaceSelectorTestData>>>;
+
+// Subdivision level of the surfaces before the numerical refinement.
+static MAX_LEVEL: uint = 6;
+// Number of Newton iterations.
+static NITER:     uint = 10;
+
+/// Collision detector between two bezier surfaces.
+///
+/// Both surfaces are subdivided until the pairs of sub-surfaces which might contain a pair of
+/// closest points are small enough for those points to be refined numerically. The pairs are
+/// culled using their bounding spheres and the cones bounding their tangents.
+///
+/// The subdivisions are stored on the subdivision tree cache shared with the other detectors
+/// involving a bezier surface. Thus, they are computed only once per surface.
+pub struct BezierSurfaceBezierSurface {
+    cache:      Arc<RWLock<SurfaceSubdivisionTreeCache<TangentConesSurfaceSelectorTestData>>>,
+    tree1:      Option<SurfaceSubdivisionTreeRef<TangentConesSurfaceSelectorTestData>>,
+    tree2:      Option<SurfaceSubdivisionTreeRef<TangentConesSurfaceSelectorTestData>>,
+    selector:   TangentConesSurfaceSelector,
+    prediction: Scalar,
+    contacts:   Vec<Contact>
+}
+
+impl Clone for BezierSurfaceBezierSurface {
+    fn clone(&self) -> BezierSurfaceBezierSurface {
+        BezierSurfaceBezierSurface {
+            cache:      self.cache.clone(),
+            tree1:      self.tree1.clone(),
+            tree2:      self.tree2.clone(),
+            selector:   self.selector.clone(),
+            prediction: self.prediction.clone(),
+            contacts:   self.contacts.clone()
+        }
+    }
+}
+
+impl BezierSurfaceBezierSurface {
+    /// Creates a new collision detector with the given prediction margin.
+    pub fn new(selector:   TangentConesSurfaceSelector,
+               prediction: Scalar,
+               cache:      Arc<RWLock<SurfaceSubdivisionTreeCache<TangentConesSurfaceSelectorTestData>>>)
+               -> BezierSurfaceBezierSurface {
+        BezierSurfaceBezierSurface {
+            cache:      cache,
+            tree1:      None,
+            tree2:      None,
+            selector:   selector,
+            prediction: prediction,
+            contacts:   Vec::new()
+        }
+    }
+}
+
+impl CollisionDetector<BezierSurface, BezierSurface> for BezierSurfaceBezierSurface {
+    fn update(&mut self, ma: &Matrix, a: &BezierSurface, mb: &Matrix, b: &BezierSurface) {
+        self.contacts.clear();
+
+        let renew_tree1 = match self.tree1 {
+            None           => true,
+            Some(ref tree) => !tree.is_the_subdivision_tree_of(a)
+        };
+
+        if renew_tree1 {
+            let selector = &mut self.selector;
+            let tree     = SurfaceSubdivisionTreeCache::find_or_insert_with(
+                               &mut self.cache.clone(), a, || selector.create_test_data(a));
+            self.tree1 = Some(tree);
+        }
+
+        let renew_tree2 = match self.tree2 {
+            None           => true,
+            Some(ref tree) => !tree.is_the_subdivision_tree_of(b)
+        };
+
+        if renew_tree2 {
+            let selector = &mut self.selector;
+            let tree     = SurfaceSubdivisionTreeCache::find_or_insert_with(
+                               &mut self.cache.clone(), b, || selector.create_test_data(b));
+            self.tree2 = Some(tree);
+        }
+
+        let tree1 = self.tree1.as_ref().unwrap().deref().clone();
+        let tree2 = self.tree2.as_ref().unwrap().deref().clone();
+
+        let ls_ma     = na::inv(mb).expect("The transformation `mb` must be inversible.") * *ma;
+        let root      = SubdivisionBoundary::new_root();
+        let mut local = Vec::new();
+
+        collide_trees(&ls_ma, &tree1, 0, &root, &tree2, 0, &root, &self.prediction, &mut self.selector,
+                      &mut local);
+
+        let scale = b.bounding_sphere(&na::one()).radius();
+
+        for &(ref p, ref q, ref n, ref depth) in local.iter() {
+            let mut c = Contact::new(mb.transform(p), mb.transform(q), mb.rotate(n), depth.clone());
+
+            c.set_id_from_point(q, &scale);
+
+            self.contacts.push(c);
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        for c in self.contacts.iter() {
+            out_colls.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_: Option<BezierSurfaceBezierSurface>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &BezierSurface,
+           mb: &Matrix, b: &BezierSurface) -> Option<Scalar> {
+        toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Computes the time of impact of a bezier surface moving along the direction `dir` with another
+/// bezier surface.
+///
+/// Both surfaces are subdivided alternatively, and the pairs of sub-surfaces are culled using the
+/// convex hulls of their control points. The time of impact of the convex hulls of the smallest
+/// sub-surfaces is returned: this is a lower bound of the exact time of impact, which gets more
+/// accurate as the sub-surfaces flatten.
+pub fn toi(m1: &Matrix, dir: &Vect, b1: &BezierSurface, m2: &Matrix, b2: &BezierSurface) -> Option<Scalar> {
+    // Each surface is subdivided `max_depth / 2` times.
+    let max_depth = 16;
+    let mut best  = None;
+
+    do_toi(m1, dir, b1, m2, b2, 0, max_depth, &mut best);
+
+    best
+}
+
+fn do_toi(m1:        &Matrix,
+          dir:       &Vect,
+          b1:        &BezierSurface,
+          m2:        &Matrix,
+          b2:        &BezierSurface,
+          level:     uint,
+          max_depth: uint,
+          best:      &mut Option<Scalar>) {
+    // Each sub-surface is inside of the convex hull of its control points.
+    let hull1 = Convex::new_with_margin(b1.control_points().to_owned(), na::zero());
+    let hull2 = Convex::new_with_margin(b2.control_points().to_owned(), na::zero());
+    let start = match implicit_implicit::toi(m1, dir, &hull1, m2, &hull2) {
+        None      => return,
+        Some(toi) => toi
+    };
+
+    match *best {
+        Some(ref best_toi) if *best_toi <= start => return,
+        _ => { }
+    }
+
+    if level == max_depth {
+        *best = Some(start);
+        return;
+    }
+
+    // Subdivide the first surface at even levels, the second one at odd levels. Each surface is
+    // subdivided along `u` and `v` alternatively.
+    let to_split  = if level % 2 == 0 { b1 } else { b2 };
+    let mut left  = BezierSurface::new_with_degrees(to_split.degree_u(), to_split.degree_v());
+    let mut right = BezierSurface::new_with_degrees(to_split.degree_u(), to_split.degree_v());
+
+    if (level / 2) % 2 == 1 {
+        to_split.subdivide_u(&na::cast(0.5), &mut left, &mut right);
+    }
+    else {
+        to_split.subdivide_v(&na::cast(0.5), &mut left, &mut right);
+    }
+
+    if level % 2 == 0 {
+        do_toi(m1, dir, &left,  m2, b2, level + 1, max_depth, best);
+        do_toi(m1, dir, &right, m2, b2, level + 1, max_depth, best);
+    }
+    else {
+        do_toi(m1, dir, b1, m2, &left,  level + 1, max_depth, best);
+        do_toi(m1, dir, b1, m2, &right, level + 1, max_depth, best);
+    }
+}
+
+// Collects the contacts between the sub-surface `t1` (at the subdivision level `l1`, with the
+// boundary `s1`) transformed by `m`, and the sub-surface `t2` (at the subdivision level `l2`, with
+// the boundary `s2`). Everything is expressed in the local space of the second surface.
+//
+// Each contact is given as the closest point on the first surface, the closest point on the second
+// surface, the normal (from the first surface to the second) and the penetration depth.
+fn collide_trees(m:          &Matrix,
+                 t1:         &Tree,
+                 l1:         uint,
+                 s1:         &SubdivisionBoundary,
+                 t2:         &Tree,
+                 l2:         uint,
+                 s2:         &SubdivisionBoundary,
+                 prediction: &Scalar,
+                 selector:   &mut TangentConesSurfaceSelector,
+                 out:        &mut Vec<(Vect, Vect, Vect, Scalar)>) {
+    let subdivide_first;
+
+    // >>>>>>>> get a read-only lock on both nodes.
+    {
+        let r_t1 = t1.read();
+        let r_t2 = t2.read();
+        let d1   = r_t1.data();
+        let d2   = r_t2.data();
+
+        /*
+         * Bounding spheres culling.
+         */
+        let c1 = m.transform(d1.bounding_sphere().center());
+        let c2 = d2.bounding_sphere().center().clone();
+        let r1 = d1.bounding_sphere().radius();
+        let r2 = d2.bounding_sphere().radius();
+
+        let mut axis = c2 - c1;
+        let dist     = axis.normalize();
+
+        // Pairs farther than the maximum local minimal distance of the selector are ignored too.
+        if dist > r1 + r2 + na::min(prediction.clone(), selector.max_lmd()) {
+            return;
+        }
+
+        /*
+         * Tangent cones culling: the segment joining two closest points must be orthogonal to the
+         * tangent planes of both surfaces. This can be tested only if the bounding spheres are
+         * disjoint. This does not hold for a closest point on the boundary of the original
+         * surface: the cones of sub-surfaces touching it are ignored.
+         */
+        if dist > r1 + r2 {
+            let spread: Scalar = ((r1 + r2) / dist).asin();
+            let mut cones      = Vec::with_capacity(4);
+
+            if !s1.touches_boundary() {
+                let (axis_u1, spread_u1) = d1.tangent_cone_u();
+                let (axis_v1, spread_v1) = d1.tangent_cone_v();
+
+                cones.push((m.rotate(&axis_u1), spread_u1));
+                cones.push((m.rotate(&axis_v1), spread_v1));
+            }
+
+            if !s2.touches_boundary() {
+                cones.push(d2.tangent_cone_u());
+                cones.push(d2.tangent_cone_v());
+            }
+
+            for &(ref cone_axis, ref cone_spread) in cones.iter() {
+                let ang = na::dot(&axis, cone_axis).acos();
+
+                if ang - spread - *cone_spread > Float::frac_pi_2() ||
+                   ang + spread + *cone_spread < Float::frac_pi_2() {
+                    return;
+                }
+            }
+        }
+
+        if l1 >= MAX_LEVEL && l2 >= MAX_LEVEL {
+            // stop the recursion and refine the closest points.
+            match closest_points(m, r_t1.surface(), s1, r_t2.surface(), s2) {
+                Some((p, q, n, depth)) => {
+                    if depth >= -*prediction && !is_duplicate(&p, &q, out.as_slice()) {
+                        out.push((p, q, n, depth))
+                    }
+                },
+                None => { }
+            }
+
+            return;
+        }
+
+        // subdivide the biggest sub-surface first.
+        subdivide_first = l2 >= MAX_LEVEL || (l1 < MAX_LEVEL && r1 >= r2);
+    }
+    // <<<<<<<< end of read-only lock on both nodes.
+
+    if subdivide_first {
+        let (left, right)    = surface_subdivision_tree::children(t1, l1, selector);
+        let (lbound, rbound) = s1.children(l1);
+
+        collide_trees(m, &left,  l1 + 1, &lbound, t2, l2, s2, prediction, selector, out);
+        collide_trees(m, &right, l1 + 1, &rbound, t2, l2, s2, prediction, selector, out);
+    }
+    else {
+        let (left, right)    = surface_subdivision_tree::children(t2, l2, selector);
+        let (lbound, rbound) = s2.children(l2);
+
+        collide_trees(m, t1, l1, s1, &left,  l2 + 1, &lbound, prediction, selector, out);
+        collide_trees(m, t1, l1, s1, &right, l2 + 1, &rbound, prediction, selector, out);
+    }
+}
+
+// The first and second derivatives of a surface, wrt. `u`, `v`, `uu`, `uv` and `vv`.
+struct Derivatives {
+    du:  BezierSurface,
+    dv:  BezierSurface,
+    duu: BezierSurface,
+    duv: BezierSurface,
+    dvv: BezierSurface
+}
+
+impl Derivatives {
+    fn new(b: &BezierSurface) -> Derivatives {
+        let mut res = Derivatives {
+            du:  BezierSurface::new_with_degrees(0, 0),
+            dv:  BezierSurface::new_with_degrees(0, 0),
+            duu: BezierSurface::new_with_degrees(0, 0),
+            duv: BezierSurface::new_with_degrees(0, 0),
+            dvv: BezierSurface::new_with_degrees(0, 0)
+        };
+
+        b.diff_u(&mut res.du);
+        b.diff_v(&mut res.dv);
+        res.du.diff_u(&mut res.duu);
+        res.du.diff_v(&mut res.duv);
+        res.dv.diff_v(&mut res.dvv);
+
+        res
+    }
+}
+
+// Refines the closest points between `b1` transformed by `m` and `b2` with the Newton method on
+// the squared distance between `b1(u1, v1)` and `b2(u2, v2)`, starting from the parametric centers
+// of both surfaces.
+//
+// The parameters leaving the parametric domains are clamped and fixed: the method then projects on
+// the boundary curves of the surfaces. Such constrained solutions are kept only on the sides which
+// are part of the boundary of the original surfaces (`s1` and `s2`), or if they are stationary
+// points of the distance function.
+//
+// Returns `None` if the method does not converge, or if the solution should be found on a neighbor
+// pair of sub-surfaces.
+fn closest_points(m:  &Matrix,
+                  b1: &BezierSurface,
+                  s1: &SubdivisionBoundary,
+                  b2: &BezierSurface,
+                  s2: &SubdivisionBoundary)
+                  -> Option<(Vect, Vect, Vect, Scalar)> {
+    let d1 = Derivatives::new(b1);
+    let d2 = Derivatives::new(b2);
+
+    let _0: Scalar    = na::zero();
+    let _1: Scalar    = na::one();
+    let _0_5: Scalar  = na::cast(0.5);
+    let mut cache     = BezierSurface::new_evaluation_cache();
+    let mut x: Vec4<Scalar> = Vec4::new(_0_5, _0_5, _0_5, _0_5);
+    let mut grad: Vec4<Scalar> = na::zero();
+    // whether each parameter is fixed on a side of its domain.
+    let mut fixed     = [ false, false, false, false ];
+
+    for _ in range(0, NITER) {
+        let f   = m.transform(&b1.at(&x.x, &x.y, &mut cache)) - b2.at(&x.z, &x.w, &mut cache);
+        let au  = m.rotate(&d1.du.at(&x.x, &x.y, &mut cache));
+        let av  = m.rotate(&d1.dv.at(&x.x, &x.y, &mut cache));
+        let auu = m.rotate(&d1.duu.at(&x.x, &x.y, &mut cache));
+        let auv = m.rotate(&d1.duv.at(&x.x, &x.y, &mut cache));
+        let avv = m.rotate(&d1.dvv.at(&x.x, &x.y, &mut cache));
+        let bu  = d2.du.at(&x.z, &x.w, &mut cache);
+        let bv  = d2.dv.at(&x.z, &x.w, &mut cache);
+        let buu = d2.duu.at(&x.z, &x.w, &mut cache);
+        let buv = d2.duv.at(&x.z, &x.w, &mut cache);
+        let bvv = d2.dvv.at(&x.z, &x.w, &mut cache);
+
+        grad = Vec4::new(na::dot(&au, &f), na::dot(&av, &f), -na::dot(&bu, &f), -na::dot(&bv, &f));
+
+        let mut inv_hessian = Mat4::new(
+            na::dot(&au, &au) + na::dot(&auu, &f), na::dot(&au, &av) + na::dot(&auv, &f),
+            -na::dot(&au, &bu), -na::dot(&au, &bv),
+
+            na::dot(&av, &au) + na::dot(&auv, &f), na::dot(&av, &av) + na::dot(&avv, &f),
+            -na::dot(&av, &bu), -na::dot(&av, &bv),
+
+            -na::dot(&bu, &au), -na::dot(&bu, &av),
+            na::dot(&bu, &bu) - na::dot(&buu, &f), na::dot(&bu, &bv) - na::dot(&buv, &f),
+
+            -na::dot(&bv, &au), -na::dot(&bv, &av),
+            na::dot(&bv, &bu) - na::dot(&buv, &f), na::dot(&bv, &bv) - na::dot(&bvv, &f)
+        );
+
+        for i in range(0u, 4) {
+            if fixed[i] {
+                // release the constraints pulling the solution toward the interior of the domain.
+                if (x.at(i) <= _0 && grad.at(i) < _0) || (x.at(i) >= _1 && grad.at(i) > _0) {
+                    fixed[i] = false;
+                }
+                else {
+                    // remove the fixed parameter from the system.
+                    for j in range(0u, 4) {
+                        inv_hessian.set((i, j), _0);
+                        inv_hessian.set((j, i), _0);
+                    }
+
+                    inv_hessian.set((i, i), _1);
+                    grad.set(i, _0);
+                }
+            }
+        }
+
+        if !inv_hessian.inv() {
+            // The closest points are not isolated (e.g. the surfaces are parallel or they
+            // intersect).
+            return alternate_projections(m, b1, s1, b2, s2);
+        }
+
+        x = x - inv_hessian * grad;
+
+        for i in range(0u, 4) {
+            if x.at(i) <= _0 || x.at(i) >= _1 {
+                x.set(i, na::clamp(x.at(i), _0, _1));
+                fixed[i] = true;
+            }
+        }
+    }
+
+    let eps: Scalar = na::cast(1.0e-5);
+
+    if na::sqnorm(&grad) > eps * eps {
+        // the method did not converge.
+        return None;
+    }
+
+    let p = m.transform(&b1.at(&x.x, &x.y, &mut cache));
+    let q = b2.at(&x.z, &x.w, &mut cache);
+
+    /*
+     * Check that the constraints are satisfied.
+     */
+    let sides = [ (s1.umin, s1.umax), (s1.vmin, s1.vmax), (s2.umin, s2.umax), (s2.vmin, s2.vmax) ];
+    let f     = p - q;
+    let tgts  = [
+        m.rotate(&d1.du.at(&x.x, &x.y, &mut cache)),
+        m.rotate(&d1.dv.at(&x.x, &x.y, &mut cache)),
+        d2.du.at(&x.z, &x.w, &mut cache),
+        d2.dv.at(&x.z, &x.w, &mut cache)
+    ];
+    let _rel_tol: Scalar = na::cast(1.0e-3);
+
+    for i in range(0u, 4) {
+        let (at_min, at_max) = sides[i];
+
+        if fixed[i] && !(if x.at(i) <= _0 { at_min } else { at_max }) {
+            if na::dot(&f, &tgts[i]).abs() > _rel_tol * na::norm(&f) * na::norm(&tgts[i]) {
+                return None;
+            }
+        }
+    }
+
+    make_contact(m, b1, b2, p, q)
+}
+
+// Refines the closest points between `b1` transformed by `m` and `b2` by projecting alternatively
+// on each surface, starting from the parametric center of `b1`. The projections are clamped to the
+// sub-surfaces boundaries `s1` and `s2`.
+//
+// This converges slowly, except if the closest points are not isolated: this is used only when the
+// Newton method fails.
+fn alternate_projections(m:  &Matrix,
+                         b1: &BezierSurface,
+                         s1: &SubdivisionBoundary,
+                         b2: &BezierSurface,
+                         s2: &SubdivisionBoundary)
+                         -> Option<(Vect, Vect, Vect, Scalar)> {
+    let eps: Scalar = na::cast(1.0e-5);
+    let mut cache   = BezierSurface::new_evaluation_cache();
+    let mut p       = m.transform(&b1.at(&na::cast(0.5), &na::cast(0.5), &mut cache));
+
+    for _ in range(0, NITER) {
+        let q = match bezier_surface_ball::closest_point_with_boundary(&p, b2, s2, NITER) {
+            Some(q) => q,
+            None    => return None
+        };
+
+        let new_p = match bezier_surface_ball::closest_point_with_boundary(&na::inv_transform(m, &q),
+                                                                           b1, s1, NITER) {
+            Some(p) => m.transform(&p),
+            None    => return None
+        };
+
+        let step = na::sqnorm(&(new_p - p));
+
+        p = new_p;
+
+        if step <= eps * eps {
+            return make_contact(m, b1, b2, p, q);
+        }
+    }
+
+    // the method did not converge.
+    None
+}
+
+// Builds the contact between the closest points `p` (on `b1` transformed by `m`) and `q` (on
+// `b2`).
+fn make_contact(m: &Matrix, b1: &BezierSurface, b2: &BezierSurface, p: Vect, q: Vect)
+                -> Option<(Vect, Vect, Vect, Scalar)> {
+    let eps: Scalar = na::cast(1.0e-5);
+    let mut n       = q - p;
+    let dist        = n.normalize();
+
+    if dist <= eps {
+        // The surfaces intersect: use the direction between their centers.
+        let mut cache = BezierSurface::new_evaluation_cache();
+
+        n = b2.at(&na::cast(0.5), &na::cast(0.5), &mut cache) -
+            m.transform(&b1.at(&na::cast(0.5), &na::cast(0.5), &mut cache));
+
+        if n.normalize().is_zero() {
+            return None;
+        }
+
+        Some((p, q, n, na::zero()))
+    }
+    else {
+        Some((p, q, n, -dist))
+    }
+}
+
+// Tests if the pair of closest points `(p, q)` has already been found on a neighbor pair of
+// sub-surfaces.
+fn is_duplicate(p: &Vect, q: &Vect, found: &[(Vect, Vect, Vect, Scalar)]) -> bool {
+    let eps: Scalar = na::cast(1.0e-3);
+
+    found.iter().any(|&(ref fp, ref fq, _, _)|
+        na::sqnorm(&(*fp - *p)) <= eps * eps && na::sqnorm(&(*fq - *q)) <= eps * eps
+    )
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use std::num::Bounded;
+    use sync::{Arc, RWLock};
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::BezierSurface;
+    use narrow::CollisionDetector;
+    use narrow::surface_selector::TangentConesSurfaceSelector;
+    use narrow::surface_subdivision_tree::SurfaceSubdivisionTreeCache;
+    use super::{BezierSurfaceBezierSurface, toi};
+
+    // A 3×3 surface on the square `[0, 2]²`. Its central control point has the height `zcenter`
+    // while the other control points have the height `zborder`.
+    fn surface(zborder: f64, zcenter: f64) -> BezierSurface {
+        let mut pts = Vec::new();
+
+        for i in range(0u, 3) {
+            for j in range(0u, 3) {
+                let z = if i == 1 && j == 1 { zcenter } else { zborder };
+
+                pts.push(Vec3::new(i as f64, j as f64, z));
+            }
+        }
+
+        BezierSurface::new(pts, 3, 3)
+    }
+
+    #[test]
+    fn test_bezier_surface_bezier_surface() {
+        let flat  = surface(0.0, 0.0);
+        // Its lowest point is (1.0, 1.0, 0.3).
+        let bump  = surface(0.5, -0.3);
+        let cache = Arc::new(RWLock::new(SurfaceSubdivisionTreeCache::new()));
+        let sel   = TangentConesSurfaceSelector::new(Bounded::max_value());
+        let mut d = BezierSurfaceBezierSurface::new(sel, 0.5, cache);
+
+        d.update(&na::one(), &flat, &na::one(), &bump);
+        assert!(d.num_colls() == 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        assert!(na::approx_eq_eps(&contacts.get(0).depth, &-0.3, &1.0e-3));
+        assert!(na::approx_eq_eps(&contacts.get(0).normal, &Vec3::new(0.0, 0.0, 1.0), &1.0e-3));
+        assert!(na::approx_eq_eps(&contacts.get(0).world2, &Vec3::new(1.0, 1.0, 0.3), &1.0e-3));
+
+        d.update(&na::one(), &flat, &Iso3::new(Vec3::new(0.0, 0.0, 1.0), na::zero()), &bump);
+        assert!(d.num_colls() == 0);
+    }
+
+    // A vertical surface in the plane `x = 1`, standing on the square `[0, 2]²`. Its lowest
+    // point is (1.0, 1.0, 0.2), in the middle of its bottom edge.
+    fn standing_surface() -> BezierSurface {
+        let bottom = [ 0.4, 0.0, 0.4 ];
+        let mut pts = Vec::new();
+
+        for i in range(0u, 3) {
+            for j in range(0u, 3) {
+                pts.push(Vec3::new(1.0, 0.5 + 0.5 * j as f64, bottom[j] + 0.5 * i as f64));
+            }
+        }
+
+        BezierSurface::new(pts, 3, 3)
+    }
+
+    #[test]
+    fn test_bezier_surface_bezier_surface_t_junction() {
+        let flat     = surface(0.0, 0.0);
+        let standing = standing_surface();
+        let cache    = Arc::new(RWLock::new(SurfaceSubdivisionTreeCache::new()));
+        let sel      = TangentConesSurfaceSelector::new(Bounded::max_value());
+        let mut d    = BezierSurfaceBezierSurface::new(sel, 0.5, cache);
+
+        // The closest point of the standing surface is on its boundary.
+        d.update(&na::one(), &flat, &na::one(), &standing);
+        assert!(d.num_colls() == 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        assert!(na::approx_eq_eps(&contacts.get(0).depth, &-0.2, &1.0e-3));
+        assert!(na::approx_eq_eps(&contacts.get(0).normal, &Vec3::new(0.0, 0.0, 1.0), &1.0e-3));
+        assert!(na::approx_eq_eps(&contacts.get(0).world1, &Vec3::new(1.0, 1.0, 0.0), &1.0e-3));
+        assert!(na::approx_eq_eps(&contacts.get(0).world2, &Vec3::new(1.0, 1.0, 0.2), &1.0e-3));
+
+        d.update(&na::one(), &flat, &Iso3::new(Vec3::new(0.0, 0.0, 1.0), na::zero()), &standing);
+        assert!(d.num_colls() == 0);
+    }
+
+    #[test]
+    fn test_bezier_surface_bezier_surface_toi() {
+        let flat     = surface(0.0, 0.0);
+        let standing = standing_surface();
+        let up       = Vec3::new(0.0f64, 0.0, 1.0);
+
+        // The flat surface reaches the lowest point of the standing surface, at the height 0.2.
+        // The convex hulls of the sub-surfaces are slightly lower than the standing surface.
+        let t = toi(&na::one(), &up, &flat, &na::one(), &standing).expect("The surfaces should touch.");
+
+        assert!(t <= 0.2 + 1.0e-5 && t >= 0.2 - 1.0e-2);
+
+        let t2 = CollisionDetector::toi(None::<BezierSurfaceBezierSurface>, &na::one(), &up, &10.0, &flat,
+                                        &na::one(), &standing);
+
+        assert!(t2 == Some(t));
+        assert!(toi(&na::one(), &-up, &flat, &na::one(), &standing).is_none());
+    }
+}
@@ -0,0 +1,509 @@
+use std::num::Zero;
+use sync::{Arc, RWLock};
+use nalgebra::na::{Translation, Rotate, Transform, Norm};
+use nalgebra::na;
+use math::{Scalar, Vect, Matrix};
+use geom::{BezierSurface, Convex};
+use implicit::Implicit;
+use bounding_volume::HasBoundingSphere;
+use narrow::CollisionDetector;
+use narrow::bezier_surface_ball;
+use narrow::implicit_implicit;
+use narrow::surface_selector::SurfaceSelector;
+use narrow::surface_subdivision_tree::{SurfaceSubdivisionTreeRef, SurfaceSubdivisionTree,
+                                       SurfaceSubdivisionTreeCache, SubdivisionBoundary};
+use narrow::surface_subdivision_tree;
+use contact::Contact;
+
+// Subdivision level of the surface before the numerical refinement.
+static MAX_LEVEL: uint = 15;
+// Number of iterations of the numerical refinement.
+static NITER:     uint = 10;
+
+/// Collision detector between a geometry implementing the `Implicit` trait and a bezier surface.
+///
+/// The surface is subdivided until the `SurfaceSelector` finds the sub-surfaces which might
+/// contain a closest point. The closest points are then refined numerically, using the support
+/// mapping function of the implicit geometry. Closest points on the boundary of the surface are
+/// found too.
+///
+/// The subdivisions are stored on the subdivision tree cache shared with the other detectors
+/// involving a bezier surface. Thus, they are computed only once per surface.
+pub struct ImplicitBezierSurface<S, D, G> {
+    cache:      Arc<RWLock<SurfaceSubdivisionTreeCache<D>>>,
+    tree:       Option<SurfaceSubdivisionTreeRef<D>>,
+    selector:   S,
+    prediction: Scalar,
+    contacts:   Vec<Contact>
+}
+
+impl<S: Clone, D: Send + Share, G> Clone for ImplicitBezierSurface<S, D, G> {
+    fn clone(&self) -> ImplicitBezierSurface<S, D, G> {
+        ImplicitBezierSurface {
+            cache:      self.cache.clone(),
+            tree:       self.tree.clone(),
+            selector:   self.selector.clone(),
+            prediction: self.prediction.clone(),
+            contacts:   self.contacts.clone()
+        }
+    }
+}
+
+impl<S: SurfaceSelector<D>, D, G> ImplicitBezierSurface<S, D, G> {
+    /// Creates a new collision detector with the given prediction margin.
+    pub fn new(selector:   S,
+               prediction: Scalar,
+               cache:      Arc<RWLock<SurfaceSubdivisionTreeCache<D>>>)
+               -> ImplicitBezierSurface<S, D, G> {
+        ImplicitBezierSurface {
+            cache:      cache,
+            tree:       None,
+            selector:   selector,
+            prediction: prediction,
+            contacts:   Vec::new()
+        }
+    }
+}
+
+impl<S: SurfaceSelector<D>, D: Send + Share, G: Implicit<Vect, Matrix>>
+CollisionDetector<G, BezierSurface> for ImplicitBezierSurface<S, D, G> {
+    fn update(&mut self, ma: &Matrix, a: &G, mb: &Matrix, b: &BezierSurface) {
+        self.contacts.clear();
+
+        let renew_tree = match self.tree {
+            None           => true,
+            Some(ref tree) => !tree.is_the_subdivision_tree_of(b)
+        };
+
+        if renew_tree {
+            let selector = &mut self.selector;
+            let tree     = SurfaceSubdivisionTreeCache::find_or_insert_with(
+                               &mut self.cache.clone(), b, || selector.create_test_data(b));
+            self.tree = Some(tree);
+        }
+
+        let tree      = self.tree.as_ref().unwrap().deref().clone();
+        let ls_ma     = na::inv(mb).expect("The transformation `mb` must be inversible.") * *ma;
+        let scale     = b.bounding_sphere(&na::one()).radius();
+        let mut local = Vec::new();
+
+        do_collide(&ls_ma, a, &tree, 0, &SubdivisionBoundary::new_root(), &self.prediction, &scale,
+                   &mut self.selector, &mut local);
+
+        for &(ref p, ref q, ref n, ref depth) in local.iter() {
+            let mut c = Contact::new(mb.transform(p), mb.transform(q), mb.rotate(n), depth.clone());
+
+            c.set_id_from_point(q, &scale);
+
+            self.contacts.push(c);
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        for c in self.contacts.iter() {
+            out_colls.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_: Option<ImplicitBezierSurface<S, D, G>>, ma: &Matrix, dir: &Vect, _: &Scalar, a: &G,
+           mb: &Matrix, b: &BezierSurface) -> Option<Scalar> {
+        toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Collision detector between a bezier surface and a geometry implementing the `Implicit` trait.
+pub struct BezierSurfaceImplicit<S, D, G> {
+    detector: ImplicitBezierSurface<S, D, G>
+}
+
+impl<S: Clone, D: Send + Share, G> Clone for BezierSurfaceImplicit<S, D, G> {
+    fn clone(&self) -> BezierSurfaceImplicit<S, D, G> {
+        BezierSurfaceImplicit {
+            detector: self.detector.clone()
+        }
+    }
+}
+
+impl<S: SurfaceSelector<D>, D, G> BezierSurfaceImplicit<S, D, G> {
+    /// Creates a new collision detector with the given prediction margin.
+    pub fn new(selector:   S,
+               prediction: Scalar,
+               cache:      Arc<RWLock<SurfaceSubdivisionTreeCache<D>>>)
+               -> BezierSurfaceImplicit<S, D, G> {
+        BezierSurfaceImplicit {
+            detector: ImplicitBezierSurface::new(selector, prediction, cache)
+        }
+    }
+}
+
+impl<S: SurfaceSelector<D>, D: Send + Share, G: Implicit<Vect, Matrix>>
+CollisionDetector<BezierSurface, G> for BezierSurfaceImplicit<S, D, G> {
+    fn update(&mut self, ma: &Matrix, a: &BezierSurface, mb: &Matrix, b: &G) {
+        self.detector.update(mb, b, ma, a);
+
+        for c in self.detector.contacts.mut_iter() {
+            c.flip();
+        }
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.detector.num_colls()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        self.detector.colls(out_colls)
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.detector.set_prediction(prediction)
+    }
+
+    #[inline]
+    fn toi(_: Option<BezierSurfaceImplicit<S, D, G>>, ma: &Matrix, dir: &Vect, _: &Scalar,
+           a: &BezierSurface, mb: &Matrix, b: &G) -> Option<Scalar> {
+        toi(mb, &-*dir, b, ma, a)
+    }
+}
+
+/// Computes the time of impact of an implicit geometry moving along the direction `dir` with a
+/// bezier surface.
+///
+/// The surface is subdivided, and its parts are culled using the convex hull of their control
+/// points. The time of impact with the convex hull of the smallest sub-surfaces is returned: this
+/// is a lower bound of the exact time of impact, which gets more accurate as the sub-surfaces
+/// flatten.
+pub fn toi<G: Implicit<Vect, Matrix>>(m1: &Matrix, dir: &Vect, g1: &G, m2: &Matrix, b2: &BezierSurface)
+                                      -> Option<Scalar> {
+    let max_depth = 10;
+    let mut best  = None;
+
+    do_toi(m1, dir, g1, m2, b2, 0, max_depth, &mut best);
+
+    best
+}
+
+fn do_toi<G: Implicit<Vect, Matrix>>(m1:        &Matrix,
+                                     dir:       &Vect,
+                                     g1:        &G,
+                                     m2:        &Matrix,
+                                     b:         &BezierSurface,
+                                     level:     uint,
+                                     max_depth: uint,
+                                     best:      &mut Option<Scalar>) {
+    // The sub-surface is inside of the convex hull of its control points.
+    let hull  = Convex::new_with_margin(b.control_points().to_owned(), na::zero());
+    let start = match implicit_implicit::toi(m1, dir, g1, m2, &hull) {
+        None      => return,
+        Some(toi) => toi
+    };
+
+    match *best {
+        Some(ref best_toi) if *best_toi <= start => return,
+        _ => { }
+    }
+
+    if level == max_depth {
+        *best = Some(start);
+    }
+    else {
+        // subdivide
+        let mut left  = BezierSurface::new_with_degrees(b.degree_u(), b.degree_v());
+        let mut right = BezierSurface::new_with_degrees(b.degree_u(), b.degree_v());
+
+        if level % 2 == 1 {
+            b.subdivide_u(&na::cast(0.5), &mut left, &mut right);
+        }
+        else {
+            b.subdivide_v(&na::cast(0.5), &mut left, &mut right);
+        }
+
+        do_toi(m1, dir, g1, m2, &left,  level + 1, max_depth, best);
+        do_toi(m1, dir, g1, m2, &right, level + 1, max_depth, best);
+    }
+}
+
+// Collects the contacts between the implicit geometry `g` transformed by `m` and the sub-surface
+// `tree` at the subdivision level `level`. Everything is expressed in the local space of the
+// surface.
+//
+// Each contact is given as the closest point on `g`, the closest point on the surface, the normal
+// (from `g` to the surface) and the penetration depth.
+fn do_collide<S: SurfaceSelector<D>, D: Send + Share, G: Implicit<Vect, Matrix>>(
+              m:          &Matrix,
+              g:          &G,
+              tree:       &Arc<RWLock<SurfaceSubdivisionTree<D>>>,
+              level:      uint,
+              boundary:   &SubdivisionBoundary,
+              prediction: &Scalar,
+              scale:      &Scalar,
+              selector:   &mut S,
+              out:        &mut Vec<(Vect, Vect, Vect, Scalar)>) {
+    // >>>>>>>> get a read-only lock on the node.
+    {
+        let r_tree = tree.read();
+        let b      = r_tree.surface();
+
+        // Use the support mapping function to check if `g` is close to the surface bounding sphere.
+        let bs     = b.bounding_sphere(&na::one());
+        let center = m.translation();
+        let mut pt = center.clone();
+        let mut dir = *bs.center() - center;
+
+        if !dir.normalize().is_zero() {
+            pt = g.support_point(m, &dir);
+
+            if na::dot(&(*bs.center() - pt), &dir) > bs.radius() + *prediction {
+                return;
+            }
+        }
+
+        if level >= MAX_LEVEL || selector.is_flat(b, r_tree.data()) {
+            match closest_points(m, g, b, boundary, &pt) {
+                Some(res) => {
+                    let (_, ref q, _, ref depth) = res;
+
+                    if *depth >= -*prediction && !is_duplicate(q, scale, out.as_slice()) {
+                        out.push(res.clone())
+                    }
+                },
+                None => { }
+            }
+
+            return;
+        }
+
+        // The closest points on the boundary of the surface are not stationary points of the
+        // distance function: they are missed by the selector.
+        if !boundary.touches_boundary() &&
+           !selector.may_contain_a_closest_point(&pt, b, r_tree.data()) {
+            // there is no solution.
+            return;
+        }
+    }
+    // <<<<<<<< end of read-only lock on the node.
+
+    // subdivide
+    let (left, right)    = surface_subdivision_tree::children(tree, level, selector);
+    let (lbound, rbound) = boundary.children(level);
+
+    do_collide(m, g, &left,  level + 1, &lbound, prediction, scale, selector, out);
+    do_collide(m, g, &right, level + 1, &rbound, prediction, scale, selector, out);
+}
+
+// Refines the closest points between `g` and `b` by projecting alternatively on the surface (with
+// the Newton method) and on the implicit geometry (with its support mapping function).
+fn closest_points<G: Implicit<Vect, Matrix>>(m:        &Matrix,
+                                             g:        &G,
+                                             b:        &BezierSurface,
+                                             boundary: &SubdivisionBoundary,
+                                             pt:       &Vect)
+                                             -> Option<(Vect, Vect, Vect, Scalar)> {
+    let center  = m.translation();
+    let mut pt  = pt.clone();
+    let mut res = None;
+
+    for _ in range(0, NITER) {
+        let q = match bezier_surface_ball::closest_point_with_boundary(&pt, b, boundary, NITER) {
+            Some(q) => q,
+            None    => return None
+        };
+
+        let mut n = q - pt;
+
+        if n.normalize().is_zero() {
+            // `pt` lies on the surface.
+            n = q - center;
+
+            if n.normalize().is_zero() {
+                return None;
+            }
+        }
+
+        // The normal must point from the interior of `g` toward the surface, even if they are
+        // penetrating.
+        if na::dot(&n, &(q - center)) < na::zero() {
+            n = -n;
+        }
+
+        let p     = g.support_point(m, &n);
+        let depth = na::dot(&(p - q), &n);
+
+        res = Some((p.clone(), q, n, depth));
+        pt  = p;
+    }
+
+    res
+}
+
+// Tests if the closest point `q` has already been found on a neighbor sub-surface. `scale` is the
+// size of the whole surface.
+fn is_duplicate(q: &Vect, scale: &Scalar, found: &[(Vect, Vect, Vect, Scalar)]) -> bool {
+    let _rel_tol: Scalar = na::cast(1.0e-4);
+    let eps = *scale * _rel_tol;
+
+    found.iter().any(|&(_, ref fq, _, _)| na::sqnorm(&(*fq - *q)) <= eps * eps)
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use std::num::Bounded;
+    use sync::{Arc, RWLock};
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{BezierSurface, Cuboid};
+    use narrow::CollisionDetector;
+    use narrow::surface_selector::{TangentConesSurfaceSelector, TangentConesSurfaceSelectorTestData};
+    use narrow::surface_subdivision_tree::SurfaceSubdivisionTreeCache;
+    use super::{ImplicitBezierSurface, BezierSurfaceImplicit, toi};
+
+    type Detector = ImplicitBezierSurface<TangentConesSurfaceSelector,
+                                          TangentConesSurfaceSelectorTestData,
+                                          Cuboid>;
+
+    type FlippedDetector = BezierSurfaceImplicit<TangentConesSurfaceSelector,
+                                                 TangentConesSurfaceSelectorTestData,
+                                                 Cuboid>;
+
+    // A curved panel on the square `[0, 2]²`, with straight edges at the height 0. Its highest
+    // point is (1.0, 1.0, 0.25).
+    fn panel() -> BezierSurface {
+        let mut pts = Vec::new();
+
+        for i in range(0u, 3) {
+            for j in range(0u, 3) {
+                let z = if i == 1 && j == 1 { 1.0 } else { 0.0 };
+
+                pts.push(Vec3::new(i as f64, j as f64, z));
+            }
+        }
+
+        BezierSurface::new(pts, 3, 3)
+    }
+
+    fn detector(prediction: f64) -> Detector {
+        let cache = Arc::new(RWLock::new(SurfaceSubdivisionTreeCache::new()));
+        let sel   = TangentConesSurfaceSelector::new(Bounded::max_value());
+
+        ImplicitBezierSurface::new(sel, prediction, cache)
+    }
+
+    #[test]
+    fn test_box_on_curved_panel() {
+        let s     = panel();
+        let c     = Cuboid::new_with_margin(Vec3::new(0.2f64, 0.2, 0.2), 0.0);
+        // The bottom of the box is 0.05 below the top of the panel.
+        let mc    = Iso3::new(Vec3::new(1.0f64, 1.0, 0.4), na::zero());
+        let mut d = detector(0.1);
+
+        d.update(&mc, &c, &na::one(), &s);
+        assert!(d.num_colls() >= 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        for contact in contacts.iter() {
+            assert!(contact.depth > 0.0 && contact.depth < 0.1);
+            assert!(contact.normal.z < -0.9);
+        }
+
+        // The subdivisions are kept on the cache.
+        assert!(d.tree.as_ref().unwrap().deref().read().has_left_child());
+
+        // Same result when the subdivision tree is reused.
+        d.update(&mc, &c, &na::one(), &s);
+        assert!(d.num_colls() == contacts.len());
+
+        d.update(&Iso3::new(Vec3::new(1.0f64, 1.0, 1.0), na::zero()), &c, &na::one(), &s);
+        assert!(d.num_colls() == 0);
+    }
+
+    #[test]
+    fn test_curved_panel_on_box() {
+        let s     = panel();
+        let c     = Cuboid::new_with_margin(Vec3::new(0.2f64, 0.2, 0.2), 0.0);
+        let mc    = Iso3::new(Vec3::new(1.0f64, 1.0, 0.4), na::zero());
+        let cache = Arc::new(RWLock::new(SurfaceSubdivisionTreeCache::new()));
+        let sel   = TangentConesSurfaceSelector::new(Bounded::max_value());
+        let mut d: BezierSurfaceImplicit<TangentConesSurfaceSelector,
+                                         TangentConesSurfaceSelectorTestData,
+                                         Cuboid> = BezierSurfaceImplicit::new(sel, 0.1, cache);
+
+        d.update(&na::one(), &s, &mc, &c);
+        assert!(d.num_colls() >= 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        for contact in contacts.iter() {
+            assert!(contact.depth > 0.0);
+            assert!(contact.normal.z > 0.9);
+        }
+    }
+
+    #[test]
+    fn test_box_near_curved_panel_edge() {
+        let s     = panel();
+        let c     = Cuboid::new_with_margin(Vec3::new(0.2f64, 0.2, 0.2), 0.0);
+        // The vertical edge of the box pointing toward the panel is at `x = 2.6 - 0.2 * sqrt(2)`.
+        let mc    = Iso3::new(Vec3::new(2.6f64, 1.0, 0.0), Vec3::new(0.0, 0.0, Float::frac_pi_4()));
+        let mut d = detector(0.5);
+
+        d.update(&mc, &c, &na::one(), &s);
+        assert!(d.num_colls() >= 1);
+
+        let mut contacts = Vec::new();
+        d.colls(&mut contacts);
+
+        for contact in contacts.iter() {
+            // the closest point is on the edge `u = 1` of the panel.
+            assert!(na::approx_eq_eps(&contact.world2.x, &2.0, &1.0e-3));
+            assert!(na::approx_eq_eps(&contact.world2.y, &1.0, &1.0e-3));
+            assert!(na::approx_eq_eps(&contact.world2.z, &0.0, &1.0e-3));
+            assert!(contact.depth < 0.0 && contact.depth > -0.5);
+            assert!(contact.normal.x < 0.0);
+        }
+
+        // Too far from the edge.
+        d.update(&Iso3::new(Vec3::new(3.0f64, 1.0, 0.0), Vec3::new(0.0, 0.0, Float::frac_pi_4())),
+                 &c, &na::one(), &s);
+        assert!(d.num_colls() == 0);
+    }
+
+    #[test]
+    fn test_box_curved_panel_toi() {
+        let s    = panel();
+        let c    = Cuboid::new_with_margin(Vec3::new(0.2f64, 0.2, 0.2), 0.0);
+        let mc   = Iso3::new(Vec3::new(1.0f64, 1.0, 3.0), na::zero());
+        let down = Vec3::new(0.0f64, 0.0, -1.0);
+
+        // The bottom of the box reaches the top of the panel at the height 0.25. The convex hulls
+        // of the sub-surfaces are slightly higher than the panel.
+        let t = toi(&mc, &down, &c, &na::one(), &s).expect("The box should hit the panel.");
+
+        assert!(t <= 2.55 + 1.0e-5 && t >= 2.55 - 1.0e-2);
+
+        // Same result when the panel moves toward the box.
+        let flipped = CollisionDetector::toi(None::<FlippedDetector>, &na::one(), &-down, &10.0, &s, &mc, &c);
+
+        assert!(flipped == Some(t));
+
+        // The box moves away from the panel, or passes next to it.
+        assert!(toi(&mc, &-down, &c, &na::one(), &s).is_none());
+        assert!(toi(&Iso3::new(Vec3::new(2.3f64, 1.0, 3.0), na::zero()), &down, &c, &na::one(), &s).is_none());
+    }
+}
@@ -0,0 +1,133 @@
+use nalgebra::na::{Indexable, Transform};
+use nalgebra::na;
+use geom::Capsule;
+use narrow::{CollisionDetector, implicit_implicit, segment_segment};
+use contact::Contact;
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between two capsules.
+///
+/// Two contacts are generated when the capsules are parallel and their cylindrical parts overlap.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct CapsuleCapsule {
+    prediction: Scalar,
+    contacts:   Vec<Contact>
+}
+
+impl CapsuleCapsule {
+    /// Creates a new persistent collision detector between two capsules.
+    #[inline]
+    pub fn new(prediction: Scalar) -> CapsuleCapsule {
+        CapsuleCapsule {
+            prediction: prediction,
+            contacts:   Vec::new()
+        }
+    }
+}
+
+impl CollisionDetector<Capsule, Capsule> for CapsuleCapsule {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Capsule, mb: &Matrix, b: &Capsule) {
+        self.contacts.clear();
+
+        collide(ma, a, mb, b, &self.prediction, &mut self.contacts)
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        for c in self.contacts.iter() {
+            out_colls.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_:   Option<CapsuleCapsule>,
+           ma:  &Matrix,
+           dir: &Vect,
+           _:   &Scalar,
+           a:   &Capsule,
+           mb:  &Matrix,
+           b:   &Capsule) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Computes the contact points between two capsules.
+///
+/// Two contacts are pushed to `out` if the capsules are parallel and their cylindrical parts
+/// overlap, at most one otherwise.
+#[inline]
+pub fn collide(m1:         &Matrix,
+               c1:         &Capsule,
+               m2:         &Matrix,
+               c2:         &Capsule,
+               prediction: &Scalar,
+               out:        &mut Vec<Contact>) {
+    let (a1, b1) = axis(m1, c1);
+    let (a2, b2) = axis(m2, c2);
+
+    segment_segment::collide_with_radii(&a1, &b1, &c1.radius(), &a2, &b2, &c2.radius(), prediction, out)
+}
+
+/// The extremities of the segment supporting the cylindrical part of a transformed capsule.
+#[inline]
+pub fn axis(m: &Matrix, c: &Capsule) -> (Vect, Vect) {
+    let mut half: Vect = na::zero();
+
+    half.set(0, c.half_height());
+
+    (m.transform(&-half), m.transform(&half))
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Capsule;
+    use test_utils::assert_same_contact_as_gjk;
+    use super::collide;
+
+    #[test]
+    fn test_capsule_capsule_gjk() {
+        let c1 = Capsule::new(1.0f64, 0.5, 0.0);
+        let c2 = Capsule::new(0.5f64, 0.3, 0.0);
+
+        for m2 in [ Iso3::new(Vec3::new(0.2f64, 0.9, 0.1), Vec3::new(0.0f64, 0.0, 1.2)),
+                    Iso3::new(Vec3::new(1.8f64, 0.3, 0.4), Vec3::new(0.3f64, 0.6, 0.0)),
+                    Iso3::new(Vec3::new(0.0f64, 0.0, 3.0), na::zero()) ].iter() {
+            let mut contacts = Vec::new();
+
+            collide(&na::one(), &c1, m2, &c2, &na::zero(), &mut contacts);
+
+            // Non-parallel capsules have at most one contact.
+            assert!(contacts.len() <= 1);
+            assert_same_contact_as_gjk(&na::one(), &c1, m2, &c2, contacts.pop());
+        }
+    }
+
+    #[test]
+    fn test_parallel_capsules() {
+        let c  = Capsule::new(1.0f64, 0.5, 0.0);
+        let m2 = Iso3::new(Vec3::new(1.0f64, 0.0, 0.9), na::zero());
+        let mut contacts = Vec::new();
+
+        collide(&na::one(), &c, &m2, &c, &na::zero(), &mut contacts);
+
+        assert!(contacts.len() == 2);
+
+        for contact in contacts.iter() {
+            assert!(na::approx_eq_eps(&contact.depth, &0.1, &1.0e-7));
+            assert!(na::approx_eq_eps(&contact.normal, &Vec3::new(0.0f64, 0.0, 1.0), &1.0e-7));
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use contact::Contact;
+use contact::{Contact, ContactWLocals};
 use math::{Scalar, Vect, Matrix};
 
 /// Trait of the algorithms executed during the so-called Narrow Phase.
@@ -22,6 +22,39 @@ pub trait CollisionDetector<G1, G2> {
     /// Collects the collisions detected during the last update.
     fn colls(&self, &mut Vec<Contact>);
 
+    /// Collects the collisions detected during the last update, together with their local-space
+    /// points.
+    ///
+    /// `m1` and `m2` must be the transforms given to the last update. By default, the local points
+    /// are computed from the result of `colls`.
+    fn colls_with_locals(&self, m1: &Matrix, m2: &Matrix, out: &mut Vec<ContactWLocals>) {
+        let mut colls = Vec::new();
+
+        self.colls(&mut colls);
+
+        for c in colls.move_iter() {
+            out.push(ContactWLocals::new_with_contact(c, m1, m2))
+        }
+    }
+
+    /// Sets the maximum distance between two objects for them to generate contacts.
+    ///
+    /// This allows each pair of objects to use its own prediction distance. By default, this does
+    /// nothing: the detector keeps the prediction distance it has been created with.
+    fn set_prediction(&mut self, _: &Scalar) {
+    }
+
+    /// Collects the contacts the objects would have if the first one was slightly rotated around
+    /// the contact `contact`, in the plane spanned by its normal and `tangent`.
+    ///
+    /// This is used to generate a full contact manifold in 4D, where rotations cannot be applied
+    /// to the transforms. The contacts are expressed wrt. the unperturbed transforms. By default,
+    /// nothing is collected.
+    #[cfg(dim4)]
+    fn perturbed_colls(&self, _: &Matrix, _: &G1, _: &Matrix, _: &G2, _: &Contact, _: &Vect,
+                       _: &mut Vec<Contact>) {
+    }
+
     /// Computes the time of impact of two objects.
     ///
     /// # Arguments
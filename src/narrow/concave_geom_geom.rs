@@ -1,20 +1,26 @@
 use std::any::AnyRefExt;
+use nalgebra::na::{Translation, Rotate, Indexable};
 use nalgebra::na;
 use util::hash_map::HashMap;
-use util::hash::UintTWHash;
-use bounding_volume::{BoundingVolume, HasAABB};
+use util::hash::{UintTWHash, UintPairTWHash};
+use bounding_volume::{BoundingVolume, LooseBoundingVolume, HasAABB, AABB};
 use broad::Dispatcher;
 use narrow::{CollisionDetector, GeomGeomDispatcher, GeomGeomCollisionDetector,
-             DynamicCollisionDetector, CollisionDetectorFactory};
+             DynamicCollisionDetector, CollisionDetectorFactory, GeomTypeMismatch, downcast_geoms,
+             GeomGeomTOIDispatcher, GeomGeomTOI, DynamicTOI,
+             GeomGeomDistanceDispatcher, GeomGeomDistance, DynamicDistance, Distance,
+             GeomGeomProximityDispatcher, GeomGeomProximity, DynamicProximity, Proximity,
+             Intersecting, WithinMargin, Disjoint};
 use contact::Contact;
 use geom::{Geom, ConcaveGeom};
-use math::Matrix;
+use math::{Scalar, Vect, Matrix};
 
 /// Collision detector between a concave geometry and another geometry.
 pub struct ConcaveGeomGeom<G1, G2> {
     sub_detectors: HashMap<uint, Box<GeomGeomCollisionDetector>, UintTWHash>,
     to_delete:     Vec<uint>,
-    interferences: Vec<uint>
+    interferences: Vec<uint>,
+    prediction:    Option<Scalar>
 }
 
 impl<G1, G2> ConcaveGeomGeom<G1, G2> {
@@ -23,23 +29,25 @@ impl<G1, G2> ConcaveGeomGeom<G1, G2> {
         ConcaveGeomGeom {
             sub_detectors: HashMap::new_with_capacity(5, UintTWHash::new()),
             to_delete:     Vec::new(),
-            interferences: Vec::new()
+            interferences: Vec::new(),
+            prediction:    None
         }
     }
 }
 
-impl<G1: ConcaveGeom, G2: Geom> ConcaveGeomGeom<G1, G2> {
+impl<G1: ConcaveGeom, G2> ConcaveGeomGeom<G1, G2> {
+    // The second geometry is only used through the `Geom` trait object: `G2` is not checked here,
+    // this lets the dispatcher use this detector as a fallback for geometries of unknown types.
     fn do_update(&mut self,
                  dispatcher: &GeomGeomDispatcher,
                  m1:         &Matrix,
                  g1:         &G1,
                  m2:         &Matrix,
-                 g2:         &G2,
+                 g2:         &Geom,
                  swap:       bool) {
         // Find new collisions
         let ls_m2    = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
         let ls_aabb2 = g2.aabb(&ls_m2);
-        let g2       = g2 as &Geom;
 
         g1.approx_interferences_with_aabb(&ls_aabb2, &mut self.interferences);
 
@@ -54,7 +62,8 @@ impl<G1: ConcaveGeom, G2: Geom> ConcaveGeomGeom<G1, G2> {
             });
 
             match detector {
-                Some(detector) => {
+                Some(mut detector) => {
+                    set_sub_prediction(&mut detector, &self.prediction);
                     let _ = self.sub_detectors.insert_or_replace(*i, detector, false);
                 },
                 None => { }
@@ -67,14 +76,18 @@ impl<G1: ConcaveGeom, G2: Geom> ConcaveGeomGeom<G1, G2> {
         for detector in self.sub_detectors.elements_mut().mut_iter() {
             let key = detector.key;
             if ls_aabb2.intersects(g1.aabb_at(key)) {
-                g1.map_transformed_part_at(m1, key, |m1, g1| {
+                let sub_res = g1.map_transformed_part_at(m1, key, |m1, g1| {
                     if swap {
-                        detector.value.update(dispatcher, m2, g2, m1, g1);
+                        detector.value.update(dispatcher, m2, g2, m1, g1)
                     }
                     else {
-                        detector.value.update(dispatcher, m1, g1, m2, g2);
+                        detector.value.update(dispatcher, m1, g1, m2, g2)
                     }
                 });
+
+                if sub_res.is_err() {
+                    fail!("The collision detector dispatched for a part does not handle its type.")
+                }
             }
             else {
                 // FIXME: ask the detector if it wants to be removed or not
@@ -89,22 +102,38 @@ impl<G1: ConcaveGeom, G2: Geom> ConcaveGeomGeom<G1, G2> {
 
         self.to_delete.clear();
     }
+
+    // Collects the contacts of all the sub-detectors, tagged with the part they lie on.
+    fn collect(&self, out: &mut Vec<Contact>, swap: bool) {
+        for detector in self.sub_detectors.elements().iter() {
+            let begin = out.len();
+
+            detector.value.colls(out);
+
+            for c in out.mut_slice_from(begin).mut_iter() {
+                c.set_part(!swap, detector.key);
+            }
+        }
+    }
 }
 
-impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
+impl<G1: 'static + ConcaveGeom, G2: 'static + Send>
 GeomGeomCollisionDetector for ConcaveGeomGeom<G1, G2> {
     fn update(&mut self,
               dispatcher: &GeomGeomDispatcher,
               m1:         &Matrix,
               g1:         &Geom,
               m2:         &Matrix,
-              g2:         &Geom) {
-        self.do_update(dispatcher,
-                       m1,
-                       g1.as_ref::<G1>().expect("Invalid geometry."),
-                       m2,
-                       g2.as_ref::<G2>().expect("Invalid geometry."),
-                       false);
+              g2:         &Geom)
+              -> Result<(), GeomTypeMismatch> {
+        match g1.as_ref::<G1>() {
+            Some(g1) => {
+                self.do_update(dispatcher, m1, g1, m2, g2, false);
+
+                Ok(())
+            },
+            None => Err(GeomTypeMismatch)
+        }
     }
 
     fn num_colls(&self) -> uint {
@@ -118,8 +147,14 @@ GeomGeomCollisionDetector for ConcaveGeomGeom<G1, G2> {
     }
 
     fn colls(&self, out: &mut Vec<Contact>) {
-        for detector in self.sub_detectors.elements().iter() {
-            detector.value.colls(out);
+        self.collect(out, false)
+    }
+
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = Some(prediction.clone());
+
+        for detector in self.sub_detectors.elements_mut().mut_iter() {
+            detector.value.set_prediction(prediction)
         }
     }
 }
@@ -127,6 +162,15 @@ GeomGeomCollisionDetector for ConcaveGeomGeom<G1, G2> {
 impl<G1: ConcaveGeom, G2: Geom>
 DynamicCollisionDetector<G1, G2> for ConcaveGeomGeom<G1, G2> { }
 
+// Sets the prediction of a newly created sub-detector, if a prediction specific to the pair of
+// concave and other geometry has been set.
+fn set_sub_prediction(detector: &mut Box<GeomGeomCollisionDetector>, prediction: &Option<Scalar>) {
+    match *prediction {
+        Some(ref p) => detector.set_prediction(p),
+        None        => { }
+    }
+}
+
 /// Collision detector between a geometry and a concave geometry.
 pub struct GeomConcaveGeom<G1, G2> {
     sub_detector: ConcaveGeomGeom<G2, G1>
@@ -141,20 +185,23 @@ impl<G1, G2> GeomConcaveGeom<G1, G2> {
     }
 }
 
-impl<G1: 'static + Geom, G2: 'static + ConcaveGeom>
+impl<G1: 'static + Send, G2: 'static + ConcaveGeom>
 GeomGeomCollisionDetector for GeomConcaveGeom<G1, G2> {
     fn update(&mut self,
               dispatcher: &GeomGeomDispatcher,
               m1:         &Matrix,
               g1:         &Geom,
               m2:         &Matrix,
-              g2:         &Geom) {
-        self.sub_detector.do_update(dispatcher,
-                                    m2,
-                                    g2.as_ref::<G2>().expect("Invalid geometry."),
-                                    m1,
-                                    g1.as_ref::<G1>().expect("Invalid geometry."),
-                                    true);
+              g2:         &Geom)
+              -> Result<(), GeomTypeMismatch> {
+        match g2.as_ref::<G2>() {
+            Some(g2) => {
+                self.sub_detector.do_update(dispatcher, m2, g2, m1, g1, true);
+
+                Ok(())
+            },
+            None => Err(GeomTypeMismatch)
+        }
     }
 
     fn num_colls(&self) -> uint {
@@ -162,13 +209,725 @@ GeomGeomCollisionDetector for GeomConcaveGeom<G1, G2> {
     }
 
     fn colls(&self, out: &mut Vec<Contact>) {
-        self.sub_detector.colls(out)
+        self.sub_detector.collect(out, true)
+    }
+
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.sub_detector.set_prediction(prediction)
     }
 }
 
 impl<G1: Geom, G2: ConcaveGeom>
 DynamicCollisionDetector<G1, G2> for GeomConcaveGeom<G1, G2> { }
 
+// Stands for the type of a geometry which is not known statically.
+struct UnknownGeom;
+
+/// Builds a collision detector between the concave geometry `G` and a geometry of any type.
+///
+/// If `swap` is `true`, the concave geometry is the second one of the pair.
+pub fn concave_geom_any_detector<G: 'static + ConcaveGeom>(swap: bool) -> Box<GeomGeomCollisionDetector> {
+    if swap {
+        box GeomConcaveGeom::<UnknownGeom, G>::new() as Box<GeomGeomCollisionDetector>
+    }
+    else {
+        box ConcaveGeomGeom::<G, UnknownGeom>::new() as Box<GeomGeomCollisionDetector>
+    }
+}
+
+/// Collision detector between two concave geometries.
+///
+/// The bounding volume trees of both geometries are traversed simultaneously. A sub-detector is
+/// created for each pair of parts with intersecting bounding volumes.
+pub struct ConcaveGeomConcaveGeom<G1, G2> {
+    sub_detectors: HashMap<(uint, uint), Box<GeomGeomCollisionDetector>, UintPairTWHash>,
+    to_delete:     Vec<(uint, uint)>,
+    interferences: Vec<(uint, uint)>,
+    prediction:    Option<Scalar>
+}
+
+impl<G1, G2> ConcaveGeomConcaveGeom<G1, G2> {
+    /// Creates a new collision detector between two concave geometries.
+    pub fn new() -> ConcaveGeomConcaveGeom<G1, G2> {
+        ConcaveGeomConcaveGeom {
+            sub_detectors: HashMap::new_with_capacity(5, UintPairTWHash::new()),
+            to_delete:     Vec::new(),
+            interferences: Vec::new(),
+            prediction:    None
+        }
+    }
+}
+
+impl<G1: ConcaveGeom, G2: ConcaveGeom> ConcaveGeomConcaveGeom<G1, G2> {
+    fn do_update(&mut self,
+                 dispatcher: &GeomGeomDispatcher,
+                 m1:         &Matrix,
+                 g1:         &G1,
+                 m2:         &Matrix,
+                 g2:         &G2) {
+        // Find new collisions
+        let ls_m2 = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
+
+        {
+            let interferences = &mut self.interferences;
+
+            match (g1.bvt(), g2.bvt()) {
+                (Some(bvt1), Some(bvt2)) => {
+                    bvt1.visit_bvtt(bvt2,
+                                    &mut |bv1, bv2| bv1.intersects(&bv2.transformed(&ls_m2)),
+                                    &mut |i, j| interferences.push((*i, *j)))
+                },
+                _ => approx_interferences(&ls_m2, g1, g2, interferences)
+            }
+        }
+
+        for &(i, j) in self.interferences.iter() {
+            let detector = g1.map_part_at(i, |_, g1| {
+                g2.map_part_at(j, |_, g2| dispatcher.dispatch(g1, g2))
+            });
+
+            match detector {
+                Some(mut detector) => {
+                    set_sub_prediction(&mut detector, &self.prediction);
+                    let _ = self.sub_detectors.insert_or_replace((i, j), detector, false);
+                },
+                None => { }
+            }
+        }
+
+        self.interferences.clear();
+
+        // Update all collisions
+        for detector in self.sub_detectors.elements_mut().mut_iter() {
+            let (i, j) = detector.key;
+
+            if g1.aabb_at(i).intersects(&g2.aabb_at(j).transformed(&ls_m2)) {
+                let sub_res = g1.map_transformed_part_at(m1, i, |m1, g1| {
+                    g2.map_transformed_part_at(m2, j, |m2, g2| {
+                        detector.value.update(dispatcher, m1, g1, m2, g2)
+                    })
+                });
+
+                if sub_res.is_err() {
+                    fail!("The collision detector dispatched for a part does not handle its type.")
+                }
+            }
+            else {
+                // FIXME: ask the detector if it wants to be removed or not
+                self.to_delete.push((i, j));
+            }
+        }
+
+        // Remove outdated sub detectors
+        for key in self.to_delete.iter() {
+            self.sub_detectors.remove(key);
+        }
+
+        self.to_delete.clear();
+    }
+}
+
+// Collects the pairs of parts with intersecting AABBs, for concave geometries without BVT. `ls_m2`
+// is the transform of `g2` in the local space of `g1`.
+fn approx_interferences<G1: ConcaveGeom, G2: ConcaveGeom>(ls_m2: &Matrix,
+                                                          g1:    &G1,
+                                                          g2:    &G2,
+                                                          out:   &mut Vec<(uint, uint)>) {
+    let inv_ls_m2  = na::inv(ls_m2).expect("The transformation `m2` must be inversible.");
+    let mut parts1 = Vec::new();
+    let mut parts2 = Vec::new();
+
+    g1.approx_interferences_with_aabb(&g2.aabb(ls_m2), &mut parts1);
+
+    for i in parts1.iter() {
+        g2.approx_interferences_with_aabb(&g1.aabb_at(*i).transformed(&inv_ls_m2), &mut parts2);
+
+        for j in parts2.iter() {
+            out.push((*i, *j))
+        }
+
+        parts2.clear();
+    }
+}
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + ConcaveGeom>
+GeomGeomCollisionDetector for ConcaveGeomConcaveGeom<G1, G2> {
+    fn update(&mut self,
+              dispatcher: &GeomGeomDispatcher,
+              m1:         &Matrix,
+              g1:         &Geom,
+              m2:         &Matrix,
+              g2:         &Geom)
+              -> Result<(), GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+
+        self.do_update(dispatcher, m1, g1, m2, g2);
+
+        Ok(())
+    }
+
+    fn num_colls(&self) -> uint {
+        let mut res = 0;
+
+        for detector in self.sub_detectors.elements().iter() {
+            res = res + detector.value.num_colls()
+        }
+
+        res
+    }
+
+    fn colls(&self, out: &mut Vec<Contact>) {
+        for detector in self.sub_detectors.elements().iter() {
+            let begin  = out.len();
+            let (i, j) = detector.key;
+
+            detector.value.colls(out);
+
+            for c in out.mut_slice_from(begin).mut_iter() {
+                c.set_part(true, i);
+                c.set_part(false, j);
+            }
+        }
+    }
+
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = Some(prediction.clone());
+
+        for detector in self.sub_detectors.elements_mut().mut_iter() {
+            detector.value.set_prediction(prediction)
+        }
+    }
+}
+
+impl<G1: ConcaveGeom, G2: ConcaveGeom>
+DynamicCollisionDetector<G1, G2> for ConcaveGeomConcaveGeom<G1, G2> { }
+
+/*
+ *
+ * Time of impact
+ *
+ */
+/// Computes the Time Of Impact of a concave geometry and another geometry.
+///
+/// The parts of the concave geometry are culled using its BVT and the AABB of `g2` swept along
+/// the relative displacement. Then, the time of impact of each remaining part is computed by
+/// `dispatcher`. Hits occurring after `dist` are ignored.
+///
+/// Returns the earliest time of impact and the index of the part of `g1` it occurs with.
+///
+/// # Arguments:
+/// * `m1`   - the concave geometry transform.
+/// * `dir`  - the direction of the concave geometry movement.
+/// * `dist` - the maximum distance traveled by the concave geometry.
+/// * `g1`   - the concave geometry.
+/// * `m2`   - the other geometry transform.
+/// * `g2`   - the other geometry.
+pub fn toi<G1: ConcaveGeom, G2: Geom>(dispatcher: &GeomGeomTOIDispatcher,
+                                      m1:         &Matrix,
+                                      dir:        &Vect,
+                                      dist:       &Scalar,
+                                      g1:         &G1,
+                                      m2:         &Matrix,
+                                      g2:         &G2)
+                                      -> Option<(Scalar, uint)> {
+    toi_with(m1, dir, dist, g1, m2, g2,
+             |m1, g1, m2, g2| dispatcher.toi(m1, dir, dist, g1, m2, g2).impact())
+}
+
+/// Computes the Time Of Impact of two concave geometries.
+///
+/// This is the same as `toi` except that the parts of `g2` are culled too, using the AABB of each
+/// remaining part of `g1` swept along the displacement.
+///
+/// Returns the earliest time of impact and the indices of the parts of `g1` and `g2` it occurs
+/// with.
+///
+/// # Arguments:
+/// * `m1`   - the first concave geometry transform.
+/// * `dir`  - the direction of the first concave geometry movement.
+/// * `dist` - the maximum distance traveled by the first concave geometry.
+/// * `g1`   - the first concave geometry.
+/// * `m2`   - the second concave geometry transform.
+/// * `g2`   - the second concave geometry.
+pub fn toi_concave_concave<G1: ConcaveGeom, G2: ConcaveGeom>(
+                           dispatcher: &GeomGeomTOIDispatcher,
+                           m1:         &Matrix,
+                           dir:        &Vect,
+                           dist:       &Scalar,
+                           g1:         &G1,
+                           m2:         &Matrix,
+                           g2:         &G2)
+                           -> Option<(Scalar, uint, uint)> {
+    toi_concave_concave_with(m1, dir, dist, g1, m2, g2,
+                             |m1, g1, m2, g2| dispatcher.toi(m1, dir, dist, g1, m2, g2).impact())
+}
+
+/// Same as `toi`, except that the time of impact between a part of `g1` and `g2` is computed by
+/// `part_toi`, the first geometry given to `part_toi` being the one moving along `dir`.
+pub fn toi_with<G1: ConcaveGeom, G2: Geom>(m1:       &Matrix,
+                                           dir:      &Vect,
+                                           dist:     &Scalar,
+                                           g1:       &G1,
+                                           m2:       &Matrix,
+                                           g2:       &G2,
+                                           part_toi: |&Matrix, &Geom, &Matrix, &Geom| -> Option<Scalar>)
+                                           -> Option<(Scalar, uint)> {
+    do_toi(m1, dir, dist, g1, m2, g2 as &Geom, false, part_toi)
+}
+
+// Same as `toi_concave_concave`, except that the time of impact between two parts is computed by
+// `part_toi`.
+fn toi_concave_concave_with<G1: ConcaveGeom, G2: ConcaveGeom>(
+                            m1:       &Matrix,
+                            dir:      &Vect,
+                            dist:     &Scalar,
+                            g1:       &G1,
+                            m2:       &Matrix,
+                            g2:       &G2,
+                            part_toi: |&Matrix, &Geom, &Matrix, &Geom| -> Option<Scalar>)
+                            -> Option<(Scalar, uint, uint)> {
+    // In the local space of `g1`, `g2` moves along `-dir`, and conversely.
+    let inv_m1    = na::inv(m1).expect("The transformation `m1` must be inversible.");
+    let inv_m2    = na::inv(m2).expect("The transformation `m2` must be inversible.");
+    let ls_sweep1 = m1.inv_rotate(&(*dir * -*dist));
+    let ls_sweep2 = m2.inv_rotate(&(*dir * *dist));
+    let ls_aabb2  = swept_aabb(g2.aabb(&(inv_m1 * *m2)), &ls_sweep1);
+
+    let mut parts1 = Vec::new();
+    let mut parts2 = Vec::new();
+    let mut best   = None;
+
+    g1.approx_interferences_with_aabb(&ls_aabb2, &mut parts1);
+
+    for i in parts1.iter() {
+        g1.map_transformed_part_at(m1, *i, |pm1, part1| {
+            let ls_aabb1 = swept_aabb(part1.aabb(&(inv_m2 * *pm1)), &ls_sweep2);
+
+            g2.approx_interferences_with_aabb(&ls_aabb1, &mut parts2);
+
+            for j in parts2.iter() {
+                g2.map_transformed_part_at(m2, *j, |pm2, part2| {
+                    match part_toi(pm1, part1, pm2, part2) {
+                        Some(t) => {
+                            let is_better = t <= *dist && match best {
+                                None             => true,
+                                Some((bt, _, _)) => t < bt
+                            };
+
+                            if is_better {
+                                best = Some((t, *i, *j))
+                            }
+                        },
+                        None => { }
+                    }
+                })
+            }
+
+            parts2.clear();
+        })
+    }
+
+    best
+}
+
+fn do_toi<G1: ConcaveGeom>(m1:       &Matrix,
+                           dir:      &Vect,
+                           dist:     &Scalar,
+                           g1:       &G1,
+                           m2:       &Matrix,
+                           g2:       &Geom,
+                           swap:     bool,
+                           part_toi: |&Matrix, &Geom, &Matrix, &Geom| -> Option<Scalar>)
+                           -> Option<(Scalar, uint)> {
+    // In the local space of `g1`, `g2` moves along `-dir`.
+    let ls_m2    = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
+    let ls_sweep = m1.inv_rotate(&(*dir * -*dist));
+    let ls_aabb2 = swept_aabb(g2.aabb(&ls_m2), &ls_sweep);
+
+    let mut interferences = Vec::new();
+    let mut best          = None;
+
+    g1.approx_interferences_with_aabb(&ls_aabb2, &mut interferences);
+
+    for i in interferences.iter() {
+        let toi = g1.map_transformed_part_at(m1, *i, |pm1, part1| {
+            if swap {
+                part_toi(m2, g2, pm1, part1)
+            }
+            else {
+                part_toi(pm1, part1, m2, g2)
+            }
+        });
+
+        match toi {
+            Some(t) => {
+                let is_better = t <= *dist && match best {
+                    None          => true,
+                    Some((bt, _)) => t < bt
+                };
+
+                if is_better {
+                    best = Some((t, *i))
+                }
+            },
+            None => { }
+        }
+    }
+
+    best
+}
+
+fn swept_aabb(aabb: AABB, sweep: &Vect) -> AABB {
+    let mut moved = aabb.clone();
+
+    moved.append_translation(sweep);
+
+    aabb.merged(&moved)
+}
+
+/// Structure implementing `GeomGeomTOI` in order to compute the time of impact between a concave
+/// geometry and another geometry.
+pub struct ConcaveGeomGeomTOI<G1, G2>;
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
+GeomGeomTOI for ConcaveGeomGeomTOI<G1, G2> {
+    fn toi(&self,
+           dispatcher: &GeomGeomTOIDispatcher,
+           m1:         &Matrix,
+           dir:        &Vect,
+           dist:       &Scalar,
+           g1:         &Geom,
+           m2:         &Matrix,
+           g2:         &Geom)
+           -> Result<Option<Scalar>, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+
+        Ok(toi(dispatcher, m1, dir, dist, g1, m2, g2).map(|(toi, _)| toi))
+    }
+}
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
+DynamicTOI<G1, G2> for ConcaveGeomGeomTOI<G1, G2> { }
+
+/// Structure implementing `GeomGeomTOI` in order to compute the time of impact between a
+/// geometry and a concave geometry.
+pub struct GeomConcaveGeomTOI<G1, G2>;
+
+impl<G1: 'static + Geom, G2: 'static + ConcaveGeom>
+GeomGeomTOI for GeomConcaveGeomTOI<G1, G2> {
+    fn toi(&self,
+           dispatcher: &GeomGeomTOIDispatcher,
+           m1:         &Matrix,
+           dir:        &Vect,
+           dist:       &Scalar,
+           g1:         &Geom,
+           m2:         &Matrix,
+           g2:         &Geom)
+           -> Result<Option<Scalar>, GeomTypeMismatch> {
+        let (_, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+        let toi     = do_toi(m2, &-dir, dist, g2, m1, g1, true,
+                             |m1, g1, m2, g2| dispatcher.toi(m1, dir, dist, g1, m2, g2).impact());
+
+        Ok(toi.map(|(toi, _)| toi))
+    }
+}
+
+impl<G1: 'static + Geom, G2: 'static + ConcaveGeom>
+DynamicTOI<G1, G2> for GeomConcaveGeomTOI<G1, G2> { }
+
+/// Structure implementing `GeomGeomTOI` in order to compute the time of impact between two
+/// concave geometries.
+pub struct ConcaveGeomConcaveGeomTOI<G1, G2>;
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + ConcaveGeom>
+GeomGeomTOI for ConcaveGeomConcaveGeomTOI<G1, G2> {
+    fn toi(&self,
+           dispatcher: &GeomGeomTOIDispatcher,
+           m1:         &Matrix,
+           dir:        &Vect,
+           dist:       &Scalar,
+           g1:         &Geom,
+           m2:         &Matrix,
+           g2:         &Geom)
+           -> Result<Option<Scalar>, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+
+        Ok(toi_concave_concave(dispatcher, m1, dir, dist, g1, m2, g2).map(|(toi, _, _)| toi))
+    }
+}
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + ConcaveGeom>
+DynamicTOI<G1, G2> for ConcaveGeomConcaveGeomTOI<G1, G2> { }
+
+/*
+ *
+ * Distance
+ *
+ */
+/// Computes the distance between a concave geometry and another geometry.
+///
+/// The parts of the concave geometry are traversed in a best-first order, using the distance
+/// between their AABB and the AABB of `g2` as a lower bound. The distance between each visited
+/// part and `g2` is computed with `part_distance`, which may return `None` if a pair is not
+/// supported.
+///
+/// Returns the smallest distance and the index of the part of `g1` it is achieved with.
+///
+/// # Arguments:
+/// * `m1`            - the concave geometry transform.
+/// * `g1`            - the concave geometry.
+/// * `m2`            - the other geometry transform.
+/// * `g2`            - the other geometry.
+/// * `part_distance` - function computing the distance between a part of `g1` and `g2`.
+pub fn distance<G1: ConcaveGeom, G2: Geom>(m1:            &Matrix,
+                                           g1:            &G1,
+                                           m2:            &Matrix,
+                                           g2:            &G2,
+                                           part_distance: |&Matrix, &Geom, &Matrix, &Geom| -> Option<Distance>)
+                                           -> Option<(Distance, uint)> {
+    do_distance(m1, g1, m2, g2, false, part_distance)
+}
+
+fn do_distance<G1: ConcaveGeom, G2: Geom>(m1:            &Matrix,
+                                          g1:            &G1,
+                                          m2:            &Matrix,
+                                          g2:            &G2,
+                                          swap:          bool,
+                                          part_distance: |&Matrix, &Geom, &Matrix, &Geom| -> Option<Distance>)
+                                          -> Option<(Distance, uint)> {
+    let ls_m2    = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
+    let ls_aabb2 = g2.aabb(&ls_m2);
+    let g2       = g2 as &Geom;
+
+    let mut bv_cost   = |aabb: &AABB| aabb_aabb_distance(aabb, &ls_aabb2);
+    let mut leaf_cost = |i: &uint| {
+        let d = g1.map_transformed_part_at(m1, *i, |pm1, part1| {
+            if swap {
+                part_distance(m2, g2, pm1, part1)
+            }
+            else {
+                part_distance(pm1, part1, m2, g2)
+            }
+        });
+
+        d.map(|d| (d.dist.clone(), d))
+    };
+
+    match g1.bvt() {
+        Some(bvt) => bvt.best_first_search(&mut bv_cost, &mut leaf_cost).map(|(_, d, i)| (d, *i)),
+        None      => {
+            // Without BVT, every part is visited unless its AABB is farther than the best result.
+            let mut parts = Vec::new();
+            let mut best  = None;
+
+            g1.approx_interferences_with_aabb(&g1.aabb(&na::one()), &mut parts);
+
+            for i in parts.iter() {
+                let is_candidate = match best {
+                    None                 => true,
+                    Some((ref bc, _, _)) => bv_cost(g1.aabb_at(*i)) < *bc
+                };
+
+                if is_candidate {
+                    match leaf_cost(i) {
+                        Some((c, d)) => {
+                            let is_better = match best {
+                                None                 => true,
+                                Some((ref bc, _, _)) => c < *bc
+                            };
+
+                            if is_better {
+                                best = Some((c, d, *i))
+                            }
+                        },
+                        None => { }
+                    }
+                }
+            }
+
+            best.map(|(_, d, i)| (d, i))
+        }
+    }
+}
+
+/// Lower bound of the distance between the content of two AABB.
+fn aabb_aabb_distance(a: &AABB, b: &AABB) -> Scalar {
+    let mut sqdist: Scalar = na::zero();
+
+    for i in range(0u, na::dim::<Vect>()) {
+        let gap = na::max(a.mins().at(i) - b.maxs().at(i), b.mins().at(i) - a.maxs().at(i));
+
+        if gap > na::zero() {
+            sqdist = sqdist + gap * gap;
+        }
+    }
+
+    sqdist.sqrt()
+}
+
+/// Structure implementing `GeomGeomDistance` in order to compute the distance between a concave
+/// geometry and another geometry.
+pub struct ConcaveGeomGeomDistance<G1, G2>;
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
+GeomGeomDistance for ConcaveGeomGeomDistance<G1, G2> {
+    fn distance(&self,
+                dispatcher: &GeomGeomDistanceDispatcher,
+                m1:         &Matrix,
+                g1:         &Geom,
+                m2:         &Matrix,
+                g2:         &Geom)
+                -> Result<Option<Distance>, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+        let dist     = distance(m1, g1, m2, g2, |m1, g1, m2, g2| dispatcher.distance(m1, g1, m2, g2));
+
+        Ok(dist.map(|(d, _)| d))
+    }
+}
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
+DynamicDistance<G1, G2> for ConcaveGeomGeomDistance<G1, G2> { }
+
+/// Structure implementing `GeomGeomDistance` in order to compute the distance between a geometry
+/// and a concave geometry.
+pub struct GeomConcaveGeomDistance<G1, G2>;
+
+impl<G1: 'static + Geom, G2: 'static + ConcaveGeom>
+GeomGeomDistance for GeomConcaveGeomDistance<G1, G2> {
+    fn distance(&self,
+                dispatcher: &GeomGeomDistanceDispatcher,
+                m1:         &Matrix,
+                g1:         &Geom,
+                m2:         &Matrix,
+                g2:         &Geom)
+                -> Result<Option<Distance>, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+        let dist     = do_distance(m2, g2, m1, g1, true,
+                                   |m1, g1, m2, g2| dispatcher.distance(m1, g1, m2, g2));
+
+        Ok(dist.map(|(d, _)| d))
+    }
+}
+
+impl<G1: 'static + Geom, G2: 'static + ConcaveGeom>
+DynamicDistance<G1, G2> for GeomConcaveGeomDistance<G1, G2> { }
+
+/*
+ *
+ * Proximity
+ *
+ */
+/// Tests whether a concave geometry and another geometry are intersecting or closer than a given
+/// margin.
+///
+/// The parts of the concave geometry are culled using its BVT and the AABB of `g2` enlarged by
+/// `margin`. The proximity of each remaining part and `g2` is computed with `part_proximity`,
+/// which may return `None` if a pair is not supported. The traversal stops as soon as an
+/// intersecting part is found.
+///
+/// # Arguments:
+/// * `m1`             - the concave geometry transform.
+/// * `g1`             - the concave geometry.
+/// * `m2`             - the other geometry transform.
+/// * `g2`             - the other geometry.
+/// * `margin`         - the distance bellow which the geometries are considered close to each
+///                      other.
+/// * `part_proximity` - function computing the proximity between a part of `g1` and `g2`.
+pub fn proximity<G1: ConcaveGeom, G2: Geom>(m1:             &Matrix,
+                                            g1:             &G1,
+                                            m2:             &Matrix,
+                                            g2:             &G2,
+                                            margin:         &Scalar,
+                                            part_proximity: |&Matrix, &Geom, &Matrix, &Geom| -> Option<Proximity>)
+                                            -> Proximity {
+    do_proximity(m1, g1, m2, g2, margin, false, part_proximity)
+}
+
+fn do_proximity<G1: ConcaveGeom, G2: Geom>(m1:             &Matrix,
+                                           g1:             &G1,
+                                           m2:             &Matrix,
+                                           g2:             &G2,
+                                           margin:         &Scalar,
+                                           swap:           bool,
+                                           part_proximity: |&Matrix, &Geom, &Matrix, &Geom| -> Option<Proximity>)
+                                           -> Proximity {
+    let ls_m2    = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
+    let ls_aabb2 = g2.aabb(&ls_m2).loosened(margin.clone());
+    let g2       = g2 as &Geom;
+
+    let mut interferences = Vec::new();
+    let mut res           = Disjoint;
+
+    g1.approx_interferences_with_aabb(&ls_aabb2, &mut interferences);
+
+    for i in interferences.iter() {
+        let prox = g1.map_transformed_part_at(m1, *i, |pm1, part1| {
+            if swap {
+                part_proximity(m2, g2, pm1, part1)
+            }
+            else {
+                part_proximity(pm1, part1, m2, g2)
+            }
+        });
+
+        match prox {
+            Some(Intersecting) => return Intersecting,
+            Some(WithinMargin) => res = WithinMargin,
+            _                  => { }
+        }
+    }
+
+    res
+}
+
+/// Structure implementing `GeomGeomProximity` in order to test the proximity of a concave
+/// geometry and another geometry.
+pub struct ConcaveGeomGeomProximity<G1, G2>;
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
+GeomGeomProximity for ConcaveGeomGeomProximity<G1, G2> {
+    fn proximity(&self,
+                 dispatcher: &GeomGeomProximityDispatcher,
+                 m1:         &Matrix,
+                 g1:         &Geom,
+                 m2:         &Matrix,
+                 g2:         &Geom,
+                 margin:     &Scalar)
+                 -> Result<Proximity, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+
+        Ok(proximity(m1, g1, m2, g2, margin,
+                     |m1, g1, m2, g2| dispatcher.proximity(m1, g1, m2, g2, margin)))
+    }
+}
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
+DynamicProximity<G1, G2> for ConcaveGeomGeomProximity<G1, G2> { }
+
+/// Structure implementing `GeomGeomProximity` in order to test the proximity of a geometry and a
+/// concave geometry.
+pub struct GeomConcaveGeomProximity<G1, G2>;
+
+impl<G1: 'static + Geom, G2: 'static + ConcaveGeom>
+GeomGeomProximity for GeomConcaveGeomProximity<G1, G2> {
+    fn proximity(&self,
+                 dispatcher: &GeomGeomProximityDispatcher,
+                 m1:         &Matrix,
+                 g1:         &Geom,
+                 m2:         &Matrix,
+                 g2:         &Geom,
+                 margin:     &Scalar)
+                 -> Result<Proximity, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+
+        Ok(do_proximity(m2, g2, m1, g1, margin, true,
+                        |m1, g1, m2, g2| dispatcher.proximity(m1, g1, m2, g2, margin)))
+    }
+}
+
+impl<G1: 'static + Geom, G2: 'static + ConcaveGeom>
+DynamicProximity<G1, G2> for GeomConcaveGeomProximity<G1, G2> { }
+
 /*
  *
  * Custom factories
@@ -179,10 +938,21 @@ DynamicCollisionDetector<G1, G2> for GeomConcaveGeom<G1, G2> { }
 pub struct ConcaveGeomGeomFactory<G1, G2>;
 
 impl<G1: 'static + ConcaveGeom, G2: 'static + Geom>
-CollisionDetectorFactory for ConcaveGeomGeomFactory<G1, G2> {
-    fn build(&self) -> Box<GeomGeomCollisionDetector> {
-        let res: ConcaveGeomGeom<G1, G2> = ConcaveGeomGeom::new();
-        box res as Box<GeomGeomCollisionDetector>
+CollisionDetectorFactory<G1, G2, ConcaveGeomGeom<G1, G2>> for ConcaveGeomGeomFactory<G1, G2> {
+    fn build(&self) -> ConcaveGeomGeom<G1, G2> {
+        ConcaveGeomGeom::new()
+    }
+}
+
+/// Structure implementing `CollisionDetectorFactory` in order to create a new
+/// `ConcaveGeomConcaveGeom` collision detector.
+pub struct ConcaveGeomConcaveGeomFactory<G1, G2>;
+
+impl<G1: 'static + ConcaveGeom, G2: 'static + ConcaveGeom>
+CollisionDetectorFactory<G1, G2, ConcaveGeomConcaveGeom<G1, G2>>
+for ConcaveGeomConcaveGeomFactory<G1, G2> {
+    fn build(&self) -> ConcaveGeomConcaveGeom<G1, G2> {
+        ConcaveGeomConcaveGeom::new()
     }
 }
 
@@ -192,9 +962,268 @@ pub struct GeomConcaveGeomFactory<G1, G2>;
 
 impl<G1: 'static + Geom,
      G2: 'static + ConcaveGeom>
-CollisionDetectorFactory for GeomConcaveGeomFactory<G1, G2> {
-    fn build(&self) -> Box<GeomGeomCollisionDetector> {
-        let res: GeomConcaveGeom<G1, G2> = GeomConcaveGeom::new();
-        box res as Box<GeomGeomCollisionDetector>
+CollisionDetectorFactory<G1, G2, GeomConcaveGeom<G1, G2>> for GeomConcaveGeomFactory<G1, G2> {
+    fn build(&self) -> GeomConcaveGeom<G1, G2> {
+        GeomConcaveGeom::new()
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::{Vec3, Mat3, Iso3};
+    use nalgebra::na;
+    use geom::{Geom, ConcaveGeom, GeomCapabilities, Ball, Compound, Mesh};
+    use bounding_volume::{HasAABB, AABB, HasBoundingSphere, BoundingSphere};
+    use volumetric::Volumetric;
+    use ray::{Ray, RayCast, RayIntersection};
+    use contact::Contact;
+    use narrow::{GeomGeomDispatcher, GeomGeomCollisionDetector, GeomGeomTOIDispatcher,
+                 GeomGeomDistanceDispatcher};
+    use test_utils::{translation, two_balls};
+    use super::{ConcaveGeomGeom, ConcaveGeomConcaveGeom, toi, toi_concave_concave, distance};
+
+    // A compound without BVT, like a concave geometry implemented outside of the library.
+    #[deriving(Clone)]
+    struct Unindexed {
+        compound: Compound
+    }
+
+    impl Volumetric for Unindexed {
+        fn mass_properties(&self, density: &f64) -> (f64, Vec3<f64>, Mat3<f64>) {
+            self.compound.mass_properties(density)
+        }
+    }
+
+    impl HasAABB for Unindexed {
+        fn aabb(&self, m: &Iso3<f64>) -> AABB {
+            self.compound.aabb(m)
+        }
+    }
+
+    impl HasBoundingSphere for Unindexed {
+        fn bounding_sphere(&self, m: &Iso3<f64>) -> BoundingSphere {
+            self.compound.bounding_sphere(m)
+        }
+    }
+
+    impl RayCast for Unindexed {
+        fn toi_and_normal_with_ray(&self, ray: &Ray, solid: bool) -> Option<RayIntersection> {
+            self.compound.toi_and_normal_with_ray(ray, solid)
+        }
+    }
+
+    impl GeomCapabilities for Unindexed { }
+
+    impl ConcaveGeom for Unindexed {
+        fn map_part_at<T>(&self, i: uint, f: |&Iso3<f64>, &Geom| -> T) -> T {
+            self.compound.map_part_at(i, f)
+        }
+
+        fn map_transformed_part_at<T>(&self, m: &Iso3<f64>, i: uint,
+                                      f: |&Iso3<f64>, &Geom| -> T) -> T {
+            self.compound.map_transformed_part_at(m, i, f)
+        }
+
+        fn approx_interferences_with_aabb(&self, aabb: &AABB, out: &mut Vec<uint>) {
+            self.compound.approx_interferences_with_aabb(aabb, out)
+        }
+
+        fn approx_interferences_with_ray(&self, ray: &Ray, out: &mut Vec<uint>) {
+            self.compound.approx_interferences_with_ray(ray, out)
+        }
+
+        fn aabb_at<'a>(&'a self, i: uint) -> &'a AABB {
+            self.compound.aabb_at(i)
+        }
+    }
+
+    fn collide<G1: 'static + ConcaveGeom, G2: 'static + ConcaveGeom>(m1: &Iso3<f64>,
+                                                                    g1: &G1,
+                                                                    m2: &Iso3<f64>,
+                                                                    g2: &G2)
+                                                                    -> Vec<Contact> {
+        let dispatcher = GeomGeomDispatcher::new();
+        let mut detector: ConcaveGeomConcaveGeom<G1, G2> = ConcaveGeomConcaveGeom::new();
+        let mut res = Vec::new();
+
+        assert!(detector.update(&dispatcher, m1, g1 as &Geom, m2, g2 as &Geom).is_ok());
+        detector.colls(&mut res);
+
+        res
+    }
+
+    #[test]
+    fn test_compound_compound() {
+        let c1 = two_balls();
+        let c2 = two_balls();
+
+        let contacts = collide(&translation(0.0, 0.0, 0.0), &c1, &translation(0.0, 1.8, 0.0), &c2);
+
+        assert!(contacts.len() == 2);
+        assert!(contacts.iter().any(|c| c.part1 == Some(0)));
+        assert!(contacts.iter().any(|c| c.part1 == Some(1)));
+
+        for c in contacts.iter() {
+            assert!(c.part1 == c.part2);
+            assert!(na::approx_eq_eps(&c.depth, &0.2, &1.0e-5));
+            assert!(na::approx_eq_eps(&c.normal, &Vec3::new(0.0, 1.0, 0.0), &1.0e-5));
+        }
+    }
+
+    #[test]
+    fn test_compound_ball_ids() {
+        // Both parts touch a bigger ball.
+        let compound = two_balls();
+        let ball       = Ball::new(1.5f64);
+        let dispatcher = GeomGeomDispatcher::new();
+        let mut detector: ConcaveGeomGeom<Compound, Ball> = ConcaveGeomGeom::new();
+
+        let contacts = |detector: &mut ConcaveGeomGeom<Compound, Ball>, x: f64| {
+            let mut res = Vec::new();
+
+            assert!(detector.update(&dispatcher, &translation(0.0, 0.0, 0.0), &compound as &Geom,
+                                    &translation(x, 1.0, 0.0), &ball as &Geom).is_ok());
+            detector.colls(&mut res);
+
+            res
+        };
+
+        let before = contacts(&mut detector, 0.0);
+
+        assert!(before.len() == 2);
+        assert!(before.get(0).part1 != before.get(1).part1);
+        // Contacts on different parts have different identifiers.
+        assert!(before.get(0).id != before.get(1).id);
+
+        // The ball moves slightly: the contacts keep their identifiers.
+        let after = contacts(&mut detector, 0.01);
+
+        assert!(after.len() == 2);
+
+        for c1 in before.iter() {
+            assert!(after.iter().any(|c2| c1.part1 == c2.part1 && c1.id == c2.id));
+        }
+    }
+
+    #[test]
+    fn test_mesh_mesh() {
+        // Two disjoint triangles on the plane `y = 0`.
+        let vertices1 = vec!(Vec3::new(-2.0f64, 0.0, -1.0), Vec3::new(-0.5f64, 0.0, -1.0),
+                             Vec3::new(-1.25f64, 0.0, 1.0),
+                             Vec3::new(0.5f64, 0.0, -1.0), Vec3::new(2.0f64, 0.0, -1.0),
+                             Vec3::new(1.25f64, 0.0, 1.0));
+        let indices1  = vec!(0u, 1, 2, 3, 4, 5);
+        // A smaller triangle above the first one.
+        let vertices2 = vec!(Vec3::new(-1.6f64, 0.0, -0.5), Vec3::new(-0.9f64, 0.0, -0.5),
+                             Vec3::new(-1.25f64, 0.0, 0.5));
+        let indices2  = vec!(0u, 1, 2);
+
+        let m1 = Mesh::new(Arc::new(vertices1), Arc::new(indices1), None, None);
+        let m2 = Mesh::new(Arc::new(vertices2), Arc::new(indices2), None, None);
+
+        // Both margins are 0.04.
+        let contacts = collide(&translation(0.0, 0.0, 0.0), &m1, &translation(0.0, 0.05, 0.0), &m2);
+
+        assert!(contacts.len() > 0);
+
+        for c in contacts.iter() {
+            assert!(c.part1 == Some(0));
+            assert!(c.part2 == Some(0));
+            assert!(na::approx_eq_eps(&c.depth, &0.03, &1.0e-5));
+            assert!(na::approx_eq_eps(&c.normal, &Vec3::new(0.0, 1.0, 0.0), &1.0e-5));
+        }
+
+        // Far apart meshes have no contact.
+        assert!(collide(&translation(0.0, 0.0, 0.0), &m1, &translation(0.0, 1.0, 0.0), &m2).len() == 0);
+    }
+
+    #[test]
+    fn test_toi_compound_ball() {
+        let dispatcher = GeomGeomTOIDispatcher::new();
+        let c          = two_balls();
+        let b          = Ball::new(1.0f64);
+        let mc         = translation(0.0, 0.0, 0.0);
+        let right      = Vec3::new(1.0f64, 0.0, 0.0);
+        let left       = Vec3::new(-1.0f64, 0.0, 0.0);
+
+        // The second part is hit first.
+        match toi(&dispatcher, &mc, &right, &10.0, &c, &translation(6.0, 0.0, 0.0), &b) {
+            Some((t, part)) => {
+                assert!(na::approx_eq_eps(&t, &2.0, &1.0e-5));
+                assert!(part == 1);
+            },
+            None => fail!("The compound should touch the ball.")
+        }
+
+        match toi(&dispatcher, &mc, &left, &10.0, &c, &translation(-6.0, 0.0, 0.0), &b) {
+            Some((t, part)) => {
+                assert!(na::approx_eq_eps(&t, &2.0, &1.0e-5));
+                assert!(part == 0);
+            },
+            None => fail!("The compound should touch the ball.")
+        }
+
+        // The ball is beside the path of the compound.
+        assert!(toi(&dispatcher, &mc, &right, &10.0, &c, &translation(6.0, 2.5, 0.0), &b).is_none());
+        // The ball is behind the compound.
+        assert!(toi(&dispatcher, &mc, &left, &10.0, &c, &translation(6.0, 0.0, 0.0), &b).is_none());
+        // The ball is too far.
+        assert!(toi(&dispatcher, &mc, &right, &1.0, &c, &translation(6.0, 0.0, 0.0), &b).is_none());
+    }
+
+    #[test]
+    fn test_toi_compound_compound() {
+        let dispatcher = GeomGeomTOIDispatcher::new();
+        let c1         = two_balls();
+        let c2         = two_balls();
+        let m1         = translation(0.0, 0.0, 0.0);
+        // The parts of `c2` are centered at `(2, 5, 0)` and `(6, 5, 0)`.
+        let m2         = translation(4.0, 5.0, 0.0);
+        let up         = Vec3::new(0.0f64, 1.0, 0.0);
+
+        // Only the second part of `c1` and the first part of `c2` are aligned.
+        match toi_concave_concave(&dispatcher, &m1, &up, &10.0, &c1, &m2, &c2) {
+            Some((t, part1, part2)) => {
+                assert!(na::approx_eq_eps(&t, &3.0, &1.0e-5));
+                assert!(part1 == 1);
+                assert!(part2 == 0);
+            },
+            None => fail!("The compounds should touch.")
+        }
+
+        assert!(toi_concave_concave(&dispatcher, &m1, &up, &2.0, &c1, &m2, &c2).is_none());
+        assert!(toi_concave_concave(&dispatcher, &m1, &-up, &10.0, &c1, &m2, &c2).is_none());
+    }
+
+    #[test]
+    fn test_concave_geoms_without_bvt() {
+        let c1 = Unindexed { compound: two_balls() };
+        let c2 = two_balls();
+
+        assert!(c1.bvt().is_none());
+
+        // The parts are paired through `approx_interferences_with_aabb`.
+        let contacts = collide(&translation(0.0, 0.0, 0.0), &c1, &translation(0.0, 1.8, 0.0), &c2);
+
+        assert!(contacts.len() == 2);
+
+        for c in contacts.iter() {
+            assert!(c.part1 == c.part2);
+            assert!(na::approx_eq_eps(&c.depth, &0.2, &1.0e-5));
+        }
+
+        // Every part is visited by the distance query.
+        let dispatcher = GeomGeomDistanceDispatcher::new();
+        let b          = Ball::new(1.0f64);
+
+        match distance(&translation(0.0, 0.0, 0.0), &c1, &translation(5.0, 0.0, 0.0), &b,
+                       |m1, g1, m2, g2| dispatcher.distance(m1, g1, m2, g2)) {
+            Some((d, part)) => {
+                assert!(na::approx_eq_eps(&d.dist, &1.0, &1.0e-5));
+                assert!(part == 1);
+            },
+            None => fail!("The distance between a compound and a ball should be known.")
+        }
     }
 }
@@ -0,0 +1,621 @@
+use std::num::{Signed, Bounded};
+use nalgebra::na::{Indexable, Translation, Rotate};
+use nalgebra::na;
+use geom::Cuboid;
+use narrow::CollisionDetector;
+use narrow::implicit_implicit;
+use narrow::algorithm::clipping::clip_with_half_space;
+use contact::{Contact, FaceFeature, EdgeFeature, VertexFeature};
+use util::hash;
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between two boxes.
+///
+/// It uses the separating axis theorem to find the axis of minimum penetration (including, in 3D,
+/// the cross products of the boxes edges). Then, a full contact manifold is generated by clipping
+/// the incident face against the side planes of the reference face. Each contact is associated to
+/// the features which generated it, and its identifier does not change as long as the same features
+/// are in contact.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct CuboidCuboid {
+    prediction: Scalar,
+    contacts:   Vec<Contact>
+}
+
+impl CuboidCuboid {
+    /// Creates a new persistent collision detector between two boxes.
+    #[inline]
+    pub fn new(prediction: Scalar) -> CuboidCuboid {
+        CuboidCuboid {
+            prediction: prediction,
+            contacts:   Vec::new()
+        }
+    }
+}
+
+impl CollisionDetector<Cuboid, Cuboid> for CuboidCuboid {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Cuboid, mb: &Matrix, b: &Cuboid) {
+        self.contacts.clear();
+
+        collide(ma, a, mb, b, &self.prediction, &mut self.contacts)
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        for c in self.contacts.iter() {
+            out_colls.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_:   Option<CuboidCuboid>,
+           ma:  &Matrix,
+           dir: &Vect,
+           _:   &Scalar,
+           a:   &Cuboid,
+           mb:  &Matrix,
+           b:   &Cuboid) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+// Axis of minimum penetration found by the separating axis test.
+enum SATAxis {
+    Face1(uint),
+    Face2(uint),
+    Edges(uint, uint)
+}
+
+/// Computes the contact manifold between two boxes.
+///
+/// Each contact is pushed on `out_contacts`, together with the faces or edges which generated it.
+///
+/// # Arguments:
+/// * `m1`         - the first box transform.
+/// * `c1`         - the first box.
+/// * `m2`         - the second box transform.
+/// * `c2`         - the second box.
+/// * `prediction` - the maximum distance between two features for them to generate a contact.
+pub fn collide(m1:           &Matrix,
+               c1:           &Cuboid,
+               m2:           &Matrix,
+               c2:           &Cuboid,
+               prediction:   &Scalar,
+               out_contacts: &mut Vec<Contact>) {
+    let he1   = c1.half_extents() + c1.margin();
+    let he2   = c2.half_extents() + c2.margin();
+    let delta = m2.translation() - m1.translation();
+
+    /*
+     * Face axes.
+     */
+    let mut best_sep1: Scalar = -Bounded::max_value();
+    let mut best_sep2: Scalar = -Bounded::max_value();
+    let mut best_n1:   Vect   = na::zero();
+    let mut best_n2:   Vect   = na::zero();
+    let mut best_i1 = 0u;
+    let mut best_i2 = 0u;
+
+    for i in range(0u, na::dim::<Vect>()) {
+        let (sep, n) = separation(m1, &he1, m2, &he2, &delta, &local_axis(m1, i));
+
+        if sep > *prediction {
+            return;
+        }
+
+        if sep > best_sep1 {
+            best_sep1 = sep;
+            best_n1   = n;
+            best_i1   = i;
+        }
+
+        let (sep, n) = separation(m1, &he1, m2, &he2, &delta, &local_axis(m2, i));
+
+        if sep > *prediction {
+            return;
+        }
+
+        if sep > best_sep2 {
+            best_sep2 = sep;
+            best_n2   = n;
+            best_i2   = i;
+        }
+    }
+
+    // Prefer the faces of the first box to avoid flip-flopping between near-equivalent axes.
+    let (mut best_axis, mut best_sep, mut best_n) =
+        if is_significantly_greater(&best_sep2, &best_sep1) {
+            (Face2(best_i2), best_sep2, best_n2)
+        }
+        else {
+            (Face1(best_i1), best_sep1, best_n1)
+        };
+
+    /*
+     * Edge-edge axes.
+     */
+    match edges_separation(m1, &he1, m2, &he2, &delta, prediction) {
+        None => return,
+        Some(Some((sep, n, i, j))) => {
+            if is_significantly_greater(&sep, &best_sep) {
+                best_axis = Edges(i, j);
+                best_sep  = sep;
+                best_n    = n;
+            }
+        },
+        Some(None) => { }
+    }
+
+    /*
+     * Contact generation.
+     */
+    match best_axis {
+        Face1(i) => {
+            face_contacts(m1, &he1, i, m2, &he2, &best_n, prediction, 0, out_contacts)
+        },
+        Face2(i) => {
+            face_contacts(m2, &he2, i, m1, &he1, &-best_n, prediction, 1, out_contacts)
+        },
+        Edges(i, j) => {
+            edge_contact(m1, &he1, i, m2, &he2, j, &best_n, &best_sep, out_contacts)
+        }
+    }
+}
+
+// Tests if the separation `sep` is greater than `best` by a margin large enough to switch to its
+// axis. The tolerance is relative to the magnitude of `best`: this works for both penetrations
+// (negative separations) and positive separations within the prediction.
+fn is_significantly_greater(sep: &Scalar, best: &Scalar) -> bool {
+    let _rel_tol: Scalar = na::cast(0.05);
+    let _abs_tol: Scalar = na::cast(0.01);
+
+    *sep > *best + _rel_tol * best.abs() + _abs_tol
+}
+
+// The world-space direction of the i-th local axis of a box.
+fn local_axis(m: &Matrix, i: uint) -> Vect {
+    let mut axis: Vect = na::zero();
+
+    axis.set(i, na::one());
+
+    m.rotate(&axis)
+}
+
+// Separation of the two boxes along the unit vector `axis`. Returns the separation and the axis
+// oriented from the first box toward the second one.
+fn separation(m1: &Matrix, he1: &Vect, m2: &Matrix, he2: &Vect, delta: &Vect, axis: &Vect)
+              -> (Scalar, Vect) {
+    let local1 = m1.inv_rotate(axis);
+    let local2 = m2.inv_rotate(axis);
+    let mut r1: Scalar = na::zero();
+    let mut r2: Scalar = na::zero();
+
+    for i in range(0u, na::dim::<Vect>()) {
+        r1 = r1 + he1.at(i) * local1.at(i).abs();
+        r2 = r2 + he2.at(i) * local2.at(i).abs();
+    }
+
+    let proj = na::dot(delta, axis);
+
+    if proj < na::zero() {
+        (-proj - r1 - r2, -axis)
+    }
+    else {
+        (proj - r1 - r2, axis.clone())
+    }
+}
+
+// Finds the edge-edge axis with the smallest penetration. Returns `None` if a separating axis has
+// been found.
+#[cfg(dim3)]
+fn edges_separation(m1: &Matrix, he1: &Vect, m2: &Matrix, he2: &Vect, delta: &Vect, prediction: &Scalar)
+                    -> Option<Option<(Scalar, Vect, uint, uint)>> {
+    let _eps: Scalar = na::cast(1.0e-6);
+    let mut best = None;
+
+    for i in range(0u, 3) {
+        let a1 = local_axis(m1, i);
+
+        for j in range(0u, 3) {
+            let a2       = local_axis(m2, j);
+            let mut axis = na::cross(&a1, &a2);
+            let norm     = na::norm(&axis);
+
+            if norm <= _eps {
+                // parallel edges: this axis is already covered by the face axes.
+                continue;
+            }
+
+            axis = axis / norm;
+
+            let (sep, n) = separation(m1, he1, m2, he2, delta, &axis);
+
+            if sep > *prediction {
+                return None;
+            }
+
+            let is_better = match best {
+                None                   => true,
+                Some((bsep, _, _, _)) => sep > bsep
+            };
+
+            if is_better {
+                best = Some((sep, n, i, j))
+            }
+        }
+    }
+
+    Some(best)
+}
+
+#[cfg(not(dim3))]
+fn edges_separation(_: &Matrix, _: &Vect, _: &Matrix, _: &Vect, _: &Vect, _: &Scalar)
+                    -> Option<Option<(Scalar, Vect, uint, uint)>> {
+    // There are no edge-edge axes in 2D.
+    Some(None)
+}
+
+// Identifier of a contact generated by the clipping of an incident face.
+fn face_feature_id(ref_box: uint, ref_face: uint, inc_face: uint, feature: uint) -> uint {
+    // `feature` may use the whole `uint` (see `clip_with_half_space`) so it is hashed before being
+    // combined with the faces.
+    hash::key_from_pair((ref_box * 6 + ref_face) * 6 + inc_face, hash::tomas_wang_hash(feature))
+}
+
+// Identifier of a contact generated by two edges.
+fn edge_feature_id(edge1: uint, edge2: uint) -> uint {
+    (72 << 24) + edge1 * 12 + edge2
+}
+
+// Computes the contacts between the face `i` of the reference box and the most anti-parallel face
+// of the incident box. `n` is the reference face normal, pointing toward the incident box.
+fn face_contacts(mref:         &Matrix,
+                 heref:        &Vect,
+                 i:            uint,
+                 minc:         &Matrix,
+                 heinc:        &Vect,
+                 n:            &Vect,
+                 prediction:   &Scalar,
+                 ref_box:      uint,
+                 out_contacts: &mut Vec<Contact>) {
+    let ref_center = mref.translation();
+    let ref_axis   = local_axis(mref, i);
+    let ref_face   = if na::dot(n, &ref_axis) > na::zero() { 2 * i } else { 2 * i + 1 };
+    let ref_point  = ref_center + *n * heref.at(i);
+
+    // Find the incident face.
+    let local_n   = minc.inv_rotate(n);
+    let mut inc_i = 0u;
+
+    for j in range(1u, na::dim::<Vect>()) {
+        if local_n.at(j).abs() > local_n.at(inc_i).abs() {
+            inc_i = j;
+        }
+    }
+
+    let inc_sign: Scalar = if local_n.at(inc_i) > na::zero() { -na::one::<Scalar>() } else { na::one() };
+    let inc_face = if inc_sign > na::zero() { 2 * inc_i } else { 2 * inc_i + 1 };
+
+    let mut poly    = incident_face(minc, heinc, inc_i, &inc_sign);
+    let mut clipped = Vec::new();
+
+    // Clip it with the side planes of the reference face.
+    for k in range(0u, na::dim::<Vect>()) {
+        if k != i {
+            let side   = local_axis(mref, k);
+            let center = na::dot(&side, &ref_center);
+
+            clip_with_half_space(&poly, &side, &(center + heref.at(k)), 2 * k, &mut clipped);
+            poly.clear();
+            clip_with_half_space(&clipped, &-side, &(heref.at(k) - center), 2 * k + 1, &mut poly);
+            clipped.clear();
+        }
+    }
+
+    // Keep the points close enough to the reference face.
+    for &(ref p, feature) in poly.iter() {
+        let sep = na::dot(n, &(*p - ref_point));
+
+        if sep <= *prediction {
+            let proj = *p - *n * sep;
+
+            // The incident feature is a vertex of the clipped incident face: either a vertex of
+            // the box, or the intersection of one of its edges with a side plane.
+            let mut contact =
+                if ref_box == 0 {
+                    Contact::new_with_features(proj, p.clone(), n.clone(), -sep,
+                                               FaceFeature(ref_face), VertexFeature(feature))
+                }
+                else {
+                    Contact::new_with_features(p.clone(), proj, -n, -sep,
+                                               VertexFeature(feature), FaceFeature(ref_face))
+                };
+
+            contact.id = face_feature_id(ref_box, ref_face, inc_face, feature);
+            out_contacts.push(contact);
+        }
+    }
+}
+
+// The vertices of the face `i` of a box, with their feature identifiers.
+#[cfg(dim2)]
+fn incident_face(m: &Matrix, he: &Vect, i: uint, sign: &Scalar) -> Vec<(Vect, uint)> {
+    let k      = 1 - i;
+    let center = m.translation() + local_axis(m, i) * (*sign * he.at(i));
+    let u      = local_axis(m, k) * he.at(k);
+
+    vec!((center + u, 1), (center - u, 2))
+}
+
+// The vertices of the face `i` of a box, with their feature identifiers.
+#[cfg(dim3)]
+fn incident_face(m: &Matrix, he: &Vect, i: uint, sign: &Scalar) -> Vec<(Vect, uint)> {
+    let k      = (i + 1) % 3;
+    let l      = (i + 2) % 3;
+    let center = m.translation() + local_axis(m, i) * (*sign * he.at(i));
+    let u      = local_axis(m, k) * he.at(k);
+    let v      = local_axis(m, l) * he.at(l);
+
+    vec!((center + u + v, 1), (center - u + v, 2), (center - u - v, 3), (center + u - v, 4))
+}
+
+// Computes the contact between the edge of the first box parallel to its axis `i` and the edge of
+// the second box parallel to its axis `j`. `n` points from the first box toward the second one.
+#[cfg(dim3)]
+fn edge_contact(m1:           &Matrix,
+                he1:          &Vect,
+                i:            uint,
+                m2:           &Matrix,
+                he2:          &Vect,
+                j:            uint,
+                n:            &Vect,
+                sep:          &Scalar,
+                out_contacts: &mut Vec<Contact>) {
+    let (p1, e1) = support_edge(m1, he1, i, n);
+    let (p2, e2) = support_edge(m2, he2, j, &-n);
+    let d1 = local_axis(m1, i);
+    let d2 = local_axis(m2, j);
+
+    // Closest points between the two edges.
+    let r = p1 - p2;
+    let b = na::dot(&d1, &d2);
+    let c = na::dot(&d1, &r);
+    let f = na::dot(&d2, &r);
+
+    let denom = na::one::<Scalar>() - b * b;
+    let mut s = if denom > na::zero() { (b * f - c) / denom } else { na::zero() };
+
+    s = na::max(na::min(s, he1.at(i)), -he1.at(i));
+
+    let t = na::max(na::min(b * s + f, he2.at(j)), -he2.at(j));
+
+    let mut contact = Contact::new_with_features(p1 + d1 * s, p2 + d2 * t, n.clone(), -*sep,
+                                                 EdgeFeature(e1), EdgeFeature(e2));
+
+    contact.id = edge_feature_id(e1, e2);
+    out_contacts.push(contact);
+}
+
+#[cfg(not(dim3))]
+fn edge_contact(_: &Matrix, _: &Vect, _: uint, _: &Matrix, _: &Vect, _: uint, _: &Vect, _: &Scalar,
+                _: &mut Vec<Contact>) {
+    unreachable!()
+}
+
+// The center and the identifier of the edge of a box parallel to its axis `i` and the farthest
+// along `dir`.
+#[cfg(dim3)]
+fn support_edge(m: &Matrix, he: &Vect, i: uint, dir: &Vect) -> (Vect, uint) {
+    let local_dir  = m.inv_rotate(dir);
+    let mut center = na::zero::<Vect>();
+    let mut id     = i * 4;
+    let mut bit    = 1u;
+
+    for k in range(0u, 3) {
+        if k != i {
+            if local_dir.at(k) < na::zero() {
+                center.set(k, -he.at(k));
+            }
+            else {
+                center.set(k, he.at(k));
+                id = id + bit;
+            }
+
+            bit = bit * 2;
+        }
+    }
+
+    (m.translation() + m.rotate(&center), id)
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{AnnotatedPoint, Cuboid};
+    use contact::Contact;
+    use narrow::implicit_implicit;
+    use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+    use narrow::algorithm::gjk::Projection;
+    use super::collide;
+
+    fn contacts(m1: &Iso3<f64>, c1: &Cuboid, m2: &Iso3<f64>, c2: &Cuboid) -> Vec<Contact> {
+        let mut res = Vec::new();
+
+        collide(m1, c1, m2, c2, &0.0, &mut res);
+
+        res
+    }
+
+    fn assert_normal(c: &Contact, n: &Vec3<f64>) {
+        assert!(na::approx_eq_eps(&c.normal, n, &1.0e-7));
+    }
+
+    #[test]
+    fn test_cuboid_cuboid_face_face() {
+        let c1 = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let c2 = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let m1 = na::one::<Iso3<f64>>();
+        let m2 = Iso3::new(Vec3::new(0.0f64, 1.4, 0.0), na::zero());
+
+        let cs = contacts(&m1, &c1, &m2, &c2);
+
+        assert!(cs.len() == 4);
+
+        for c in cs.iter() {
+            assert!(na::approx_eq_eps(&c.depth, &0.1, &1.0e-7));
+            assert!(na::approx_eq_eps(&c.world1.y, &1.0, &1.0e-7));
+            assert_normal(c, &Vec3::new(0.0, 1.0, 0.0));
+        }
+
+        // Swapping the boxes flips the manifold.
+        let cs = contacts(&m2, &c2, &m1, &c1);
+
+        assert!(cs.len() == 4);
+
+        for c in cs.iter() {
+            assert!(na::approx_eq_eps(&c.depth, &0.1, &1.0e-7));
+            assert_normal(c, &Vec3::new(0.0, -1.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_cuboid_cuboid_edge_edge() {
+        let pi4: f64 = Float::frac_pi_4();
+        let sqrt2    = 2.0f64.sqrt();
+        let c        = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        // The top edge of the first box is parallel to `z`, the bottom edge of the second is
+        // parallel to `x`.
+        let m1 = Iso3::new(na::zero(), Vec3::new(0.0f64, 0.0, pi4));
+        let m2 = Iso3::new(Vec3::new(0.0f64, 2.0 * sqrt2 - 0.1, 0.0), Vec3::new(pi4, 0.0, 0.0));
+
+        let cs = contacts(&m1, &c, &m2, &c);
+
+        assert!(cs.len() == 1);
+
+        let c = cs.get(0);
+
+        assert!(na::approx_eq_eps(&c.depth, &0.1, &1.0e-7));
+        assert_normal(c, &Vec3::new(0.0, 1.0, 0.0));
+        assert!(na::approx_eq_eps(&c.world1, &Vec3::new(0.0, sqrt2, 0.0), &1.0e-7));
+    }
+
+    #[test]
+    fn test_cuboid_cuboid_rotated_about_normal() {
+        let c1 = Cuboid::new(Vec3::new(2.0f64, 1.0, 2.0));
+        let c2 = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let m1 = na::one::<Iso3<f64>>();
+        let m2 = Iso3::new(Vec3::new(0.3f64, 1.45, -0.2), Vec3::new(0.0, 0.7, 0.0));
+
+        let cs = contacts(&m1, &c1, &m2, &c2);
+
+        assert!(cs.len() == 4);
+
+        for c in cs.iter() {
+            assert!(na::approx_eq_eps(&c.depth, &0.05, &1.0e-7));
+            assert_normal(c, &Vec3::new(0.0, 1.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_cuboid_cuboid_stack() {
+        let c  = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let ms = [
+            na::one::<Iso3<f64>>(),
+            Iso3::new(Vec3::new(0.2f64, 1.95, 0.1), na::zero()),
+            Iso3::new(Vec3::new(-0.1f64, 3.9, 0.3), na::zero())
+        ];
+
+        for i in range(0u, 2) {
+            let cs = contacts(&ms[i], &c, &ms[i + 1], &c);
+
+            assert!(cs.len() == 4);
+
+            for c in cs.iter() {
+                assert!(na::approx_eq_eps(&c.depth, &0.05, &1.0e-7));
+                assert_normal(c, &Vec3::new(0.0, 1.0, 0.0));
+            }
+        }
+
+        assert!(contacts(&ms[0], &c, &ms[2], &c).len() == 0);
+    }
+
+    #[test]
+    fn test_cuboid_cuboid_vs_gjk() {
+        let c1 = Cuboid::new(Vec3::new(2.0f64, 1.0, 2.0));
+        let c2 = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let m1 = na::one::<Iso3<f64>>();
+        // Tilted box: its lowest edge penetrates the first box by 0.05.
+        let lowest = 0.5 * (0.2f64.cos() + 0.2f64.sin());
+        let m2 = Iso3::new(Vec3::new(0.1f64, 1.0 + lowest - 0.05, 0.0), Vec3::new(0.2, 0.0, 0.0));
+
+        let cs = contacts(&m1, &c1, &m2, &c2);
+
+        assert!(cs.len() == 2);
+
+        let mut deepest = cs.get(0).clone();
+
+        for c in cs.iter() {
+            if c.depth > deepest.depth {
+                deepest = c.clone();
+            }
+        }
+
+        let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+
+        match implicit_implicit::collide(&m1, &c1, &m2, &c2, &0.0, &mut simplex, None) {
+            Projection(c) => {
+                // The boxes margins round the corners seen by GJK.
+                assert!(na::approx_eq_eps(&c.depth, &deepest.depth, &1.0e-2));
+                assert!(na::approx_eq_eps(&c.normal, &deepest.normal, &1.0e-3));
+            },
+            _ => fail!("GJK should find a contact.")
+        }
+    }
+}
+
+#[cfg(dim2, f64, test)]
+mod test {
+    use nalgebra::na::{Vec1, Vec2, Iso2};
+    use nalgebra::na;
+    use geom::Cuboid;
+    use super::collide;
+
+    #[test]
+    fn test_cuboid_cuboid_separated_prefers_greatest_separation() {
+        let c1    = Cuboid::new_with_margin(Vec2::new(1.0f64, 1.0), 0.0);
+        let c2    = Cuboid::new_with_margin(Vec2::new(0.5f64, 0.5), 0.0);
+        let angle = 0.02f64;
+        // The closest corner of the second box is at the distance 0.5 from the face `x = 1` of the
+        // first box. The separation along the face normals of the second box is slightly smaller.
+        let x  = 1.5 + 0.5 * (angle.cos() + angle.sin());
+        let m1 = na::one::<Iso2<f64>>();
+        let m2 = Iso2::new(Vec2::new(x, 0.0), Vec1::new(angle));
+
+        let mut cs = Vec::new();
+
+        collide(&m1, &c1, &m2, &c2, &1.0, &mut cs);
+
+        assert!(cs.len() == 2);
+
+        let mut deepest = cs.get(0).depth;
+
+        for c in cs.iter() {
+            assert!(na::approx_eq_eps(&c.normal, &Vec2::new(1.0, 0.0), &1.0e-7));
+            deepest = na::max(deepest, c.depth);
+        }
+
+        assert!(na::approx_eq_eps(&deepest, &-0.5, &1.0e-7));
+    }
+}
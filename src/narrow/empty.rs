@@ -28,6 +28,9 @@ impl<G1, G2> CollisionDetector<G1, G2> for Empty<G1, G2> {
     fn colls(&self, _: &mut Vec<Contact>) {
     }
 
+    fn set_prediction(&mut self, _: &Scalar) {
+    }
+
     fn toi(_: Option<Empty<G1, G2>>, _: &Matrix, _: &Vect, _: &Scalar, _: &G1, _: &Matrix, _: &G2) -> Option<Scalar> {
         None
     }
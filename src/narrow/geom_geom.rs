@@ -7,36 +7,93 @@ use sync::{Arc, RWLock};
 use collections::HashMap;
 use nalgebra::na;
 use geom::{AnnotatedPoint, Geom, ConcaveGeom, Cone, Cuboid, Ball, Capsule, Convex, Cylinder,
-           Compound, Mesh, Triangle, Segment, Plane};
+           Compound, Mesh, Triangle, Tetrahedron, Segment, Plane};
 use implicit::{Implicit, PreferedSamplingDirections};
-use contact::Contact;
+use contact::{Contact, ContactWLocals};
 use narrow::algorithm::simplex::Simplex;
 use narrow::algorithm::johnson_simplex::{JohnsonSimplex, RecursionTemplate};
-use narrow::{CollisionDetector, ImplicitImplicit, BallBall,
-             ImplicitPlane, PlaneImplicit, ConcaveGeomGeomFactory, GeomConcaveGeomFactory,
-             BezierSurfaceBall, BallBezierSurface};
-use narrow::surface_selector::TangentConesSurfaceSelector; // HyperPlaneSurfaceSelector;
+use narrow::{CollisionDetector, ImplicitImplicit, BallBall, BallCapsule, CapsuleBall, BallSegment,
+             SegmentBall, CapsuleCapsule, SegmentSegment, BallTriangle, TriangleBall,
+             ImplicitPlane, PlaneImplicit, ConcaveGeomGeom, GeomConcaveGeom, ConcaveGeomConcaveGeom,
+             ConcaveGeomGeomFactory, GeomConcaveGeomFactory, ConcaveGeomConcaveGeomFactory,
+             BezierSurfaceBall, BallBezierSurface, ImplicitBezierSurface, BezierSurfaceImplicit,
+             BezierSurfaceBezierSurface,
+             BallBezierCurve, BezierCurveBall, ImplicitBezierCurve, BezierCurveImplicit,
+             PenetrationFallback, MinkowskiSamplingFallback,
+             DispatchStrategy, RegisteredStrategy, FallbackDetectorFactory, FallbackDetectors};
+use narrow::geom_geom_fallback;
+use narrow::surface_selector::{TangentConesSurfaceSelector, TangentConesSurfaceSelectorTestData}; // HyperPlaneSurfaceSelector;
 use narrow::surface_subdivision_tree::SurfaceSubdivisionTreeCache;
 use OSCMG = narrow::OneShotContactManifoldGenerator;
+use ICMG = narrow::IncrementalContactManifoldGenerator;
+#[cfg(not(dim4))]
+use narrow::{CuboidCuboid, PolyhedralContactManifoldGenerator, Polyhedron};
+#[cfg(dim3)]
+use narrow::{BallMesh, MeshBall};
+#[cfg(dim3)]
+use geom::TetMesh;
 use math::{Scalar, Vect, Matrix};
 
+/// Error returned by a collision detector given geometries of types it cannot handle.
+#[deriving(Show, PartialEq, Clone)]
+pub struct GeomTypeMismatch;
+
+/// Downcasts two geometries to the types `G1` and `G2`.
+///
+/// Returns a `GeomTypeMismatch` error if either geometry does not have the expected type.
+pub fn downcast_geoms<'a, G1: 'static, G2: 'static>(g1: &'a Geom, g2: &'a Geom)
+                                                    -> Result<(&'a G1, &'a G2), GeomTypeMismatch> {
+    match (g1.as_ref::<G1>(), g2.as_ref::<G2>()) {
+        (Some(g1), Some(g2)) => Ok((g1, g2)),
+        _                    => Err(GeomTypeMismatch)
+    }
+}
+
 /// Same as the `CollisionDetector` trait but using dynamic dispatch on the geometries.
+///
+/// The time of impact of two `Geom` is computed by a `GeomGeomTOIDispatcher` instead.
 pub trait GeomGeomCollisionDetector {
     /// Runs the collision detection on two objects. It is assumed that the same
     /// collision detector (the same structure) is always used with the same
     /// pair of object.
+    ///
+    /// If the objects do not have the types this detector was built for, nothing is done and a
+    /// `GeomTypeMismatch` error is returned.
     fn update(&mut self,
               &GeomGeomDispatcher,
               &Matrix,
               &Geom,
               &Matrix,
-              &Geom);
+              &Geom)
+              -> Result<(), GeomTypeMismatch>;
 
     /// The number of collision detected during the last update.
     fn num_colls(&self) -> uint;
 
     /// Collects the collisions detected during the last update.
     fn colls(&self, &mut Vec<Contact>);
+
+    /// Collects the collisions detected during the last update, together with their local-space
+    /// points.
+    ///
+    /// `m1` and `m2` must be the transforms given to the last update. By default, the local points
+    /// are computed from the result of `colls`.
+    fn colls_with_locals(&self, m1: &Matrix, m2: &Matrix, out: &mut Vec<ContactWLocals>) {
+        let mut colls = Vec::new();
+
+        self.colls(&mut colls);
+
+        for c in colls.move_iter() {
+            out.push(ContactWLocals::new_with_contact(c, m1, m2))
+        }
+    }
+
+    /// Sets the maximum distance between two objects for them to generate contacts.
+    ///
+    /// This allows each pair of objects to use its own prediction distance. By default, this does
+    /// nothing: the detector keeps the prediction distance it has been created with.
+    fn set_prediction(&mut self, _: &Scalar) {
+    }
 }
 
 /// Trait to be implemented by collision detector using dynamic dispatch.
@@ -62,6 +119,8 @@ CollisionDetector<G1, G2> for DetectorWithoutRedispatch<D> {
     fn update(&mut self, _: &Matrix, _: &G1, _: &Matrix, _: &G2) { unreachable!() }
     fn num_colls(&self) -> uint { unreachable!() }
     fn colls(&self, _: &mut Vec<Contact>) { unreachable!() }
+    fn colls_with_locals(&self, _: &Matrix, _: &Matrix, _: &mut Vec<ContactWLocals>) { unreachable!() }
+    fn set_prediction(&mut self, _: &Scalar) { unreachable!() }
     fn toi(_: Option<DetectorWithoutRedispatch<D>>, _: &Matrix, _: &Vect, _: &Scalar, _: &G1, _: &Matrix, _: &G2) -> Option<Scalar> {
         unreachable!()
     }
@@ -78,12 +137,13 @@ GeomGeomCollisionDetector for DetectorWithoutRedispatch<D> {
               m1: &Matrix,
               g1: &Geom,
               m2: &Matrix,
-              g2: &Geom) {
-        self.detector.update(
-            m1,
-            g1.as_ref::<G1>().expect("Invalid geometry."),
-            m2,
-            g2.as_ref::<G2>().expect("Invalid geometry."))
+              g2: &Geom)
+              -> Result<(), GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+
+        self.detector.update(m1, g1, m2, g2);
+
+        Ok(())
     }
 
     #[inline]
@@ -95,34 +155,68 @@ GeomGeomCollisionDetector for DetectorWithoutRedispatch<D> {
     fn colls(&self, cs: &mut Vec<Contact>) {
         self.detector.colls(cs)
     }
+
+    #[inline]
+    fn colls_with_locals(&self, m1: &Matrix, m2: &Matrix, out: &mut Vec<ContactWLocals>) {
+        self.detector.colls_with_locals(m1, m2, out)
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.detector.set_prediction(prediction)
+    }
 }
 
 /// Collision dispatcher between two `~Geom`.
 pub struct GeomGeomDispatcher {
-    constructors: HashMap<(TypeId, TypeId), Box<CollisionDetectorFactory>>
+    constructors: HashMap<(TypeId, TypeId), Box<GeomGeomCollisionDetectorFactory>>,
+    fallback:     Option<Box<FallbackDetectorFactory>>
 }
 
 impl GeomGeomDispatcher {
     /// Creates a new `GeomGeomDispatcher` without the default set of collision detectors
     /// factories.
+    ///
+    /// It has no fallback detectors either: only the pairs of geometries with a registered
+    /// collision detector will be handled.
     pub fn new_without_default() -> GeomGeomDispatcher {
         GeomGeomDispatcher {
-            constructors: HashMap::new()
+            constructors: HashMap::new(),
+            fallback:     None
         }
     }
 
+    /// Sets the factory of the generic collision detectors used for pairs of geometries without
+    /// registered collision detector.
+    ///
+    /// Those are selected depending on the capability advertised by each geometry. See
+    /// `GeomCapabilities`.
+    pub fn set_fallback_detectors<F: 'static + FallbackDetectorFactory>(&mut self, factory: F) {
+        self.fallback = Some(box factory as Box<FallbackDetectorFactory>)
+    }
+
+    /// Removes the factory of generic collision detectors.
+    ///
+    /// After this, only the pairs of geometries with a registered collision detector will be
+    /// handled.
+    pub fn remove_fallback_detectors(&mut self) {
+        self.fallback = None
+    }
+
     /// Registers a new collision detection algorithm factory for a pair of geometries.
     ///
-    /// This is unsafe because there is no way to check that the factory will really generate
-    /// collision detectors suited for `G1` and `G2`. Whenever possible, use `register_detector` or
-    /// `register_dynamic_detector` instead.
-    pub unsafe fn register_factory<G1: 'static + Any,
-                                   G2: 'static + Any,
-                                   F:  CollisionDetectorFactory>(
-                                   &mut self,
-                                   factory: F) {
-        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
-        self.constructors.insert(key, box factory as Box<CollisionDetectorFactory>);
+    /// The factory must build collision detectors suited for `G1` and `G2`, i.e., implementing
+    /// `DynamicCollisionDetector<G1, G2>`.
+    pub fn register_factory<G1: 'static + Any,
+                            G2: 'static + Any,
+                            D:  'static + Send + DynamicCollisionDetector<G1, G2>,
+                            F:  'static + CollisionDetectorFactory<G1, G2, D>>(
+                            &mut self,
+                            factory: F) {
+        let key     = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        let factory = TypedFactory::<G1, G2, D, F>::new(factory);
+
+        self.constructors.insert(key, box factory as Box<GeomGeomCollisionDetectorFactory>);
     }
 
     /// Registers a new dynamic collision detector for two geometries.
@@ -133,7 +227,7 @@ impl GeomGeomDispatcher {
                                      &mut self,
                                      d:   D) {
         let factory = CollisionDetectorCloner::new(d);
-        unsafe { self.register_factory::<G1, G2, CollisionDetectorCloner<D>>(factory) }
+        self.register_factory::<G1, G2, D, CollisionDetectorCloner<D>>(factory)
     }
 
     /// Registers a new collision detector for two geometries.
@@ -151,91 +245,96 @@ impl GeomGeomDispatcher {
         self.constructors.remove(&key);
     }
 
-    /// If registered, creates a new collision detector adapted for the two given geometries.
+    /// Creates a new collision detector adapted for the two given geometries.
+    ///
+    /// If no collision detector is registered for this pair, a generic one is built from the
+    /// capabilities of the geometries. Returns `None` if there is none either.
     pub fn dispatch(&self, a: &Geom, b: &Geom) -> Option<Box<GeomGeomCollisionDetector>> {
-        self.constructors.find(&(a.get_type_id(), b.get_type_id())).map(|f| f.build())
+        match self.constructors.find(&(a.get_type_id(), b.get_type_id())) {
+            Some(f) => Some(f.build()),
+            None    => {
+                match self.fallback {
+                    Some(ref fallback) => {
+                        geom_geom_fallback::fallback_strategy(a, b).and_then(|strategy| {
+                            fallback.build(&strategy, a, b)
+                        })
+                    },
+                    None => None
+                }
+            }
+        }
     }
-}
 
-impl GeomGeomDispatcher {
-    // FIXME: make this a function which has the simplex and the prediction margin as parameters
-    /// Creates a new `GeomGeomDispatcher` able do build collision detectors for any valid pair of
-    /// geometries supported by `ncollide`.
-    pub fn new() -> GeomGeomDispatcher {
-        let mut res = GeomGeomDispatcher::new_without_default();
+    /// The strategy `dispatch` would use to build a collision detector for the two given
+    /// geometries.
+    ///
+    /// Returns `None` if `dispatch` would not build any collision detector.
+    pub fn strategy(&self, a: &Geom, b: &Geom) -> Option<DispatchStrategy> {
+        if self.constructors.contains_key(&(a.get_type_id(), b.get_type_id())) {
+            Some(RegisteredStrategy)
+        }
+        else if self.fallback.is_some() {
+            geom_geom_fallback::fallback_strategy(a, b)
+        }
+        else {
+            None
+        }
+    }
 
-        type Simplex  = JohnsonSimplex<AnnotatedPoint>;
-        type Self     = GeomGeomDispatcher;
-        type Super    = Box<GeomGeomCollisionDetector>;
+    /// Lists the pairs of geometry types `dispatch` would handle, and the strategy used for each
+    /// pair.
+    ///
+    /// Each ordered pair of geometries from `geoms` is tested. The pairs which would not be
+    /// handled are not listed.
+    pub fn handled_pairs(&self, geoms: &[&Geom]) -> Vec<(TypeId, TypeId, DispatchStrategy)> {
+        let mut res = Vec::new();
 
-        /*
-         * Involving a Plane
-         */
-        let prediction: &Scalar = &na::cast(0.1);
+        for a in geoms.iter() {
+            for b in geoms.iter() {
+                match self.strategy(*a, *b) {
+                    Some(strategy) => res.push((a.get_type_id(), b.get_type_id(), strategy)),
+                    None           => { }
+                }
+            }
+        }
 
-        // Ball vs. Ball
-        let bb = BallBall::new(prediction.clone());
-        res.register_detector(bb);
+        res
+    }
 
-        // Ball vs Surface
+    /// If registered, creates a new collision detector adapted for the two given geometries, using
+    /// a prediction distance specific to this pair.
+    ///
+    /// The prediction distance of the pair is the largest of the prediction distances of both
+    /// objects. It replaces the prediction distance the detector has been registered with. Use
+    /// `GeomGeomCollisionDetector::set_prediction` to change it afterward.
+    pub fn dispatch_with_predictions(&self,
+                                     a:            &Geom,
+                                     prediction_a: &Scalar,
+                                     b:            &Geom,
+                                     prediction_b: &Scalar)
+                                     -> Option<Box<GeomGeomCollisionDetector>> {
+        let prediction = na::max(prediction_a.clone(), prediction_b.clone());
 
-        // let mut selector = YesSirSurfaceSelector::new();
-        // let selector = HyperPlaneSurfaceSelector::new(Bounded::max_value());
-        let cache    = Arc::new(RWLock::new(SurfaceSubdivisionTreeCache::new()));
-        let selector = TangentConesSurfaceSelector::new(Bounded::max_value());
-        let bs = BallBezierSurface::new(selector.clone(), prediction.clone(), cache.clone());
-        let sb = BezierSurfaceBall::new(selector.clone(), prediction.clone(), cache.clone());
-        res.register_detector(bs);
-        res.register_detector(sb);
-
-        // Plane vs. Implicit
-        res.register_default_plane_implicit_detector::<Ball>(false, prediction);
-        res.register_default_plane_implicit_detector::<Cuboid >(true, prediction);
-        res.register_default_plane_implicit_detector::<Cone>(true, prediction);
-        res.register_default_plane_implicit_detector::<Cylinder>(true, prediction);
-        res.register_default_plane_implicit_detector::<Capsule>(true, prediction);
-        res.register_default_plane_implicit_detector::<Convex>(true, prediction);
-        res.register_default_plane_implicit_detector::<Triangle>(true, prediction);
-        res.register_default_plane_implicit_detector::<Segment>(true, prediction);
-
-        // Implicit vs. Implicit
-        // NOTE: some pair will be registered twice…
-        res.register_default_implicit_detectors::<Cuboid>(true, prediction);
-        res.register_default_implicit_detectors::<Cone>(true, prediction);
-        res.register_default_implicit_detectors::<Cylinder>(true, prediction);
-        res.register_default_implicit_detectors::<Capsule>(true, prediction);
-        res.register_default_implicit_detectors::<Convex>(true, prediction);
-        res.register_default_implicit_detectors::<Triangle>(true, prediction);
-        res.register_default_implicit_detectors::<Segment>(true, prediction);
-
-        // FIXME: refactor the three following blocks?
-        // Compound vs. Other
-        res.register_default_concave_geom_geom_detector::<Compound, Plane>();
-        res.register_default_concave_geom_geom_detector::<Compound, Ball>();
-        res.register_default_concave_geom_geom_detector::<Compound, Cuboid>();
-        res.register_default_concave_geom_geom_detector::<Compound, Cone>();
-        res.register_default_concave_geom_geom_detector::<Compound, Cylinder>();
-        res.register_default_concave_geom_geom_detector::<Compound, Capsule>();
-        res.register_default_concave_geom_geom_detector::<Compound, Convex>();
-        res.register_default_concave_geom_geom_detector::<Compound, Triangle>();
-        res.register_default_concave_geom_geom_detector::<Compound, Segment>();
-
-        // TriangleMesh vs. Other
-        res.register_default_concave_geom_geom_detector::<Mesh, Plane>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Ball>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Cuboid>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Cone>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Cylinder>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Capsule>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Convex>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Triangle>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Segment>();
-
-        // FIXME: implement a ConcaveGeomConcaveGeom detector?
-        res.register_default_concave_geom_geom_detector::<Compound, Compound>();
-        res.register_default_concave_geom_geom_detector::<Mesh, Compound>();
+        self.dispatch(a, b).map(|mut d| {
+            d.set_prediction(&prediction);
+            d
+        })
+    }
+}
 
-        res
+impl GeomGeomDispatcher {
+    /// Creates a new `GeomGeomDispatcher` able do build collision detectors for any valid pair of
+    /// geometries supported by `ncollide`.
+    ///
+    /// This uses the default settings of `GeomGeomDispatcherBuilder`. Use a builder to customize
+    /// the prediction distance, simplex, contact manifold strategy or registered shape families.
+    pub fn new() -> GeomGeomDispatcher {
+        GeomGeomDispatcherBuilder::new().build()
+    }
+
+    /// Creates a builder of `GeomGeomDispatcher` initialized with the default settings.
+    pub fn builder() -> GeomGeomDispatcherBuilder<JohnsonSimplex<AnnotatedPoint>> {
+        GeomGeomDispatcherBuilder::new()
     }
 
     /// Registers a `PlaneImplicit` collision detector between a given implicit geometry and a plane.
@@ -243,19 +342,9 @@ impl GeomGeomDispatcher {
                                                     &mut self,
                                                     generate_manifold: bool,
                                                     prediction:        &Scalar) {
-        let p = if generate_manifold { na::zero() } else { prediction.clone() };
-
-        let d1 = ImplicitPlane::<I>::new(p.clone());
-        let d2 = PlaneImplicit::<I>::new(p.clone());
-
-        if generate_manifold {
-            self.register_detector_with_contact_manifold_generator(d1, prediction);
-            self.register_detector_with_contact_manifold_generator(d2, prediction);
-        }
-        else {
-            self.register_detector(d1);
-            self.register_detector(d2);
-        }
+        GeomGeomDispatcherBuilder::new()
+            .prediction(prediction.clone())
+            .register_plane_implicit::<I>(self, manifold_strategy(generate_manifold))
     }
 
     /// Register an `ImplicitImplicit` collision detector between two implicit geometries.
@@ -265,24 +354,71 @@ impl GeomGeomDispatcher {
                                                        G2: 'static            +
                                                            Implicit<Vect, Matrix> +
                                                            PreferedSamplingDirections<Vect, Matrix>,
-                                                       S:  Send + Clone + Simplex<AnnotatedPoint>>(
+                                                       S:  'static + Send + Clone +
+                                                           Simplex<AnnotatedPoint>>(
                                                        &mut self,
                                                        generate_manifold: bool,
                                                        prediction:        &Scalar,
                                                        simplex:           &S) {
-        let p = if generate_manifold { na::zero() } else { prediction.clone() };
+        GeomGeomDispatcherBuilder::new()
+            .prediction(prediction.clone())
+            .simplex(simplex.clone())
+            .register_implicit_implicit::<G1, G2>(self, manifold_strategy(generate_manifold))
+    }
 
-        let d1 = ImplicitImplicit::<S, G1, G2>::new(p.clone(), simplex.clone());
-        let d2 = ImplicitImplicit::<S, G2, G1>::new(p.clone(), simplex.clone());
+    /// Registers a `BallMesh` collision detector between a ball and a triangle mesh.
+    #[cfg(dim3)]
+    pub fn register_default_ball_mesh_detector(&mut self, prediction: &Scalar) {
+        self.register_detector(BallMesh::new(prediction.clone()));
+        self.register_detector(MeshBall::new(prediction.clone()));
+    }
 
-        if generate_manifold {
-            self.register_detector_with_contact_manifold_generator(d1, prediction);
-            self.register_detector_with_contact_manifold_generator(d2, prediction);
-        }
-        else {
-            self.register_detector(d1);
-            self.register_detector(d2);
-        }
+    /// Registers a `BallMesh` collision detector between a ball and a triangle mesh.
+    ///
+    /// There is no such detector in 2D and 4D: this does nothing.
+    #[cfg(not(dim3))]
+    pub fn register_default_ball_mesh_detector(&mut self, _: &Scalar) {
+    }
+
+    /// Registers a `CuboidCuboid` collision detector between two boxes.
+    #[cfg(not(dim4))]
+    pub fn register_default_cuboid_cuboid_detector(&mut self, prediction: &Scalar) {
+        self.register_detector(CuboidCuboid::new(prediction.clone()));
+    }
+
+    /// Registers a `CuboidCuboid` collision detector between two boxes.
+    ///
+    /// There is no such detector in 4D: this does nothing.
+    #[cfg(dim4)]
+    pub fn register_default_cuboid_cuboid_detector(&mut self, _: &Scalar) {
+    }
+
+    /// Registers an `ImplicitImplicit` collision detector with a
+    /// `PolyhedralContactManifoldGenerator` between two polyhedral geometries.
+    #[cfg(not(dim4))]
+    pub fn register_default_polyhedral_detector<G1: 'static                 +
+                                                    Polyhedron             +
+                                                    Implicit<Vect, Matrix> +
+                                                    PreferedSamplingDirections<Vect, Matrix>,
+                                                G2: 'static                 +
+                                                    Polyhedron             +
+                                                    Implicit<Vect, Matrix> +
+                                                    PreferedSamplingDirections<Vect, Matrix>>(
+                                                &mut self,
+                                                prediction: &Scalar) {
+        GeomGeomDispatcherBuilder::new()
+            .prediction(prediction.clone())
+            .register_polyhedral::<G1, G2>(self)
+    }
+
+    /// Registers a polyhedral collision detector between each pair of `Convex`, `Cuboid`,
+    /// `Triangle` and `Segment`, except `Cuboid` vs. `Cuboid`.
+    ///
+    /// There is no polyhedral contact manifold generator in 4D: this does nothing.
+    pub fn register_default_polyhedral_detectors(&mut self, prediction: &Scalar) {
+        GeomGeomDispatcherBuilder::new()
+            .prediction(prediction.clone())
+            .register_polyhedrals(self)
     }
 
     /// Register an `ConcaveGeomGeom` collision detector between a given concave geometry and a
@@ -292,9 +428,23 @@ impl GeomGeomDispatcher {
         let  f1 = ConcaveGeomGeomFactory::<G1, G2>;
         let  f2 = GeomConcaveGeomFactory::<G2, G1>;
 
-        // FIXME: find a way to factorize that?
-        unsafe { self.register_factory::<G1, G2, ConcaveGeomGeomFactory<G1, G2>>(f1) }
-        unsafe { self.register_factory::<G2, G1, GeomConcaveGeomFactory<G2, G1>>(f2) }
+        // FIXME: find a way to factorize that?
+        self.register_factory::<G1, G2, ConcaveGeomGeom<G1, G2>, ConcaveGeomGeomFactory<G1, G2>>(f1);
+        self.register_factory::<G2, G1, GeomConcaveGeom<G2, G1>, GeomConcaveGeomFactory<G2, G1>>(f2);
+    }
+
+    /// Register a `ConcaveGeomConcaveGeom` collision detector between two given concave
+    /// geometries.
+    pub fn register_default_concave_geom_concave_geom_detector<G1: 'static + ConcaveGeom,
+                                                               G2: 'static + ConcaveGeom>(
+                                                               &mut self) {
+        let  f1 = ConcaveGeomConcaveGeomFactory::<G1, G2>;
+        let  f2 = ConcaveGeomConcaveGeomFactory::<G2, G1>;
+
+        self.register_factory::<G1, G2, ConcaveGeomConcaveGeom<G1, G2>,
+                                ConcaveGeomConcaveGeomFactory<G1, G2>>(f1);
+        self.register_factory::<G2, G1, ConcaveGeomConcaveGeom<G2, G1>,
+                                ConcaveGeomConcaveGeomFactory<G2, G1>>(f2);
     }
 
     /// Register a given collision detector and adds it a contact manifold generator (a
@@ -323,30 +473,499 @@ impl GeomGeomDispatcher {
                                                &mut self,
                                                generate_manifold: bool,
                                                prediction:        &Scalar) {
-        type Simplex  = JohnsonSimplex<AnnotatedPoint>;
+        GeomGeomDispatcherBuilder::new()
+            .prediction(prediction.clone())
+            .manifold(manifold_strategy(generate_manifold))
+            .register_implicits::<G>(self)
+    }
+}
 
-        // Implicit vs. Implicit
-        let rt = RecursionTemplate::new(na::dim::<Vect>());
-        let js = &JohnsonSimplex::new(rt);
+fn manifold_strategy(generate_manifold: bool) -> ContactManifoldStrategy {
+    if generate_manifold { OneShotManifold } else { NoManifold }
+}
 
-        self.register_default_implicit_implicit_detector::<Ball, G, Simplex>(false, prediction, js);
-        self.register_default_implicit_implicit_detector::<Cuboid, G, Simplex>(generate_manifold, prediction, js);
-        self.register_default_implicit_implicit_detector::<Cone, G, Simplex>(generate_manifold, prediction, js);
-        self.register_default_implicit_implicit_detector::<Cylinder, G, Simplex>(generate_manifold, prediction, js);
-        self.register_default_implicit_implicit_detector::<Capsule, G, Simplex>(generate_manifold, prediction, js);
-        self.register_default_implicit_implicit_detector::<Convex, G, Simplex>(generate_manifold, prediction, js);
-        self.register_default_implicit_implicit_detector::<Triangle, G, Simplex>(generate_manifold, prediction, js);
-        self.register_default_implicit_implicit_detector::<Segment, G, Simplex>(generate_manifold, prediction, js);
+/// Strategy used by the default collision detectors to generate a contact manifold.
+#[deriving(Show, PartialEq, Clone)]
+pub enum ContactManifoldStrategy {
+    /// Each collision detector generates a single contact.
+    NoManifold,
+    /// A full manifold is generated by a `OneShotContactManifoldGenerator`.
+    OneShotManifold,
+    /// The manifold is built one contact per update by an `IncrementalContactManifoldGenerator`.
+    IncrementalManifold,
+    /// Same as `OneShotManifold`, except that boxes and polyhedrons use specialized detectors
+    /// generating their manifold by clipping their faces. This is not available in 4D.
+    ClippingManifold
+}
+
+/// Builder of a `GeomGeomDispatcher` registering the default collision detectors.
+pub struct GeomGeomDispatcherBuilder<S> {
+    prediction:      Scalar,
+    simplex:         S,
+    manifold:        ContactManifoldStrategy,
+    fallback:        PenetrationFallback,
+    surface_max_lmd: Scalar,
+    planes:          bool,
+    implicits:       bool,
+    surfaces:        bool,
+    curves:          bool,
+    compounds:       bool,
+    meshes:          bool,
+    fallbacks:       bool
+}
+
+impl GeomGeomDispatcherBuilder<JohnsonSimplex<AnnotatedPoint>> {
+    /// Creates a builder with the default settings.
+    ///
+    /// Those are a prediction distance of 0.1, a `JohnsonSimplex`, the `ClippingManifold`
+    /// strategy, the `MinkowskiSamplingFallback`, an unbounded surface selector, every shape
+    /// family registered, and the fallback detectors enabled.
+    pub fn new() -> GeomGeomDispatcherBuilder<JohnsonSimplex<AnnotatedPoint>> {
+        GeomGeomDispatcherBuilder {
+            prediction:      na::cast(0.1),
+            simplex:         JohnsonSimplex::new(RecursionTemplate::new(na::dim::<Vect>())),
+            manifold:        ClippingManifold,
+            fallback:        MinkowskiSamplingFallback,
+            surface_max_lmd: Bounded::max_value(),
+            planes:          true,
+            implicits:       true,
+            surfaces:        true,
+            curves:          true,
+            compounds:       true,
+            meshes:          true,
+            fallbacks:       true
+        }
     }
 }
 
-// FIXME: rename that GeomGeomCollisionDetectorFactory ?
-/// Trait of structures able do build a new collision detector.
-pub trait CollisionDetectorFactory : Send {
+impl<S: 'static + Send + Clone + Simplex<AnnotatedPoint>> GeomGeomDispatcherBuilder<S> {
+    /// Sets the maximum distance between two objects for them to generate contacts.
+    pub fn prediction(mut self, prediction: Scalar) -> GeomGeomDispatcherBuilder<S> {
+        self.prediction = prediction;
+        self
+    }
+
+    /// Sets the simplex used by the GJK-based collision detectors.
+    pub fn simplex<S2: 'static + Send + Clone + Simplex<AnnotatedPoint>>(self, simplex: S2)
+                                                                         -> GeomGeomDispatcherBuilder<S2> {
+        GeomGeomDispatcherBuilder {
+            prediction:      self.prediction,
+            simplex:         simplex,
+            manifold:        self.manifold,
+            fallback:        self.fallback,
+            surface_max_lmd: self.surface_max_lmd,
+            planes:          self.planes,
+            implicits:       self.implicits,
+            surfaces:        self.surfaces,
+            curves:          self.curves,
+            compounds:       self.compounds,
+            meshes:          self.meshes,
+            fallbacks:       self.fallbacks
+        }
+    }
+
+    /// Sets the strategy used to generate contact manifolds.
+    pub fn manifold(mut self, manifold: ContactManifoldStrategy) -> GeomGeomDispatcherBuilder<S> {
+        self.manifold = manifold;
+        self
+    }
+
+    /// Sets the algorithm used by the GJK-based collision detectors when two objects penetrate.
+    pub fn penetration_fallback(mut self, fallback: PenetrationFallback) -> GeomGeomDispatcherBuilder<S> {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Sets the maximum local minimal distance of the surface selector used by the collision
+    /// detectors involving a `BezierSurface`.
+    ///
+    /// Closest points farther than this distance are ignored. The detectors between a `Ball` and a
+    /// `BezierSurface` further bound it by the ball radius and the prediction distance.
+    pub fn surface_max_lmd(mut self, max_lmd: Scalar) -> GeomGeomDispatcherBuilder<S> {
+        self.surface_max_lmd = max_lmd;
+        self
+    }
+
+    /// Sets whether the collision detectors involving a `Plane` are registered.
+    pub fn planes(mut self, enable: bool) -> GeomGeomDispatcherBuilder<S> {
+        self.planes = enable;
+        self
+    }
+
+    /// Sets whether the collision detectors involving implicit geometries (`Ball`, `Cuboid`,
+    /// `Cone`, `Cylinder`, `Capsule`, `Convex`, `Triangle`, `Tetrahedron` and `Segment`) are
+    /// registered.
+    pub fn implicits(mut self, enable: bool) -> GeomGeomDispatcherBuilder<S> {
+        self.implicits = enable;
+        self
+    }
+
+    /// Sets whether the collision detectors involving a `BezierSurface` are registered.
+    ///
+    /// The detectors between a `BezierSurface` and an implicit geometry are registered only if the
+    /// implicit geometries are enabled too.
+    pub fn bezier_surfaces(mut self, enable: bool) -> GeomGeomDispatcherBuilder<S> {
+        self.surfaces = enable;
+        self
+    }
+
+    /// Sets whether the collision detectors involving a `BezierCurve` are registered.
+    ///
+    /// The detectors between a `BezierCurve` and an implicit geometry are registered only if the
+    /// implicit geometries are enabled too.
+    pub fn bezier_curves(mut self, enable: bool) -> GeomGeomDispatcherBuilder<S> {
+        self.curves = enable;
+        self
+    }
+
+    /// Sets whether the collision detectors involving a `Compound` are registered.
+    pub fn compounds(mut self, enable: bool) -> GeomGeomDispatcherBuilder<S> {
+        self.compounds = enable;
+        self
+    }
+
+    /// Sets whether the collision detectors involving a `Mesh` (or a `TetMesh` in 3D) are
+    /// registered.
+    pub fn meshes(mut self, enable: bool) -> GeomGeomDispatcherBuilder<S> {
+        self.meshes = enable;
+        self
+    }
+
+    /// Sets whether generic collision detectors are used for the pairs of geometries without
+    /// registered collision detector.
+    pub fn fallback_detectors(mut self, enable: bool) -> GeomGeomDispatcherBuilder<S> {
+        self.fallbacks = enable;
+        self
+    }
+
+    /// Creates a new `GeomGeomDispatcher` with the collision detectors selected by this builder.
+    ///
+    /// A geometry pair involving a shape family which is not enabled has no registered collision
+    /// detector. It might still be handled by the fallback detectors, unless they are disabled.
+    pub fn build(&self) -> GeomGeomDispatcher {
+        let mut res = GeomGeomDispatcher::new_without_default();
+        let prediction = &self.prediction;
+
+        if self.fallbacks {
+            res.set_fallback_detectors(FallbackDetectors::new(prediction.clone(),
+                                                              self.simplex.clone(),
+                                                              self.fallback.clone()));
+        }
+
+        if self.implicits {
+            // Ball vs. Ball
+            res.register_detector(BallBall::new(prediction.clone()));
+        }
+
+        // let mut selector = YesSirSurfaceSelector::new();
+        // let selector = HyperPlaneSurfaceSelector::new(Bounded::max_value());
+        let cache    = Arc::new(RWLock::new(SurfaceSubdivisionTreeCache::new()));
+        let selector = TangentConesSurfaceSelector::new(self.surface_max_lmd.clone());
+
+        if self.surfaces {
+            // Surface vs Surface
+            res.register_detector(BezierSurfaceBezierSurface::new(selector.clone(), prediction.clone(),
+                                                                  cache.clone()));
+        }
+
+        if self.implicits && self.surfaces {
+            // Ball vs Surface
+            let bs = BallBezierSurface::new(selector.clone(), prediction.clone(), cache.clone());
+            let sb = BezierSurfaceBall::new(selector.clone(), prediction.clone(), cache.clone());
+            res.register_detector(bs);
+            res.register_detector(sb);
+
+            // Implicit vs Surface
+            self.register_implicit_surface::<Cuboid>(&mut res, &selector, &cache);
+            self.register_implicit_surface::<Cone>(&mut res, &selector, &cache);
+            self.register_implicit_surface::<Cylinder>(&mut res, &selector, &cache);
+            self.register_implicit_surface::<Capsule>(&mut res, &selector, &cache);
+            self.register_implicit_surface::<Convex>(&mut res, &selector, &cache);
+            self.register_implicit_surface::<Triangle>(&mut res, &selector, &cache);
+            self.register_implicit_surface::<Tetrahedron>(&mut res, &selector, &cache);
+            self.register_implicit_surface::<Segment>(&mut res, &selector, &cache);
+        }
+
+        if self.implicits && self.curves {
+            // Ball vs Curve
+            res.register_detector(BallBezierCurve::new(prediction.clone()));
+            res.register_detector(BezierCurveBall::new(prediction.clone()));
+
+            // Implicit vs Curve
+            self.register_implicit_curve::<Cuboid>(&mut res);
+            self.register_implicit_curve::<Cone>(&mut res);
+            self.register_implicit_curve::<Cylinder>(&mut res);
+            self.register_implicit_curve::<Capsule>(&mut res);
+            self.register_implicit_curve::<Convex>(&mut res);
+            self.register_implicit_curve::<Triangle>(&mut res);
+            self.register_implicit_curve::<Tetrahedron>(&mut res);
+            self.register_implicit_curve::<Segment>(&mut res);
+        }
+
+        if self.planes && self.implicits {
+            // Plane vs. Implicit
+            self.register_plane_implicit::<Ball>(&mut res, NoManifold);
+            self.register_plane_implicit::<Cuboid>(&mut res, self.manifold.clone());
+            self.register_plane_implicit::<Cone>(&mut res, self.manifold.clone());
+            self.register_plane_implicit::<Cylinder>(&mut res, self.manifold.clone());
+            self.register_plane_implicit::<Capsule>(&mut res, self.manifold.clone());
+            self.register_plane_implicit::<Convex>(&mut res, self.manifold.clone());
+            self.register_plane_implicit::<Triangle>(&mut res, self.manifold.clone());
+            self.register_plane_implicit::<Tetrahedron>(&mut res, self.manifold.clone());
+            self.register_plane_implicit::<Segment>(&mut res, self.manifold.clone());
+        }
+
+        if self.implicits {
+            // Implicit vs. Implicit
+            // NOTE: some pair will be registered twice…
+            self.register_implicits::<Cuboid>(&mut res);
+            self.register_implicits::<Cone>(&mut res);
+            self.register_implicits::<Cylinder>(&mut res);
+            self.register_implicits::<Capsule>(&mut res);
+            self.register_implicits::<Convex>(&mut res);
+            self.register_implicits::<Triangle>(&mut res);
+            self.register_implicits::<Tetrahedron>(&mut res);
+            self.register_implicits::<Segment>(&mut res);
+
+            if self.manifold == ClippingManifold {
+                // Cuboid vs. Cuboid
+                // NOTE: this replaces the `ImplicitImplicit` detector registered above.
+                res.register_default_cuboid_cuboid_detector(prediction);
+
+                // Polyhedron vs. Polyhedron
+                // NOTE: this replaces the `ImplicitImplicit` detectors registered above.
+                self.register_polyhedrals(&mut res);
+            }
+
+            // Capsule and Segment vs. Ball, Capsule vs. Capsule, Segment vs. Segment
+            // NOTE: this replaces the `ImplicitImplicit` and polyhedral detectors registered above.
+            res.register_detector(BallCapsule::new(prediction.clone()));
+            res.register_detector(CapsuleBall::new(prediction.clone()));
+            res.register_detector(BallSegment::new(prediction.clone()));
+            res.register_detector(SegmentBall::new(prediction.clone()));
+            res.register_detector(CapsuleCapsule::new(prediction.clone()));
+            res.register_detector(SegmentSegment::new(prediction.clone()));
+
+            // Ball vs. Triangle
+            // NOTE: this replaces the `ImplicitImplicit` detectors registered above.
+            res.register_detector(BallTriangle::new(prediction.clone()));
+            res.register_detector(TriangleBall::new(prediction.clone()));
+        }
+
+        if self.compounds {
+            // Compound vs. Other
+            self.register_concave_vs_others::<Compound>(&mut res);
+        }
+
+        if self.meshes {
+            // TriangleMesh vs. Other
+            self.register_concave_vs_others::<Mesh>(&mut res);
+
+            if self.implicits {
+                // Ball vs. TriangleMesh
+                // NOTE: this replaces the `ConcaveGeomGeom` detectors registered above.
+                res.register_default_ball_mesh_detector(prediction);
+            }
+        }
+
+        // Concave vs. Concave
+        if self.compounds {
+            res.register_default_concave_geom_concave_geom_detector::<Compound, Compound>();
+        }
+
+        if self.compounds && self.meshes {
+            res.register_default_concave_geom_concave_geom_detector::<Mesh, Compound>();
+        }
+
+        if self.meshes {
+            res.register_default_concave_geom_concave_geom_detector::<Mesh, Mesh>();
+            self.register_tet_meshes(&mut res);
+        }
+
+        res
+    }
+
+    // Registers a collision detector, wrapped into the contact manifold generator corresponding to
+    // `manifold`.
+    fn register_with_manifold<G1: 'static + Any,
+                              G2: 'static + Any,
+                              D:  'static + Send + CollisionDetector<G1, G2> + Clone>(
+                              &self,
+                              res:      &mut GeomGeomDispatcher,
+                              d:        D,
+                              manifold: ContactManifoldStrategy) {
+        match manifold {
+            NoManifold          => res.register_detector(d),
+            IncrementalManifold => res.register_detector(ICMG::<D>::new(self.prediction.clone(), d)),
+            OneShotManifold     |
+            ClippingManifold    => res.register_detector_with_contact_manifold_generator(d, &self.prediction)
+        }
+    }
+
+    // The prediction of the collision detectors wrapped into a contact manifold generator.
+    fn sub_prediction(&self, manifold: &ContactManifoldStrategy) -> Scalar {
+        if *manifold == NoManifold { self.prediction.clone() } else { na::zero() }
+    }
+
+    fn register_plane_implicit<I: 'static + Implicit<Vect, Matrix>>(
+                               &self,
+                               res:      &mut GeomGeomDispatcher,
+                               manifold: ContactManifoldStrategy) {
+        let p = self.sub_prediction(&manifold);
+
+        self.register_with_manifold(res, ImplicitPlane::<I>::new(p.clone()), manifold.clone());
+        self.register_with_manifold(res, PlaneImplicit::<I>::new(p), manifold);
+    }
+
+    fn register_implicit_surface<G: 'static + Send + Implicit<Vect, Matrix>>(
+                                 &self,
+                                 res:      &mut GeomGeomDispatcher,
+                                 selector: &TangentConesSurfaceSelector,
+                                 cache:    &Arc<RWLock<SurfaceSubdivisionTreeCache<TangentConesSurfaceSelectorTestData>>>) {
+        let gs: ImplicitBezierSurface<TangentConesSurfaceSelector, TangentConesSurfaceSelectorTestData, G> =
+            ImplicitBezierSurface::new(selector.clone(), self.prediction.clone(), cache.clone());
+        let sg: BezierSurfaceImplicit<TangentConesSurfaceSelector, TangentConesSurfaceSelectorTestData, G> =
+            BezierSurfaceImplicit::new(selector.clone(), self.prediction.clone(), cache.clone());
+
+        res.register_detector(gs);
+        res.register_detector(sg);
+    }
+
+    fn register_implicit_curve<G: 'static + Send + Implicit<Vect, Matrix>>(
+                               &self,
+                               res: &mut GeomGeomDispatcher) {
+        res.register_detector(ImplicitBezierCurve::<G>::new(self.prediction.clone()));
+        res.register_detector(BezierCurveImplicit::<G>::new(self.prediction.clone()));
+    }
+
+    fn register_implicit_implicit<G1: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+                                  G2: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                                  &self,
+                                  res:      &mut GeomGeomDispatcher,
+                                  manifold: ContactManifoldStrategy) {
+        let p  = self.sub_prediction(&manifold);
+        let d1 = ImplicitImplicit::<S, G1, G2>::new_with_fallback(p.clone(), self.simplex.clone(),
+                                                                  self.fallback.clone());
+        let d2 = ImplicitImplicit::<S, G2, G1>::new_with_fallback(p, self.simplex.clone(),
+                                                                  self.fallback.clone());
+
+        self.register_with_manifold(res, d1, manifold.clone());
+        self.register_with_manifold(res, d2, manifold);
+    }
+
+    fn register_implicits<G: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                          &self,
+                          res: &mut GeomGeomDispatcher) {
+        self.register_implicit_implicit::<Ball, G>(res, NoManifold);
+        self.register_implicit_implicit::<Cuboid, G>(res, self.manifold.clone());
+        self.register_implicit_implicit::<Cone, G>(res, self.manifold.clone());
+        self.register_implicit_implicit::<Cylinder, G>(res, self.manifold.clone());
+        self.register_implicit_implicit::<Capsule, G>(res, self.manifold.clone());
+        self.register_implicit_implicit::<Convex, G>(res, self.manifold.clone());
+        self.register_implicit_implicit::<Triangle, G>(res, self.manifold.clone());
+        self.register_implicit_implicit::<Tetrahedron, G>(res, self.manifold.clone());
+        self.register_implicit_implicit::<Segment, G>(res, self.manifold.clone());
+    }
+
+    #[cfg(not(dim4))]
+    fn register_polyhedral<G1: 'static + Polyhedron + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+                           G2: 'static + Polyhedron + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                           &self,
+                           res: &mut GeomGeomDispatcher) {
+        let prediction = &self.prediction;
+        let d1 = ImplicitImplicit::<S, G1, G2>::new_with_fallback(prediction.clone(), self.simplex.clone(),
+                                                                  self.fallback.clone());
+        let d2 = ImplicitImplicit::<S, G2, G1>::new_with_fallback(prediction.clone(), self.simplex.clone(),
+                                                                  self.fallback.clone());
+
+        res.register_detector(PolyhedralContactManifoldGenerator::new(prediction.clone(), d1));
+        res.register_detector(PolyhedralContactManifoldGenerator::new(prediction.clone(), d2));
+    }
+
+    #[cfg(not(dim4))]
+    fn register_polyhedrals(&self, res: &mut GeomGeomDispatcher) {
+        self.register_polyhedral::<Convex, Convex>(res);
+        self.register_polyhedral::<Convex, Cuboid>(res);
+        self.register_polyhedral::<Convex, Triangle>(res);
+        self.register_polyhedral::<Convex, Segment>(res);
+        self.register_polyhedral::<Cuboid, Triangle>(res);
+        self.register_polyhedral::<Cuboid, Segment>(res);
+        self.register_polyhedral::<Triangle, Triangle>(res);
+        self.register_polyhedral::<Triangle, Segment>(res);
+        self.register_polyhedral::<Segment, Segment>(res);
+    }
+
+    // There is no polyhedral contact manifold generator in 4D: this does nothing.
+    #[cfg(dim4)]
+    fn register_polyhedrals(&self, _: &mut GeomGeomDispatcher) {
+    }
+
+    #[cfg(dim3)]
+    fn register_tet_meshes(&self, res: &mut GeomGeomDispatcher) {
+        self.register_concave_vs_others::<TetMesh>(res);
+
+        if self.compounds {
+            res.register_default_concave_geom_concave_geom_detector::<TetMesh, Compound>();
+        }
+
+        res.register_default_concave_geom_concave_geom_detector::<TetMesh, Mesh>();
+        res.register_default_concave_geom_concave_geom_detector::<TetMesh, TetMesh>();
+    }
+
+    // There is no tetrahedral mesh outside of 3D: this does nothing.
+    #[cfg(not(dim3))]
+    fn register_tet_meshes(&self, _: &mut GeomGeomDispatcher) {
+    }
+
+    fn register_concave_vs_others<G: 'static + ConcaveGeom>(&self, res: &mut GeomGeomDispatcher) {
+        if self.planes {
+            res.register_default_concave_geom_geom_detector::<G, Plane>();
+        }
+
+        if self.implicits {
+            res.register_default_concave_geom_geom_detector::<G, Ball>();
+            res.register_default_concave_geom_geom_detector::<G, Cuboid>();
+            res.register_default_concave_geom_geom_detector::<G, Cone>();
+            res.register_default_concave_geom_geom_detector::<G, Cylinder>();
+            res.register_default_concave_geom_geom_detector::<G, Capsule>();
+            res.register_default_concave_geom_geom_detector::<G, Convex>();
+            res.register_default_concave_geom_geom_detector::<G, Triangle>();
+            res.register_default_concave_geom_geom_detector::<G, Tetrahedron>();
+            res.register_default_concave_geom_geom_detector::<G, Segment>();
+        }
+    }
+}
+
+/// Trait of structures able do build a new collision detector for the geometries `G1` and `G2`.
+pub trait CollisionDetectorFactory<G1, G2, D: DynamicCollisionDetector<G1, G2>> : Send {
     /// Builds a new collision detector.
+    fn build(&self) -> D;
+}
+
+// Factory with the types of the geometries erased, as stored by the `GeomGeomDispatcher`.
+trait GeomGeomCollisionDetectorFactory : Send {
     fn build(&self) -> Box<GeomGeomCollisionDetector>;
 }
 
+struct TypedFactory<G1, G2, D, F> {
+    factory: F
+}
+
+impl<G1, G2, D, F> TypedFactory<G1, G2, D, F> {
+    fn new(factory: F) -> TypedFactory<G1, G2, D, F> {
+        TypedFactory {
+            factory: factory
+        }
+    }
+}
+
+impl<G1, G2, D: 'static + Send + DynamicCollisionDetector<G1, G2>, F: CollisionDetectorFactory<G1, G2, D>>
+GeomGeomCollisionDetectorFactory for TypedFactory<G1, G2, D, F> {
+    fn build(&self) -> Box<GeomGeomCollisionDetector> {
+        box self.factory.build() as Box<GeomGeomCollisionDetector>
+    }
+}
+
 /// Cloning-based collision detector factory.
 pub struct CollisionDetectorCloner<CD> {
     template: CD
@@ -356,16 +975,299 @@ impl<CD: GeomGeomCollisionDetector + Clone> CollisionDetectorCloner<CD> {
     /// Creates a new `CollisionDetectorCloner`.
     ///
     /// The cloned detector is `CD`.
-    fn new(detector: CD) -> CollisionDetectorCloner<CD> {
+    pub fn new(detector: CD) -> CollisionDetectorCloner<CD> {
         CollisionDetectorCloner {
             template: detector
         }
     }
 }
 
-impl<CD: 'static + Send + GeomGeomCollisionDetector + Clone>
-CollisionDetectorFactory for CollisionDetectorCloner<CD> {
-    fn build(&self) -> Box<GeomGeomCollisionDetector> {
-        box self.template.clone() as Box<GeomGeomCollisionDetector>
+impl<G1, G2, CD: Send + DynamicCollisionDetector<G1, G2> + Clone>
+CollisionDetectorFactory<G1, G2, CD> for CollisionDetectorCloner<CD> {
+    fn build(&self) -> CD {
+        self.template.clone()
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Geom, Ball, Cuboid, Cone, Cylinder, Plane, Compound, Mesh, BezierCurve};
+    use narrow::{GeomGeomDispatcher, ContactManifoldStrategy, NoManifold, IncrementalManifold,
+                 PenetrationFallback, MinkowskiSamplingFallback, NoPenetrationFallback,
+                 ImplicitImplicitStrategy};
+    use test_utils::flat_surface;
+
+    #[test]
+    fn test_builder_family_toggles() {
+        let b     = Ball::new(0.5f64);
+        let c     = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let p     = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let s     = flat_surface();
+        let curve = BezierCurve::new(vec!(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0),
+                                          Vec3::new(2.0, 0.0, 0.0)));
+        let comp  = Compound::new(vec!((na::one::<Iso3<f64>>(), box Ball::new(0.5f64) as Box<Geom:Send>)));
+        let mesh  = Mesh::new(Arc::new(vec!(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0),
+                                            Vec3::new(0.0, 0.0, 1.0))),
+                              Arc::new(vec!(0u, 1, 2)), None, None);
+
+        let all = GeomGeomDispatcher::builder().fallback_detectors(false).build();
+
+        // The i-th dispatcher disables the family of the i-th pair.
+        let pairs = [
+            (&p as &Geom, &b as &Geom),
+            (&b as &Geom, &c as &Geom),
+            (&s as &Geom, &s as &Geom),
+            (&curve as &Geom, &b as &Geom),
+            (&comp as &Geom, &b as &Geom),
+            (&mesh as &Geom, &b as &Geom)
+        ];
+        let dispatchers = [
+            GeomGeomDispatcher::builder().fallback_detectors(false).planes(false).build(),
+            GeomGeomDispatcher::builder().fallback_detectors(false).implicits(false).build(),
+            GeomGeomDispatcher::builder().fallback_detectors(false).bezier_surfaces(false).build(),
+            GeomGeomDispatcher::builder().fallback_detectors(false).bezier_curves(false).build(),
+            GeomGeomDispatcher::builder().fallback_detectors(false).compounds(false).build(),
+            GeomGeomDispatcher::builder().fallback_detectors(false).meshes(false).build()
+        ];
+
+        for (i, &(g1, g2)) in pairs.iter().enumerate() {
+            assert!(all.dispatch(g1, g2).is_some());
+            assert!(dispatchers[i].dispatch(g1, g2).is_none());
+        }
+
+        // With the fallback detectors, a disabled family is still handled.
+        let fallback = GeomGeomDispatcher::builder().implicits(false).build();
+
+        assert!(fallback.strategy(&b as &Geom, &c as &Geom) == Some(ImplicitImplicitStrategy));
+        assert!(fallback.dispatch(&b as &Geom, &c as &Geom).is_some());
+    }
+
+    // Number of contacts between a box rocking on a plane after a few updates.
+    fn rocking_box_num_colls(manifold: ContactManifoldStrategy) -> uint {
+        let dispatcher   = GeomGeomDispatcher::builder().manifold(manifold).build();
+        let p            = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let c            = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let mp           = na::one::<Iso3<f64>>();
+        let mut detector = dispatcher.dispatch(&p as &Geom, &c as &Geom).expect("No detector for this pair.");
+
+        let tilts = [ Vec3::new(0.02f64, 0.0, 0.0), Vec3::new(-0.02f64, 0.0, 0.0),
+                      Vec3::new(0.0f64, 0.0, 0.02), Vec3::new(0.0f64, 0.0, -0.02) ];
+
+        for tilt in tilts.iter() {
+            let mc = Iso3::new(Vec3::new(0.0f64, 0.99, 0.0), tilt.clone());
+
+            assert!(detector.update(&dispatcher, &mp, &p as &Geom, &mc, &c as &Geom).is_ok());
+        }
+
+        detector.num_colls()
+    }
+
+    #[test]
+    fn test_builder_manifold() {
+        assert!(rocking_box_num_colls(NoManifold) == 1);
+        assert!(rocking_box_num_colls(IncrementalManifold) > 1);
+    }
+
+    // Number of contacts between a cone and a cylinder with the same center.
+    fn deep_penetration_num_colls(fallback: PenetrationFallback) -> uint {
+        let dispatcher   = GeomGeomDispatcher::builder().manifold(NoManifold)
+                                                        .penetration_fallback(fallback)
+                                                        .build();
+        let cone         = Cone::new(0.5f64, 0.5);
+        let cyl          = Cylinder::new(0.5f64, 0.5);
+        let m            = na::one::<Iso3<f64>>();
+        let mut detector = dispatcher.dispatch(&cone as &Geom, &cyl as &Geom).expect("No detector for this pair.");
+
+        assert!(detector.update(&dispatcher, &m, &cone as &Geom, &m, &cyl as &Geom).is_ok());
+
+        detector.num_colls()
+    }
+
+    #[test]
+    fn test_builder_penetration_fallback() {
+        assert!(deep_penetration_num_colls(MinkowskiSamplingFallback) == 1);
+        assert!(deep_penetration_num_colls(NoPenetrationFallback) == 0);
+    }
+
+    // Number of contacts between a ball slightly penetrating a flat surface.
+    fn ball_surface_num_colls(dispatcher: &GeomGeomDispatcher) -> uint {
+        let s            = flat_surface();
+        let b            = Ball::new(0.5f64);
+        let ms           = na::one::<Iso3<f64>>();
+        let mb           = Iso3::new(Vec3::new(0.8f64, 1.3, 0.45), na::zero());
+        let mut detector = dispatcher.dispatch(&b as &Geom, &s as &Geom).expect("No detector for this pair.");
+
+        assert!(detector.update(dispatcher, &mb, &b as &Geom, &ms, &s as &Geom).is_ok());
+
+        detector.num_colls()
+    }
+
+    #[test]
+    fn test_builder_surface_max_lmd() {
+        // The surface is 0.45 away from the ball center: the closest point is ignored if the
+        // maximum local minimal distance is smaller.
+        let unbounded = GeomGeomDispatcher::builder().build();
+        let bounded   = GeomGeomDispatcher::builder().surface_max_lmd(0.01).build();
+
+        assert!(ball_surface_num_colls(&unbounded) > 0);
+        assert!(ball_surface_num_colls(&bounded) == 0);
+    }
+
+    #[test]
+    fn test_dispatch_with_predictions() {
+        let dispatcher = GeomGeomDispatcher::new();
+        let b          = Ball::new(0.5f64);
+        let p          = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let c          = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let m          = na::one::<Iso3<f64>>();
+        let pairs      = [
+            (&b as &Geom, &b as &Geom, Iso3::new(Vec3::new(0.0f64, 1.3, 0.0), na::zero())),
+            (&p as &Geom, &c as &Geom, Iso3::new(Vec3::new(0.0f64, 0.8, 0.0), na::zero()))
+        ];
+
+        // Both pairs are 0.3 apart: farther than the default prediction (0.1). The plane-box
+        // detector is wrapped into a contact manifold generator.
+        for &(g1, g2, ref above) in pairs.iter() {
+            let mut default = dispatcher.dispatch(g1, g2).unwrap();
+            let mut near    = dispatcher.dispatch_with_predictions(g1, &0.05, g2, &0.5).unwrap();
+            let mut far     = dispatcher.dispatch_with_predictions(g1, &0.05, g2, &0.2).unwrap();
+
+            assert!(default.update(&dispatcher, &m, g1, above, g2).is_ok());
+            assert!(near.update(&dispatcher, &m, g1, above, g2).is_ok());
+            assert!(far.update(&dispatcher, &m, g1, above, g2).is_ok());
+
+            assert!(default.num_colls() == 0);
+            assert!(near.num_colls() > 0);
+            assert!(far.num_colls() == 0);
+        }
+    }
+}
+
+#[cfg(dim4, f64, test)]
+mod test {
+    use nalgebra::na::{Vec4, Iso4};
+    use nalgebra::na;
+    use geom::{Geom, Ball, Cuboid, Cylinder, Cone, Capsule, Plane};
+    use contact::Contact;
+    use narrow::{GeomGeomDispatcher, RegisteredStrategy};
+
+    fn translation(x: f64, y: f64, z: f64, w: f64) -> Iso4<f64> {
+        na::append_translation(&na::one::<Iso4<f64>>(), &Vec4::new(x, y, z, w))
+    }
+
+    fn collide(m1: &Iso4<f64>, g1: &Geom, m2: &Iso4<f64>, g2: &Geom, nupdates: uint) -> Vec<Contact> {
+        let dispatcher   = GeomGeomDispatcher::new();
+        let mut detector = dispatcher.dispatch(g1, g2).expect("No collision detector for this pair.");
+        let mut res      = Vec::new();
+
+        for _ in range(0u, nupdates) {
+            assert!(detector.update(&dispatcher, m1, g1, m2, g2).is_ok());
+        }
+
+        detector.colls(&mut res);
+
+        res
+    }
+
+    #[test]
+    fn test_cuboid_cuboid_4d() {
+        let c  = Cuboid::new(Vec4::new(1.0f64, 1.0, 1.0, 1.0));
+        let m1 = translation(0.0, 0.0, 0.0, 0.0);
+        let m2 = translation(1.9, 0.0, 0.0, 0.0);
+
+        let contacts = collide(&m1, &c as &Geom, &m2, &c as &Geom, 1);
+
+        assert!(contacts.len() > 0);
+
+        for contact in contacts.iter() {
+            assert!(na::approx_eq_eps(&contact.normal, &Vec4::new(1.0f64, 0.0, 0.0, 0.0), &1.0e-5));
+            assert!(na::approx_eq_eps(&contact.depth, &0.1, &1.0e-3));
+        }
+    }
+
+    #[test]
+    fn test_cuboid_cuboid_4d_manifold_updates() {
+        let c1 = Cuboid::new(Vec4::new(1.0f64, 0.5, 1.0, 2.0));
+        let c2 = Cuboid::new(Vec4::new(0.5f64, 0.5, 0.5, 0.5));
+        let m1 = translation(0.0, 0.0, 0.0, 0.0);
+
+        let dispatcher   = GeomGeomDispatcher::new();
+        let mut detector = dispatcher.dispatch(&c1 as &Geom, &c2 as &Geom)
+                                     .expect("No collision detector for this pair.");
+        let mut nprev    = 0u;
+
+        // The small box slides on the top face of the big one. Its bottom face is a cube with 8
+        // corners but the manifold holds at most 6 contacts. The box moves slowly enough for the
+        // contacts to remain valid: the manifold never shrinks.
+        for i in range(0u, 10) {
+            let shift = 0.005 * (i as f64);
+            let m2    = translation(0.3 - shift, 0.95, 0.2 - shift, shift);
+            let mut contacts = Vec::new();
+
+            assert!(detector.update(&dispatcher, &m1, &c1 as &Geom, &m2, &c2 as &Geom).is_ok());
+            detector.colls(&mut contacts);
+
+            assert!(contacts.len() >= 4 && contacts.len() <= 6);
+            assert!(contacts.len() >= nprev);
+
+            for contact in contacts.iter() {
+                assert!(na::approx_eq_eps(&contact.normal, &Vec4::new(0.0f64, 1.0, 0.0, 0.0), &1.0e-5));
+                assert!(na::approx_eq_eps(&contact.depth, &0.05, &1.0e-3));
+            }
+
+            nprev = contacts.len();
+        }
+    }
+
+    #[test]
+    fn test_implicits_cuboid_4d() {
+        let b      = Ball::new(0.5f64);
+        let cone   = Cone::new(0.5f64, 0.5);
+        let cyl    = Cylinder::new(0.5f64, 0.5);
+        let caps   = Capsule::new(0.5f64, 0.5, 0.0);
+        let c      = Cuboid::new(Vec4::new(1.0f64, 1.0, 1.0, 1.0));
+        let mc     = translation(0.0, 0.0, 0.0, 0.0);
+        let near   = translation(0.0, 0.0, 0.0, 1.4);
+        let far    = translation(0.0, 0.0, 0.0, 3.0);
+        let others = [ &b as &Geom, &cone as &Geom, &cyl as &Geom, &caps as &Geom ];
+
+        for other in others.iter() {
+            assert!(collide(&mc, &c as &Geom, &near, *other, 3).len() > 0);
+            assert!(collide(&near, *other, &mc, &c as &Geom, 3).len() > 0);
+            assert!(collide(&mc, &c as &Geom, &far, *other, 3).len() == 0);
+        }
+    }
+
+    #[test]
+    fn test_plane_cuboid_4d() {
+        let p  = Plane::new(Vec4::new(0.0f64, 0.0, 0.0, 1.0));
+        let c  = Cuboid::new(Vec4::new(1.0f64, 1.0, 1.0, 1.0));
+        let mp = translation(0.0, 0.0, 0.0, 0.0);
+        let mc = translation(0.5, -0.3, 0.2, 0.9);
+
+        let contacts = collide(&mp, &p as &Geom, &mc, &c as &Geom, 1);
+
+        // The box corners touching the plane are found at the first update.
+        assert!(contacts.len() > 1);
+
+        for contact in contacts.iter() {
+            assert!(na::approx_eq_eps(&contact.depth, &0.1, &1.0e-5));
+        }
+    }
+
+    #[test]
+    fn test_default_dispatcher_4d_pairs() {
+        let dispatcher = GeomGeomDispatcher::new();
+        let b          = Ball::new(0.5f64);
+        let c          = Cuboid::new(Vec4::new(1.0f64, 1.0, 1.0, 1.0));
+        let p          = Plane::new(Vec4::new(0.0f64, 0.0, 0.0, 1.0));
+
+        assert!(dispatcher.strategy(&c as &Geom, &c as &Geom) == Some(RegisteredStrategy));
+        assert!(dispatcher.strategy(&b as &Geom, &c as &Geom) == Some(RegisteredStrategy));
+        assert!(dispatcher.strategy(&p as &Geom, &c as &Geom) == Some(RegisteredStrategy));
+        assert!(dispatcher.strategy(&p as &Geom, &p as &Geom).is_none());
     }
 }
@@ -0,0 +1,443 @@
+//! Distance computation between two `Box<Geom>`.
+
+use std::mem;
+use std::intrinsics::TypeId;
+use std::any::Any;
+use collections::HashMap;
+use nalgebra::na::Translation;
+use geom::{AnnotatedPoint, Geom, ConcaveGeom, Cone, Cuboid, Ball, Capsule, Convex, Cylinder,
+           Compound, Mesh, Triangle, Tetrahedron, Segment, Plane};
+#[cfg(dim3)]
+use geom::TetMesh;
+use implicit::{Implicit, PreferedSamplingDirections};
+use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+use narrow::{ConcaveGeomGeomDistance, GeomConcaveGeomDistance, GeomTypeMismatch, downcast_geoms};
+use narrow::ball_ball;
+use narrow::plane_implicit;
+use narrow::implicit_implicit;
+use math::{Scalar, Vect, Matrix};
+
+/// Result of a distance query between two geometries.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct Distance {
+    /// Point of the first object closest to the second object. The position is expressed in world
+    /// space.
+    pub world1: Vect,
+
+    /// Point of the second object closest to the first object. The position is expressed in world
+    /// space.
+    pub world2: Vect,
+
+    /// Unit separating direction, pointing from the first object toward the second one.
+    pub normal: Vect,
+
+    /// Distance between the two objects. This is zero if they are intersecting.
+    pub dist:   Scalar
+}
+
+impl Distance {
+    /// Creates a new distance query result.
+    #[inline]
+    pub fn new(world1: Vect, world2: Vect, normal: Vect, dist: Scalar) -> Distance {
+        Distance {
+            world1: world1,
+            world2: world2,
+            normal: normal,
+            dist:   dist
+        }
+    }
+}
+
+impl Distance {
+    /// Reverts the separating direction and swaps `world1` and `world2`.
+    pub fn flip(&mut self) {
+        mem::swap(&mut self.world1, &mut self.world2);
+        self.normal = -self.normal;
+    }
+}
+
+/// Trait of distance algorithms using dynamic dispatch on the geometries.
+pub trait GeomGeomDistance : Send {
+    /// Computes the distance between two objects.
+    ///
+    /// If the objects do not have the types this algorithm was built for, a `GeomTypeMismatch`
+    /// error is returned.
+    ///
+    /// # Arguments
+    /// * `m1` - the first object transform.
+    /// * `g1` - the first object.
+    /// * `m2` - the second object transform.
+    /// * `g2` - the second object.
+    fn distance(&self,
+                dispatcher: &GeomGeomDistanceDispatcher,
+                m1:         &Matrix,
+                g1:         &Geom,
+                m2:         &Matrix,
+                g2:         &Geom)
+                -> Result<Option<Distance>, GeomTypeMismatch>;
+}
+
+/// Trait to be implemented by distance algorithms using dynamic dispatch.
+///
+/// This is used to know the exact type of the geometries.
+pub trait DynamicDistance<G1, G2>: GeomGeomDistance { }
+
+/// Distance dispatcher between two `Box<Geom>`.
+pub struct GeomGeomDistanceDispatcher {
+    distances: HashMap<(TypeId, TypeId), Box<GeomGeomDistance>>
+}
+
+impl GeomGeomDistanceDispatcher {
+    /// Creates a new `GeomGeomDistanceDispatcher` without the default set of distance algorithms.
+    pub fn new_without_default() -> GeomGeomDistanceDispatcher {
+        GeomGeomDistanceDispatcher {
+            distances: HashMap::new()
+        }
+    }
+
+    /// Registers a new distance algorithm for a pair of geometries.
+    ///
+    /// The algorithm must be suited for `G1` and `G2`, i.e., implement `DynamicDistance<G1, G2>`.
+    pub fn register_distance<G1: 'static + Any,
+                             G2: 'static + Any,
+                             D:  'static + DynamicDistance<G1, G2>>(
+                             &mut self,
+                             distance: D) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.distances.insert(key, box distance as Box<GeomGeomDistance>);
+    }
+
+    /// Unregister the distance algorithm for a given pair of geometries.
+    pub fn unregister_distance<G1: 'static + Any, G2: 'static + Any>(&mut self) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.distances.remove(&key);
+    }
+
+    /// Tests whether a distance algorithm is registered for the two given geometries.
+    pub fn is_supported(&self, a: &Geom, b: &Geom) -> bool {
+        self.distances.contains_key(&(a.get_type_id(), b.get_type_id()))
+    }
+
+    /// Computes the distance between two geometries.
+    ///
+    /// Returns `None` if no distance algorithm is registered for this pair of geometries.
+    ///
+    /// # Arguments
+    /// * `m1` - the first object transform.
+    /// * `g1` - the first object.
+    /// * `m2` - the second object transform.
+    /// * `g2` - the second object.
+    pub fn distance(&self, m1: &Matrix, g1: &Geom, m2: &Matrix, g2: &Geom) -> Option<Distance> {
+        match self.distances.find(&(g1.get_type_id(), g2.get_type_id())) {
+            Some(d) => match d.distance(self, m1, g1, m2, g2) {
+                Ok(distance) => distance,
+                Err(_)       => fail!("The distance algorithm registered for those geometries does not handle their types.")
+            },
+            None => None
+        }
+    }
+}
+
+impl GeomGeomDistanceDispatcher {
+    /// Creates a new `GeomGeomDistanceDispatcher` able to compute the distance between any pair
+    /// of geometries supported by the functions of `narrow::distance`.
+    pub fn new() -> GeomGeomDistanceDispatcher {
+        let mut res = GeomGeomDistanceDispatcher::new_without_default();
+
+        // Ball vs. Ball
+        res.register_distance::<Ball, Ball, BallBallDistance>(BallBallDistance);
+
+        // Plane vs. Implicit
+        res.register_default_plane_implicit_distance::<Ball>();
+        res.register_default_plane_implicit_distance::<Cuboid>();
+        res.register_default_plane_implicit_distance::<Cone>();
+        res.register_default_plane_implicit_distance::<Cylinder>();
+        res.register_default_plane_implicit_distance::<Capsule>();
+        res.register_default_plane_implicit_distance::<Convex>();
+        res.register_default_plane_implicit_distance::<Triangle>();
+        res.register_default_plane_implicit_distance::<Tetrahedron>();
+        res.register_default_plane_implicit_distance::<Segment>();
+
+        // Implicit vs. Implicit
+        // NOTE: some pair will be registered twice…
+        res.register_default_implicit_distances::<Cuboid>();
+        res.register_default_implicit_distances::<Cone>();
+        res.register_default_implicit_distances::<Cylinder>();
+        res.register_default_implicit_distances::<Capsule>();
+        res.register_default_implicit_distances::<Convex>();
+        res.register_default_implicit_distances::<Triangle>();
+        res.register_default_implicit_distances::<Tetrahedron>();
+        res.register_default_implicit_distances::<Segment>();
+
+        // Compound vs. Other
+        res.register_default_concave_geom_geom_distance::<Compound, Plane>();
+        res.register_default_concave_geom_geom_distance::<Compound, Ball>();
+        res.register_default_concave_geom_geom_distance::<Compound, Cuboid>();
+        res.register_default_concave_geom_geom_distance::<Compound, Cone>();
+        res.register_default_concave_geom_geom_distance::<Compound, Cylinder>();
+        res.register_default_concave_geom_geom_distance::<Compound, Capsule>();
+        res.register_default_concave_geom_geom_distance::<Compound, Convex>();
+        res.register_default_concave_geom_geom_distance::<Compound, Triangle>();
+        res.register_default_concave_geom_geom_distance::<Compound, Tetrahedron>();
+        res.register_default_concave_geom_geom_distance::<Compound, Segment>();
+
+        // TriangleMesh vs. Other
+        res.register_default_concave_geom_geom_distance::<Mesh, Plane>();
+        res.register_default_concave_geom_geom_distance::<Mesh, Ball>();
+        res.register_default_concave_geom_geom_distance::<Mesh, Cuboid>();
+        res.register_default_concave_geom_geom_distance::<Mesh, Cone>();
+        res.register_default_concave_geom_geom_distance::<Mesh, Cylinder>();
+        res.register_default_concave_geom_geom_distance::<Mesh, Capsule>();
+        res.register_default_concave_geom_geom_distance::<Mesh, Convex>();
+        res.register_default_concave_geom_geom_distance::<Mesh, Triangle>();
+        res.register_default_concave_geom_geom_distance::<Mesh, Tetrahedron>();
+        res.register_default_concave_geom_geom_distance::<Mesh, Segment>();
+
+        // Concave vs. Concave
+        // The parts of the second geometry are handled by the `GeomConcaveGeomDistance`
+        // registered above.
+        res.register_default_concave_geom_concave_geom_distance::<Compound, Compound>();
+        res.register_default_concave_geom_concave_geom_distance::<Mesh, Compound>();
+        res.register_default_concave_geom_concave_geom_distance::<Mesh, Mesh>();
+
+        // TetMesh vs. Other
+        res.register_tet_mesh_distances();
+
+        res
+    }
+
+    /// Registers the distance algorithm between a given implicit geometry and a plane.
+    pub fn register_default_plane_implicit_distance<I: 'static + Implicit<Vect, Matrix>>(&mut self) {
+        self.register_distance::<Plane, I, PlaneImplicitDistance<I>>(PlaneImplicitDistance);
+        self.register_distance::<I, Plane, ImplicitPlaneDistance<I>>(ImplicitPlaneDistance);
+    }
+
+    /// Registers the distance algorithm between two implicit geometries.
+    pub fn register_default_implicit_implicit_distance<G1: 'static                 +
+                                                           Implicit<Vect, Matrix> +
+                                                           PreferedSamplingDirections<Vect, Matrix>,
+                                                       G2: 'static                 +
+                                                           Implicit<Vect, Matrix> +
+                                                           PreferedSamplingDirections<Vect, Matrix>>(
+                                                       &mut self) {
+        self.register_distance::<G1, G2, ImplicitImplicitDistance<G1, G2>>(ImplicitImplicitDistance);
+        self.register_distance::<G2, G1, ImplicitImplicitDistance<G2, G1>>(ImplicitImplicitDistance);
+    }
+
+    /// Registers the distance algorithm between a given geometry and every implicit geometry
+    /// supported by `ncollide`.
+    pub fn register_default_implicit_distances<G: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                                               &mut self) {
+        self.register_default_implicit_implicit_distance::<Ball, G>();
+        self.register_default_implicit_implicit_distance::<Cuboid, G>();
+        self.register_default_implicit_implicit_distance::<Cone, G>();
+        self.register_default_implicit_implicit_distance::<Cylinder, G>();
+        self.register_default_implicit_implicit_distance::<Capsule, G>();
+        self.register_default_implicit_implicit_distance::<Convex, G>();
+        self.register_default_implicit_implicit_distance::<Triangle, G>();
+        self.register_default_implicit_implicit_distance::<Tetrahedron, G>();
+        self.register_default_implicit_implicit_distance::<Segment, G>();
+    }
+
+    /// Registers the distance algorithm between a given concave geometry and a given geometry.
+    pub fn register_default_concave_geom_geom_distance<G1: 'static + ConcaveGeom,
+                                                       G2: 'static + Geom>(&mut self) {
+        let d1 = ConcaveGeomGeomDistance::<G1, G2>;
+        let d2 = GeomConcaveGeomDistance::<G2, G1>;
+
+        self.register_distance::<G1, G2, ConcaveGeomGeomDistance<G1, G2>>(d1);
+        self.register_distance::<G2, G1, GeomConcaveGeomDistance<G2, G1>>(d2);
+    }
+
+    /// Registers the distance algorithm between two given concave geometries.
+    pub fn register_default_concave_geom_concave_geom_distance<G1: 'static + ConcaveGeom,
+                                                               G2: 'static + ConcaveGeom>(
+                                                               &mut self) {
+        let d1 = ConcaveGeomGeomDistance::<G1, G2>;
+        let d2 = ConcaveGeomGeomDistance::<G2, G1>;
+
+        self.register_distance::<G1, G2, ConcaveGeomGeomDistance<G1, G2>>(d1);
+        self.register_distance::<G2, G1, ConcaveGeomGeomDistance<G2, G1>>(d2);
+    }
+
+    #[cfg(dim3)]
+    fn register_tet_mesh_distances(&mut self) {
+        self.register_default_concave_geom_geom_distance::<TetMesh, Plane>();
+        self.register_default_concave_geom_geom_distance::<TetMesh, Ball>();
+        self.register_default_concave_geom_geom_distance::<TetMesh, Cuboid>();
+        self.register_default_concave_geom_geom_distance::<TetMesh, Cone>();
+        self.register_default_concave_geom_geom_distance::<TetMesh, Cylinder>();
+        self.register_default_concave_geom_geom_distance::<TetMesh, Capsule>();
+        self.register_default_concave_geom_geom_distance::<TetMesh, Convex>();
+        self.register_default_concave_geom_geom_distance::<TetMesh, Triangle>();
+        self.register_default_concave_geom_geom_distance::<TetMesh, Tetrahedron>();
+        self.register_default_concave_geom_geom_distance::<TetMesh, Segment>();
+
+        self.register_default_concave_geom_concave_geom_distance::<TetMesh, Compound>();
+        self.register_default_concave_geom_concave_geom_distance::<TetMesh, Mesh>();
+        self.register_default_concave_geom_concave_geom_distance::<TetMesh, TetMesh>();
+    }
+
+    // There is no tetrahedral mesh outside of 3D: this does nothing.
+    #[cfg(not(dim3))]
+    fn register_tet_mesh_distances(&mut self) {
+    }
+}
+
+/*
+ *
+ * Distance algorithms for the non-concave geometries.
+ *
+ */
+/// Structure implementing `GeomGeomDistance` using `distance::ball_ball`.
+pub struct BallBallDistance;
+
+impl GeomGeomDistance for BallBallDistance {
+    #[inline]
+    fn distance(&self,
+                _:  &GeomGeomDistanceDispatcher,
+                m1: &Matrix,
+                g1: &Geom,
+                m2: &Matrix,
+                g2: &Geom)
+                -> Result<Option<Distance>, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<Ball, Ball>(g1, g2));
+
+        Ok(Some(ball_ball::distance(&m1.translation(), g1, &m2.translation(), g2)))
+    }
+}
+
+impl DynamicDistance<Ball, Ball> for BallBallDistance { }
+
+/// Structure implementing `GeomGeomDistance` using `distance::plane_implicit`.
+pub struct PlaneImplicitDistance<G>;
+
+impl<G: 'static + Implicit<Vect, Matrix>> GeomGeomDistance for PlaneImplicitDistance<G> {
+    #[inline]
+    fn distance(&self,
+                _:  &GeomGeomDistanceDispatcher,
+                m1: &Matrix,
+                g1: &Geom,
+                m2: &Matrix,
+                g2: &Geom)
+                -> Result<Option<Distance>, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<Plane, G>(g1, g2));
+
+        Ok(Some(plane_implicit::distance(m1, g1, m2, g2)))
+    }
+}
+
+impl<G: 'static + Implicit<Vect, Matrix>> DynamicDistance<Plane, G> for PlaneImplicitDistance<G> { }
+
+/// Structure implementing `GeomGeomDistance` using `distance::plane_implicit` with its arguments
+/// swapped.
+pub struct ImplicitPlaneDistance<G>;
+
+impl<G: 'static + Implicit<Vect, Matrix>> GeomGeomDistance for ImplicitPlaneDistance<G> {
+    #[inline]
+    fn distance(&self,
+                _:  &GeomGeomDistanceDispatcher,
+                m1: &Matrix,
+                g1: &Geom,
+                m2: &Matrix,
+                g2: &Geom)
+                -> Result<Option<Distance>, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G, Plane>(g1, g2));
+        let mut res  = plane_implicit::distance(m2, g2, m1, g1);
+
+        res.flip();
+
+        Ok(Some(res))
+    }
+}
+
+impl<G: 'static + Implicit<Vect, Matrix>> DynamicDistance<G, Plane> for ImplicitPlaneDistance<G> { }
+
+/// Structure implementing `GeomGeomDistance` using `distance::implicit_implicit`.
+pub struct ImplicitImplicitDistance<G1, G2>;
+
+impl<G1: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+     G2: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>
+GeomGeomDistance for ImplicitImplicitDistance<G1, G2> {
+    #[inline]
+    fn distance(&self,
+                _:  &GeomGeomDistanceDispatcher,
+                m1: &Matrix,
+                g1: &Geom,
+                m2: &Matrix,
+                g2: &Geom)
+                -> Result<Option<Distance>, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+        let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+
+        Ok(implicit_implicit::distance(m1, g1, m2, g2, &mut simplex))
+    }
+}
+
+impl<G1: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+     G2: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>
+DynamicDistance<G1, G2> for ImplicitImplicitDistance<G1, G2> { }
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Geom, Ball, Cuboid, Plane};
+    use narrow::GeomGeomDistanceDispatcher;
+    use test_utils::{translation, two_balls};
+
+    fn dist(g1: &Geom, m1: &Iso3<f64>, g2: &Geom, m2: &Iso3<f64>) -> f64 {
+        let dispatcher = GeomGeomDistanceDispatcher::new();
+
+        match dispatcher.distance(m1, g1, m2, g2) {
+            Some(d) => d.dist,
+            None    => fail!("The distance should be computable.")
+        }
+    }
+
+    #[test]
+    fn test_distance_ball_ball() {
+        let b = Ball::new(1.0f64);
+        let d = dist(&b as &Geom, &translation(0.0, 0.0, 0.0), &b as &Geom, &translation(0.0, 5.0, 0.0));
+
+        assert!(na::approx_eq_eps(&d, &3.0, &1.0e-5));
+    }
+
+    #[test]
+    fn test_distance_cuboid_ball() {
+        let c = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let b = Ball::new(0.5f64);
+        let d = dist(&c as &Geom, &translation(0.0, 0.0, 0.0), &b as &Geom, &translation(4.0, 0.0, 0.0));
+
+        assert!(na::approx_eq_eps(&d, &2.5, &1.0e-3));
+    }
+
+    #[test]
+    fn test_distance_plane_ball() {
+        let p  = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let b  = Ball::new(0.5f64);
+        let d1 = dist(&p as &Geom, &translation(0.0, 0.0, 0.0), &b as &Geom, &translation(1.0, 2.0, 0.0));
+        let d2 = dist(&b as &Geom, &translation(1.0, 2.0, 0.0), &p as &Geom, &translation(0.0, 0.0, 0.0));
+
+        assert!(na::approx_eq_eps(&d1, &1.5, &1.0e-5));
+        assert!(na::approx_eq_eps(&d2, &1.5, &1.0e-5));
+    }
+
+    #[test]
+    fn test_distance_compound_ball() {
+        let c = two_balls();
+        let b = Ball::new(1.0f64);
+        let d = dist(&c as &Geom, &translation(0.0, 0.0, 0.0), &b as &Geom, &translation(2.0, 4.0, 0.0));
+
+        assert!(na::approx_eq_eps(&d, &2.0, &1.0e-5));
+    }
+
+    #[test]
+    fn test_distance_intersecting() {
+        let c = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let b = Ball::new(1.0f64);
+        let d = dist(&c as &Geom, &translation(0.0, 0.0, 0.0), &b as &Geom, &translation(1.5, 0.0, 0.0));
+
+        assert!(d == 0.0);
+    }
+}
@@ -0,0 +1,512 @@
+//! Generic collision detectors used for pairs of geometries without registered collision detector.
+
+use geom::{AnnotatedPoint, Geom, GeomCapabilities, Plane, ImplicitCapability, PlaneCapability,
+           ConcaveCapability};
+use implicit::ImplicitGeom;
+use contact::Contact;
+use narrow::algorithm::simplex::Simplex;
+use narrow::algorithm::gjk::{GJKResult, NoIntersection, Intersection, Projection};
+use narrow::{GeomGeomDispatcher, GeomGeomCollisionDetector, GeomTypeMismatch, PenetrationFallback,
+             TOIResult, Impact, NoImpact, UnsupportedGeomPair};
+use narrow::implicit_implicit;
+use narrow::plane_implicit;
+use math::{Scalar, Vect, Matrix};
+
+/// Strategy used by the `GeomGeomDispatcher` to build a collision detector for a pair of
+/// geometries.
+#[deriving(Show, PartialEq, Clone)]
+pub enum DispatchStrategy {
+    /// A collision detector has been registered for this pair of geometry types.
+    RegisteredStrategy,
+    /// Both geometries have a support mapping: a GJK-based detector is used.
+    ImplicitImplicitStrategy,
+    /// The first geometry is a plane and the second one has a support mapping.
+    PlaneImplicitStrategy,
+    /// The first geometry has a support mapping and the second one is a plane.
+    ImplicitPlaneStrategy,
+    /// The first geometry is concave: each of its parts is dispatched with the second geometry.
+    ConcaveGeomStrategy,
+    /// The second geometry is concave: each of its parts is dispatched with the first geometry.
+    GeomConcaveStrategy
+}
+
+/// Computes the strategy used to build a collision detector for two geometries, ignoring the
+/// registered collision detectors.
+///
+/// Returns `None` if their capabilities do not allow any generic collision detector.
+pub fn fallback_strategy(a: &Geom, b: &Geom) -> Option<DispatchStrategy> {
+    match (a.capability(), b.capability()) {
+        (ConcaveCapability(_), _)                      => Some(ConcaveGeomStrategy),
+        (_, ConcaveCapability(_))                      => Some(GeomConcaveStrategy),
+        (PlaneCapability(_), ImplicitCapability(_))    => Some(PlaneImplicitStrategy),
+        (ImplicitCapability(_), PlaneCapability(_))    => Some(ImplicitPlaneStrategy),
+        (ImplicitCapability(_), ImplicitCapability(_)) => Some(ImplicitImplicitStrategy),
+        _                                              => None
+    }
+}
+
+/// Trait of structures able to build a generic collision detector for a pair of geometries.
+pub trait FallbackDetectorFactory : Send {
+    /// Builds a new collision detector for `a` and `b`, using the given strategy.
+    ///
+    /// Returns `None` if `strategy` is not supported by this factory.
+    fn build(&self, strategy: &DispatchStrategy, a: &Geom, b: &Geom)
+             -> Option<Box<GeomGeomCollisionDetector>>;
+}
+
+/// The default generic collision detectors factory.
+///
+/// The detectors it builds generate only one contact point.
+#[deriving(Clone)]
+pub struct FallbackDetectors<S> {
+    prediction: Scalar,
+    simplex:    S,
+    fallback:   PenetrationFallback
+}
+
+impl<S> FallbackDetectors<S> {
+    /// Creates a new factory of generic collision detectors.
+    ///
+    /// # Arguments:
+    /// * `prediction` - the prediction distance of the built detectors.
+    /// * `simplex`    - the simplex used by the GJK-based detectors.
+    /// * `fallback`   - the algorithm used by the GJK-based detectors to handle penetrations.
+    pub fn new(prediction: Scalar, simplex: S, fallback: PenetrationFallback) -> FallbackDetectors<S> {
+        FallbackDetectors {
+            prediction: prediction,
+            simplex:    simplex,
+            fallback:   fallback
+        }
+    }
+}
+
+impl<S: 'static + Send + Clone + Simplex<AnnotatedPoint>>
+FallbackDetectorFactory for FallbackDetectors<S> {
+    fn build(&self, strategy: &DispatchStrategy, a: &Geom, b: &Geom)
+             -> Option<Box<GeomGeomCollisionDetector>> {
+        match *strategy {
+            RegisteredStrategy       => None,
+            ImplicitImplicitStrategy => {
+                let d = ImplicitGeomImplicitGeom::new(self.prediction.clone(),
+                                                      self.simplex.clone(),
+                                                      self.fallback.clone());
+                Some(box d as Box<GeomGeomCollisionDetector>)
+            },
+            PlaneImplicitStrategy => {
+                let d = PlaneImplicitGeom::new(self.prediction.clone(), false);
+                Some(box d as Box<GeomGeomCollisionDetector>)
+            },
+            ImplicitPlaneStrategy => {
+                let d = PlaneImplicitGeom::new(self.prediction.clone(), true);
+                Some(box d as Box<GeomGeomCollisionDetector>)
+            },
+            ConcaveGeomStrategy => match a.capability() {
+                ConcaveCapability(f) => Some(f(false)),
+                _                    => None
+            },
+            GeomConcaveStrategy => match b.capability() {
+                ConcaveCapability(f) => Some(f(true)),
+                _                    => None
+            }
+        }
+    }
+}
+
+fn implicit_capability<'a>(g: &'a Geom) -> Result<&'a ImplicitGeom, GeomTypeMismatch> {
+    match g.capability() {
+        ImplicitCapability(i) => Ok(i),
+        _                     => Err(GeomTypeMismatch)
+    }
+}
+
+fn plane_capability<'a>(g: &'a Geom) -> Result<&'a Plane, GeomTypeMismatch> {
+    match g.capability() {
+        PlaneCapability(p) => Ok(p),
+        _                  => Err(GeomTypeMismatch)
+    }
+}
+
+/// Collision detector between two geometries having the implicit capability, whatever their
+/// types.
+///
+/// It is based on the GJK algorithm. This detector generates only one contact point.
+#[deriving(Clone)]
+pub struct ImplicitGeomImplicitGeom<S> {
+    simplex:    S,
+    prediction: Scalar,
+    fallback:   PenetrationFallback,
+    contact:    GJKResult<Contact, Vect>
+}
+
+impl<S> ImplicitGeomImplicitGeom<S> {
+    /// Creates a new persistent collision detector between two geometries having the implicit
+    /// capability.
+    pub fn new(prediction: Scalar, simplex: S, fallback: PenetrationFallback)
+               -> ImplicitGeomImplicitGeom<S> {
+        ImplicitGeomImplicitGeom {
+            simplex:    simplex,
+            prediction: prediction,
+            fallback:   fallback,
+            contact:    Intersection
+        }
+    }
+}
+
+impl<S: Simplex<AnnotatedPoint>> GeomGeomCollisionDetector for ImplicitGeomImplicitGeom<S> {
+    fn update(&mut self,
+              _:  &GeomGeomDispatcher,
+              m1: &Matrix,
+              g1: &Geom,
+              m2: &Matrix,
+              g2: &Geom)
+              -> Result<(), GeomTypeMismatch> {
+        let i1 = try!(implicit_capability(g1));
+        let i2 = try!(implicit_capability(g2));
+
+        let initial_direction = match self.contact {
+            NoIntersection(ref separator) => Some(separator.clone()),
+            Projection(ref contact)       => Some(contact.normal.clone()),
+            Intersection                  => None
+        };
+
+        self.contact = implicit_implicit::collide_with_fallback(
+            m1,
+            &i1,
+            m2,
+            &i2,
+            &self.prediction,
+            &mut self.simplex,
+            initial_direction,
+            &self.fallback);
+
+        Ok(())
+    }
+
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            Projection(_) => 1,
+            _             => 0
+        }
+    }
+
+    fn colls(&self, out: &mut Vec<Contact>) {
+        match self.contact {
+            Projection(ref c) => out.push(c.clone()),
+            _                 => ()
+        }
+    }
+
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+}
+
+/// Collision detector between a geometry having the plane capability and a geometry having the
+/// implicit capability, whatever their types.
+///
+/// This detector generates only one contact point.
+#[deriving(Clone)]
+pub struct PlaneImplicitGeom {
+    prediction: Scalar,
+    swap:       bool,
+    contact:    Option<Contact>
+}
+
+impl PlaneImplicitGeom {
+    /// Creates a new persistent collision detector between a plane and a geometry having the
+    /// implicit capability.
+    ///
+    /// If `swap` is `true`, the plane is the second geometry of the pair.
+    pub fn new(prediction: Scalar, swap: bool) -> PlaneImplicitGeom {
+        PlaneImplicitGeom {
+            prediction: prediction,
+            swap:       swap,
+            contact:    None
+        }
+    }
+}
+
+impl GeomGeomCollisionDetector for PlaneImplicitGeom {
+    fn update(&mut self,
+              _:  &GeomGeomDispatcher,
+              m1: &Matrix,
+              g1: &Geom,
+              m2: &Matrix,
+              g2: &Geom)
+              -> Result<(), GeomTypeMismatch> {
+        if self.swap {
+            let plane = try!(plane_capability(g2));
+            let other = try!(implicit_capability(g1));
+
+            self.contact = plane_implicit::collide(m2, plane, m1, &other, &self.prediction);
+            self.contact.mutate(|mut c| { c.flip(); c });
+        }
+        else {
+            let plane = try!(plane_capability(g1));
+            let other = try!(implicit_capability(g2));
+
+            self.contact = plane_implicit::collide(m1, plane, m2, &other, &self.prediction);
+        }
+
+        Ok(())
+    }
+
+    fn num_colls(&self) -> uint {
+        match self.contact {
+            None    => 0,
+            Some(_) => 1
+        }
+    }
+
+    fn colls(&self, out: &mut Vec<Contact>) {
+        match self.contact {
+            Some(ref c) => out.push(c.clone()),
+            None        => ()
+        }
+    }
+
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+}
+
+/// Computes the time of impact of two geometries using their capabilities, whatever their types.
+///
+/// This is used by the `GeomGeomTOIDispatcher` for pairs of geometries without registered time of
+/// impact algorithm. Returns `UnsupportedGeomPair` if their capabilities do not allow any generic
+/// time of impact algorithm.
+///
+/// # Arguments
+/// * `m1`  - the first object transform.
+/// * `dir` - the first object displacement direction.
+/// * `g1`  - the first object.
+/// * `m2`  - the second object transform.
+/// * `g2`  - the second object.
+pub fn fallback_toi(m1: &Matrix, dir: &Vect, g1: &Geom, m2: &Matrix, g2: &Geom) -> TOIResult {
+    let toi = match (g1.capability(), g2.capability()) {
+        (PlaneCapability(plane), ImplicitCapability(other)) =>
+            plane_implicit::toi(m1, plane, m2, &-dir, &other),
+        (ImplicitCapability(other), PlaneCapability(plane)) =>
+            plane_implicit::toi(m2, plane, m1, dir, &other),
+        (ImplicitCapability(i1), ImplicitCapability(i2)) =>
+            implicit_implicit::toi(m1, dir, &i1, m2, &i2),
+        _ => return UnsupportedGeomPair
+    };
+
+    match toi {
+        Some(t) => Impact(t),
+        None    => NoImpact
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use std::any::Any;
+    use nalgebra::na::{Vec3, Mat3, Iso3, Rotate, Transform, Translation, Indexable};
+    use nalgebra::na;
+    use geom::{Geom, GeomCapabilities, GeomCapability, ImplicitCapability, Ball, Plane,
+               BezierSurface};
+    use implicit::{Implicit, HasMargin, PreferedSamplingDirections};
+    use bounding_volume::{HasAABB, AABB, HasBoundingSphere, BoundingSphere};
+    use bounding_volume;
+    use volumetric::Volumetric;
+    use ray::{Ray, RayCast, RayIntersection};
+    use narrow::{GeomGeomDispatcher, GeomGeomTOIDispatcher, RegisteredStrategy,
+                 ImplicitImplicitStrategy, PlaneImplicitStrategy, ImplicitPlaneStrategy, Impact,
+                 NoImpact};
+    use super::fallback_strategy;
+
+    // An octahedron with its vertices on the axes: an implicit geometry unknown to the dispatcher.
+    #[deriving(Clone)]
+    struct Octahedron {
+        radius: f64
+    }
+
+    impl HasMargin for Octahedron {
+        fn margin(&self) -> f64 {
+            0.0
+        }
+    }
+
+    impl Implicit<Vec3<f64>, Iso3<f64>> for Octahedron {
+        fn support_point_without_margin(&self, m: &Iso3<f64>, dir: &Vec3<f64>) -> Vec3<f64> {
+            let local_dir = m.inv_rotate(dir);
+            let mut best  = 0u;
+
+            for i in range(1u, 3) {
+                if local_dir.at(i).abs() > local_dir.at(best).abs() {
+                    best = i
+                }
+            }
+
+            let mut pt: Vec3<f64> = na::zero();
+
+            pt.set(best, if local_dir.at(best) < 0.0 { -self.radius } else { self.radius });
+
+            m.transform(&pt)
+        }
+    }
+
+    impl PreferedSamplingDirections<Vec3<f64>, Iso3<f64>> for Octahedron {
+        fn sample(&self, _: &Iso3<f64>, _: |Vec3<f64>| -> ()) {
+        }
+    }
+
+    impl HasAABB for Octahedron {
+        fn aabb(&self, m: &Iso3<f64>) -> AABB {
+            bounding_volume::implicit_shape_aabb(m, self)
+        }
+    }
+
+    impl HasBoundingSphere for Octahedron {
+        fn bounding_sphere(&self, m: &Iso3<f64>) -> BoundingSphere {
+            BoundingSphere::new(m.translation(), self.radius)
+        }
+    }
+
+    impl Volumetric for Octahedron {
+        fn mass_properties(&self, _: &f64) -> (f64, Vec3<f64>, Mat3<f64>) {
+            (na::zero(), na::zero(), na::zero())
+        }
+    }
+
+    impl RayCast for Octahedron {
+        fn toi_and_normal_with_ray(&self, _: &Ray, _: bool) -> Option<RayIntersection> {
+            None
+        }
+    }
+
+    impl GeomCapabilities for Octahedron {
+        fn capability<'a>(&'a self) -> GeomCapability<'a> {
+            ImplicitCapability(self)
+        }
+    }
+
+    #[test]
+    fn test_fallback_strategy() {
+        let o = Octahedron { radius: 1.0 };
+        let b = Ball::new(0.5f64);
+        let p = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let s = BezierSurface::new(vec!(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0),
+                                        Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 1.0)), 2, 2);
+
+        assert!(fallback_strategy(&o as &Geom, &b as &Geom) == Some(ImplicitImplicitStrategy));
+        assert!(fallback_strategy(&p as &Geom, &o as &Geom) == Some(PlaneImplicitStrategy));
+        assert!(fallback_strategy(&o as &Geom, &p as &Geom) == Some(ImplicitPlaneStrategy));
+        // Bezier surfaces keep the default capability.
+        assert!(fallback_strategy(&o as &Geom, &s as &Geom).is_none());
+        assert!(fallback_strategy(&p as &Geom, &p as &Geom).is_none());
+    }
+
+    #[test]
+    fn test_handled_pairs() {
+        let dispatcher = GeomGeomDispatcher::new();
+        let o          = Octahedron { radius: 1.0 };
+        let b          = Ball::new(0.5f64);
+        let p          = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let pairs      = dispatcher.handled_pairs(&[ &o as &Geom, &b as &Geom, &p as &Geom ]);
+
+        let strategy = |g1: &Geom, g2: &Geom| {
+            let (t1, t2) = (g1.get_type_id(), g2.get_type_id());
+
+            pairs.iter().find(|&&(ref a1, ref a2, _)| *a1 == t1 && *a2 == t2).map(|&(_, _, ref s)| s.clone())
+        };
+
+        assert!(strategy(&o as &Geom, &o as &Geom) == Some(ImplicitImplicitStrategy));
+        assert!(strategy(&o as &Geom, &b as &Geom) == Some(ImplicitImplicitStrategy));
+        assert!(strategy(&b as &Geom, &o as &Geom) == Some(ImplicitImplicitStrategy));
+        assert!(strategy(&p as &Geom, &o as &Geom) == Some(PlaneImplicitStrategy));
+        assert!(strategy(&o as &Geom, &p as &Geom) == Some(ImplicitPlaneStrategy));
+        assert!(strategy(&p as &Geom, &b as &Geom) == Some(RegisteredStrategy));
+        assert!(strategy(&p as &Geom, &p as &Geom).is_none());
+    }
+
+    #[test]
+    fn test_implicit_geom_implicit_geom() {
+        let dispatcher = GeomGeomDispatcher::new();
+        let o          = Octahedron { radius: 1.0 };
+        let b          = Ball::new(0.5f64);
+        let mo         = na::one::<Iso3<f64>>();
+        let mb         = Iso3::new(Vec3::new(1.4f64, 0.0, 0.0), na::zero());
+        let mut colls  = Vec::new();
+
+        // The ball penetrates the octahedron vertex `(1, 0, 0)` by 0.1.
+        let mut detector = dispatcher.dispatch(&o as &Geom, &b as &Geom).unwrap();
+
+        assert!(detector.update(&dispatcher, &mo, &o as &Geom, &mb, &b as &Geom).is_ok());
+        detector.colls(&mut colls);
+
+        assert!(colls.len() == 1);
+        assert!(na::approx_eq_eps(&colls.get(0).normal, &Vec3::new(1.0, 0.0, 0.0), &1.0e-7));
+        assert!(na::approx_eq_eps(&colls.get(0).depth, &0.1, &1.0e-7));
+    }
+
+    #[test]
+    fn test_plane_implicit_geom() {
+        let dispatcher = GeomGeomDispatcher::new();
+        let o          = Octahedron { radius: 1.0 };
+        let p          = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let mp         = na::one::<Iso3<f64>>();
+        let mo         = Iso3::new(Vec3::new(0.3f64, 0.8, -0.2), na::zero());
+
+        // The bottom vertex of the octahedron is 0.2 below the plane.
+        for swap in [ false, true ].iter() {
+            let mut colls = Vec::new();
+
+            if *swap {
+                let mut detector = dispatcher.dispatch(&o as &Geom, &p as &Geom).unwrap();
+
+                assert!(detector.update(&dispatcher, &mo, &o as &Geom, &mp, &p as &Geom).is_ok());
+                detector.colls(&mut colls);
+            }
+            else {
+                let mut detector = dispatcher.dispatch(&p as &Geom, &o as &Geom).unwrap();
+
+                assert!(detector.update(&dispatcher, &mp, &p as &Geom, &mo, &o as &Geom).is_ok());
+                detector.colls(&mut colls);
+            }
+
+            assert!(colls.len() == 1);
+            assert!(na::approx_eq_eps(&colls.get(0).depth, &0.2, &1.0e-7));
+
+            let expected = if *swap { Vec3::new(0.0, -1.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+            assert!(na::approx_eq_eps(&colls.get(0).normal, &expected, &1.0e-7));
+        }
+    }
+
+    #[test]
+    fn test_fallback_toi() {
+        let dispatcher = GeomGeomTOIDispatcher::new();
+        let o          = Octahedron { radius: 1.0 };
+        let b          = Ball::new(0.5f64);
+        let p          = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let mo         = Iso3::new(Vec3::new(0.0f64, 5.0, 0.0), na::zero());
+        let mb         = Iso3::new(Vec3::new(5.0f64, 5.0, 0.0), na::zero());
+        let mp         = na::one::<Iso3<f64>>();
+        let right      = Vec3::new(1.0f64, 0.0, 0.0);
+        let down       = Vec3::new(0.0f64, -1.0, 0.0);
+
+        assert!(dispatcher.is_supported(&o as &Geom, &b as &Geom));
+        assert!(dispatcher.is_supported(&o as &Geom, &p as &Geom));
+        assert!(dispatcher.is_supported(&p as &Geom, &o as &Geom));
+
+        // The vertex `(1, 0, 0)` of the octahedron hits the ball.
+        match dispatcher.toi(&mo, &right, &10.0, &o as &Geom, &mb, &b as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &3.5, &1.0e-5)),
+            _           => fail!("The octahedron should touch the ball.")
+        }
+
+        // The bottom vertex of the octahedron hits the plane.
+        match dispatcher.toi(&mo, &down, &10.0, &o as &Geom, &mp, &p as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &4.0, &1.0e-5)),
+            _           => fail!("The octahedron should touch the plane.")
+        }
+
+        match dispatcher.toi(&mp, &-down, &10.0, &p as &Geom, &mo, &o as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &4.0, &1.0e-5)),
+            _           => fail!("The plane should touch the octahedron.")
+        }
+
+        assert!(dispatcher.toi(&mo, &-down, &10.0, &o as &Geom, &mp, &p as &Geom) == NoImpact);
+    }
+}
@@ -0,0 +1,560 @@
+//! Proximity queries between two `Box<Geom>`.
+
+use std::num::Bounded;
+use std::intrinsics::TypeId;
+use std::any::Any;
+use sync::{Arc, RWLock};
+use collections::HashMap;
+use nalgebra::na::Translation;
+use nalgebra::na;
+use geom::{AnnotatedPoint, Geom, ConcaveGeom, Cone, Cuboid, Ball, Capsule, Convex, Cylinder,
+           Compound, Mesh, Triangle, Tetrahedron, Segment, Plane};
+#[cfg(dim3)]
+use geom::TetMesh;
+use implicit::Implicit;
+use contact::Contact;
+use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+use narrow::{CollisionDetector, Proximity, Intersecting, WithinMargin, Disjoint,
+             BallBezierSurface, BezierSurfaceBall, ImplicitBezierSurface, BezierSurfaceImplicit,
+             BezierSurfaceBezierSurface, BallBezierCurve, BezierCurveBall, ImplicitBezierCurve,
+             BezierCurveImplicit, ConcaveGeomGeomProximity, GeomConcaveGeomProximity,
+             GeomTypeMismatch, downcast_geoms};
+use narrow::surface_selector::{TangentConesSurfaceSelector, TangentConesSurfaceSelectorTestData};
+use narrow::surface_subdivision_tree::SurfaceSubdivisionTreeCache;
+use narrow::ball_ball;
+use narrow::plane_implicit;
+use narrow::implicit_implicit;
+use math::{Scalar, Vect, Matrix};
+
+/// Trait of proximity algorithms using dynamic dispatch on the geometries.
+pub trait GeomGeomProximity : Send {
+    /// Tests whether two objects are intersecting or closer than `margin`.
+    ///
+    /// If the objects do not have the types this algorithm was built for, a `GeomTypeMismatch`
+    /// error is returned.
+    ///
+    /// # Arguments
+    /// * `m1`     - the first object transform.
+    /// * `g1`     - the first object.
+    /// * `m2`     - the second object transform.
+    /// * `g2`     - the second object.
+    /// * `margin` - the distance bellow which the objects are considered close to each other.
+    fn proximity(&self,
+                 dispatcher: &GeomGeomProximityDispatcher,
+                 m1:         &Matrix,
+                 g1:         &Geom,
+                 m2:         &Matrix,
+                 g2:         &Geom,
+                 margin:     &Scalar)
+                 -> Result<Proximity, GeomTypeMismatch>;
+}
+
+/// Trait to be implemented by proximity algorithms using dynamic dispatch.
+///
+/// This is used to know the exact type of the geometries.
+pub trait DynamicProximity<G1, G2>: GeomGeomProximity { }
+
+/// Proximity dispatcher between two `Box<Geom>`.
+pub struct GeomGeomProximityDispatcher {
+    proximities: HashMap<(TypeId, TypeId), Box<GeomGeomProximity>>
+}
+
+impl GeomGeomProximityDispatcher {
+    /// Creates a new `GeomGeomProximityDispatcher` without the default set of proximity
+    /// algorithms.
+    pub fn new_without_default() -> GeomGeomProximityDispatcher {
+        GeomGeomProximityDispatcher {
+            proximities: HashMap::new()
+        }
+    }
+
+    /// Registers a new proximity algorithm for a pair of geometries.
+    ///
+    /// The algorithm must be suited for `G1` and `G2`, i.e., implement `DynamicProximity<G1, G2>`.
+    pub fn register_proximity<G1: 'static + Any,
+                              G2: 'static + Any,
+                              P:  'static + DynamicProximity<G1, G2>>(
+                              &mut self,
+                              proximity: P) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.proximities.insert(key, box proximity as Box<GeomGeomProximity>);
+    }
+
+    /// Registers a collision detector as the proximity algorithm of two geometries.
+    ///
+    /// The collision detector is cloned and updated at each query, with its prediction margin set
+    /// to the query margin. Thus, this should be used only for pairs of geometries without
+    /// dedicated proximity algorithm.
+    pub fn register_detector_proximity<G1: 'static + Any,
+                                       G2: 'static + Any,
+                                       D:  'static + Send + Clone + CollisionDetector<G1, G2>>(
+                                       &mut self,
+                                       d:   D) {
+        let proximity = CollisionDetectorProximity::new(d);
+        self.register_proximity::<G1, G2, CollisionDetectorProximity<D, G1, G2>>(proximity)
+    }
+
+    /// Unregister the proximity algorithm for a given pair of geometries.
+    pub fn unregister_proximity<G1: 'static + Any, G2: 'static + Any>(&mut self) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.proximities.remove(&key);
+    }
+
+    /// Tests whether a proximity algorithm is registered for the two given geometries.
+    pub fn is_supported(&self, a: &Geom, b: &Geom) -> bool {
+        self.proximities.contains_key(&(a.get_type_id(), b.get_type_id()))
+    }
+
+    /// Tests whether two geometries are intersecting or closer than `margin`.
+    ///
+    /// Returns `None` if no proximity algorithm is registered for this pair of geometries.
+    ///
+    /// # Arguments
+    /// * `m1`     - the first object transform.
+    /// * `g1`     - the first object.
+    /// * `m2`     - the second object transform.
+    /// * `g2`     - the second object.
+    /// * `margin` - the distance bellow which the objects are considered close to each other.
+    pub fn proximity(&self,
+                     m1:     &Matrix,
+                     g1:     &Geom,
+                     m2:     &Matrix,
+                     g2:     &Geom,
+                     margin: &Scalar)
+                     -> Option<Proximity> {
+        match self.proximities.find(&(g1.get_type_id(), g2.get_type_id())) {
+            Some(p) => match p.proximity(self, m1, g1, m2, g2, margin) {
+                Ok(proximity) => Some(proximity),
+                Err(_)        => fail!("The proximity algorithm registered for those geometries does not handle their types.")
+            },
+            None => None
+        }
+    }
+}
+
+impl GeomGeomProximityDispatcher {
+    /// Creates a new `GeomGeomProximityDispatcher` able to test the proximity of any pair of
+    /// geometries with a collision detector registered by the default `GeomGeomDispatcher`.
+    pub fn new() -> GeomGeomProximityDispatcher {
+        let mut res = GeomGeomProximityDispatcher::new_without_default();
+
+        // Ball vs. Ball
+        res.register_proximity::<Ball, Ball, BallBallProximity>(BallBallProximity);
+
+        // NOTE: there is no dedicated algorithm for the pairs involving a bezier surface or a
+        // bezier curve, so we use the collision detectors. Their prediction is overwritten by the
+        // query margin.
+        let prediction: Scalar = na::zero();
+        let cache    = Arc::new(RWLock::new(SurfaceSubdivisionTreeCache::new()));
+        let selector = TangentConesSurfaceSelector::new(Bounded::max_value());
+
+        // Surface vs Surface
+        res.register_detector_proximity(BezierSurfaceBezierSurface::new(selector.clone(), prediction.clone(),
+                                                                        cache.clone()));
+
+        // Ball vs Surface
+        let bs = BallBezierSurface::new(selector.clone(), prediction.clone(), cache.clone());
+        let sb = BezierSurfaceBall::new(selector.clone(), prediction.clone(), cache.clone());
+        res.register_detector_proximity(bs);
+        res.register_detector_proximity(sb);
+
+        // Implicit vs Surface
+        res.register_implicit_surface_proximity::<Cuboid>(&selector, &cache);
+        res.register_implicit_surface_proximity::<Cone>(&selector, &cache);
+        res.register_implicit_surface_proximity::<Cylinder>(&selector, &cache);
+        res.register_implicit_surface_proximity::<Capsule>(&selector, &cache);
+        res.register_implicit_surface_proximity::<Convex>(&selector, &cache);
+        res.register_implicit_surface_proximity::<Triangle>(&selector, &cache);
+        res.register_implicit_surface_proximity::<Tetrahedron>(&selector, &cache);
+        res.register_implicit_surface_proximity::<Segment>(&selector, &cache);
+
+        // Ball vs Curve
+        res.register_detector_proximity(BallBezierCurve::new(prediction.clone()));
+        res.register_detector_proximity(BezierCurveBall::new(prediction.clone()));
+
+        // Implicit vs Curve
+        res.register_implicit_curve_proximity::<Cuboid>();
+        res.register_implicit_curve_proximity::<Cone>();
+        res.register_implicit_curve_proximity::<Cylinder>();
+        res.register_implicit_curve_proximity::<Capsule>();
+        res.register_implicit_curve_proximity::<Convex>();
+        res.register_implicit_curve_proximity::<Triangle>();
+        res.register_implicit_curve_proximity::<Tetrahedron>();
+        res.register_implicit_curve_proximity::<Segment>();
+
+        // Plane vs. Implicit
+        res.register_default_plane_implicit_proximity::<Ball>();
+        res.register_default_plane_implicit_proximity::<Cuboid>();
+        res.register_default_plane_implicit_proximity::<Cone>();
+        res.register_default_plane_implicit_proximity::<Cylinder>();
+        res.register_default_plane_implicit_proximity::<Capsule>();
+        res.register_default_plane_implicit_proximity::<Convex>();
+        res.register_default_plane_implicit_proximity::<Triangle>();
+        res.register_default_plane_implicit_proximity::<Tetrahedron>();
+        res.register_default_plane_implicit_proximity::<Segment>();
+
+        // Implicit vs. Implicit
+        // NOTE: some pair will be registered twice…
+        res.register_default_implicit_proximities::<Cuboid>();
+        res.register_default_implicit_proximities::<Cone>();
+        res.register_default_implicit_proximities::<Cylinder>();
+        res.register_default_implicit_proximities::<Capsule>();
+        res.register_default_implicit_proximities::<Convex>();
+        res.register_default_implicit_proximities::<Triangle>();
+        res.register_default_implicit_proximities::<Tetrahedron>();
+        res.register_default_implicit_proximities::<Segment>();
+
+        // Compound vs. Other
+        res.register_default_concave_geom_geom_proximity::<Compound, Plane>();
+        res.register_default_concave_geom_geom_proximity::<Compound, Ball>();
+        res.register_default_concave_geom_geom_proximity::<Compound, Cuboid>();
+        res.register_default_concave_geom_geom_proximity::<Compound, Cone>();
+        res.register_default_concave_geom_geom_proximity::<Compound, Cylinder>();
+        res.register_default_concave_geom_geom_proximity::<Compound, Capsule>();
+        res.register_default_concave_geom_geom_proximity::<Compound, Convex>();
+        res.register_default_concave_geom_geom_proximity::<Compound, Triangle>();
+        res.register_default_concave_geom_geom_proximity::<Compound, Tetrahedron>();
+        res.register_default_concave_geom_geom_proximity::<Compound, Segment>();
+
+        // TriangleMesh vs. Other
+        res.register_default_concave_geom_geom_proximity::<Mesh, Plane>();
+        res.register_default_concave_geom_geom_proximity::<Mesh, Ball>();
+        res.register_default_concave_geom_geom_proximity::<Mesh, Cuboid>();
+        res.register_default_concave_geom_geom_proximity::<Mesh, Cone>();
+        res.register_default_concave_geom_geom_proximity::<Mesh, Cylinder>();
+        res.register_default_concave_geom_geom_proximity::<Mesh, Capsule>();
+        res.register_default_concave_geom_geom_proximity::<Mesh, Convex>();
+        res.register_default_concave_geom_geom_proximity::<Mesh, Triangle>();
+        res.register_default_concave_geom_geom_proximity::<Mesh, Tetrahedron>();
+        res.register_default_concave_geom_geom_proximity::<Mesh, Segment>();
+
+        // Concave vs. Concave
+        // The parts of the second geometry are handled by the `GeomConcaveGeomProximity`
+        // registered above.
+        res.register_default_concave_geom_concave_geom_proximity::<Compound, Compound>();
+        res.register_default_concave_geom_concave_geom_proximity::<Mesh, Compound>();
+        res.register_default_concave_geom_concave_geom_proximity::<Mesh, Mesh>();
+
+        // TetMesh vs. Other
+        res.register_tet_mesh_proximities();
+
+        res
+    }
+
+    /// Registers the proximity algorithm between a given implicit geometry and a plane.
+    pub fn register_default_plane_implicit_proximity<I: 'static + Implicit<Vect, Matrix>>(
+                                                     &mut self) {
+        self.register_proximity::<Plane, I, PlaneImplicitProximity<I>>(PlaneImplicitProximity);
+        self.register_proximity::<I, Plane, ImplicitPlaneProximity<I>>(ImplicitPlaneProximity);
+    }
+
+    /// Registers the proximity algorithm between two implicit geometries.
+    pub fn register_default_implicit_implicit_proximity<G1: 'static + Implicit<Vect, Matrix>,
+                                                        G2: 'static + Implicit<Vect, Matrix>>(
+                                                        &mut self) {
+        self.register_proximity::<G1, G2, ImplicitImplicitProximity<G1, G2>>(ImplicitImplicitProximity);
+        self.register_proximity::<G2, G1, ImplicitImplicitProximity<G2, G1>>(ImplicitImplicitProximity);
+    }
+
+    /// Registers the proximity algorithm between a given geometry and every implicit geometry
+    /// supported by `ncollide`.
+    pub fn register_default_implicit_proximities<G: 'static + Implicit<Vect, Matrix>>(&mut self) {
+        self.register_default_implicit_implicit_proximity::<Ball, G>();
+        self.register_default_implicit_implicit_proximity::<Cuboid, G>();
+        self.register_default_implicit_implicit_proximity::<Cone, G>();
+        self.register_default_implicit_implicit_proximity::<Cylinder, G>();
+        self.register_default_implicit_implicit_proximity::<Capsule, G>();
+        self.register_default_implicit_implicit_proximity::<Convex, G>();
+        self.register_default_implicit_implicit_proximity::<Triangle, G>();
+        self.register_default_implicit_implicit_proximity::<Tetrahedron, G>();
+        self.register_default_implicit_implicit_proximity::<Segment, G>();
+    }
+
+    /// Registers the proximity algorithm between a given concave geometry and a given geometry.
+    pub fn register_default_concave_geom_geom_proximity<G1: 'static + ConcaveGeom,
+                                                        G2: 'static + Geom>(&mut self) {
+        let p1 = ConcaveGeomGeomProximity::<G1, G2>;
+        let p2 = GeomConcaveGeomProximity::<G2, G1>;
+
+        self.register_proximity::<G1, G2, ConcaveGeomGeomProximity<G1, G2>>(p1);
+        self.register_proximity::<G2, G1, GeomConcaveGeomProximity<G2, G1>>(p2);
+    }
+
+    /// Registers the proximity algorithm between two given concave geometries.
+    pub fn register_default_concave_geom_concave_geom_proximity<G1: 'static + ConcaveGeom,
+                                                                G2: 'static + ConcaveGeom>(
+                                                                &mut self) {
+        let p1 = ConcaveGeomGeomProximity::<G1, G2>;
+        let p2 = ConcaveGeomGeomProximity::<G2, G1>;
+
+        self.register_proximity::<G1, G2, ConcaveGeomGeomProximity<G1, G2>>(p1);
+        self.register_proximity::<G2, G1, ConcaveGeomGeomProximity<G2, G1>>(p2);
+    }
+
+    fn register_implicit_surface_proximity<G: 'static + Send + Implicit<Vect, Matrix>>(
+                                           &mut self,
+                                           selector: &TangentConesSurfaceSelector,
+                                           cache:    &Arc<RWLock<SurfaceSubdivisionTreeCache<TangentConesSurfaceSelectorTestData>>>) {
+        let prediction: Scalar = na::zero();
+        let gs: ImplicitBezierSurface<TangentConesSurfaceSelector, TangentConesSurfaceSelectorTestData, G> =
+            ImplicitBezierSurface::new(selector.clone(), prediction.clone(), cache.clone());
+        let sg: BezierSurfaceImplicit<TangentConesSurfaceSelector, TangentConesSurfaceSelectorTestData, G> =
+            BezierSurfaceImplicit::new(selector.clone(), prediction, cache.clone());
+
+        self.register_detector_proximity(gs);
+        self.register_detector_proximity(sg);
+    }
+
+    fn register_implicit_curve_proximity<G: 'static + Send + Implicit<Vect, Matrix>>(&mut self) {
+        let prediction: Scalar = na::zero();
+
+        self.register_detector_proximity(ImplicitBezierCurve::<G>::new(prediction.clone()));
+        self.register_detector_proximity(BezierCurveImplicit::<G>::new(prediction));
+    }
+
+    #[cfg(dim3)]
+    fn register_tet_mesh_proximities(&mut self) {
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Plane>();
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Ball>();
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Cuboid>();
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Cone>();
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Cylinder>();
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Capsule>();
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Convex>();
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Triangle>();
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Tetrahedron>();
+        self.register_default_concave_geom_geom_proximity::<TetMesh, Segment>();
+
+        self.register_default_concave_geom_concave_geom_proximity::<TetMesh, Compound>();
+        self.register_default_concave_geom_concave_geom_proximity::<TetMesh, Mesh>();
+        self.register_default_concave_geom_concave_geom_proximity::<TetMesh, TetMesh>();
+    }
+
+    // There is no tetrahedral mesh outside of 3D: this does nothing.
+    #[cfg(not(dim3))]
+    fn register_tet_mesh_proximities(&mut self) {
+    }
+}
+
+/*
+ *
+ * Proximity algorithms for the non-concave geometries.
+ *
+ */
+/// Structure implementing `GeomGeomProximity` using `proximity::ball_ball`.
+pub struct BallBallProximity;
+
+impl GeomGeomProximity for BallBallProximity {
+    #[inline]
+    fn proximity(&self,
+                 _:      &GeomGeomProximityDispatcher,
+                 m1:     &Matrix,
+                 g1:     &Geom,
+                 m2:     &Matrix,
+                 g2:     &Geom,
+                 margin: &Scalar)
+                 -> Result<Proximity, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<Ball, Ball>(g1, g2));
+
+        Ok(ball_ball::proximity(&m1.translation(), g1, &m2.translation(), g2, margin))
+    }
+}
+
+impl DynamicProximity<Ball, Ball> for BallBallProximity { }
+
+/// Structure implementing `GeomGeomProximity` using `proximity::plane_implicit`.
+pub struct PlaneImplicitProximity<G>;
+
+impl<G: 'static + Implicit<Vect, Matrix>> GeomGeomProximity for PlaneImplicitProximity<G> {
+    #[inline]
+    fn proximity(&self,
+                 _:      &GeomGeomProximityDispatcher,
+                 m1:     &Matrix,
+                 g1:     &Geom,
+                 m2:     &Matrix,
+                 g2:     &Geom,
+                 margin: &Scalar)
+                 -> Result<Proximity, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<Plane, G>(g1, g2));
+
+        Ok(plane_implicit::proximity(m1, g1, m2, g2, margin))
+    }
+}
+
+impl<G: 'static + Implicit<Vect, Matrix>> DynamicProximity<Plane, G> for PlaneImplicitProximity<G> { }
+
+/// Structure implementing `GeomGeomProximity` using `proximity::plane_implicit` with its
+/// arguments swapped.
+pub struct ImplicitPlaneProximity<G>;
+
+impl<G: 'static + Implicit<Vect, Matrix>> GeomGeomProximity for ImplicitPlaneProximity<G> {
+    #[inline]
+    fn proximity(&self,
+                 _:      &GeomGeomProximityDispatcher,
+                 m1:     &Matrix,
+                 g1:     &Geom,
+                 m2:     &Matrix,
+                 g2:     &Geom,
+                 margin: &Scalar)
+                 -> Result<Proximity, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G, Plane>(g1, g2));
+
+        Ok(plane_implicit::proximity(m2, g2, m1, g1, margin))
+    }
+}
+
+impl<G: 'static + Implicit<Vect, Matrix>> DynamicProximity<G, Plane> for ImplicitPlaneProximity<G> { }
+
+/// Structure implementing `GeomGeomProximity` using `proximity::implicit_implicit`.
+pub struct ImplicitImplicitProximity<G1, G2>;
+
+impl<G1: 'static + Implicit<Vect, Matrix>, G2: 'static + Implicit<Vect, Matrix>>
+GeomGeomProximity for ImplicitImplicitProximity<G1, G2> {
+    #[inline]
+    fn proximity(&self,
+                 _:      &GeomGeomProximityDispatcher,
+                 m1:     &Matrix,
+                 g1:     &Geom,
+                 m2:     &Matrix,
+                 g2:     &Geom,
+                 margin: &Scalar)
+                 -> Result<Proximity, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+        let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+
+        let (res, _) = implicit_implicit::proximity(m1, g1, m2, g2, margin, &mut simplex, None);
+
+        Ok(res)
+    }
+}
+
+impl<G1: 'static + Implicit<Vect, Matrix>, G2: 'static + Implicit<Vect, Matrix>>
+DynamicProximity<G1, G2> for ImplicitImplicitProximity<G1, G2> { }
+
+/// Proximity algorithm using the contacts computed by a collision detector.
+pub struct CollisionDetectorProximity<D, G1, G2> {
+    template: D
+}
+
+impl<D, G1, G2> CollisionDetectorProximity<D, G1, G2> {
+    /// Creates a new proximity algorithm using a clone of `detector` at each query.
+    pub fn new(detector: D) -> CollisionDetectorProximity<D, G1, G2> {
+        CollisionDetectorProximity {
+            template: detector
+        }
+    }
+}
+
+impl<D:  Send + Clone + CollisionDetector<G1, G2>,
+     G1: 'static,
+     G2: 'static>
+GeomGeomProximity for CollisionDetectorProximity<D, G1, G2> {
+    fn proximity(&self,
+                 _:      &GeomGeomProximityDispatcher,
+                 m1:     &Matrix,
+                 g1:     &Geom,
+                 m2:     &Matrix,
+                 g2:     &Geom,
+                 margin: &Scalar)
+                 -> Result<Proximity, GeomTypeMismatch> {
+        let (g1, g2)     = try!(downcast_geoms::<G1, G2>(g1, g2));
+        let mut detector = self.template.clone();
+
+        detector.set_prediction(margin);
+        detector.update(m1, g1, m2, g2);
+
+        let mut colls: Vec<Contact> = Vec::new();
+        let mut res = Disjoint;
+
+        detector.colls(&mut colls);
+
+        for c in colls.iter() {
+            if c.depth >= na::zero() {
+                return Ok(Intersecting)
+            }
+            else if -c.depth <= *margin {
+                res = WithinMargin
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+impl<D:  Send + Clone + CollisionDetector<G1, G2>,
+     G1: 'static,
+     G2: 'static>
+DynamicProximity<G1, G2> for CollisionDetectorProximity<D, G1, G2> { }
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{Geom, Ball, Cuboid, Mesh, BezierCurve};
+    use narrow::{GeomGeomProximityDispatcher, WithinMargin, Disjoint};
+    use test_utils::flat_surface;
+
+    #[test]
+    fn test_proximity_ball_bezier_surface_uses_margin() {
+        let dispatcher = GeomGeomProximityDispatcher::new();
+        let s          = flat_surface();
+        let b          = Ball::new(0.5f64);
+        let ms         = na::one::<Iso3<f64>>();
+        let mb         = Iso3::new(Vec3::new(1.0f64, 1.0, 1.0), na::zero());
+
+        // The ball is 0.5 away from the surface: farther than the default collision detector
+        // prediction, but closer than the query margin.
+        let near = dispatcher.proximity(&mb, &b as &Geom, &ms, &s as &Geom, &1.0);
+        let far  = dispatcher.proximity(&mb, &b as &Geom, &ms, &s as &Geom, &0.2);
+        let flip = dispatcher.proximity(&ms, &s as &Geom, &mb, &b as &Geom, &1.0);
+
+        assert!(near == Some(WithinMargin));
+        assert!(far  == Some(Disjoint));
+        assert!(flip == Some(WithinMargin));
+    }
+
+    #[test]
+    fn test_proximity_cuboid_bezier_surface() {
+        let dispatcher = GeomGeomProximityDispatcher::new();
+        let s          = flat_surface();
+        let c          = Cuboid::new_with_margin(Vec3::new(0.5f64, 0.5, 0.5), 0.0);
+        let ms         = na::one::<Iso3<f64>>();
+        let mc         = Iso3::new(Vec3::new(1.0f64, 1.0, 1.0), na::zero());
+
+        // The cuboid is 0.5 away from the surface.
+        assert!(dispatcher.proximity(&mc, &c as &Geom, &ms, &s as &Geom, &1.0) == Some(WithinMargin));
+        assert!(dispatcher.proximity(&mc, &c as &Geom, &ms, &s as &Geom, &0.2) == Some(Disjoint));
+        assert!(dispatcher.proximity(&ms, &s as &Geom, &mc, &c as &Geom, &1.0) == Some(WithinMargin));
+    }
+
+    #[test]
+    fn test_proximity_ball_bezier_curve() {
+        let dispatcher = GeomGeomProximityDispatcher::new();
+        let pts        = vec!(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+        let c          = BezierCurve::new(pts);
+        let b          = Ball::new(0.5f64);
+        let mc         = na::one::<Iso3<f64>>();
+        let mb         = Iso3::new(Vec3::new(1.0f64, 1.0, 0.0), na::zero());
+
+        // The ball is 0.5 away from the curve.
+        assert!(dispatcher.proximity(&mb, &b as &Geom, &mc, &c as &Geom, &1.0) == Some(WithinMargin));
+        assert!(dispatcher.proximity(&mb, &b as &Geom, &mc, &c as &Geom, &0.2) == Some(Disjoint));
+        assert!(dispatcher.proximity(&mc, &c as &Geom, &mb, &b as &Geom, &1.0) == Some(WithinMargin));
+    }
+
+    #[test]
+    fn test_proximity_mesh_mesh() {
+        let dispatcher = GeomGeomProximityDispatcher::new();
+        let vertices   = Arc::new(vec!(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0),
+                                       Vec3::new(0.0, 1.0, 0.0)));
+        let indices    = Arc::new(vec!(0u, 1, 2));
+        let m          = Mesh::new_with_margin(vertices, indices, None, None, 0.0);
+        let m1         = na::one::<Iso3<f64>>();
+        let m2         = Iso3::new(Vec3::new(0.0f64, 0.0, 0.5), na::zero());
+
+        assert!(dispatcher.is_supported(&m as &Geom, &m as &Geom));
+        assert!(dispatcher.proximity(&m1, &m as &Geom, &m2, &m as &Geom, &1.0) == Some(WithinMargin));
+        assert!(dispatcher.proximity(&m1, &m as &Geom, &m2, &m as &Geom, &0.2) == Some(Disjoint));
+    }
+}
@@ -0,0 +1,509 @@
+//! Time of impact computation between two `Box<Geom>`.
+
+use std::intrinsics::TypeId;
+use std::any::Any;
+use collections::HashMap;
+use geom::{AnnotatedPoint, Geom, ConcaveGeom, Cone, Cuboid, Ball, Capsule, Convex, Cylinder,
+           Compound, Mesh, Triangle, Tetrahedron, Segment, Plane, BezierSurface, BezierCurve};
+#[cfg(dim3)]
+use geom::TetMesh;
+use implicit::{Implicit, PreferedSamplingDirections};
+use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+use narrow::{CollisionDetector, BallBall, ImplicitImplicit, ImplicitPlane, PlaneImplicit,
+             BallBezierSurface, BezierSurfaceBall, ImplicitBezierSurface, BezierSurfaceImplicit,
+             BezierSurfaceBezierSurface, BallBezierCurve, BezierCurveBall, ImplicitBezierCurve,
+             BezierCurveImplicit, ConcaveGeomGeomTOI, GeomConcaveGeomTOI,
+             ConcaveGeomConcaveGeomTOI, GeomTypeMismatch, downcast_geoms, ImplicitImplicitStrategy,
+             PlaneImplicitStrategy, ImplicitPlaneStrategy};
+use narrow::geom_geom_fallback;
+use narrow::surface_selector::{TangentConesSurfaceSelector, TangentConesSurfaceSelectorTestData};
+use math::{Scalar, Vect, Matrix};
+
+/// Result of a time of impact query between two `Geom`.
+#[deriving(Show, PartialEq, Clone)]
+pub enum TOIResult {
+    /// The geometries touch at the given time of impact.
+    Impact(Scalar),
+    /// The geometries do not touch during the displacement.
+    NoImpact,
+    /// No time of impact algorithm is registered for this pair of geometries.
+    UnsupportedGeomPair
+}
+
+impl TOIResult {
+    /// The time of impact, if the geometries touch.
+    ///
+    /// Both `NoImpact` and `UnsupportedGeomPair` give `None`.
+    #[inline]
+    pub fn impact(&self) -> Option<Scalar> {
+        match *self {
+            Impact(ref toi) => Some(toi.clone()),
+            _               => None
+        }
+    }
+
+    /// Whether or not this result comes from a pair of geometries without time of impact
+    /// algorithm.
+    #[inline]
+    pub fn is_unsupported(&self) -> bool {
+        match *self {
+            UnsupportedGeomPair => true,
+            _                   => false
+        }
+    }
+}
+
+/// Trait of time of impact algorithms using dynamic dispatch on the geometries.
+pub trait GeomGeomTOI : Send {
+    /// Computes the time of impact of two objects.
+    ///
+    /// If the objects do not have the types this algorithm was built for, a `GeomTypeMismatch`
+    /// error is returned.
+    ///
+    /// # Arguments
+    /// * `m1`   - the first object transform.
+    /// * `dir`  - the first object displacement direction.
+    /// * `dist` - the first object displacement distance.
+    /// * `g1`   - the first object.
+    /// * `m2`   - the second object transform.
+    /// * `g2`   - the second object.
+    fn toi(&self,
+           dispatcher: &GeomGeomTOIDispatcher,
+           m1:         &Matrix,
+           dir:        &Vect,
+           dist:       &Scalar,
+           g1:         &Geom,
+           m2:         &Matrix,
+           g2:         &Geom)
+           -> Result<Option<Scalar>, GeomTypeMismatch>;
+}
+
+/// Trait to be implemented by time of impact algorithms using dynamic dispatch.
+///
+/// This is used to know the exact type of the geometries.
+pub trait DynamicTOI<G1, G2>: GeomGeomTOI { }
+
+/// Time of impact algorithm using the static `toi` method of a collision detector.
+pub struct CollisionDetectorTOI<D, G1, G2>;
+
+impl<D: CollisionDetector<G1, G2>, G1: 'static, G2: 'static>
+GeomGeomTOI for CollisionDetectorTOI<D, G1, G2> {
+    #[inline]
+    fn toi(&self,
+           _:    &GeomGeomTOIDispatcher,
+           m1:   &Matrix,
+           dir:  &Vect,
+           dist: &Scalar,
+           g1:   &Geom,
+           m2:   &Matrix,
+           g2:   &Geom)
+           -> Result<Option<Scalar>, GeomTypeMismatch> {
+        let (g1, g2) = try!(downcast_geoms::<G1, G2>(g1, g2));
+
+        Ok(CollisionDetector::toi(None::<D>, m1, dir, dist, g1, m2, g2))
+    }
+}
+
+impl<D: CollisionDetector<G1, G2>, G1: 'static, G2: 'static>
+DynamicTOI<G1, G2> for CollisionDetectorTOI<D, G1, G2> { }
+
+/// Time of impact dispatcher between two `Box<Geom>`.
+pub struct GeomGeomTOIDispatcher {
+    tois: HashMap<(TypeId, TypeId), Box<GeomGeomTOI>>
+}
+
+impl GeomGeomTOIDispatcher {
+    /// Creates a new `GeomGeomTOIDispatcher` without the default set of time of impact
+    /// algorithms.
+    pub fn new_without_default() -> GeomGeomTOIDispatcher {
+        GeomGeomTOIDispatcher {
+            tois: HashMap::new()
+        }
+    }
+
+    /// Registers a new time of impact algorithm for a pair of geometries.
+    ///
+    /// The algorithm must be suited for `G1` and `G2`, i.e., implement `DynamicTOI<G1, G2>`.
+    pub fn register_toi<G1: 'static + Any,
+                        G2: 'static + Any,
+                        T:  'static + DynamicTOI<G1, G2>>(
+                        &mut self,
+                        toi: T) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.tois.insert(key, box toi as Box<GeomGeomTOI>);
+    }
+
+    /// Registers the time of impact algorithm of a collision detector for two geometries.
+    pub fn register_detector_toi<G1: 'static + Any,
+                                 G2: 'static + Any,
+                                 D:  'static + CollisionDetector<G1, G2>>(&mut self) {
+        let toi = CollisionDetectorTOI::<D, G1, G2>;
+        self.register_toi::<G1, G2, CollisionDetectorTOI<D, G1, G2>>(toi)
+    }
+
+    /// Unregister the time of impact algorithm for a given pair of geometries.
+    pub fn unregister_toi<G1: 'static + Any, G2: 'static + Any>(&mut self) {
+        let key = (TypeId::of::<G1>(), TypeId::of::<G2>());
+        self.tois.remove(&key);
+    }
+
+    /// Tests whether the time of impact of the two given geometries can be computed.
+    ///
+    /// This is the case if an algorithm is registered for their types, or if their capabilities
+    /// allow a generic algorithm.
+    pub fn is_supported(&self, a: &Geom, b: &Geom) -> bool {
+        if self.tois.contains_key(&(a.get_type_id(), b.get_type_id())) {
+            return true;
+        }
+
+        match geom_geom_fallback::fallback_strategy(a, b) {
+            Some(ImplicitImplicitStrategy) |
+            Some(PlaneImplicitStrategy)    |
+            Some(ImplicitPlaneStrategy)    => true,
+            _                              => false
+        }
+    }
+
+    /// Computes the time of impact of two geometries.
+    ///
+    /// If no algorithm is registered for their types, a generic algorithm is selected depending
+    /// on their capabilities. See `GeomCapabilities`.
+    ///
+    /// # Arguments
+    /// * `m1`   - the first object transform.
+    /// * `dir`  - the first object displacement direction.
+    /// * `dist` - the first object displacement distance.
+    /// * `g1`   - the first object.
+    /// * `m2`   - the second object transform.
+    /// * `g2`   - the second object.
+    pub fn toi(&self,
+               m1:   &Matrix,
+               dir:  &Vect,
+               dist: &Scalar,
+               g1:   &Geom,
+               m2:   &Matrix,
+               g2:   &Geom)
+               -> TOIResult {
+        match self.tois.find(&(g1.get_type_id(), g2.get_type_id())) {
+            Some(toi) => {
+                match toi.toi(self, m1, dir, dist, g1, m2, g2) {
+                    Ok(Some(t)) => Impact(t),
+                    Ok(None)    => NoImpact,
+                    Err(_)      => fail!("The time of impact algorithm registered for those geometries does not handle their types.")
+                }
+            },
+            None => geom_geom_fallback::fallback_toi(m1, dir, g1, m2, g2)
+        }
+    }
+}
+
+impl GeomGeomTOIDispatcher {
+    /// Creates a new `GeomGeomTOIDispatcher` able to compute the time of impact of any pair of
+    /// geometries supported by the functions of `narrow::toi`.
+    pub fn new() -> GeomGeomTOIDispatcher {
+        type Selector     = TangentConesSurfaceSelector;
+        type SelectorData = TangentConesSurfaceSelectorTestData;
+
+        let mut res = GeomGeomTOIDispatcher::new_without_default();
+
+        // Ball vs. Ball
+        res.register_detector_toi::<Ball, Ball, BallBall>();
+
+        // Ball vs. BezierSurface
+        res.register_detector_toi::<Ball, BezierSurface, BallBezierSurface<Selector, SelectorData>>();
+        res.register_detector_toi::<BezierSurface, Ball, BezierSurfaceBall<Selector, SelectorData>>();
+
+        // BezierSurface vs. BezierSurface
+        res.register_detector_toi::<BezierSurface, BezierSurface, BezierSurfaceBezierSurface>();
+
+        // Implicit vs. BezierSurface
+        res.register_implicit_bezier_surface_toi::<Cuboid>();
+        res.register_implicit_bezier_surface_toi::<Cone>();
+        res.register_implicit_bezier_surface_toi::<Cylinder>();
+        res.register_implicit_bezier_surface_toi::<Capsule>();
+        res.register_implicit_bezier_surface_toi::<Convex>();
+        res.register_implicit_bezier_surface_toi::<Triangle>();
+        res.register_implicit_bezier_surface_toi::<Tetrahedron>();
+        res.register_implicit_bezier_surface_toi::<Segment>();
+
+        // Ball vs. BezierCurve
+        res.register_detector_toi::<Ball, BezierCurve, BallBezierCurve>();
+        res.register_detector_toi::<BezierCurve, Ball, BezierCurveBall>();
+
+        // Implicit vs. BezierCurve
+        res.register_implicit_bezier_curve_toi::<Cuboid>();
+        res.register_implicit_bezier_curve_toi::<Cone>();
+        res.register_implicit_bezier_curve_toi::<Cylinder>();
+        res.register_implicit_bezier_curve_toi::<Capsule>();
+        res.register_implicit_bezier_curve_toi::<Convex>();
+        res.register_implicit_bezier_curve_toi::<Triangle>();
+        res.register_implicit_bezier_curve_toi::<Tetrahedron>();
+        res.register_implicit_bezier_curve_toi::<Segment>();
+
+        // Plane vs. Implicit
+        res.register_default_plane_implicit_toi::<Ball>();
+        res.register_default_plane_implicit_toi::<Cuboid>();
+        res.register_default_plane_implicit_toi::<Cone>();
+        res.register_default_plane_implicit_toi::<Cylinder>();
+        res.register_default_plane_implicit_toi::<Capsule>();
+        res.register_default_plane_implicit_toi::<Convex>();
+        res.register_default_plane_implicit_toi::<Triangle>();
+        res.register_default_plane_implicit_toi::<Tetrahedron>();
+        res.register_default_plane_implicit_toi::<Segment>();
+
+        // Implicit vs. Implicit
+        // NOTE: some pair will be registered twice…
+        res.register_default_implicit_tois::<Cuboid>();
+        res.register_default_implicit_tois::<Cone>();
+        res.register_default_implicit_tois::<Cylinder>();
+        res.register_default_implicit_tois::<Capsule>();
+        res.register_default_implicit_tois::<Convex>();
+        res.register_default_implicit_tois::<Triangle>();
+        res.register_default_implicit_tois::<Tetrahedron>();
+        res.register_default_implicit_tois::<Segment>();
+
+        // Compound vs. Other
+        res.register_default_concave_geom_geom_toi::<Compound, Plane>();
+        res.register_default_concave_geom_geom_toi::<Compound, Ball>();
+        res.register_default_concave_geom_geom_toi::<Compound, Cuboid>();
+        res.register_default_concave_geom_geom_toi::<Compound, Cone>();
+        res.register_default_concave_geom_geom_toi::<Compound, Cylinder>();
+        res.register_default_concave_geom_geom_toi::<Compound, Capsule>();
+        res.register_default_concave_geom_geom_toi::<Compound, Convex>();
+        res.register_default_concave_geom_geom_toi::<Compound, Triangle>();
+        res.register_default_concave_geom_geom_toi::<Compound, Tetrahedron>();
+        res.register_default_concave_geom_geom_toi::<Compound, Segment>();
+
+        // TriangleMesh vs. Other
+        res.register_default_concave_geom_geom_toi::<Mesh, Plane>();
+        res.register_default_concave_geom_geom_toi::<Mesh, Ball>();
+        res.register_default_concave_geom_geom_toi::<Mesh, Cuboid>();
+        res.register_default_concave_geom_geom_toi::<Mesh, Cone>();
+        res.register_default_concave_geom_geom_toi::<Mesh, Cylinder>();
+        res.register_default_concave_geom_geom_toi::<Mesh, Capsule>();
+        res.register_default_concave_geom_geom_toi::<Mesh, Convex>();
+        res.register_default_concave_geom_geom_toi::<Mesh, Triangle>();
+        res.register_default_concave_geom_geom_toi::<Mesh, Tetrahedron>();
+        res.register_default_concave_geom_geom_toi::<Mesh, Segment>();
+
+        // Concave vs. Concave
+        res.register_default_concave_geom_concave_geom_toi::<Compound, Compound>();
+        res.register_default_concave_geom_concave_geom_toi::<Mesh, Compound>();
+        res.register_default_concave_geom_concave_geom_toi::<Mesh, Mesh>();
+
+        // TetMesh vs. Other
+        res.register_tet_mesh_tois();
+
+        res
+    }
+
+    /// Registers the time of impact algorithm between a given implicit geometry and a plane.
+    pub fn register_default_plane_implicit_toi<I: 'static + Implicit<Vect, Matrix>>(&mut self) {
+        self.register_detector_toi::<I, Plane, ImplicitPlane<I>>();
+        self.register_detector_toi::<Plane, I, PlaneImplicit<I>>();
+    }
+
+    fn register_implicit_bezier_surface_toi<G: 'static + Send + Implicit<Vect, Matrix>>(&mut self) {
+        type Selector     = TangentConesSurfaceSelector;
+        type SelectorData = TangentConesSurfaceSelectorTestData;
+
+        self.register_detector_toi::<G, BezierSurface, ImplicitBezierSurface<Selector, SelectorData, G>>();
+        self.register_detector_toi::<BezierSurface, G, BezierSurfaceImplicit<Selector, SelectorData, G>>();
+    }
+
+    fn register_implicit_bezier_curve_toi<G: 'static + Send + Implicit<Vect, Matrix>>(&mut self) {
+        self.register_detector_toi::<G, BezierCurve, ImplicitBezierCurve<G>>();
+        self.register_detector_toi::<BezierCurve, G, BezierCurveImplicit<G>>();
+    }
+
+    /// Registers the time of impact algorithm between two implicit geometries.
+    pub fn register_default_implicit_implicit_toi<G1: 'static                 +
+                                                      Implicit<Vect, Matrix> +
+                                                      PreferedSamplingDirections<Vect, Matrix>,
+                                                  G2: 'static                 +
+                                                      Implicit<Vect, Matrix> +
+                                                      PreferedSamplingDirections<Vect, Matrix>>(
+                                                  &mut self) {
+        type Simplex = JohnsonSimplex<AnnotatedPoint>;
+
+        self.register_detector_toi::<G1, G2, ImplicitImplicit<Simplex, G1, G2>>();
+        self.register_detector_toi::<G2, G1, ImplicitImplicit<Simplex, G2, G1>>();
+    }
+
+    /// Registers the time of impact algorithm between a given geometry and every implicit
+    /// geometry supported by `ncollide`.
+    pub fn register_default_implicit_tois<G: 'static + Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                                          &mut self) {
+        self.register_default_implicit_implicit_toi::<Ball, G>();
+        self.register_default_implicit_implicit_toi::<Cuboid, G>();
+        self.register_default_implicit_implicit_toi::<Cone, G>();
+        self.register_default_implicit_implicit_toi::<Cylinder, G>();
+        self.register_default_implicit_implicit_toi::<Capsule, G>();
+        self.register_default_implicit_implicit_toi::<Convex, G>();
+        self.register_default_implicit_implicit_toi::<Triangle, G>();
+        self.register_default_implicit_implicit_toi::<Tetrahedron, G>();
+        self.register_default_implicit_implicit_toi::<Segment, G>();
+    }
+
+    /// Registers the time of impact algorithm between a given concave geometry and a given
+    /// geometry.
+    pub fn register_default_concave_geom_geom_toi<G1: 'static + ConcaveGeom,
+                                                  G2: 'static + Geom>(&mut self) {
+        let t1 = ConcaveGeomGeomTOI::<G1, G2>;
+        let t2 = GeomConcaveGeomTOI::<G2, G1>;
+
+        self.register_toi::<G1, G2, ConcaveGeomGeomTOI<G1, G2>>(t1);
+        self.register_toi::<G2, G1, GeomConcaveGeomTOI<G2, G1>>(t2);
+    }
+
+    /// Registers the time of impact algorithm between two given concave geometries.
+    pub fn register_default_concave_geom_concave_geom_toi<G1: 'static + ConcaveGeom,
+                                                          G2: 'static + ConcaveGeom>(&mut self) {
+        let t1 = ConcaveGeomConcaveGeomTOI::<G1, G2>;
+        let t2 = ConcaveGeomConcaveGeomTOI::<G2, G1>;
+
+        self.register_toi::<G1, G2, ConcaveGeomConcaveGeomTOI<G1, G2>>(t1);
+        self.register_toi::<G2, G1, ConcaveGeomConcaveGeomTOI<G2, G1>>(t2);
+    }
+
+    #[cfg(dim3)]
+    fn register_tet_mesh_tois(&mut self) {
+        self.register_default_concave_geom_geom_toi::<TetMesh, Plane>();
+        self.register_default_concave_geom_geom_toi::<TetMesh, Ball>();
+        self.register_default_concave_geom_geom_toi::<TetMesh, Cuboid>();
+        self.register_default_concave_geom_geom_toi::<TetMesh, Cone>();
+        self.register_default_concave_geom_geom_toi::<TetMesh, Cylinder>();
+        self.register_default_concave_geom_geom_toi::<TetMesh, Capsule>();
+        self.register_default_concave_geom_geom_toi::<TetMesh, Convex>();
+        self.register_default_concave_geom_geom_toi::<TetMesh, Triangle>();
+        self.register_default_concave_geom_geom_toi::<TetMesh, Tetrahedron>();
+        self.register_default_concave_geom_geom_toi::<TetMesh, Segment>();
+
+        self.register_default_concave_geom_concave_geom_toi::<TetMesh, Compound>();
+        self.register_default_concave_geom_concave_geom_toi::<TetMesh, Mesh>();
+        self.register_default_concave_geom_concave_geom_toi::<TetMesh, TetMesh>();
+    }
+
+    // There is no tetrahedral mesh outside of 3D: this does nothing.
+    #[cfg(not(dim3))]
+    fn register_tet_mesh_tois(&mut self) {
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::Vec3;
+    use nalgebra::na;
+    use geom::{Geom, Ball, Cuboid, Plane};
+    use narrow::{GeomGeomTOIDispatcher, Impact, NoImpact, UnsupportedGeomPair};
+    use test_utils::{translation, two_balls, flat_surface};
+
+    #[test]
+    fn test_toi_dispatcher_ball_ball() {
+        let dispatcher = GeomGeomTOIDispatcher::new();
+        let b          = Ball::new(1.0f64);
+        let m1         = translation(0.0, 0.0, 0.0);
+        let m2         = translation(5.0, 0.0, 0.0);
+
+        let hit  = dispatcher.toi(&m1, &Vec3::new(1.0f64, 0.0, 0.0), &10.0, &b as &Geom, &m2, &b as &Geom);
+        let miss = dispatcher.toi(&m1, &Vec3::new(-1.0f64, 0.0, 0.0), &10.0, &b as &Geom, &m2, &b as &Geom);
+
+        match hit {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &3.0, &1.0e-5)),
+            _           => fail!("The balls should touch.")
+        }
+
+        assert!(miss == NoImpact);
+    }
+
+    #[test]
+    fn test_toi_dispatcher_compound_ball() {
+        let dispatcher = GeomGeomTOIDispatcher::new();
+        let c          = two_balls();
+        let b          = Ball::new(1.0f64);
+        let mc         = translation(0.0, 0.0, 0.0);
+        let mb         = translation(6.0, 0.0, 0.0);
+
+        // The right part of the compound touches the ball first.
+        match dispatcher.toi(&mc, &Vec3::new(1.0f64, 0.0, 0.0), &10.0, &c as &Geom, &mb, &b as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &2.0, &1.0e-5)),
+            _           => fail!("The compound should touch the ball.")
+        }
+
+        match dispatcher.toi(&mb, &Vec3::new(-1.0f64, 0.0, 0.0), &10.0, &b as &Geom, &mc, &c as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &2.0, &1.0e-5)),
+            _           => fail!("The ball should touch the compound.")
+        }
+
+        // The impact occurs after the maximum displacement.
+        assert!(dispatcher.toi(&mc, &Vec3::new(1.0f64, 0.0, 0.0), &1.0, &c as &Geom, &mb, &b as &Geom) == NoImpact);
+    }
+
+    #[test]
+    fn test_toi_dispatcher_ball_bezier_surface() {
+        let dispatcher = GeomGeomTOIDispatcher::new();
+        let s          = flat_surface();
+        let b          = Ball::new(0.5f64);
+        let ms         = translation(0.0, 0.0, 0.0);
+        let mb         = translation(0.9, 1.3, 3.0);
+        // The ball center passes at the distance 0.3 from the edge `x = 2`.
+        let medge      = translation(2.3, 1.3, 3.0);
+        let down       = Vec3::new(0.0f64, 0.0, -1.0);
+        let up         = Vec3::new(0.0f64, 0.0, 1.0);
+
+        match dispatcher.toi(&mb, &down, &10.0, &b as &Geom, &ms, &s as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &2.5, &1.0e-5)),
+            _           => fail!("The ball should touch the surface.")
+        }
+
+        match dispatcher.toi(&ms, &up, &10.0, &s as &Geom, &mb, &b as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &2.5, &1.0e-5)),
+            _           => fail!("The surface should touch the ball.")
+        }
+
+        // Edge hits.
+        match dispatcher.toi(&medge, &down, &10.0, &b as &Geom, &ms, &s as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &2.6, &1.0e-5)),
+            _           => fail!("The ball should touch the surface edge.")
+        }
+
+        match dispatcher.toi(&ms, &up, &10.0, &s as &Geom, &medge, &b as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &2.6, &1.0e-5)),
+            _           => fail!("The surface edge should touch the ball.")
+        }
+
+        assert!(dispatcher.toi(&mb, &up, &10.0, &b as &Geom, &ms, &s as &Geom) == NoImpact);
+    }
+
+    #[test]
+    fn test_toi_dispatcher_cuboid_bezier_surface() {
+        let dispatcher = GeomGeomTOIDispatcher::new();
+        let s          = flat_surface();
+        let c          = Cuboid::new_with_margin(Vec3::new(0.5f64, 0.5, 0.5), 0.0);
+        let ms         = translation(0.0, 0.0, 0.0);
+        let mc         = translation(1.0, 1.0, 3.0);
+        let down       = Vec3::new(0.0f64, 0.0, -1.0);
+
+        match dispatcher.toi(&mc, &down, &10.0, &c as &Geom, &ms, &s as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &2.5, &1.0e-5)),
+            _           => fail!("The cuboid should touch the surface.")
+        }
+
+        match dispatcher.toi(&ms, &-down, &10.0, &s as &Geom, &mc, &c as &Geom) {
+            Impact(toi) => assert!(na::approx_eq_eps(&toi, &2.5, &1.0e-5)),
+            _           => fail!("The surface should touch the cuboid.")
+        }
+
+        assert!(dispatcher.toi(&mc, &-down, &10.0, &c as &Geom, &ms, &s as &Geom) == NoImpact);
+    }
+
+    #[test]
+    fn test_toi_dispatcher_unsupported() {
+        let dispatcher = GeomGeomTOIDispatcher::new();
+        let p          = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let m          = translation(0.0, 0.0, 0.0);
+
+        assert!(!dispatcher.is_supported(&p as &Geom, &p as &Geom));
+        assert!(dispatcher.toi(&m, &Vec3::new(1.0f64, 0.0, 0.0), &1.0, &p as &Geom, &m, &p as &Geom) == UnsupportedGeomPair);
+    }
+}
@@ -8,11 +8,21 @@ use narrow::algorithm::simplex::Simplex;
 use narrow::algorithm::gjk;
 use narrow::algorithm::gjk::{GJKResult, NoIntersection, Intersection, Projection};
 use narrow::algorithm::minkowski_sampling;
-use narrow::CollisionDetector;
+use narrow::{CollisionDetector, ProximityDetector, Distance, Proximity, Intersecting,
+             WithinMargin, Disjoint};
 use contact::Contact;
 use ray::{Ray, RayCast};
 use math::{Scalar, Vect, Matrix};
 
+/// Algorithm used to compute the contact when the GJK algorithm finds two geometries penetrating.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub enum PenetrationFallback {
+    /// The penetration depth is estimated by sampling the Minkowski difference of the geometries.
+    MinkowskiSamplingFallback,
+    /// The penetration depth is not estimated: penetrating geometries do not generate any contact.
+    NoPenetrationFallback
+}
+
 /// Persistent collision detector between two shapes having a support mapping function.
 ///
 /// It is based on the GJK algorithm.  This detector generates only one contact point. For a full
@@ -21,6 +31,7 @@ use math::{Scalar, Vect, Matrix};
 pub struct ImplicitImplicit<S, G1, G2> {
     simplex:       S,
     prediction:    Scalar,
+    fallback:      PenetrationFallback,
     contact:       GJKResult<Contact, Vect>
 }
 
@@ -29,6 +40,7 @@ impl<S: Clone, G1, G2> Clone for ImplicitImplicit<S, G1, G2> {
         ImplicitImplicit {
             simplex:    self.simplex.clone(),
             prediction: self.prediction.clone(),
+            fallback:   self.fallback.clone(),
             contact:    self.contact.clone()
         }
     }
@@ -40,13 +52,24 @@ impl<S, G1, G2> ImplicitImplicit<S, G1, G2> {
     ///
     /// It is initialized with a pre-created simplex.
     pub fn new(prediction: Scalar, simplex: S) -> ImplicitImplicit<S, G1, G2> {
+        ImplicitImplicit::new_with_fallback(prediction, simplex, MinkowskiSamplingFallback)
+    }
+
+    /// Creates a new persistent collision detector between two geometries with support mapping
+    /// functions, using the given algorithm to handle penetrations.
+    ///
+    /// It is initialized with a pre-created simplex.
+    pub fn new_with_fallback(prediction: Scalar,
+                             simplex:    S,
+                             fallback:   PenetrationFallback)
+                             -> ImplicitImplicit<S, G1, G2> {
         ImplicitImplicit {
             simplex:    simplex,
             prediction: prediction,
+            fallback:   fallback,
             contact:    Intersection
         }
     }
-
 }
 
 impl<S:  Simplex<AnnotatedPoint>,
@@ -61,14 +84,28 @@ impl<S:  Simplex<AnnotatedPoint>,
             Intersection                  => None
         };
 
-        self.contact = collide(
+        self.contact = collide_with_fallback(
             ma,
             a,
             mb,
             b,
             &self.prediction,
             &mut self.simplex,
-            initial_direction)
+            initial_direction,
+            &self.fallback)
+    }
+
+    #[cfg(dim4)]
+    #[inline]
+    fn perturbed_colls(&self,
+                       ma:      &Matrix,
+                       a:       &G1,
+                       mb:      &Matrix,
+                       b:       &G2,
+                       contact: &Contact,
+                       tangent: &Vect,
+                       out:     &mut Vec<Contact>) {
+        perturbed_contacts(ma, a, mb, b, contact, tangent, &self.prediction, out)
     }
 
     #[inline]
@@ -87,6 +124,11 @@ impl<S:  Simplex<AnnotatedPoint>,
         }
     }
 
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
     #[inline]
     fn toi(_:   Option<ImplicitImplicit<S, G1, G2>>,
            ma:  &Matrix,
@@ -99,6 +141,72 @@ impl<S:  Simplex<AnnotatedPoint>,
     }
 }
 
+/// Persistent proximity detector between two shapes having a support mapping function.
+///
+/// It is based on the GJK algorithm. The last separating axis found is used to initialize the
+/// next query.
+#[deriving(Encodable, Decodable)]
+pub struct ImplicitImplicitProximityDetector<S, G1, G2> {
+    simplex:   S,
+    margin:    Scalar,
+    proximity: Proximity,
+    sep_axis:  Option<Vect>
+}
+
+impl<S: Clone, G1, G2> Clone for ImplicitImplicitProximityDetector<S, G1, G2> {
+    fn clone(&self) -> ImplicitImplicitProximityDetector<S, G1, G2> {
+        ImplicitImplicitProximityDetector {
+            simplex:   self.simplex.clone(),
+            margin:    self.margin.clone(),
+            proximity: self.proximity.clone(),
+            sep_axis:  self.sep_axis.clone()
+        }
+    }
+}
+
+impl<S, G1, G2> ImplicitImplicitProximityDetector<S, G1, G2> {
+    /// Creates a new persistent proximity detector between two geometries with support mapping
+    /// functions.
+    ///
+    /// It is initialized with a pre-created simplex.
+    pub fn new(margin: Scalar, simplex: S) -> ImplicitImplicitProximityDetector<S, G1, G2> {
+        ImplicitImplicitProximityDetector {
+            simplex:   simplex,
+            margin:    margin,
+            proximity: Disjoint,
+            sep_axis:  None
+        }
+    }
+}
+
+impl<S:  Simplex<AnnotatedPoint>,
+     G1: Implicit<Vect, Matrix>,
+     G2: Implicit<Vect, Matrix>>
+     ProximityDetector<G1, G2> for ImplicitImplicitProximityDetector<S, G1, G2> {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &G1, mb: &Matrix, b: &G2) {
+        let (proximity, sep_axis) = proximity(
+            ma,
+            a,
+            mb,
+            b,
+            &self.margin,
+            &mut self.simplex,
+            self.sep_axis.clone());
+
+        self.proximity = proximity;
+
+        if sep_axis.is_some() {
+            self.sep_axis = sep_axis
+        }
+    }
+
+    #[inline]
+    fn proximity(&self) -> Proximity {
+        self.proximity.clone()
+    }
+}
+
 /// Computes a contact point between two implicit geometries.
 ///
 /// For optimizations purposes the objects are artificially enlarged by a small margin. This uses
@@ -121,6 +229,25 @@ pub fn collide<S:  Simplex<AnnotatedPoint>,
                simplex:    &mut S,
                init_dir:   Option<Vect>)
                -> GJKResult<Contact, Vect> {
+    collide_with_fallback(m1, g1, m2, g2, prediction, simplex, init_dir, &MinkowskiSamplingFallback)
+}
+
+/// Computes the contact between two implicit geometries, using the given algorithm when they are
+/// penetrating.
+///
+/// With `NoPenetrationFallback`, `Intersection` is returned for penetrating geometries.
+pub fn collide_with_fallback<S:  Simplex<AnnotatedPoint>,
+                             G1: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+                             G2: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                             m1:         &Matrix,
+                             g1:         &G1,
+                             m2:         &Matrix,
+                             g2:         &G2,
+                             prediction: &Scalar,
+                             simplex:    &mut S,
+                             init_dir:   Option<Vect>,
+                             fallback:   &PenetrationFallback)
+                             -> GJKResult<Contact, Vect> {
     let mut dir = 
         match init_dir {
             None      => m1.translation() - m2.translation(), // FIXME: or m2.translation - m1.translation ?
@@ -159,6 +286,10 @@ pub fn collide<S:  Simplex<AnnotatedPoint>,
         Intersection        => { } // fallback
     }
 
+    if *fallback == NoPenetrationFallback {
+        return Intersection
+    }
+
     // The point is inside of the CSO: use the fallback algorithm
     match minkowski_sampling::closest_points(m1, g1, m2, g2, simplex) {
         Some((p1, p2)) => {
@@ -182,6 +313,181 @@ pub fn collide<S:  Simplex<AnnotatedPoint>,
     }
 }
 
+/// Computes the contacts two geometries would have if the first one was slightly rotated around
+/// `contact`, in the plane spanned by its normal and `tangent`.
+///
+/// The rotation is emulated by tilting the directions along which the deepest points of the
+/// geometries are searched. The deepest point of each geometry is projected on the support
+/// hyperplane of the other one and kept only if this projection lies within the extents of the
+/// other geometry along the contact tangents. The contacts are expressed wrt. the unperturbed
+/// transforms.
+///
+/// # Arguments:
+/// * `contact` - a contact between `g1` and `g2`, as computed by `collide`.
+/// * `tangent` - a unit vector orthogonal to the contact normal.
+/// * `out`     - the vector to which the contacts are added.
+#[cfg(dim4)]
+pub fn perturbed_contacts<G1: Implicit<Vect, Matrix>, G2: Implicit<Vect, Matrix>>(
+                          m1:         &Matrix,
+                          g1:         &G1,
+                          m2:         &Matrix,
+                          g2:         &G2,
+                          contact:    &Contact,
+                          tangent:    &Vect,
+                          prediction: &Scalar,
+                          out:        &mut Vec<Contact>) {
+    let normal = contact.normal.clone();
+    let tilted = na::normalize(&(normal + *tangent * na::cast::<f32, Scalar>(0.01)));
+
+    // deepest point of `g1` projected on the support hyperplane of `g2`
+    let deepest1 = g1.support_point(m1, &tilted);
+    let depth1   = na::dot(&(deepest1 - contact.world2), &normal);
+    let proj1    = deepest1 - normal * depth1;
+
+    if depth1 >= -*prediction && is_within_tangent_extents(m2, g2, &normal, &proj1) {
+        out.push(Contact::new(deepest1, proj1, normal.clone(), depth1))
+    }
+
+    // deepest point of `g2` projected on the support hyperplane of `g1`
+    let deepest2 = g2.support_point(m2, &-tilted);
+    let depth2   = na::dot(&(contact.world1 - deepest2), &normal);
+    let proj2    = deepest2 + normal * depth2;
+
+    if depth2 >= -*prediction && is_within_tangent_extents(m1, g1, &normal, &proj2) {
+        out.push(Contact::new(proj2, deepest2, normal, depth2))
+    }
+}
+
+// Tests whether the projection of `pt` along `normal` lies between the support points of `g` along
+// each direction orthogonal to `normal`. The tolerance is relative to the extents of `g`.
+#[cfg(dim4)]
+fn is_within_tangent_extents<G: Implicit<Vect, Matrix>>(m: &Matrix, g: &G, normal: &Vect, pt: &Vect) -> bool {
+    let _rel_tol: Scalar = na::cast(1.0e-3);
+    let mut res = true;
+
+    na::orthonormal_subspace_basis(normal, |t| {
+        let max = na::dot(&t, &g.support_point(m, &t));
+        let min = na::dot(&t, &g.support_point(m, &-t));
+        let tol = (max - min) * _rel_tol;
+        let x   = na::dot(&t, pt);
+
+        res = x >= min - tol && x <= max + tol;
+
+        res
+    });
+
+    res
+}
+
+/// Tests whether two implicit geometries are intersecting or closer than a given margin.
+///
+/// This uses a version of the GJK algorithm which exits as soon as the result is known. Returns
+/// the proximity and, if the geometries are not intersecting, a separating axis which can be used
+/// as `init_dir` for the next query.
+///
+/// # Arguments:
+/// * `m1`       - the first geometry transform.
+/// * `g1`       - the first geometry.
+/// * `m2`       - the second geometry transform.
+/// * `g2`       - the second geometry.
+/// * `margin`   - the distance bellow which the geometries are considered close to each other.
+/// * `simplex`  - the simplex the GJK algorithm must use. It is reinitialized before being passed
+/// to GJK.
+/// * `init_dir` - the initial search direction, typically the separating axis returned by a
+/// previous query.
+pub fn proximity<S:  Simplex<AnnotatedPoint>,
+                 G1: Implicit<Vect, Matrix>,
+                 G2: Implicit<Vect, Matrix>>(
+                 m1:       &Matrix,
+                 g1:       &G1,
+                 m2:       &Matrix,
+                 g2:       &G2,
+                 margin:   &Scalar,
+                 simplex:  &mut S,
+                 init_dir: Option<Vect>)
+                 -> (Proximity, Option<Vect>) {
+    let mut dir =
+        match init_dir {
+            None      => m1.translation() - m2.translation(),
+            Some(dir) => dir
+        };
+
+    if dir.is_zero() {
+        dir.set(0, na::one());
+    }
+
+    simplex.reset(implicit::cso_support_point(m1, g1, m2, g2, dir));
+
+    match gjk::proximity(m1, g1, m2, g2, margin, simplex) {
+        Intersection        => (Intersecting, None),
+        Projection(dir)     => (WithinMargin, Some(dir)),
+        NoIntersection(dir) => (Disjoint, Some(dir))
+    }
+}
+
+/// Computes the distance between two implicit geometries.
+///
+/// The distance is computed with the GJK algorithm. If the geometries are intersecting, the
+/// distance is zero and the witness points are computed using a Minkowski sum sampling based
+/// algorithm (they correspond to the penetration depth).
+///
+/// Returns `None` if both algorithms failed.
+///
+/// # Arguments:
+/// * `m1`      - the first geometry transform.
+/// * `g1`      - the first geometry.
+/// * `m2`      - the second geometry transform.
+/// * `g2`      - the second geometry.
+/// * `simplex` - the simplex the GJK algorithm must use. It is reinitialized before being passed
+/// to GJK.
+pub fn distance<S:  Simplex<AnnotatedPoint>,
+                G1: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+                G2: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                m1:      &Matrix,
+                g1:      &G1,
+                m2:      &Matrix,
+                g2:      &G2,
+                simplex: &mut S)
+                -> Option<Distance> {
+    let mut dir = m1.translation() - m2.translation();
+
+    if dir.is_zero() {
+        dir.set(0, na::one());
+    }
+
+    simplex.reset(implicit::cso_support_point(m1, g1, m2, g2, dir.clone()));
+
+    match gjk::closest_points(m1, g1, m2, g2, simplex) {
+        Some((p1, p2)) => {
+            let mut normal = p2 - p1;
+            let dist       = normal.normalize();
+
+            if !dist.is_zero() {
+                return Some(Distance::new(p1, p2, normal, dist));
+            }
+        },
+        None => { } // fallback
+    }
+
+    // The geometries are intersecting: use the fallback algorithm
+    simplex.reset(implicit::cso_support_point_without_margin(m1, g1, m2, g2, dir.clone()));
+
+    match minkowski_sampling::closest_points(m1, g1, m2, g2, simplex) {
+        Some((p1, p2)) => {
+            let mut normal = p1 - p2;
+            let depth      = normal.normalize();
+
+            if !depth.is_zero() {
+                return Some(Distance::new(p1, p2, normal, na::zero()));
+            }
+        }
+        None => { }
+    }
+
+    // FIXME: both algorithms failed (see the FIXME on `collide`).
+    None
+}
+
 /// Computes the Time Of Impact of two geometries.
 ///
 /// # Arguments:
@@ -203,3 +509,76 @@ pub fn toi<G1: Implicit<Vect, Matrix>,
 
     cso.toi_with_ray(&Ray::new(Zero::zero(), -dir), true)
 }
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use std::cell::Cell;
+    use nalgebra::na::{Vec3, Iso3, Transform};
+    use nalgebra::na;
+    use geom::{AnnotatedPoint, Ball};
+    use implicit::{Implicit, HasMargin};
+    use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+    use narrow::{ProximityDetector, Intersecting, WithinMargin, Disjoint};
+    use super::ImplicitImplicitProximityDetector;
+
+    // A ball counting the number of times its support function is evaluated.
+    struct CountingBall {
+        center: Vec3<f64>,
+        radius: f64,
+        calls:  Cell<uint>
+    }
+
+    impl HasMargin for CountingBall {
+        fn margin(&self) -> f64 {
+            0.0
+        }
+    }
+
+    impl Implicit<Vec3<f64>, Iso3<f64>> for CountingBall {
+        fn support_point_without_margin(&self, m: &Iso3<f64>, dir: &Vec3<f64>) -> Vec3<f64> {
+            self.calls.set(self.calls.get() + 1);
+
+            m.transform(&self.center) + na::normalize(dir) * self.radius
+        }
+    }
+
+    #[test]
+    fn test_proximity_detector_ball_ball() {
+        let mut detector: ImplicitImplicitProximityDetector<JohnsonSimplex<AnnotatedPoint>, Ball, Ball> =
+            ImplicitImplicitProximityDetector::new(0.5, JohnsonSimplex::new_w_tls());
+        let b  = Ball::new(1.0f64);
+        let m1 = na::one::<Iso3<f64>>();
+
+        detector.update(&m1, &b, &Iso3::new(Vec3::new(1.5f64, 0.0, 0.0), na::zero()), &b);
+        assert!(detector.proximity() == Intersecting);
+
+        detector.update(&m1, &b, &Iso3::new(Vec3::new(2.3f64, 0.0, 0.0), na::zero()), &b);
+        assert!(detector.proximity() == WithinMargin);
+
+        detector.update(&m1, &b, &Iso3::new(Vec3::new(3.0f64, 0.0, 0.0), na::zero()), &b);
+        assert!(detector.proximity() == Disjoint);
+    }
+
+    #[test]
+    fn test_proximity_detector_reuses_separating_axis() {
+        let mut detector: ImplicitImplicitProximityDetector<JohnsonSimplex<AnnotatedPoint>, CountingBall, Ball> =
+            ImplicitImplicitProximityDetector::new(0.5, JohnsonSimplex::new_w_tls());
+        let b1 = CountingBall { center: Vec3::new(0.0f64, 10.0, 0.0), radius: 1.0, calls: Cell::new(0) };
+        let b2 = Ball::new(1.0f64);
+        let m1 = na::one::<Iso3<f64>>();
+        let m2 = Iso3::new(Vec3::new(2.8f64, 10.0, 0.0), na::zero());
+
+        // The initial direction joins the transform origins: it is far from the separating axis.
+        detector.update(&m1, &b1, &m2, &b2);
+        assert!(detector.proximity() == Disjoint);
+        assert!(detector.sep_axis.is_some());
+        assert!(b1.calls.get() > 2);
+
+        // The cached separating axis makes GJK exit at its first iteration: one support point
+        // evaluation initializes the simplex, the other one proves the separation.
+        b1.calls.set(0);
+        detector.update(&m1, &b1, &m2, &b2);
+        assert!(detector.proximity() == Disjoint);
+        assert!(b1.calls.get() == 2);
+    }
+}
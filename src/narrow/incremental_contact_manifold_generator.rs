@@ -1,39 +1,26 @@
 use nalgebra::na::Transform;
 use nalgebra::na;
 use narrow::CollisionDetector;
-use contact::Contact;
+use contact::{Contact, ContactWLocals, UnknownFeature};
 use math::{Scalar, Vect, Matrix};
 
-#[deriving(Encodable, Decodable, Clone)]
-struct ContactWLocals {
-    local1:  Vect,
-    local2:  Vect,
-    center:  Vect,
-    contact: Contact
-}
-
-impl ContactWLocals {
-    fn new_with_contact(contact: Contact, m1: &Matrix, m2: &Matrix) -> ContactWLocals {
-            ContactWLocals {
-                local1: m1.inv_transform(&contact.world1),
-                local2: m2.inv_transform(&contact.world2),
-                center: (contact.world1 + contact.world2) * na::cast::<f32, Scalar>(0.5),
-                contact: contact
-            }
-        }
-}
-
 /// Contact manifold generator which keeps track of several contacts.
 ///
 /// One contact is added per update until the maximum number of contact is reached. When the
 /// maximum number of contact is reached, each time a new contact is created, the new manifold is
 /// computed by maximizing the variance along each canonical axis (of the space in which leaves the
 /// contacts).
+///
+/// Each contact is given an identifier which does not change as long as the contact stays on the
+/// manifold. A new contact generated by the same features as an existing one replaces it and keeps
+/// its identifier. If the features are not fully known, the new contact replaces an existing one
+/// closer than 0.1 to it.
 #[deriving(Encodable, Decodable, Clone)]
 pub struct IncrementalContactManifoldGenerator<CD> {
     contacts:     Vec<ContactWLocals>,
     collector:    Vec<Contact>,
     prediction:   Scalar,
+    next_id:      uint,
     sub_detector: CD
 }
 
@@ -47,11 +34,49 @@ impl<CD> IncrementalContactManifoldGenerator<CD> {
             contacts:     Vec::new(),
             collector:    Vec::new(),
             prediction:   prediction,
+            next_id:      0,
             sub_detector: cd
         }
     }
 }
 
+impl<CD> IncrementalContactManifoldGenerator<CD> {
+    /// The contacts of this manifold, together with their local-space points.
+    #[inline]
+    pub fn contacts_with_locals<'a>(&'a self) -> &'a [ContactWLocals] {
+        self.contacts.as_slice()
+    }
+
+    /// Adds a contact to the manifold.
+    ///
+    /// If the contact already exists on the manifold, it is replaced and keeps its identifier.
+    /// Otherwise, if the manifold is full, the contacts maximizing the variance are kept.
+    pub fn add_contact(&mut self, contact: Contact, m1: &Matrix, m2: &Matrix) {
+        let mut c = contact;
+        let _max_num_contact = (na::dim::<Vect>() - 1) * 2;
+
+        match self.contacts.iter().position(|old| same_contact(&old.contact, &c)) {
+            Some(i) => {
+                c.id = self.contacts.get(i).contact.id;
+                *self.contacts.get_mut(i) = ContactWLocals::new_with_contact(c, m1, m2);
+
+                return;
+            },
+            None => {
+                c.id         = self.next_id;
+                self.next_id = self.next_id + 1;
+            }
+        }
+
+        if self.contacts.len() == _max_num_contact {
+            add_reduce_by_variance(self.contacts.as_mut_slice(), c, m1, m2)
+        }
+        else {
+            self.contacts.push(ContactWLocals::new_with_contact(c, m1, m2))
+        }
+    }
+}
+
 impl<CD: CollisionDetector<G1, G2>, G1, G2> IncrementalContactManifoldGenerator<CD> {
     /// Gets a collision from the sub-detector used by this manifold generator. This does not
     /// update the manifold itself.
@@ -77,17 +102,29 @@ impl<CD: CollisionDetector<G1, G2>, G1, G2> IncrementalContactManifoldGenerator<
         self.sub_detector.update(m1, g1, m2, g2);
 
         self.sub_detector.colls(&mut self.collector);
+        self.add_collected_contacts(m1, m2);
+    }
 
-        // remove duplicates
-        let _max_num_contact = (na::dim::<Vect>() - 1) * 2;
+    /// Updates the current manifold by adding the contacts the objects would have if the first
+    /// one was slightly rotated around `contact`, in the plane spanned by its normal and
+    /// `tangent`.
+    #[cfg(dim4)]
+    pub fn add_perturbed_contacts(&mut self,
+                                  m1:      &Matrix,
+                                  g1:      &G1,
+                                  m2:      &Matrix,
+                                  g2:      &G2,
+                                  contact: &Contact,
+                                  tangent: &Vect) {
+        self.sub_detector.perturbed_colls(m1, g1, m2, g2, contact, tangent, &mut self.collector);
+        self.add_collected_contacts(m1, m2);
+    }
 
-        for c in self.collector.iter() {
-            if self.contacts.len() == _max_num_contact {
-                add_reduce_by_variance(self.contacts.as_mut_slice(), c.clone(), m1, m2)
-            }
-            else {
-                self.contacts.push(ContactWLocals::new_with_contact(c.clone(), m1, m2))
-            }
+    fn add_collected_contacts(&mut self, m1: &Matrix, m2: &Matrix) {
+        for i in range(0u, self.collector.len()) {
+            let c = self.collector.get(i).clone();
+
+            self.add_contact(c, m1, m2);
         }
 
         self.collector.clear();
@@ -105,9 +142,10 @@ impl<CD: CollisionDetector<G1, G2>, G1, G2> IncrementalContactManifoldGenerator<
 
                 let dw    = world1 - world2;
                 let depth = na::dot(&dw, &c.contact.normal);
+                let tol   = tolerance();
 
                 if depth >= -self.prediction &&
-                   na::sqnorm(&(dw - c.contact.normal * depth)) <= na::cast(0.01) {
+                   na::sqnorm(&(dw - c.contact.normal * depth)) <= tol * tol {
                         c.contact.depth  = depth;
                         c.contact.world1 = world1;
                         c.contact.world2 = world2;
@@ -149,6 +187,23 @@ CollisionDetector<G1, G2> for IncrementalContactManifoldGenerator<CD> {
         }
     }
 
+    // The local points are those stored on the manifold, which are already up to date.
+    #[inline]
+    fn colls_with_locals(&self, _: &Matrix, _: &Matrix, out: &mut Vec<ContactWLocals>) {
+        for c in self.contacts.iter() {
+            out.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        // The sub-detector is usually created with a zero prediction, contacts being kept up to
+        // the prediction distance by the manifold. Once a pair-specific prediction is set, the
+        // sub-detector generates new contacts up to this distance too.
+        self.prediction = prediction.clone();
+        self.sub_detector.set_prediction(prediction)
+    }
+
     #[inline]
     fn toi(_:    Option<IncrementalContactManifoldGenerator<CD>>,
            m1:   &Matrix,
@@ -162,6 +217,34 @@ CollisionDetector<G1, G2> for IncrementalContactManifoldGenerator<CD> {
 
 }
 
+// Whether `new` is a new occurence of the contact `old`, i.e., if they are generated by the same
+// features or if they are close enough.
+//
+// Features identify a contact only if both are known: e.g., all the contacts between a plane and
+// an implicit geometry lie on the same plane face.
+fn same_contact(old: &Contact, new: &Contact) -> bool {
+    let known = old.feature1 != UnknownFeature && old.feature2 != UnknownFeature &&
+                new.feature1 != UnknownFeature && new.feature2 != UnknownFeature;
+
+    if known {
+        old.feature1 == new.feature1 && old.feature2 == new.feature2 &&
+        old.part1 == new.part1 && old.part2 == new.part2
+    }
+    else {
+        let old_center = (old.world1 + old.world2) * na::cast::<f32, Scalar>(0.5);
+        let new_center = (new.world1 + new.world2) * na::cast::<f32, Scalar>(0.5);
+        let tol        = tolerance();
+
+        na::sqnorm(&(old_center - new_center)) <= tol * tol
+    }
+}
+
+// Distance under which two contacts are considered the same.
+#[inline]
+fn tolerance() -> Scalar {
+    na::cast(0.1f64)
+}
+
 fn add_reduce_by_variance(pts: &mut [ContactWLocals], to_add: Contact, m1: &Matrix, m2: &Matrix) {
     let mut argmax = 0;
     let mut varmax = approx_variance(pts, &to_add, 0);
@@ -186,7 +269,7 @@ fn approx_variance(pts: &[ContactWLocals], to_add: &Contact, to_ignore: uint) ->
 
     for i in range(0u, pts.len()) {
         if i != to_ignore {
-            mean = mean + pts[i].center
+            mean = mean + pts[i].center()
         }
     }
 
@@ -198,9 +281,142 @@ fn approx_variance(pts: &[ContactWLocals], to_add: &Contact, to_ignore: uint) ->
 
     for i in range(0u, pts.len()) {
         if i != to_ignore {
-            sum = sum + na::sqnorm(&(pts[i].center - mean));
+            sum = sum + na::sqnorm(&(pts[i].center() - mean));
         }
     }
 
     sum
 }
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3, Transform};
+    use nalgebra::na;
+    use geom::{Plane, Cuboid, Ball};
+    use narrow::{CollisionDetector, PlaneImplicit};
+    use super::IncrementalContactManifoldGenerator;
+
+    #[test]
+    fn test_box_on_plane_manifold() {
+        let p  = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let c  = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let mp = na::one::<Iso3<f64>>();
+        let mut manifold = IncrementalContactManifoldGenerator::new(0.1, PlaneImplicit::<Cuboid>::new(0.1));
+
+        // The box rocks slightly so that its deepest point changes at each update.
+        let tilts = [ Vec3::new(0.02f64, 0.0, 0.0), Vec3::new(-0.02f64, 0.0, 0.0),
+                      Vec3::new(0.0f64, 0.0, 0.02), Vec3::new(0.0f64, 0.0, -0.02) ];
+
+        for tilt in tilts.iter() {
+            let mc = Iso3::new(Vec3::new(0.0f64, 0.99, 0.0), tilt.clone());
+
+            manifold.update(&mp, &p, &mc, &c);
+        }
+
+        assert!(manifold.num_colls() > 1);
+
+        let mut contacts = Vec::new();
+
+        manifold.colls(&mut contacts);
+
+        for (i, c1) in contacts.iter().enumerate() {
+            assert!(na::approx_eq_eps(&c1.normal, &Vec3::new(0.0, 1.0, 0.0), &1.0e-7));
+
+            for c2 in contacts.slice_from(i + 1).iter() {
+                // All the contacts are distinct corners of the box.
+                assert!(na::norm(&(c1.world2 - c2.world2)) > 1.0);
+                assert!(c1.id != c2.id);
+            }
+        }
+
+        // The box moves slightly: its corners keep their identifiers.
+        let mc = Iso3::new(Vec3::new(0.01f64, 0.99, 0.0), tilts[3].clone());
+
+        manifold.update(&mp, &p, &mc, &c);
+
+        let mut moved = Vec::new();
+
+        manifold.colls(&mut moved);
+
+        assert!(moved.len() == contacts.len());
+
+        for c1 in contacts.iter() {
+            assert!(moved.iter().any(|c2| c1.id == c2.id &&
+                                          na::norm(&(c1.world2 - c2.world2)) < 0.05));
+        }
+    }
+
+    #[test]
+    fn test_ball_on_plane_at_origin_keeps_id() {
+        // The contact point on the plane is its origin.
+        let p  = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let b  = Ball::new(1.0f64);
+        let mp = na::one::<Iso3<f64>>();
+        let mut manifold = IncrementalContactManifoldGenerator::new(0.1, PlaneImplicit::<Ball>::new(0.1));
+
+        manifold.update(&mp, &p, &Iso3::new(Vec3::new(0.0f64, 0.99, 0.0), na::zero()), &b);
+
+        let mut contacts = Vec::new();
+
+        manifold.colls(&mut contacts);
+        assert!(contacts.len() == 1);
+
+        let id = contacts.get(0).id;
+
+        contacts.clear();
+        manifold.update(&mp, &p, &Iso3::new(Vec3::new(0.001f64, 0.98, 0.0), na::zero()), &b);
+        manifold.colls(&mut contacts);
+
+        assert!(contacts.len() == 1);
+        assert!(contacts.get(0).id == id);
+        assert!(na::approx_eq_eps(&contacts.get(0).depth, &0.02, &1.0e-7));
+        assert!(na::approx_eq_eps(&contacts.get(0).world1, &Vec3::new(0.001, 0.0, 0.0), &1.0e-7));
+
+        // Far from the previous one, the contact is a new one.
+        contacts.clear();
+        manifold.update(&mp, &p, &Iso3::new(Vec3::new(2.0f64, 0.98, 0.0), na::zero()), &b);
+        manifold.colls(&mut contacts);
+
+        assert!(contacts.len() == 1);
+        assert!(contacts.get(0).id != id);
+    }
+
+    #[test]
+    fn test_colls_with_locals() {
+        let p  = Plane::new(Vec3::new(0.0f64, 1.0, 0.0));
+        let c  = Cuboid::new(Vec3::new(1.0f64, 1.0, 1.0));
+        let mp = na::one::<Iso3<f64>>();
+        let mc = Iso3::new(Vec3::new(0.0f64, 0.9, 0.0), Vec3::new(0.0f64, 0.0, 0.1));
+        let mut manifold = IncrementalContactManifoldGenerator::new(0.1, PlaneImplicit::<Cuboid>::new(0.1));
+        let mut detector = PlaneImplicit::<Cuboid>::new(0.1);
+
+        manifold.update(&mp, &p, &mc, &c);
+        detector.update(&mp, &p, &mc, &c);
+
+        let mut manifold_locals = Vec::new();
+        let mut detector_locals = Vec::new();
+
+        manifold.colls_with_locals(&mp, &mc, &mut manifold_locals);
+        detector.colls_with_locals(&mp, &mc, &mut detector_locals);
+
+        assert!(manifold_locals.len() == manifold.num_colls());
+        assert!(detector_locals.len() == detector.num_colls());
+        assert!(detector_locals.len() == 1);
+
+        for c in manifold_locals.iter().chain(detector_locals.iter()) {
+            assert!(na::approx_eq_eps(&mp.transform(&c.local1), &c.contact.world1, &1.0e-7));
+            assert!(na::approx_eq_eps(&mc.transform(&c.local2), &c.contact.world2, &1.0e-7));
+
+            let mut ntangents = 0u;
+
+            c.tangents(|t| {
+                assert!(na::approx_eq_eps(&na::dot(&t, &c.contact.normal), &0.0, &1.0e-7));
+                assert!(na::approx_eq_eps(&na::norm(&t), &1.0, &1.0e-7));
+                ntangents = ntangents + 1;
+                true
+            });
+
+            assert!(ntangents == 2);
+        }
+    }
+}
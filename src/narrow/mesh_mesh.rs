@@ -0,0 +1,357 @@
+//! Exact intersection between two triangle meshes, and mesh self-intersection.
+
+use nalgebra::na;
+use bounding_volume::BoundingVolume;
+use geom::Mesh;
+use narrow::triangle_triangle;
+use math::{Scalar, Vect, Matrix};
+
+/// The intersection between two triangle meshes.
+#[deriving(Show, Clone)]
+pub struct MeshIntersection {
+    /// Indices of the pairs of intersecting triangles. The first index is a triangle of the first
+    /// mesh, the second index is a triangle of the second mesh.
+    pub pairs:     Vec<(uint, uint)>,
+
+    /// The segments along which the triangles of both meshes cross, in world space.
+    ///
+    /// Coplanar triangles do not generate any segment.
+    pub segments:  Vec<(Vect, Vect)>,
+
+    /// The polylines obtained by joining the segments sharing an extremity, in world space.
+    ///
+    /// A closed polyline has its first and last points equal.
+    pub polylines: Vec<Vec<Vect>>
+}
+
+/// The defects of a triangle mesh.
+#[deriving(Show, Clone)]
+pub struct MeshDefects {
+    /// Pairs of intersecting triangles which do not share any vertex. The first index is always
+    /// smaller than the second one.
+    pub intersecting_pairs:   Vec<(uint, uint)>,
+
+    /// Triangles with a zero area.
+    pub degenerate_triangles: Vec<uint>,
+
+    /// Pairs of triangles with the same vertices. The first index is always smaller than the
+    /// second one.
+    pub duplicate_faces:      Vec<(uint, uint)>
+}
+
+impl MeshDefects {
+    /// Whether no defect has been found.
+    pub fn is_empty(&self) -> bool {
+        self.intersecting_pairs.is_empty() &&
+        self.degenerate_triangles.is_empty() &&
+        self.duplicate_faces.is_empty()
+    }
+}
+
+/// Computes the intersection between two transformed triangle meshes.
+///
+/// The bounding volume trees of both meshes are traversed simultaneously to find pairs of
+/// triangles that might intersect. Margins are ignored.
+pub fn intersection(m1: &Matrix, g1: &Mesh, m2: &Matrix, g2: &Mesh) -> MeshIntersection {
+    let ls_m2 = na::inv(m1).expect("The transformation `m1` must be inversible.") * *m2;
+
+    let mut candidates = Vec::new();
+
+    g1.bvt().visit_bvtt(g2.bvt(),
+                        &mut |bv1, bv2| bv1.intersects(&bv2.transformed(&ls_m2)),
+                        &mut |i, j| candidates.push((*i, *j)));
+
+    let mut pairs    = Vec::new();
+    let mut segments = Vec::new();
+
+    for &(i, j) in candidates.iter() {
+        let t1 = g1.element_at(i);
+        let t2 = g2.element_at(j);
+
+        if triangle_triangle::intersects(m1, &t1, m2, &t2) {
+            pairs.push((i, j));
+
+            match triangle_triangle::intersection(m1, &t1, m2, &t2) {
+                Some(s) => segments.push(s),
+                None    => { }
+            }
+        }
+    }
+
+    // The extremities of the segments are computed independently by each pair of triangles: use a
+    // tolerance relative to their magnitude.
+    let mut scale: Scalar = na::one();
+
+    for &(ref a, ref b) in segments.iter() {
+        scale = na::max(scale, na::max(na::norm(a), na::norm(b)));
+    }
+
+    let polylines = join_segments(segments.as_slice(), &(_eps() * scale));
+
+    MeshIntersection {
+        pairs:     pairs,
+        segments:  segments,
+        polylines: polylines
+    }
+}
+
+/// Finds the self-intersections, degenerate triangles and duplicated faces of a triangle mesh.
+///
+/// The bounding volume tree of the mesh is traversed against itself. Triangles sharing a vertex
+/// (either the same index or the same position) are adjacent and are not tested for intersection.
+/// Degenerate triangles are not tested for intersection either.
+pub fn self_intersection(g: &Mesh) -> MeshDefects {
+    let id: Matrix = na::one();
+    let mut candidates = Vec::new();
+
+    g.bvt().visit_bvtt(g.bvt(),
+                       &mut |bv1, bv2| bv1.intersects(bv2),
+                       &mut |i, j| if *i < *j { candidates.push((*i, *j)) });
+
+    let is = g.indices().as_slice();
+    let vs = g.vertices().as_slice();
+
+    let mut res = MeshDefects {
+        intersecting_pairs:   Vec::new(),
+        degenerate_triangles: Vec::new(),
+        duplicate_faces:      Vec::new()
+    };
+
+    let ntriangles     = is.len() / 3;
+    let mut degenerate = Vec::from_elem(ntriangles, false);
+
+    for i in range(0u, ntriangles) {
+        if is_degenerate(g, i) {
+            *degenerate.get_mut(i) = true;
+            res.degenerate_triangles.push(i)
+        }
+    }
+
+    for &(i, j) in candidates.iter() {
+        let face1 = is.slice(i * 3, i * 3 + 3);
+        let face2 = is.slice(j * 3, j * 3 + 3);
+
+        let mut nshared = 0u;
+
+        for a in face1.iter() {
+            if face2.iter().any(|b| *a == *b || vs[*a] == vs[*b]) {
+                nshared = nshared + 1;
+            }
+        }
+
+        if nshared == 3 {
+            res.duplicate_faces.push((i, j));
+        }
+        else if nshared == 0 &&
+                !*degenerate.get(i) &&
+                !*degenerate.get(j) &&
+                triangle_triangle::intersects(&id, &g.element_at(i), &id, &g.element_at(j)) {
+            res.intersecting_pairs.push((i, j));
+        }
+    }
+
+    res
+}
+
+fn is_degenerate(g: &Mesh, i: uint) -> bool {
+    let t     = g.element_at(i);
+    let ab    = t.b() - *t.a();
+    let ac    = t.c() - *t.a();
+    let scale = na::max(na::sqnorm(&ab), na::sqnorm(&ac));
+    let area2 = na::sqnorm(&na::cross(&ab, &ac));
+
+    area2 <= _eps() * _eps() * scale * scale
+}
+
+/// Joins segments sharing an extremity into polylines.
+///
+/// Two points closer than `eps` are considered equal. This is quadratic wrt. the number of
+/// segments.
+pub fn join_segments(segments: &[(Vect, Vect)], eps: &Scalar) -> Vec<Vec<Vect>> {
+    let mut used = Vec::from_elem(segments.len(), false);
+    let mut res  = Vec::new();
+
+    for i in range(0u, segments.len()) {
+        if *used.get(i) {
+            continue;
+        }
+
+        *used.get_mut(i) = true;
+
+        let (ref a, ref b) = segments[i];
+
+        // `front` is stored in reverse order.
+        let mut front = vec!(a.clone());
+        let mut back  = vec!(b.clone());
+
+        extend(segments, &mut used, &mut back, eps);
+        extend(segments, &mut used, &mut front, eps);
+
+        let mut polyline: Vec<Vect> = front.move_iter().rev().collect();
+
+        polyline.push_all_move(back);
+        res.push(polyline);
+    }
+
+    res
+}
+
+// Appends to `line` the unused segments connected to its last point.
+fn extend(segments: &[(Vect, Vect)], used: &mut Vec<bool>, line: &mut Vec<Vect>, eps: &Scalar) {
+    loop {
+        let last = line.last().unwrap().clone();
+        let mut next = None;
+
+        for (j, &(ref a, ref b)) in segments.iter().enumerate() {
+            if !*used.get(j) {
+                if na::approx_eq_eps(a, &last, eps) {
+                    next = Some((j, b.clone()));
+                    break;
+                }
+                else if na::approx_eq_eps(b, &last, eps) {
+                    next = Some((j, a.clone()));
+                    break;
+                }
+            }
+        }
+
+        match next {
+            Some((j, pt)) => {
+                *used.get_mut(j) = true;
+                line.push(pt);
+            },
+            None => break
+        }
+    }
+}
+
+// Relative tolerance. It must stay above the precision of `Scalar`, i.e., about 1.0e-5 for `f32`.
+#[inline]
+fn _eps() -> Scalar {
+    na::max(na::cast(1.0e-7), Float::epsilon() * na::cast(100.0))
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use sync::Arc;
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Mesh;
+    use super::{intersection, self_intersection, join_segments};
+
+    // A square made of two triangles, given its four corners.
+    fn square(a: Vec3<f64>, b: Vec3<f64>, c: Vec3<f64>, d: Vec3<f64>) -> Mesh {
+        Mesh::new(Arc::new(vec!(a, b, c, d)), Arc::new(vec!(0u, 1, 2, 0, 2, 3)), None, None)
+    }
+
+    #[test]
+    fn test_mesh_mesh_intersection() {
+        // Horizontal square on the plane `y = 0`.
+        let m1 = square(Vec3::new(-1.0, 0.0, -1.0), Vec3::new(1.0, 0.0, -1.0),
+                        Vec3::new(1.0, 0.0, 1.0), Vec3::new(-1.0, 0.0, 1.0));
+        // Vertical square on the plane `z = 0`, crossing the first one along `x` in [-0.4, 0.6].
+        let m2 = square(Vec3::new(-0.4, -0.5, 0.0), Vec3::new(0.6, -0.5, 0.0),
+                        Vec3::new(0.6, 0.5, 0.0), Vec3::new(-0.4, 0.5, 0.0));
+        let id = na::one::<Iso3<f64>>();
+
+        let res = intersection(&id, &m1, &id, &m2);
+
+        assert!(!res.pairs.is_empty());
+        assert!(res.pairs.len() == res.segments.len());
+        assert!(res.polylines.len() == 1);
+
+        let line  = res.polylines.get(0);
+        let first = line.get(0).clone();
+        let last  = line.last().unwrap().clone();
+        let a     = Vec3::new(-0.4, 0.0, 0.0);
+        let b     = Vec3::new(0.6, 0.0, 0.0);
+
+        assert!((na::approx_eq(&first, &a) && na::approx_eq(&last, &b)) ||
+                (na::approx_eq(&first, &b) && na::approx_eq(&last, &a)));
+
+        for pt in line.iter() {
+            assert!(na::approx_eq(&pt.y, &0.0));
+            assert!(na::approx_eq(&pt.z, &0.0));
+        }
+
+        // Once moved away, the meshes do not intersect.
+        let far = Iso3::new(Vec3::new(0.0f64, 0.0, 3.0), na::zero());
+        let res = intersection(&id, &m1, &far, &m2);
+
+        assert!(res.pairs.is_empty());
+        assert!(res.polylines.is_empty());
+    }
+
+    #[test]
+    fn test_join_segments() {
+        let a = Vec3::new(0.0f64, 0.0, 0.0);
+        let b = Vec3::new(1.0f64, 0.0, 0.0);
+        let c = Vec3::new(1.0f64, 1.0, 0.0);
+        let d = Vec3::new(1.0f64, 1.0, 1.0);
+        let e = Vec3::new(5.0f64, 0.0, 0.0);
+        let f = Vec3::new(6.0f64, 0.0, 0.0);
+
+        // Unordered and inconsistently oriented segments.
+        let segments = [ (b, c), (e, f), (b, a), (c, d) ];
+        let lines    = join_segments(segments.as_slice(), &1.0e-7);
+
+        assert!(lines.len() == 2);
+        assert!(*lines.get(0) == vec!(a, b, c, d) || *lines.get(0) == vec!(d, c, b, a));
+        assert!(*lines.get(1) == vec!(e, f) || *lines.get(1) == vec!(f, e));
+
+        // A closed polyline.
+        let segments = [ (a, b), (c, a), (b, c) ];
+        let lines    = join_segments(segments.as_slice(), &1.0e-7);
+
+        assert!(lines.len() == 1);
+        assert!(lines.get(0).len() == 4);
+        assert!(lines.get(0).get(0) == lines.get(0).last().unwrap());
+    }
+
+    #[test]
+    fn test_self_intersection_clean() {
+        // A tetrahedron.
+        let vertices = vec!(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0f64, 0.0, 0.0),
+                            Vec3::new(0.0f64, 1.0, 0.0), Vec3::new(0.0f64, 0.0, 1.0));
+        let indices  = vec!(0u, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3);
+        let m        = Mesh::new(Arc::new(vertices), Arc::new(indices), None, None);
+
+        assert!(self_intersection(&m).is_empty());
+    }
+
+    #[test]
+    fn test_self_intersection_duplicate_and_degenerate() {
+        let vertices = vec!(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0f64, 0.0, 0.0),
+                            Vec3::new(0.0f64, 1.0, 0.0), Vec3::new(0.5f64, 0.0, 0.0),
+                            // A copy of the first vertex.
+                            Vec3::new(0.0f64, 0.0, 0.0));
+        // The second face duplicates the first one using the copied vertex. The third face is
+        // flat.
+        let indices  = vec!(0u, 1, 2, 2, 1, 4, 0, 3, 1);
+        let m        = Mesh::new(Arc::new(vertices), Arc::new(indices), None, None);
+
+        let defects = self_intersection(&m);
+
+        assert!(defects.duplicate_faces == vec!((0u, 1u)));
+        assert!(defects.degenerate_triangles == vec!(2u));
+        assert!(defects.intersecting_pairs.is_empty());
+    }
+
+    #[test]
+    fn test_self_intersection_folded() {
+        // Two triangles on the plane `y = 0` and a third one folded over them, crossing the first
+        // one without sharing any vertex.
+        let vertices = vec!(Vec3::new(-1.0f64, 0.0, -1.0), Vec3::new(1.0f64, 0.0, -1.0),
+                            Vec3::new(1.0f64, 0.0, 1.0), Vec3::new(-1.0f64, 0.0, 1.0),
+                            Vec3::new(0.5f64, -0.5, -0.5), Vec3::new(0.5f64, 0.5, -0.5),
+                            Vec3::new(0.9f64, 0.5, -0.9));
+        let indices  = vec!(0u, 1, 2, 0, 2, 3, 4, 5, 6);
+        let m        = Mesh::new(Arc::new(vertices), Arc::new(indices), None, None);
+
+        let defects = self_intersection(&m);
+
+        assert!(defects.intersecting_pairs == vec!((0u, 2u)));
+        assert!(defects.degenerate_triangles.is_empty());
+        assert!(defects.duplicate_faces.is_empty());
+    }
+}
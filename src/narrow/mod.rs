@@ -2,49 +2,162 @@
 
 // types and traits
 pub use narrow::collision_detector::CollisionDetector;
+pub use narrow::proximity_detector::{ProximityDetector, Proximity, Intersecting, WithinMargin, Disjoint};
 pub use narrow::empty::Empty;
 pub use narrow::ball_ball::BallBall;
 pub use narrow::plane_implicit::{PlaneImplicit, ImplicitPlane};
-pub use narrow::implicit_implicit::ImplicitImplicit;
+pub use narrow::ball_capsule::{BallCapsule, CapsuleBall};
+pub use narrow::ball_segment::{BallSegment, SegmentBall};
+pub use narrow::ball_triangle::{BallTriangle, TriangleBall, TriangleFeature, TriangleFace, TriangleEdge,
+                                TriangleVertex};
+#[cfg(dim3)]
+pub use narrow::ball_mesh::{BallMesh, MeshBall};
+pub use narrow::capsule_capsule::CapsuleCapsule;
+pub use narrow::segment_segment::SegmentSegment;
+#[cfg(not(dim4))]
+pub use narrow::cuboid_cuboid::CuboidCuboid;
+pub use narrow::implicit_implicit::{ImplicitImplicit, ImplicitImplicitProximityDetector,
+                                    PenetrationFallback, MinkowskiSamplingFallback,
+                                    NoPenetrationFallback};
 pub use narrow::incremental_contact_manifold_generator::IncrementalContactManifoldGenerator;
 pub use narrow::one_shot_contact_manifold_generator::OneShotContactManifoldGenerator;
+#[cfg(not(dim4))]
+pub use narrow::polyhedral_contact_manifold_generator::{PolyhedralContactManifoldGenerator, Polyhedron};
 pub use narrow::geom_geom::{DynamicCollisionDetector, GeomGeomCollisionDetector, GeomGeomDispatcher,
-                            CollisionDetectorFactory};
-pub use narrow::concave_geom_geom::{ConcaveGeomGeom, GeomConcaveGeom, ConcaveGeomGeomFactory,
-                                    GeomConcaveGeomFactory};
+                            GeomGeomDispatcherBuilder, ContactManifoldStrategy, NoManifold,
+                            OneShotManifold, IncrementalManifold, ClippingManifold,
+                            CollisionDetectorFactory, CollisionDetectorCloner, GeomTypeMismatch,
+                            downcast_geoms};
+pub use narrow::geom_geom_fallback::{DispatchStrategy, RegisteredStrategy, ImplicitImplicitStrategy,
+                                     PlaneImplicitStrategy, ImplicitPlaneStrategy, ConcaveGeomStrategy,
+                                     GeomConcaveStrategy, FallbackDetectorFactory, FallbackDetectors,
+                                     ImplicitGeomImplicitGeom, PlaneImplicitGeom};
+pub use narrow::geom_geom_toi::{GeomGeomTOIDispatcher, GeomGeomTOI, DynamicTOI, CollisionDetectorTOI,
+                                TOIResult, Impact, NoImpact, UnsupportedGeomPair};
+pub use narrow::geom_geom_distance::{Distance, GeomGeomDistanceDispatcher, GeomGeomDistance,
+                                     DynamicDistance, BallBallDistance, PlaneImplicitDistance, ImplicitPlaneDistance,
+                                     ImplicitImplicitDistance};
+pub use narrow::geom_geom_proximity::{GeomGeomProximityDispatcher, GeomGeomProximity,
+                                      DynamicProximity, CollisionDetectorProximity, BallBallProximity,
+                                      PlaneImplicitProximity, ImplicitPlaneProximity,
+                                      ImplicitImplicitProximity};
+pub use narrow::concave_geom_geom::{ConcaveGeomGeom, GeomConcaveGeom, ConcaveGeomConcaveGeom,
+                                    ConcaveGeomGeomFactory, GeomConcaveGeomFactory,
+                                    ConcaveGeomConcaveGeomFactory, ConcaveGeomGeomTOI, GeomConcaveGeomTOI,
+                                    ConcaveGeomConcaveGeomTOI, ConcaveGeomGeomDistance,
+                                    GeomConcaveGeomDistance, ConcaveGeomGeomProximity,
+                                    GeomConcaveGeomProximity, concave_geom_any_detector};
 pub use narrow::bezier_surface_ball::{BallBezierSurface, BezierSurfaceBall};
+pub use narrow::bezier_surface_implicit::{ImplicitBezierSurface, BezierSurfaceImplicit};
+pub use narrow::bezier_surface_bezier_surface::BezierSurfaceBezierSurface;
+pub use narrow::bezier_curve_ball::{BallBezierCurve, BezierCurveBall};
+pub use narrow::bezier_curve_implicit::{ImplicitBezierCurve, BezierCurveImplicit};
+#[cfg(dim3)]
+pub use narrow::mesh_mesh::{MeshIntersection, MeshDefects};
 
 // functions
 /// Functions to compute the time of impact between two geometries.
 pub mod toi {
-    pub use ball_ball         = narrow::ball_ball::toi;
-    pub use plane_implicit    = narrow::plane_implicit::toi;
-    pub use implicit_implicit = narrow::implicit_implicit::toi;
+    pub use ball_ball                     = narrow::ball_ball::toi;
+    pub use ball_bezier_surface           = narrow::bezier_surface_ball::toi;
+    pub use implicit_bezier_surface       = narrow::bezier_surface_implicit::toi;
+    pub use bezier_surface_bezier_surface = narrow::bezier_surface_bezier_surface::toi;
+    pub use implicit_bezier_curve         = narrow::bezier_curve_implicit::toi;
+    pub use plane_implicit                = narrow::plane_implicit::toi;
+    pub use implicit_implicit             = narrow::implicit_implicit::toi;
+    pub use concave_geom_geom             = narrow::concave_geom_geom::toi;
+    pub use concave_geom_concave_geom     = narrow::concave_geom_geom::toi_concave_concave;
 }
 
 /// Functions to compute one contact point between two geometries.
 pub mod collide {
     pub use ball_ball         = narrow::ball_ball::collide;
     pub use plane_implicit    = narrow::plane_implicit::collide;
+    pub use ball_capsule      = narrow::ball_capsule::collide;
+    pub use ball_segment      = narrow::ball_segment::collide;
+    pub use ball_triangle     = narrow::ball_triangle::collide;
+    pub use capsule_capsule   = narrow::capsule_capsule::collide;
+    pub use segment_segment   = narrow::segment_segment::collide;
     pub use implicit_implicit = narrow::implicit_implicit::collide;
+    #[cfg(not(dim4))]
+    pub use cuboid_cuboid     = narrow::cuboid_cuboid::collide;
+}
+
+/// Functions to compute a full contact manifold between two geometries.
+pub mod manifold {
+    #[cfg(not(dim4))]
+    pub use polyhedral        = narrow::polyhedral_contact_manifold_generator::clip_manifold;
 }
 
 /// Functions to compute the closest points between two geometries.
 pub mod closest_points {
     pub use ball_ball         = narrow::ball_ball::closest_points;
+    pub use segment_segment   = narrow::segment_segment::closest_points;
+    pub use point_triangle    = narrow::ball_triangle::closest_point;
+}
+
+/// Functions to compute the distance, closest points, and separating direction between two
+/// geometries.
+pub mod distance {
+    pub use ball_ball         = narrow::ball_ball::distance;
+    pub use plane_implicit    = narrow::plane_implicit::distance;
+    pub use implicit_implicit = narrow::implicit_implicit::distance;
+    pub use concave_geom_geom = narrow::concave_geom_geom::distance;
+}
+
+/// Functions to test whether two geometries are intersecting or close to each other.
+pub mod proximity {
+    pub use ball_ball         = narrow::ball_ball::proximity;
+    pub use plane_implicit    = narrow::plane_implicit::proximity;
+    pub use implicit_implicit = narrow::implicit_implicit::proximity;
+    pub use concave_geom_geom = narrow::concave_geom_geom::proximity;
+}
+
+/// Functions to compute the exact intersection between two geometries.
+#[cfg(dim3)]
+pub mod intersection {
+    pub use triangle_triangle      = narrow::triangle_triangle::intersection;
+    pub use test_triangle_triangle = narrow::triangle_triangle::intersects;
+    pub use mesh_mesh              = narrow::mesh_mesh::intersection;
+    pub use join_segments          = narrow::mesh_mesh::join_segments;
+    pub use mesh_self_intersection = narrow::mesh_mesh::self_intersection;
 }
 
 // modules
 mod collision_detector;
+mod proximity_detector;
 mod empty;
 mod ball_ball;
 mod plane_implicit;
+mod ball_capsule;
+mod ball_segment;
+mod ball_triangle;
+#[cfg(dim3)]
+mod ball_mesh;
+mod capsule_capsule;
+mod segment_segment;
 mod implicit_implicit;
+#[cfg(not(dim4))]
+mod cuboid_cuboid;
 mod incremental_contact_manifold_generator;
 mod one_shot_contact_manifold_generator;
+#[cfg(not(dim4))]
+mod polyhedral_contact_manifold_generator;
 mod concave_geom_geom;
 mod geom_geom;
+mod geom_geom_fallback;
+mod geom_geom_toi;
+mod geom_geom_distance;
+mod geom_geom_proximity;
 mod bezier_surface_ball;
+mod bezier_surface_implicit;
+mod bezier_surface_bezier_surface;
+mod bezier_curve_ball;
+mod bezier_curve_implicit;
+#[cfg(dim3)]
+mod triangle_triangle;
+#[cfg(dim3)]
+mod mesh_mesh;
 
 // FIXME: move this module somewhere else!
 /// Algorithms needed for distance and penetration depth computation.
@@ -55,6 +168,7 @@ pub mod algorithm
     pub mod brute_force_simplex;
     pub mod gjk;
     pub mod minkowski_sampling;
+    pub mod clipping;
 }
 
 // FIXME: move those modules somewhere else!
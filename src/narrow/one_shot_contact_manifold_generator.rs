@@ -1,8 +1,7 @@
-#[cfg(not(dim4))]
 use nalgebra::na;
 
 use narrow::{CollisionDetector, IncrementalContactManifoldGenerator};
-use contact::Contact;
+use contact::{Contact, ContactWLocals};
 use math::{Scalar, Vect, Matrix};
 
 #[cfg(not(dim4))]
@@ -13,6 +12,10 @@ use math::Orientation;
 /// Whenever a new contact is detected (i.e. when the current manifold is empty) a full manifold is
 /// generated. Then, the manifold is incrementally updated by an
 /// `IncrementalContactManifoldGenerator`.
+///
+/// In 4D, the transforms cannot be rotated. Instead, the sub-detector emulates small rotations of
+/// the first object in each plane spanned by the contact normal and a tangent (see
+/// `CollisionDetector::perturbed_colls`).
 #[deriving(Encodable, Decodable, Clone)]
 pub struct OneShotContactManifoldGenerator<CD> {
     sub_detector: IncrementalContactManifoldGenerator<CD>
@@ -25,6 +28,12 @@ impl<CD> OneShotContactManifoldGenerator<CD> {
             sub_detector: IncrementalContactManifoldGenerator::new(prediction, cd)
         }
     }
+
+    /// The contacts of this manifold, together with their local-space points.
+    #[inline]
+    pub fn contacts_with_locals<'a>(&'a self) -> &'a [ContactWLocals] {
+        self.sub_detector.contacts_with_locals()
+    }
 }
 
 impl<CD: CollisionDetector<G1, G2>, G1, G2>
@@ -64,9 +73,31 @@ CollisionDetector<G1, G2> for OneShotContactManifoldGenerator<CD> {
         }
     }
 
+    // 4D rotations are not supported by `nalgebra`: the perturbations are emulated by the
+    // sub-detector.
     #[cfg(dim4)]
-    fn update(&mut self, _: &Matrix, _: &G1, _: &Matrix, _: &G2) {
-        fail!("Not yet implemented.")
+    fn update(&mut self, m1: &Matrix, g1: &G1, m2: &Matrix, g2: &G2) {
+        if self.sub_detector.num_colls() == 0 {
+            // do the one-shot manifold generation
+            match self.sub_detector.get_sub_collision(m1, g1, m2, g2) {
+                Some(coll) => {
+                    self.sub_detector.add_contact(coll.clone(), m1, m2);
+
+                    na::orthonormal_subspace_basis(&coll.normal, |b| {
+                        // perturbations in both directions
+                        self.sub_detector.add_perturbed_contacts(m1, g1, m2, g2, &coll, &b);
+                        self.sub_detector.add_perturbed_contacts(m1, g1, m2, g2, &coll, &-b);
+
+                        true
+                    });
+                },
+                None => { } // no collision
+            }
+        }
+        else {
+            // otherwise, let the incremental manifold do its job
+            self.sub_detector.update(m1, g1, m2, g2)
+        }
     }
 
     #[inline]
@@ -79,6 +110,16 @@ CollisionDetector<G1, G2> for OneShotContactManifoldGenerator<CD> {
         self.sub_detector.colls(out_colls)
     }
 
+    #[inline]
+    fn colls_with_locals(&self, m1: &Matrix, m2: &Matrix, out: &mut Vec<ContactWLocals>) {
+        self.sub_detector.colls_with_locals(m1, m2, out)
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.sub_detector.set_prediction(prediction)
+    }
+
     #[inline]
     fn toi(_:    Option<OneShotContactManifoldGenerator<CD>>,
            m1:   &Matrix,
@@ -1,9 +1,9 @@
 use nalgebra::na::{Translation, Rotate};
 use nalgebra::na;
-use narrow::CollisionDetector;
+use narrow::{CollisionDetector, Distance, Proximity, Intersecting, WithinMargin, Disjoint};
 use geom::Plane;
 use implicit::Implicit;
-use contact::Contact;
+use contact::{Contact, FaceFeature, UnknownFeature};
 use ray::{Ray, RayCast};
 use math::{Scalar, Vect, Matrix};
 
@@ -49,6 +49,22 @@ impl<G: Implicit<Vect, Matrix>> CollisionDetector<Plane, G> for PlaneImplicit<G>
             &self.prediction)
     }
 
+    #[cfg(dim4)]
+    #[inline]
+    fn perturbed_colls(&self,
+                       ma:      &Matrix,
+                       plane:   &Plane,
+                       mb:      &Matrix,
+                       b:       &G,
+                       _:       &Contact,
+                       tangent: &Vect,
+                       out:     &mut Vec<Contact>) {
+        match perturbed_collide(ma, plane, mb, b, tangent, &self.prediction) {
+            Some(c) => out.push(c),
+            None    => { }
+        }
+    }
+
     #[inline]
     fn num_colls(&self) -> uint {
         match self.contact {
@@ -65,6 +81,11 @@ impl<G: Implicit<Vect, Matrix>> CollisionDetector<Plane, G> for PlaneImplicit<G>
         }
     }
 
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
     #[inline]
     fn toi(_:     Option<PlaneImplicit<G>>,
            ma:    &Matrix,
@@ -115,6 +136,22 @@ impl<G: Implicit<Vect, Matrix>> CollisionDetector<G, Plane> for ImplicitPlane<G>
         self.contact.mutate(|mut c| { c.flip(); c });
     }
 
+    #[cfg(dim4)]
+    #[inline]
+    fn perturbed_colls(&self,
+                       ma:      &Matrix,
+                       a:       &G,
+                       mb:      &Matrix,
+                       plane:   &Plane,
+                       _:       &Contact,
+                       tangent: &Vect,
+                       out:     &mut Vec<Contact>) {
+        match perturbed_collide(mb, plane, ma, a, tangent, &self.prediction) {
+            Some(mut c) => { c.flip(); out.push(c) },
+            None        => { }
+        }
+    }
+
     #[inline]
     fn num_colls(&self) -> uint {
         match self.contact {
@@ -131,6 +168,11 @@ impl<G: Implicit<Vect, Matrix>> CollisionDetector<G, Plane> for ImplicitPlane<G>
         }
     }
 
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
     #[inline]
     fn toi(_:     Option<ImplicitPlane<G>>,
            ma:    &Matrix,
@@ -156,21 +198,109 @@ pub fn collide<G: Implicit<Vect, Matrix>>(
                prediction: &Scalar)
                -> Option<Contact> {
     let plane_normal = mplane.rotate(&plane.normal());
-    let plane_center = mplane.translation();
     let deepest      = other.support_point(mother, &-plane_normal);
 
-    let dist = na::dot(&plane_normal, &(plane_center - deepest));
+    contact_with_deepest(mplane, plane_normal, deepest, prediction)
+}
 
-    if dist > -prediction {
+/// Same as `collide` but with the plane slightly rotated in the plane spanned by its normal and
+/// `tangent`.
+///
+/// The rotation only changes the deepest point of `other`: the returned contact is expressed wrt.
+/// the unperturbed plane.
+#[cfg(dim4)]
+pub fn perturbed_collide<G: Implicit<Vect, Matrix>>(
+                         mplane:     &Matrix,
+                         plane:      &Plane,
+                         mother:     &Matrix,
+                         other:      &G,
+                         tangent:    &Vect,
+                         prediction: &Scalar)
+                         -> Option<Contact> {
+    let plane_normal = mplane.rotate(&plane.normal());
+    let tilted       = na::normalize(&(plane_normal + *tangent * na::cast::<f32, Scalar>(0.01)));
+    let deepest      = other.support_point(mother, &-tilted);
+
+    contact_with_deepest(mplane, plane_normal, deepest, prediction)
+}
+
+fn contact_with_deepest(mplane:       &Matrix,
+                        plane_normal: Vect,
+                        deepest:      Vect,
+                        prediction:   &Scalar)
+                        -> Option<Contact> {
+    let plane_center = mplane.translation();
+    let dist         = na::dot(&plane_normal, &(plane_center - deepest));
+
+    if dist > -*prediction {
         let c1 = deepest + plane_normal * dist;
 
-        Some(Contact::new(c1, deepest, plane_normal, dist))
+        Some(Contact::new_with_features(c1, deepest, plane_normal, dist, FaceFeature(0), UnknownFeature))
     }
     else {
         None
     }
 }
 
+/// Computes the distance between a plane and a geometry implementing the `Implicit` trait.
+///
+/// The returned normal is the plane normal. If the geometry penetrates the plane, the distance is
+/// zero and the witness points correspond to the penetration depth.
+///
+/// # Arguments:
+/// * `mplane` - the plane transform.
+/// * `plane`  - the plane.
+/// * `mother` - the geometry transform.
+/// * `other`  - the geometry.
+pub fn distance<G: Implicit<Vect, Matrix>>(
+                mplane: &Matrix,
+                plane:  &Plane,
+                mother: &Matrix,
+                other:  &G)
+                -> Distance {
+    let plane_normal = mplane.rotate(&plane.normal());
+    let plane_center = mplane.translation();
+    let deepest      = other.support_point(mother, &-plane_normal);
+
+    let dist = na::dot(&plane_normal, &(deepest - plane_center));
+    let c1   = deepest - plane_normal * dist;
+
+    Distance::new(c1, deepest, plane_normal, na::max(dist, na::zero()))
+}
+
+/// Tests whether a plane and a geometry implementing the `Implicit` trait are intersecting or
+/// closer than a given margin.
+///
+/// # Arguments:
+/// * `mplane` - the plane transform.
+/// * `plane`  - the plane.
+/// * `mother` - the geometry transform.
+/// * `other`  - the geometry.
+/// * `margin` - the distance bellow which the geometries are considered close to each other.
+pub fn proximity<G: Implicit<Vect, Matrix>>(
+                 mplane: &Matrix,
+                 plane:  &Plane,
+                 mother: &Matrix,
+                 other:  &G,
+                 margin: &Scalar)
+                 -> Proximity {
+    let plane_normal = mplane.rotate(&plane.normal());
+    let plane_center = mplane.translation();
+    let deepest      = other.support_point(mother, &-plane_normal);
+
+    let dist = na::dot(&plane_normal, &(deepest - plane_center));
+
+    if dist <= na::zero() {
+        Intersecting
+    }
+    else if dist <= *margin {
+        WithinMargin
+    }
+    else {
+        Disjoint
+    }
+}
+
 /// Computes the Time Of Impact of a geometry and a plane.
 ///
 /// Arguments:
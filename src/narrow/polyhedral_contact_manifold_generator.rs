@@ -0,0 +1,724 @@
+use std::mem;
+use std::num::{Zero, Signed, Bounded};
+use nalgebra::na::{Transform, Rotate, Indexable};
+use nalgebra::na;
+use geom::{Convex, Cuboid, Triangle, Segment};
+use implicit::HasMargin;
+use narrow::CollisionDetector;
+use narrow::algorithm::clipping::clip_with_half_space;
+use contact::{Contact, VertexFeature, UnknownFeature};
+use util::hash;
+use math::{Scalar, Vect, Matrix};
+
+/// Trait of geometries which are polyhedrons, possibly enlarged by a margin.
+pub trait Polyhedron : HasMargin {
+    /// Computes the vertices of the face of this polyhedron the most aligned with `dir`.
+    ///
+    /// The vertices are expressed in world-space and pushed on `out` together with their index on
+    /// the polyhedron. The margin is not taken into account.
+    fn support_face(&self, m: &Matrix, dir: &Vect, out: &mut Vec<(Vect, uint)>);
+}
+
+impl Polyhedron for Convex {
+    #[inline]
+    fn support_face(&self, m: &Matrix, dir: &Vect, out: &mut Vec<(Vect, uint)>) {
+        support_face_from_vertices(m, self.pts(), dir, out)
+    }
+}
+
+impl Polyhedron for Triangle {
+    #[inline]
+    fn support_face(&self, m: &Matrix, dir: &Vect, out: &mut Vec<(Vect, uint)>) {
+        let pts = [ self.a().clone(), self.b().clone(), self.c().clone() ];
+
+        support_face_from_vertices(m, pts.as_slice(), dir, out)
+    }
+}
+
+impl Polyhedron for Segment {
+    #[inline]
+    fn support_face(&self, m: &Matrix, dir: &Vect, out: &mut Vec<(Vect, uint)>) {
+        let pts = [ self.a().clone(), self.b().clone() ];
+
+        support_face_from_vertices(m, pts.as_slice(), dir, out)
+    }
+}
+
+impl Polyhedron for Cuboid {
+    #[inline]
+    fn support_face(&self, m: &Matrix, dir: &Vect, out: &mut Vec<(Vect, uint)>) {
+        let he       = self.half_extents();
+        let _dim     = na::dim::<Vect>();
+        let mut pts  = Vec::with_capacity(1 << _dim);
+
+        for i in range(0u, 1 << _dim) {
+            let mut pt = he.clone();
+
+            for k in range(0u, _dim) {
+                if i & (1 << k) != 0 {
+                    pt.set(k, -he.at(k));
+                }
+            }
+
+            pts.push(pt);
+        }
+
+        support_face_from_vertices(m, pts.as_slice(), dir, out)
+    }
+}
+
+// Collects the vertices of `pts` lying on the supporting plane orthogonal to `dir`, up to a small
+// angular tolerance.
+fn support_face_from_vertices(m: &Matrix, pts: &[Vect], dir: &Vect, out: &mut Vec<(Vect, uint)>) {
+    let _sin_tol: Scalar = na::cast(0.05);
+    let local_dir = m.inv_rotate(dir);
+
+    let _max: Scalar = Bounded::max_value();
+    let mut best_dot = -_max;
+    let mut best_pt  = 0u;
+
+    for (i, p) in pts.iter().enumerate() {
+        let dot = na::dot(p, &local_dir);
+
+        if dot > best_dot {
+            best_dot = dot;
+            best_pt  = i;
+        }
+    }
+
+    let support = &pts[best_pt];
+
+    for (i, p) in pts.iter().enumerate() {
+        let sp = *support - *p;
+
+        if na::dot(&sp, &local_dir) <= _sin_tol * na::norm(&sp) {
+            out.push((m.transform(p), i));
+        }
+    }
+}
+
+/// Contact manifold generator for polyhedrons.
+///
+/// The contact normal is given by a sub-detector (typically based on GJK and on the Minkowski
+/// sampling algorithm). Then, the faces of both polyhedrons the most aligned with this normal are
+/// computed: the best aligned is the reference face while the other is the incident face. The
+/// complete contact manifold is obtained in one step by clipping the incident face against the
+/// side planes of the reference face.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct PolyhedralContactManifoldGenerator<CD> {
+    contacts:     Vec<Contact>,
+    collector:    Vec<Contact>,
+    prediction:   Scalar,
+    sub_detector: CD
+}
+
+impl<CD> PolyhedralContactManifoldGenerator<CD> {
+    /// Creates a new polyhedral contact manifold generator.
+    ///
+    /// # Arguments:
+    /// * `cd` - collision detection sub-algorithm used to generate the contact normal.
+    pub fn new(prediction: Scalar, cd: CD) -> PolyhedralContactManifoldGenerator<CD> {
+        PolyhedralContactManifoldGenerator {
+            contacts:     Vec::new(),
+            collector:    Vec::new(),
+            prediction:   prediction,
+            sub_detector: cd
+        }
+    }
+}
+
+impl<CD: CollisionDetector<G1, G2>, G1: Polyhedron, G2: Polyhedron>
+CollisionDetector<G1, G2> for PolyhedralContactManifoldGenerator<CD> {
+    fn update(&mut self, m1: &Matrix, g1: &G1, m2: &Matrix, g2: &G2) {
+        self.contacts.clear();
+
+        self.sub_detector.update(m1, g1, m2, g2);
+        self.sub_detector.colls(&mut self.collector);
+
+        if self.collector.len() != 0 {
+            let normal = self.collector.get(0).normal.clone();
+
+            if !clip_manifold(m1, g1, m2, g2, &normal, &self.prediction, &mut self.contacts) {
+                // the faces are not suitable for clipping: keep the contact of the sub-detector.
+                self.contacts.push(self.collector.get(0).clone())
+            }
+        }
+
+        self.collector.clear();
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        for c in self.contacts.iter() {
+            out_colls.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone();
+        self.sub_detector.set_prediction(prediction)
+    }
+
+    #[inline]
+    fn toi(_:    Option<PolyhedralContactManifoldGenerator<CD>>,
+           m1:   &Matrix,
+           dir:  &Vect,
+           dist: &Scalar,
+           g1:   &G1,
+           m2:   &Matrix,
+           g2:   &G2) -> Option<Scalar> {
+        CollisionDetector::toi(None::<CD>, m1, dir, dist, g1, m2, g2)
+    }
+}
+
+/// Computes a contact manifold between two polyhedrons by clipping their faces.
+///
+/// Returns `false` (and does not generate any contact) if no face is suitable for clipping, e.g.
+/// if both support features are vertices.
+///
+/// # Arguments:
+/// * `m1`         - the first polyhedron transform.
+/// * `g1`         - the first polyhedron.
+/// * `m2`         - the second polyhedron transform.
+/// * `g2`         - the second polyhedron.
+/// * `normal`     - the contact normal, pointing from the first polyhedron toward the second one.
+/// * `prediction` - the maximum distance between two features for them to generate a contact.
+/// * `out`        - the vector which will contain the new contacts.
+pub fn clip_manifold<G1: Polyhedron, G2: Polyhedron>(m1:         &Matrix,
+                                                     g1:         &G1,
+                                                     m2:         &Matrix,
+                                                     g2:         &G2,
+                                                     normal:     &Vect,
+                                                     prediction: &Scalar,
+                                                     out:        &mut Vec<Contact>)
+                                                     -> bool {
+    let mut face1 = Vec::new();
+    let mut face2 = Vec::new();
+
+    g1.support_face(m1, normal, &mut face1);
+    g2.support_face(m2, &-normal, &mut face2);
+
+    order_face(&mut face1, normal);
+    order_face(&mut face2, normal);
+
+    let score1 = face_score(&face1, normal);
+    let score2 = face_score(&face2, normal);
+
+    if score1.is_zero() && score2.is_zero() {
+        return false;
+    }
+
+    if !clippable_edges(&face1, &face2) {
+        return false;
+    }
+
+    let num_contacts = out.len();
+
+    if score1 >= score2 {
+        clip_faces(&face1, &face2, normal, &g1.margin(), &g2.margin(), prediction, false, out)
+    }
+    else {
+        clip_faces(&face2, &face1, &-normal, &g2.margin(), &g1.margin(), prediction, true, out)
+    }
+
+    out.len() != num_contacts
+}
+
+// Clips the incident face against the reference face. `n` is the contact normal, pointing from
+// the reference toward the incident face. The side planes and the contact depths are computed from
+// the reference face normal.
+fn clip_faces(reference:  &Vec<(Vect, uint)>,
+              incident:   &Vec<(Vect, uint)>,
+              n:          &Vect,
+              ref_margin: &Scalar,
+              inc_margin: &Scalar,
+              prediction: &Scalar,
+              flip:       bool,
+              out:        &mut Vec<Contact>) {
+    let &(ref ref_point, _) = reference.get(0);
+    let ref_n = reference_normal(reference, n);
+
+    let mut poly    = incident.clone();
+    let mut clipped = Vec::new();
+
+    if reference.len() == 2 {
+        let &(ref a, _) = reference.get(0);
+        let &(ref b, _) = reference.get(1);
+        let dir = na::normalize(&(*b - *a));
+
+        clip_with_half_space(&poly, &dir, &na::dot(&dir, b), 0, &mut clipped);
+        poly.clear();
+        clip_with_half_space(&clipped, &-dir, &-na::dot(&dir, a), 1, &mut poly);
+    }
+    else {
+        let center = face_center(reference);
+
+        for e in range(0u, reference.len()) {
+            let &(ref a, _) = reference.get(e);
+            let &(ref b, _) = reference.get((e + 1) % reference.len());
+
+            let mut side = side_normal(a, b, &ref_n);
+
+            if na::dot(&side, &(center - *a)) > na::zero() {
+                side = -side;
+            }
+
+            clip_with_half_space(&poly, &side, &na::dot(&side, a), e, &mut clipped);
+            poly.clear();
+            mem::swap(&mut poly, &mut clipped);
+        }
+    }
+
+    // The reference face is identified by its smallest vertex index.
+    let ref_id = reference.iter().map(|&(_, id)| id).min().unwrap_or(0);
+
+    for &(ref p, id) in poly.iter() {
+        let dist = na::dot(&ref_n, &(*p - *ref_point));
+        let sep  = dist - *ref_margin - *inc_margin;
+
+        if sep <= *prediction {
+            let ref_world = *p - ref_n * dist + ref_n * *ref_margin;
+            let inc_world = *p - ref_n * *inc_margin;
+
+            // Points created by the clipping do not lie on a vertex of the incident face.
+            let inc_feature =
+                if incident.iter().any(|&(_, iid)| iid == id) { VertexFeature(id) } else { UnknownFeature };
+
+            let mut contact =
+                if flip {
+                    Contact::new_with_features(inc_world, ref_world, -ref_n, -sep, inc_feature, UnknownFeature)
+                }
+                else {
+                    Contact::new_with_features(ref_world, inc_world, ref_n.clone(), -sep, UnknownFeature, inc_feature)
+                };
+
+            // `id` may use the whole `uint` (see `clip_with_half_space`) so it is hashed first.
+            contact.id = hash::key_from_pair(ref_id, hash::tomas_wang_hash(id));
+            out.push(contact)
+        }
+    }
+}
+
+// The unit normal of the reference face, oriented like `n`. For an edge, this is the direction
+// orthogonal to the edge the closest to `n`.
+fn reference_normal(reference: &Vec<(Vect, uint)>, n: &Vect) -> Vect {
+    let a = reference.get(0).ref0();
+    let b = reference.get(1).ref0();
+
+    let mut res =
+        if reference.len() == 2 {
+            let dir = na::normalize(&(*b - *a));
+
+            *n - dir * na::dot(n, &dir)
+        }
+        else {
+            face_normal(a, b, reference.get(2).ref0())
+        };
+
+    if na::norm(&res).is_zero() {
+        return n.clone();
+    }
+
+    res = na::normalize(&res);
+
+    if na::dot(&res, n) < na::zero() {
+        -res
+    }
+    else {
+        res
+    }
+}
+
+fn face_center(face: &Vec<(Vect, uint)>) -> Vect {
+    let mut center: Vect = na::zero();
+
+    for &(ref p, _) in face.iter() {
+        center = center + *p;
+    }
+
+    center / na::cast::<uint, Scalar>(face.len())
+}
+
+// Orders the vertices of a face along its boundary.
+#[cfg(dim3)]
+fn order_face(face: &mut Vec<(Vect, uint)>, n: &Vect) {
+    if face.len() <= 3 {
+        return;
+    }
+
+    let center    = face_center(face);
+    let mut basis = Vec::new();
+
+    na::orthonormal_subspace_basis(n, |b| { basis.push(b); true });
+
+    // Sort the vertices wrt. their pseudo-angle around the center.
+    let mut sorted: Vec<(Scalar, (Vect, uint))> = Vec::with_capacity(face.len());
+
+    for pt in face.iter() {
+        let &(ref p, _) = pt;
+        let x = na::dot(basis.get(0), &(*p - center));
+        let y = na::dot(basis.get(1), &(*p - center));
+        let l = x.abs() + y.abs();
+
+        let angle: Scalar =
+            if l.is_zero() {
+                na::zero()
+            }
+            else if y >= na::zero() {
+                na::one::<Scalar>() - x / l
+            }
+            else {
+                na::cast::<f32, Scalar>(3.0) + x / l
+            };
+
+        // insertion sort: faces have few vertices.
+        let mut pos = sorted.len();
+
+        while pos > 0 && *sorted.get(pos - 1).ref0() > angle {
+            pos = pos - 1;
+        }
+
+        sorted.insert(pos, (angle, pt.clone()));
+    }
+
+    face.clear();
+
+    for (_, pt) in sorted.move_iter() {
+        face.push(pt)
+    }
+}
+
+// Keeps only the two extremities of a face.
+#[cfg(dim2)]
+fn order_face(face: &mut Vec<(Vect, uint)>, n: &Vect) {
+    if face.len() <= 2 {
+        return;
+    }
+
+    let tangent = Vect::new(-n.y, n.x);
+
+    let mut imin = 0u;
+    let mut imax = 0u;
+
+    for i in range(1u, face.len()) {
+        let d = na::dot(face.get(i).ref0(), &tangent);
+
+        if d < na::dot(face.get(imin).ref0(), &tangent) {
+            imin = i
+        }
+
+        if d > na::dot(face.get(imax).ref0(), &tangent) {
+            imax = i
+        }
+    }
+
+    let a = face.get(imin).clone();
+    let b = face.get(imax).clone();
+
+    face.clear();
+    face.push(a);
+    face.push(b);
+}
+
+// How well a face is suited to be the reference face.
+#[cfg(dim3)]
+fn face_score(face: &Vec<(Vect, uint)>, n: &Vect) -> Scalar {
+    if face.len() < 2 {
+        na::zero()
+    }
+    else if face.len() == 2 {
+        // an edge
+        na::one()
+    }
+    else {
+        let a = face.get(0).ref0();
+        let b = face.get(1).ref0();
+        let c = face.get(2).ref0();
+        let face_n = na::cross(&(*b - *a), &(*c - *a));
+        let norm   = na::norm(&face_n);
+
+        if norm.is_zero() {
+            na::one()
+        }
+        else {
+            na::cast::<f32, Scalar>(2.0) + na::dot(&face_n, n).abs() / norm
+        }
+    }
+}
+
+// How well a face is suited to be the reference face.
+#[cfg(dim2)]
+fn face_score(face: &Vec<(Vect, uint)>, n: &Vect) -> Scalar {
+    if face.len() < 2 {
+        na::zero()
+    }
+    else {
+        let dir = na::normalize(&(*face.get(1).ref0() - *face.get(0).ref0()));
+
+        na::cast::<f32, Scalar>(2.0) - na::dot(&dir, n).abs()
+    }
+}
+
+// Non-parallel edges cannot be clipped against each other.
+#[cfg(dim3)]
+fn clippable_edges(face1: &Vec<(Vect, uint)>, face2: &Vec<(Vect, uint)>) -> bool {
+    if face1.len() != 2 || face2.len() != 2 {
+        return true;
+    }
+
+    let _cos_tol: Scalar = na::cast(0.999);
+    let d1 = na::normalize(&(*face1.get(1).ref0() - *face1.get(0).ref0()));
+    let d2 = na::normalize(&(*face2.get(1).ref0() - *face2.get(0).ref0()));
+
+    na::dot(&d1, &d2).abs() >= _cos_tol
+}
+
+#[cfg(dim2)]
+fn clippable_edges(_: &Vec<(Vect, uint)>, _: &Vec<(Vect, uint)>) -> bool {
+    true
+}
+
+#[cfg(dim3)]
+fn side_normal(a: &Vect, b: &Vect, n: &Vect) -> Vect {
+    na::normalize(&na::cross(&(*b - *a), n))
+}
+
+#[cfg(dim2)]
+fn side_normal(_: &Vect, _: &Vect, _: &Vect) -> Vect {
+    unreachable!() // faces have at most two vertices in 2D.
+}
+
+#[cfg(dim3)]
+fn face_normal(a: &Vect, b: &Vect, c: &Vect) -> Vect {
+    na::cross(&(*b - *a), &(*c - *a))
+}
+
+#[cfg(dim2)]
+fn face_normal(_: &Vect, _: &Vect, _: &Vect) -> Vect {
+    unreachable!() // faces have at most two vertices in 2D.
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::{AnnotatedPoint, Convex, Cuboid, Triangle, Segment};
+    use contact::Contact;
+    use narrow::{CollisionDetector, ImplicitImplicit};
+    use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+    use super::{Polyhedron, PolyhedralContactManifoldGenerator, clip_manifold};
+
+    // A 4x0.4x4 slab with its top face at `y = 0` (plus its margin).
+    fn slab() -> Convex {
+        let mut pts = Vec::new();
+
+        for &y in [ 0.0f64, -0.4 ].iter() {
+            pts.push(Vec3::new(-2.0, y, -2.0));
+            pts.push(Vec3::new(2.0, y, -2.0));
+            pts.push(Vec3::new(2.0, y, 2.0));
+            pts.push(Vec3::new(-2.0, y, 2.0));
+        }
+
+        Convex::new(pts)
+    }
+
+    fn manifold(m1: &Iso3<f64>, g1: &Convex, m2: &Iso3<f64>, g2: &Cuboid) -> Vec<Contact> {
+        let simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+        let sub: ImplicitImplicit<JohnsonSimplex<AnnotatedPoint>, Convex, Cuboid> =
+            ImplicitImplicit::new(0.0, simplex);
+        let mut generator = PolyhedralContactManifoldGenerator::new(0.0, sub);
+        let mut res = Vec::new();
+
+        generator.update(m1, g1, m2, g2);
+        generator.colls(&mut res);
+
+        res
+    }
+
+    // Clips the two polyhedrons along the `y` axis.
+    fn clip<G1: Polyhedron, G2: Polyhedron>(m1: &Iso3<f64>, g1: &G1, m2: &Iso3<f64>, g2: &G2)
+                                            -> Vec<Contact> {
+        let mut res = Vec::new();
+
+        assert!(clip_manifold(m1, g1, m2, g2, &Vec3::new(0.0, 1.0, 0.0), &0.0, &mut res));
+
+        res
+    }
+
+    // A large triangle lying on the `y = 0` plane. It contains the `[-0.75, 0.75]` square.
+    fn ground_triangle() -> Triangle {
+        Triangle::new_with_margin(Vec3::new(-2.0f64, 0.0, -2.0),
+                                  Vec3::new(2.0, 0.0, -2.0),
+                                  Vec3::new(0.0, 0.0, 2.0),
+                                  0.0)
+    }
+
+    fn assert_resting(cs: &Vec<Contact>, depth: f64) {
+        for c in cs.iter() {
+            assert!(na::approx_eq_eps(&c.depth, &depth, &1.0e-7));
+            assert!(na::approx_eq_eps(&c.normal, &Vec3::new(0.0, 1.0, 0.0), &1.0e-7));
+        }
+    }
+
+    #[test]
+    fn test_box_on_convex() {
+        let s  = slab();
+        let b  = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let ms = na::one::<Iso3<f64>>();
+        // Both margins are 0.04: the box penetrates the slab by 0.02.
+        let mb = Iso3::new(Vec3::new(0.3f64, 0.52, 0.1), na::zero());
+
+        let cs = manifold(&ms, &s, &mb, &b);
+
+        assert!(cs.len() == 4);
+
+        for c in cs.iter() {
+            assert!(na::approx_eq_eps(&c.depth, &0.02, &1.0e-7));
+            assert!(na::approx_eq_eps(&c.normal, &Vec3::new(0.0, 1.0, 0.0), &1.0e-7));
+        }
+    }
+
+    #[test]
+    fn test_tilted_box_on_convex() {
+        let s   = slab();
+        let b   = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let ms  = na::one::<Iso3<f64>>();
+        // The lowest edge of the tilted box penetrates the slab by 0.02.
+        let he  = b.half_extents().y;
+        let low = he * (0.2f64.cos() + 0.2f64.sin());
+        let mb  = Iso3::new(Vec3::new(0.3f64, low + 0.06, 0.1), Vec3::new(0.2, 0.0, 0.0));
+
+        let cs = manifold(&ms, &s, &mb, &b);
+
+        assert!(cs.len() == 2);
+
+        for c in cs.iter() {
+            assert!(na::approx_eq_eps(&c.depth, &0.02, &1.0e-7));
+            assert!(na::approx_eq_eps(&c.normal, &Vec3::new(0.0, 1.0, 0.0), &1.0e-7));
+        }
+    }
+
+    #[test]
+    fn test_clip_manifold_uses_reference_normal() {
+        let s  = slab();
+        let b  = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let ms = na::one::<Iso3<f64>>();
+        let mb = Iso3::new(Vec3::new(0.3f64, 0.52, 0.1), na::zero());
+        // A contact normal slightly off the slab normal, e.g., as given by an approximate
+        // penetration algorithm.
+        let n  = na::normalize(&Vec3::new(0.02f64, 1.0, 0.0));
+        let mut cs = Vec::new();
+
+        assert!(clip_manifold(&ms, &s, &mb, &b, &n, &0.0, &mut cs));
+        assert!(cs.len() == 4);
+
+        for c in cs.iter() {
+            assert!(na::approx_eq_eps(&c.depth, &0.02, &1.0e-7));
+            assert!(na::approx_eq_eps(&c.normal, &Vec3::new(0.0, 1.0, 0.0), &1.0e-7));
+        }
+    }
+
+    #[test]
+    fn test_triangle_on_box() {
+        let b  = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let t  = Triangle::new_with_margin(Vec3::new(-0.2f64, 0.0, -0.2),
+                                           Vec3::new(0.3, 0.0, -0.2),
+                                           Vec3::new(0.0, 0.0, 0.3),
+                                           0.0);
+        let mb = na::one::<Iso3<f64>>();
+        // The triangle lies inside of the box margin.
+        let mt = Iso3::new(Vec3::new(0.0f64, 0.52, 0.0), na::zero());
+
+        let cs = clip(&mb, &b, &mt, &t);
+
+        // The whole triangle is inside of the top face of the box.
+        assert!(cs.len() == 3);
+        assert_resting(&cs, 0.02);
+    }
+
+    #[test]
+    fn test_box_on_triangle() {
+        let t  = ground_triangle();
+        let b  = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let mt = na::one::<Iso3<f64>>();
+        let mb = Iso3::new(Vec3::new(0.0f64, 0.52, 0.0), na::zero());
+
+        let cs = clip(&mt, &t, &mb, &b);
+
+        // The triangle is the reference face and contains the whole bottom face of the box.
+        assert!(cs.len() == 4);
+        assert_resting(&cs, 0.02);
+    }
+
+    #[test]
+    fn test_segment_on_box() {
+        let b  = Cuboid::new(Vec3::new(0.5f64, 0.5, 0.5));
+        let s  = Segment::new_with_margin(Vec3::new(-1.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let mb = na::one::<Iso3<f64>>();
+        let ms = Iso3::new(Vec3::new(0.0f64, 0.52, 0.0), na::zero());
+
+        let cs = clip(&mb, &b, &ms, &s);
+
+        // The segment overhangs the box on both sides: it is clipped by the box side planes.
+        assert!(cs.len() == 2);
+        assert_resting(&cs, 0.02);
+
+        for c in cs.iter() {
+            assert!(na::approx_eq_eps(&c.world2.x.abs(), &0.5, &1.0e-7));
+        }
+    }
+
+    #[test]
+    fn test_segment_on_triangle() {
+        let t  = ground_triangle();
+        // The segment has the default margin.
+        let s  = Segment::new(Vec3::new(-3.0f64, 0.0, 0.0), Vec3::new(0.5, 0.0, 0.0));
+        let mt = na::one::<Iso3<f64>>();
+        let ms = Iso3::new(Vec3::new(0.0f64, 0.02, 0.0), na::zero());
+
+        let cs = clip(&mt, &t, &ms, &s);
+
+        assert!(cs.len() == 2);
+        assert_resting(&cs, 0.02);
+
+        // One extremity is clipped by the triangle side, the other lies inside of the triangle.
+        assert!(cs.iter().any(|c| na::approx_eq_eps(&c.world2.x, &-1.0, &1.0e-7)));
+        assert!(cs.iter().any(|c| na::approx_eq_eps(&c.world2.x, &0.5, &1.0e-7)));
+    }
+
+    #[test]
+    fn test_parallel_segments() {
+        let s1 = Segment::new(Vec3::new(-1.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let s2 = Segment::new(Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+        let m1 = na::one::<Iso3<f64>>();
+        // Both margins are 0.04: the segments overlap by 0.02.
+        let m2 = Iso3::new(Vec3::new(0.0f64, 0.06, 0.0), na::zero());
+
+        let cs = clip(&m1, &s1, &m2, &s2);
+
+        assert!(cs.len() == 2);
+        assert_resting(&cs, 0.02);
+
+        // Only the overlapping part of the segments generates contacts.
+        assert!(cs.iter().any(|c| na::approx_eq_eps(&c.world2.x, &0.0, &1.0e-7)));
+        assert!(cs.iter().any(|c| na::approx_eq_eps(&c.world2.x, &1.0, &1.0e-7)));
+    }
+
+    #[test]
+    fn test_crossing_segments_are_not_clipped() {
+        let s1 = Segment::new(Vec3::new(-1.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let s2 = Segment::new(Vec3::new(0.0f64, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let m1 = na::one::<Iso3<f64>>();
+        let m2 = Iso3::new(Vec3::new(0.0f64, 0.06, 0.0), na::zero());
+        let mut cs = Vec::new();
+
+        assert!(!clip_manifold(&m1, &s1, &m2, &s2, &Vec3::new(0.0, 1.0, 0.0), &0.0, &mut cs));
+        assert!(cs.len() == 0);
+    }
+}
@@ -0,0 +1,30 @@
+use math::Matrix;
+
+/// Result of a proximity query between two objects.
+#[deriving(Show, PartialEq, Eq, Clone, Encodable, Decodable)]
+pub enum Proximity {
+    /// The two objects are intersecting.
+    Intersecting,
+    /// The two objects are not intersecting but are closer than the query margin.
+    WithinMargin,
+    /// The two objects are farther than the query margin.
+    Disjoint
+}
+
+/// Trait of persistent proximity detectors.
+///
+/// Unlike a `CollisionDetector`, a proximity detector does not compute any contact point. It only
+/// determines whether two objects are intersecting, close to each other, or far apart. This is
+/// useful for triggers or sensors.
+///
+/// # Arguments
+/// * `G1`- the type of the first object involved on the proximity query.
+/// * `G2`- the type of the second object involved on the proximity query.
+pub trait ProximityDetector<G1, G2> {
+    /// Runs the proximity query on two objects. It is assumed that the same proximity detector
+    /// (the same structure) is always used with the same pair of object.
+    fn update(&mut self, &Matrix, &G1, &Matrix, &G2);
+
+    /// The proximity of the two objects computed during the last update.
+    fn proximity(&self) -> Proximity;
+}
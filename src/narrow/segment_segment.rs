@@ -0,0 +1,361 @@
+use std::num::Zero;
+use nalgebra::na::Transform;
+use nalgebra::na;
+use geom::Segment;
+use narrow::{CollisionDetector, implicit_implicit, ball_segment};
+use contact::{Contact, FeatureId, VertexFeature, EdgeFeature};
+use math::{Scalar, Vect, Matrix};
+
+/// Collision detector between two segments.
+///
+/// The margin of each segment is taken into account, i.e., the segments are seen as capsules with
+/// radiuses equal to their respective margins. Two contacts are generated when the segments are
+/// parallel and overlapping.
+#[deriving(Encodable, Decodable, Clone)]
+pub struct SegmentSegment {
+    prediction: Scalar,
+    contacts:   Vec<Contact>
+}
+
+impl SegmentSegment {
+    /// Creates a new persistent collision detector between two segments.
+    #[inline]
+    pub fn new(prediction: Scalar) -> SegmentSegment {
+        SegmentSegment {
+            prediction: prediction,
+            contacts:   Vec::new()
+        }
+    }
+}
+
+impl CollisionDetector<Segment, Segment> for SegmentSegment {
+    #[inline]
+    fn update(&mut self, ma: &Matrix, a: &Segment, mb: &Matrix, b: &Segment) {
+        self.contacts.clear();
+
+        collide(ma, a, mb, b, &self.prediction, &mut self.contacts)
+    }
+
+    #[inline]
+    fn num_colls(&self) -> uint {
+        self.contacts.len()
+    }
+
+    #[inline]
+    fn colls(&self, out_colls: &mut Vec<Contact>) {
+        for c in self.contacts.iter() {
+            out_colls.push(c.clone())
+        }
+    }
+
+    #[inline]
+    fn set_prediction(&mut self, prediction: &Scalar) {
+        self.prediction = prediction.clone()
+    }
+
+    #[inline]
+    fn toi(_:   Option<SegmentSegment>,
+           ma:  &Matrix,
+           dir: &Vect,
+           _:   &Scalar,
+           a:   &Segment,
+           mb:  &Matrix,
+           b:   &Segment) -> Option<Scalar> {
+        implicit_implicit::toi(ma, dir, a, mb, b)
+    }
+}
+
+/// Computes the contact points between two segments.
+///
+/// The segments margins are taken into account. Two contacts are pushed to `out` if the segments
+/// are parallel and overlapping, at most one otherwise.
+#[inline]
+pub fn collide(m1:         &Matrix,
+               s1:         &Segment,
+               m2:         &Matrix,
+               s2:         &Segment,
+               prediction: &Scalar,
+               out:        &mut Vec<Contact>) {
+    collide_with_radii(
+        &m1.transform(s1.a()), &m1.transform(s1.b()), &s1.margin(),
+        &m2.transform(s2.a()), &m2.transform(s2.b()), &s2.margin(),
+        prediction,
+        out)
+}
+
+/// Computes the contact points between two segments enlarged by the given radiuses.
+///
+/// The segments are given in world space.
+pub fn collide_with_radii(a1:         &Vect,
+                          b1:         &Vect,
+                          r1:         &Scalar,
+                          a2:         &Vect,
+                          b2:         &Vect,
+                          r2:         &Scalar,
+                          prediction: &Scalar,
+                          out:        &mut Vec<Contact>) {
+    let d1 = b1 - *a1;
+    let d2 = b2 - *a2;
+    let sqn1 = na::sqnorm(&d1);
+
+    if !sqn1.is_zero() && !na::sqnorm(&d2).is_zero() {
+        let dot12 = na::dot(&d1, &d2);
+        let sin2  = na::one::<Scalar>() - dot12 * dot12 / (sqn1 * na::sqnorm(&d2));
+
+        if sin2 <= _parallel_tol() {
+            // The segments are parallel: try to generate one contact at each extremity of their
+            // overlapping part.
+            let sa = na::dot(&(a2 - *a1), &d1) / sqn1;
+            let sb = na::dot(&(b2 - *a1), &d1) / sqn1;
+            let lo = na::max(na::min(sa.clone(), sb.clone()), na::zero());
+            let hi = na::min(na::max(sa, sb), na::one());
+
+            if lo < hi {
+                let p1 = a1 + d1 * lo;
+                let p2 = a1 + d1 * hi;
+
+                for p in [p1, p2].iter() {
+                    match ball_segment::collide_with_radii(p, r1, a2, b2, r2, prediction) {
+                        Some(mut c) => {
+                            let f2 = c.feature2.clone();
+
+                            c.set_features(segment_feature(a1, b1, p), f2);
+                            out.push(c)
+                        },
+                        None => { }
+                    }
+                }
+
+                return;
+            }
+        }
+    }
+
+    let (p1, p2) = closest_points(a1, b1, a2, b2);
+
+    match collide_points(&p1, r1, &p2, r2, &d1, &d2, prediction) {
+        Some(mut c) => {
+            c.set_features(segment_feature(a1, b1, &p1), segment_feature(a2, b2, &p2));
+            out.push(c)
+        },
+        None => { }
+    }
+}
+
+/// The feature of the segment `[a, b]` a point computed by `closest_points` lies on.
+///
+/// The extremities `a` and `b` are the vertices 0 and 1. The segment interior is the edge 0.
+#[inline]
+pub fn segment_feature(a: &Vect, b: &Vect, p: &Vect) -> FeatureId {
+    if na::approx_eq(p, a) {
+        VertexFeature(0)
+    }
+    else if na::approx_eq(p, b) {
+        VertexFeature(1)
+    }
+    else {
+        EdgeFeature(0)
+    }
+}
+
+/// Computes the closest points between two segments given in world space.
+pub fn closest_points(a1: &Vect, b1: &Vect, a2: &Vect, b2: &Vect) -> (Vect, Vect) {
+    let d1 = b1 - *a1;
+    let d2 = b2 - *a2;
+    let r  = a1 - *a2;
+
+    let a = na::sqnorm(&d1);
+    let e = na::sqnorm(&d2);
+    let f = na::dot(&d2, &r);
+
+    let mut s: Scalar;
+    let mut t: Scalar;
+
+    if a.is_zero() && e.is_zero() {
+        return (a1.clone(), a2.clone())
+    }
+
+    if a.is_zero() {
+        s = na::zero();
+        t = na::clamp(f / e, na::zero(), na::one());
+    }
+    else {
+        let c = na::dot(&d1, &r);
+
+        if e.is_zero() {
+            t = na::zero();
+            s = na::clamp(-c / a, na::zero(), na::one());
+        }
+        else {
+            let b     = na::dot(&d1, &d2);
+            let denom = a * e - b * b;
+
+            s = if denom > na::zero() {
+                na::clamp((b * f - c * e) / denom, na::zero(), na::one())
+            }
+            else {
+                // parallel segments: any point works.
+                na::zero()
+            };
+
+            t = (b * s + f) / e;
+
+            if t < na::zero() {
+                t = na::zero();
+                s = na::clamp(-c / a, na::zero(), na::one());
+            }
+            else if t > na::one() {
+                t = na::one();
+                s = na::clamp((b - c) / a, na::zero(), na::one());
+            }
+        }
+    }
+
+    (a1 + d1 * s, a2 + d2 * t)
+}
+
+/// Computes the contact between two balls centered at `p1` and `p2`.
+///
+/// If the balls centers coincide, the normal is chosen orthogonal to `d1` and, if possible, to
+/// `d2`.
+pub fn collide_points(p1:         &Vect,
+                      r1:         &Scalar,
+                      p2:         &Vect,
+                      r2:         &Scalar,
+                      d1:         &Vect,
+                      d2:         &Vect,
+                      prediction: &Scalar)
+                      -> Option<Contact> {
+    let delta      = p2 - *p1;
+    let sqdist     = na::sqnorm(&delta);
+    let sum_radius = *r1 + *r2;
+    let sum_radius_with_error = sum_radius + *prediction;
+
+    if sqdist < sum_radius_with_error * sum_radius_with_error {
+        let normal =
+            if sqdist.is_zero() {
+                degenerate_normal(d1, d2)
+            }
+            else {
+                delta / sqdist.sqrt()
+            };
+
+        Some(Contact::new(
+                p1 + normal * *r1,
+                p2 - normal * *r2,
+                normal,
+                sum_radius - sqdist.sqrt()))
+    }
+    else {
+        None
+    }
+}
+
+/// Computes an unit vector orthogonal to `d1` and, if possible, to `d2`.
+pub fn degenerate_normal(d1: &Vect, d2: &Vect) -> Vect {
+    let mut res: Vect = na::zero();
+
+    if na::sqnorm(d1).is_zero() {
+        if na::sqnorm(d2).is_zero() {
+            na::canonical_basis(|b| {
+                res = b;
+
+                false
+            });
+
+            return res;
+        }
+
+        return degenerate_normal(d2, d1);
+    }
+
+    let n1      = na::normalize(d1);
+    let perp2   = d2 - n1 * na::dot(&n1, d2);
+    let has_n2  = na::sqnorm(&perp2) > _ortho_tol();
+    let n2      = if has_n2 { na::normalize(&perp2) } else { na::zero() };
+    let mut first = true;
+
+    na::orthonormal_subspace_basis(&n1, |b| {
+        if first {
+            res   = b.clone();
+            first = false;
+        }
+
+        let ortho = b - n2 * na::dot(&b, &n2);
+
+        if na::sqnorm(&ortho) > _ortho_tol() {
+            res = na::normalize(&ortho);
+
+            false
+        }
+        else {
+            true
+        }
+    });
+
+    res
+}
+
+// Squared sine of the angle under which two segments are considered parallel.
+#[inline]
+fn _parallel_tol() -> Scalar {
+    na::cast(1.0e-6)
+}
+
+#[inline]
+fn _ortho_tol() -> Scalar {
+    na::cast(1.0e-10)
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Segment;
+    use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+    use narrow::algorithm::gjk::Projection;
+    use narrow::implicit_implicit;
+    use super::collide;
+
+    fn check(m1: &Iso3<f64>, s1: &Segment, m2: &Iso3<f64>, s2: &Segment) {
+        let mut simplex = JohnsonSimplex::new_w_tls();
+        let mut contacts = Vec::new();
+
+        collide(m1, s1, m2, s2, &na::zero(), &mut contacts);
+
+        match implicit_implicit::collide(m1, s1, m2, s2, &na::zero(), &mut simplex, None) {
+            Projection(c) => {
+                assert!(contacts.len() == 1);
+                assert!(na::approx_eq_eps(&contacts.get(0).depth, &c.depth, &1.0e-5));
+                assert!(na::approx_eq_eps(&contacts.get(0).normal, &c.normal, &1.0e-5));
+            },
+            _ => assert!(contacts.is_empty())
+        }
+    }
+
+    #[test]
+    fn test_segment_segment_gjk() {
+        let s1 = Segment::new_with_margin(Vec3::new(-1.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.5);
+        let s2 = Segment::new_with_margin(Vec3::new(0.0f64, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.3);
+
+        check(&na::one(), &s1, &Iso3::new(Vec3::new(0.2f64, 0.5, 0.7), Vec3::new(0.3f64, 0.0, 0.1)), &s2);
+        check(&na::one(), &s1, &Iso3::new(Vec3::new(1.4f64, 0.8, 0.1), Vec3::new(0.0f64, 0.5, 0.0)), &s2);
+        check(&na::one(), &s1, &Iso3::new(Vec3::new(0.0f64, 0.0, 2.0), na::zero()), &s2);
+    }
+
+    #[test]
+    fn test_parallel_segments() {
+        let s = Segment::new_with_margin(Vec3::new(-1.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.5);
+        let m = Iso3::new(Vec3::new(0.5f64, 0.8, 0.0), na::zero());
+        let mut contacts = Vec::new();
+
+        collide(&na::one(), &s, &m, &s, &na::zero(), &mut contacts);
+
+        assert!(contacts.len() == 2);
+
+        for c in contacts.iter() {
+            assert!(na::approx_eq_eps(&c.depth, &0.2, &1.0e-7));
+            assert!(na::approx_eq_eps(&c.normal, &Vec3::new(0.0f64, 1.0, 0.0), &1.0e-7));
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Heuristics to select surface containing the projection of a point.
 
+use std::num::Bounded;
 use nalgebra::na;
 use bounding_volume::{HasBoundingSphere, BoundingVolume, BoundingSphere};
 use geom::BezierSurface;
@@ -9,6 +10,8 @@ use math::{Scalar, Vect};
 pub trait SurfaceSelector<D> {
     /// Sets the maximum local minimal distance.
     fn set_max_lmd(&mut self, max_lmd: Scalar);
+    /// The maximum local minimal distance.
+    fn max_lmd(&self) -> Scalar;
     /// Tells whether a surface is flat enough to run a numerical resolution algorithm.
     fn is_flat(&mut self, surf: &BezierSurface, data: &D) -> bool;
     /// Tells whether a surface might contain a closest point or not.
@@ -34,6 +37,10 @@ impl SurfaceSelector<()> for YesSirSurfaceSelector {
     fn set_max_lmd(&mut self, _: Scalar) {
     }
 
+    fn max_lmd(&self) -> Scalar {
+        Bounded::max_value()
+    }
+
     fn is_flat(&mut self, _: &BezierSurface, _: &()) -> bool {
         false
     }
@@ -70,6 +77,10 @@ impl SurfaceSelector<BoundingSphere> for HyperPlaneSurfaceSelector {
         self.max_lmd = max_lmd
     }
 
+    fn max_lmd(&self) -> Scalar {
+        self.max_lmd.clone()
+    }
+
     fn is_flat(&mut self, _: &BezierSurface, _: &BoundingSphere) -> bool {
         false
     }
@@ -139,11 +150,37 @@ pub struct TangentConesSurfaceSelectorTestData {
     axis_v:          Vect
 }
 
+impl TangentConesSurfaceSelectorTestData {
+    /// The bounding sphere of the tested surface.
+    #[inline]
+    pub fn bounding_sphere<'a>(&'a self) -> &'a BoundingSphere {
+        &'a self.bounding_sphere
+    }
+
+    /// The axis and the half-angle of the cone containing the derivatives of the tested surface
+    /// wrt. `u`.
+    #[inline]
+    pub fn tangent_cone_u(&self) -> (Vect, Scalar) {
+        (self.axis_u.clone(), self.spread_u.clone())
+    }
+
+    /// The axis and the half-angle of the cone containing the derivatives of the tested surface
+    /// wrt. `v`.
+    #[inline]
+    pub fn tangent_cone_v(&self) -> (Vect, Scalar) {
+        (self.axis_v.clone(), self.spread_v.clone())
+    }
+}
+
 impl SurfaceSelector<TangentConesSurfaceSelectorTestData> for TangentConesSurfaceSelector {
     fn set_max_lmd(&mut self, max_lmd: Scalar) {
         self.max_lmd = max_lmd
     }
 
+    fn max_lmd(&self) -> Scalar {
+        self.max_lmd.clone()
+    }
+
     fn is_flat(&mut self, _: &BezierSurface, _: &TangentConesSurfaceSelectorTestData) -> bool {
         false // XXX: we could be smarter here
     }
@@ -2,7 +2,9 @@
 
 use collections::HashMap;
 use sync::{Arc, RWLock};
+use nalgebra::na;
 use geom::BezierSurface;
+use narrow::surface_selector::SurfaceSelector;
 
 /*
  * FIXME:
@@ -260,3 +262,106 @@ impl<D: Send + Share> SurfaceSubdivisionTree<D> {
         self.lchild = None;
     }
 }
+
+/// Gets the two children of a node of a subdivision tree, subdividing it if necessary.
+///
+/// The node is subdivided at its parametric center along `u` if `level` is odd, and along `v`
+/// otherwise. The test data of the children are created by `selector`.
+pub fn children<S: SurfaceSelector<D>, D: Send + Share>(tree:     &Arc<RWLock<SurfaceSubdivisionTree<D>>>,
+                                                        level:    uint,
+                                                        selector: &mut S)
+                                                        -> (Arc<RWLock<SurfaceSubdivisionTree<D>>>,
+                                                            Arc<RWLock<SurfaceSubdivisionTree<D>>>) {
+    let can_go_down = tree.read().has_left_child(); // proper tree
+
+    if !can_go_down {
+        // Somebody might have created the children in-between the read-lock and the
+        // read-write-lock. Therefore, we have to re-test once the read-write-lock is taken.
+
+        // >>>> get a read-write lock on the node.
+        let mut w_tree = tree.write();
+
+        if !w_tree.has_left_child() { // proper tree
+            let mut left  = BezierSurface::new_with_degrees(0, 0);
+            let mut right = BezierSurface::new_with_degrees(0, 0);
+
+            if level % 2 == 1 {
+                w_tree.surface().subdivide_u(&na::cast(0.5), &mut left, &mut right);
+            }
+            else {
+                w_tree.surface().subdivide_v(&na::cast(0.5), &mut left, &mut right);
+            }
+
+            let ldata = selector.create_test_data(&left);
+            let rdata = selector.create_test_data(&right);
+
+            let lchild = SurfaceSubdivisionTree::new_orphan(left, ldata, w_tree.timestamp());
+            let rchild = SurfaceSubdivisionTree::new_orphan(right, rdata, w_tree.timestamp());
+
+            w_tree.set_right_child(rchild);
+            w_tree.set_left_child(lchild);
+        }
+        // <<<< end of read-write lock on the node.
+    }
+
+    let r_tree = tree.read();
+
+    (r_tree.left_child().expect("Internal error: no left child."),
+     r_tree.right_child().expect("Internal error: no right child."))
+}
+
+/// The sides of a sub-surface which are part of the boundary of the surface it has been
+/// subdivided from.
+///
+/// A closest point may lie on those sides without being a stationary point of the distance
+/// function. On the other sides, it is found on the neighbor sub-surfaces.
+#[deriving(Clone, PartialEq, Show)]
+pub struct SubdivisionBoundary {
+    /// Whether the side `u = 0` is on the original boundary.
+    pub umin: bool,
+    /// Whether the side `u = 1` is on the original boundary.
+    pub umax: bool,
+    /// Whether the side `v = 0` is on the original boundary.
+    pub vmin: bool,
+    /// Whether the side `v = 1` is on the original boundary.
+    pub vmax: bool
+}
+
+impl SubdivisionBoundary {
+    /// The boundary of a surface which has not been subdivided: all its sides are on the boundary.
+    #[inline]
+    pub fn new_root() -> SubdivisionBoundary {
+        SubdivisionBoundary {
+            umin: true,
+            umax: true,
+            vmin: true,
+            vmax: true
+        }
+    }
+
+    /// Whether at least one side is on the original boundary.
+    #[inline]
+    pub fn touches_boundary(&self) -> bool {
+        self.umin || self.umax || self.vmin || self.vmax
+    }
+
+    /// The boundaries of the two children of a node of a subdivision tree at the level `level`.
+    ///
+    /// This follows the subdivision directions of `children`.
+    #[inline]
+    pub fn children(&self, level: uint) -> (SubdivisionBoundary, SubdivisionBoundary) {
+        let mut left  = self.clone();
+        let mut right = self.clone();
+
+        if level % 2 == 1 {
+            left.umax  = false;
+            right.umin = false;
+        }
+        else {
+            left.vmax  = false;
+            right.vmin = false;
+        }
+
+        (left, right)
+    }
+}
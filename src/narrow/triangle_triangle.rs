@@ -0,0 +1,258 @@
+//! Exact intersection between two triangles.
+
+use std::num::{Zero, Signed};
+use nalgebra::na::Transform;
+use nalgebra::na;
+use geom::Triangle;
+use math::{Scalar, Vect, Matrix};
+
+/// Tests whether two transformed triangles intersect.
+///
+/// Margins are ignored. Coplanar triangles are handled as well.
+pub fn intersects(m1: &Matrix, t1: &Triangle, m2: &Matrix, t2: &Triangle) -> bool {
+    let pts1 = world_vertices(m1, t1);
+    let pts2 = world_vertices(m2, t2);
+
+    match classify(&pts1, &pts2) {
+        Crossing(s1, s2)      => overlap(&s1, &s2).is_some(),
+        Coplanar(ref normal)  => coplanar_intersects(normal, &pts1, &pts2),
+        Separated             => false
+    }
+}
+
+/// Computes the segment along which two transformed triangles intersect.
+///
+/// Margins are ignored. Returns `None` if the triangles do not intersect, or if they are
+/// coplanar (in which case their intersection is not a curve). If the triangles touch at a single
+/// point, both extremities of the returned segment are equal.
+pub fn intersection(m1: &Matrix, t1: &Triangle, m2: &Matrix, t2: &Triangle) -> Option<(Vect, Vect)> {
+    let pts1 = world_vertices(m1, t1);
+    let pts2 = world_vertices(m2, t2);
+
+    intersection_with_vertices(&pts1, &pts2)
+}
+
+/// Computes the segment along which two triangles given by their vertices intersect.
+///
+/// See `intersection` for details.
+pub fn intersection_with_vertices(pts1: &[Vect, ..3], pts2: &[Vect, ..3]) -> Option<(Vect, Vect)> {
+    match classify(pts1, pts2) {
+        Crossing(s1, s2) => overlap(&s1, &s2),
+        _                => None
+    }
+}
+
+// Relative position of two triangles.
+enum Configuration {
+    // Each triangle crosses the plane of the other along the given segments.
+    Crossing((Vect, Vect), (Vect, Vect)),
+    // The triangles lie on the same plane, with the given normal.
+    Coplanar(Vect),
+    // The triangles do not intersect, or one of them is degenerate.
+    Separated
+}
+
+fn world_vertices(m: &Matrix, t: &Triangle) -> [Vect, ..3] {
+    [ m.transform(t.a()), m.transform(t.b()), m.transform(t.c()) ]
+}
+
+fn classify(pts1: &[Vect, ..3], pts2: &[Vect, ..3]) -> Configuration {
+    let n1 = na::cross(&(pts1[1] - pts1[0]), &(pts1[2] - pts1[0]));
+    let n2 = na::cross(&(pts2[1] - pts2[0]), &(pts2[2] - pts2[0]));
+
+    if na::sqnorm(&n1).is_zero() || na::sqnorm(&n2).is_zero() {
+        return Separated
+    }
+
+    let n1  = na::normalize(&n1);
+    let n2  = na::normalize(&n2);
+    let eps = _eps() * na::max(scale(pts1), scale(pts2));
+
+    // Signed distances of the vertices of each triangle to the plane of the other.
+    let d1 = signed_distances(pts1, &n2, &pts2[0], &eps);
+    let d2 = signed_distances(pts2, &n1, &pts1[0], &eps);
+
+    if d1.iter().all(|d| d.is_zero()) {
+        return Coplanar(n1)
+    }
+
+    if same_strict_sign(&d1) || same_strict_sign(&d2) {
+        return Separated
+    }
+
+    match (plane_crossing(pts1, &d1), plane_crossing(pts2, &d2)) {
+        (Some(s1), Some(s2)) => Crossing(s1, s2),
+        _                    => Separated
+    }
+}
+
+// The segments lie on the intersection line of both planes: intersect them along this line.
+fn overlap(s1: &(Vect, Vect), s2: &(Vect, Vect)) -> Option<(Vect, Vect)> {
+    let dir = {
+        let d1 = *s1.ref1() - *s1.ref0();
+        let d2 = *s2.ref1() - *s2.ref0();
+
+        if na::sqnorm(&d1) >= na::sqnorm(&d2) { d1 } else { d2 }
+    };
+
+    if na::sqnorm(&dir).is_zero() {
+        // Both segments are points.
+        return if na::approx_eq_eps(s1.ref0(), s2.ref0(), &_eps()) {
+            Some(s1.clone())
+        }
+        else {
+            None
+        }
+    }
+
+    let (lo1, hi1) = ordered(s1, &dir);
+    let (lo2, hi2) = ordered(s2, &dir);
+
+    let lo = if na::dot(&lo1, &dir) >= na::dot(&lo2, &dir) { lo1 } else { lo2 };
+    let hi = if na::dot(&hi1, &dir) <= na::dot(&hi2, &dir) { hi1 } else { hi2 };
+
+    if na::dot(&(hi - lo), &dir) < -_eps() * na::norm(&dir) {
+        None
+    }
+    else {
+        Some((lo, hi))
+    }
+}
+
+fn ordered(s: &(Vect, Vect), dir: &Vect) -> (Vect, Vect) {
+    if na::dot(s.ref0(), dir) <= na::dot(s.ref1(), dir) {
+        s.clone()
+    }
+    else {
+        (s.ref1().clone(), s.ref0().clone())
+    }
+}
+
+fn signed_distances(pts: &[Vect, ..3], n: &Vect, origin: &Vect, eps: &Scalar) -> [Scalar, ..3] {
+    let mut res = [ na::zero(), na::zero(), na::zero() ];
+
+    for i in range(0u, 3) {
+        let d = na::dot(n, &(pts[i] - *origin));
+
+        // snap to the plane for robustness
+        res[i] = if d.abs() <= *eps { na::zero() } else { d };
+    }
+
+    res
+}
+
+fn same_strict_sign(d: &[Scalar, ..3]) -> bool {
+    d.iter().all(|d| *d > na::zero()) || d.iter().all(|d| *d < na::zero())
+}
+
+// The segment along which a triangle crosses a plane, given the signed distances of its vertices.
+fn plane_crossing(pts: &[Vect, ..3], d: &[Scalar, ..3]) -> Option<(Vect, Vect)> {
+    let mut found = Vec::new();
+
+    for i in range(0u, 3) {
+        let j = (i + 1) % 3;
+
+        if d[i].is_zero() {
+            found.push(pts[i].clone());
+        }
+
+        if (d[i] > na::zero() && d[j] < na::zero()) || (d[i] < na::zero() && d[j] > na::zero()) {
+            let t = d[i] / (d[i] - d[j]);
+
+            found.push(pts[i] + (pts[j] - pts[i]) * t);
+        }
+    }
+
+    match found.len() {
+        0 => None,
+        1 => Some((found.get(0).clone(), found.get(0).clone())),
+        _ => Some((found.get(0).clone(), found.get(1).clone()))
+    }
+}
+
+// Separating axis test between two coplanar triangles.
+fn coplanar_intersects(normal: &Vect, pts1: &[Vect, ..3], pts2: &[Vect, ..3]) -> bool {
+    !has_separating_edge(normal, pts1, pts1, pts2) && !has_separating_edge(normal, pts2, pts1, pts2)
+}
+
+fn has_separating_edge(normal: &Vect, edges: &[Vect, ..3], pts1: &[Vect, ..3], pts2: &[Vect, ..3]) -> bool {
+    for i in range(0u, 3) {
+        let axis = na::cross(normal, &(edges[(i + 1) % 3] - edges[i]));
+
+        let (min1, max1) = project(pts1, &axis);
+        let (min2, max2) = project(pts2, &axis);
+
+        if max1 < min2 || max2 < min1 {
+            return true
+        }
+    }
+
+    false
+}
+
+fn project(pts: &[Vect, ..3], axis: &Vect) -> (Scalar, Scalar) {
+    let mut min = na::dot(&pts[0], axis);
+    let mut max = min.clone();
+
+    for pt in pts.slice_from(1).iter() {
+        let d = na::dot(pt, axis);
+
+        min = na::min(min, d.clone());
+        max = na::max(max, d);
+    }
+
+    (min, max)
+}
+
+// Length of the longest edge of a triangle.
+fn scale(pts: &[Vect, ..3]) -> Scalar {
+    let mut res: Scalar = na::zero();
+
+    for i in range(0u, 3) {
+        res = na::max(res, na::norm(&(pts[(i + 1) % 3] - pts[i])));
+    }
+
+    res
+}
+
+// Relative tolerance. It must stay above the precision of `Scalar`, i.e., about 1.0e-5 for `f32`.
+#[inline]
+fn _eps() -> Scalar {
+    na::max(na::cast(1.0e-7), Float::epsilon() * na::cast(100.0))
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Iso3};
+    use nalgebra::na;
+    use geom::Triangle;
+    use super::{intersects, intersection};
+
+    #[test]
+    fn test_crossing_triangles() {
+        let t1 = Triangle::new(Vec3::new(-1.0f64, -1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let t2 = Triangle::new(Vec3::new(0.0f64, -2.0, -1.0), Vec3::new(0.0, 2.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let id = na::one::<Iso3<f64>>();
+
+        assert!(intersects(&id, &t1, &id, &t2));
+
+        let (a, b) = intersection(&id, &t1, &id, &t2).unwrap();
+
+        assert!(na::approx_eq_eps(&na::norm(&(b - a)), &2.0, &1.0e-7));
+        assert!(na::approx_eq(&a.x, &0.0) && na::approx_eq(&a.z, &0.0));
+        assert!(na::approx_eq(&b.x, &0.0) && na::approx_eq(&b.z, &0.0));
+    }
+
+    #[test]
+    fn test_separated_and_coplanar_triangles() {
+        let t  = Triangle::new(Vec3::new(-1.0f64, -1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let id = na::one::<Iso3<f64>>();
+        let up = Iso3::new(Vec3::new(0.0f64, 0.0, 0.5), na::zero());
+        let sh = Iso3::new(Vec3::new(0.5f64, 0.0, 0.0), na::zero());
+
+        assert!(!intersects(&id, &t, &up, &t));
+        assert!(intersection(&id, &t, &up, &t).is_none());
+        assert!(intersects(&id, &t, &sh, &t));
+        assert!(intersection(&id, &t, &sh, &t).is_none());
+    }
+}
@@ -235,6 +235,135 @@ impl<B, BV: RayCast> BVTNode<B, BV> {
     }
 }
 
+impl<B, BV> BVT<B, BV> {
+    /// Finds the leaf with the smallest cost using a branch-and-bound traversal.
+    ///
+    /// # Arguments:
+    /// * `bv_cost` - a lower bound of the cost of every leaf contained by a bounding volume.
+    /// * `leaf_cost` - the exact cost of a leaf, and some user-defined data. Returns `None` if
+    /// the leaf has to be ignored.
+    pub fn best_first_search<'a, T>(&'a self,
+                                    bv_cost:   &mut |&BV| -> Scalar,
+                                    leaf_cost: &mut |&B| -> Option<(Scalar, T)>)
+                                    -> Option<(Scalar, T, &'a B)> {
+        match self.tree {
+            None        => None,
+            Some(ref n) => n.best_first_search(Bounded::max_value(), bv_cost, leaf_cost)
+        }
+    }
+}
+
+impl<B, BV> BVTNode<B, BV> {
+    fn best_first_search<'a, T>(&'a self,
+                                upper_bound: Scalar,
+                                bv_cost:     &mut |&BV| -> Scalar,
+                                leaf_cost:   &mut |&B| -> Option<(Scalar, T)>)
+                                -> Option<(Scalar, T, &'a B)> {
+        match *self {
+            Internal(_, ref left, ref right) => {
+                let left_cost  = (*bv_cost)(left.bounding_volume());
+                let right_cost = (*bv_cost)(right.bounding_volume());
+
+                let (best, best_cost, other, other_cost) =
+                    if left_cost < right_cost {
+                        (left, left_cost, right, right_cost)
+                    }
+                    else {
+                        (right, right_cost, left, left_cost)
+                    };
+
+                if best_cost >= upper_bound {
+                    // a better solution has already been found
+                    return None;
+                }
+
+                match best.best_first_search(upper_bound, bv_cost, leaf_cost) {
+                    None    => {
+                        if other_cost < upper_bound {
+                            other.best_first_search(upper_bound, bv_cost, leaf_cost)
+                        }
+                        else {
+                            None
+                        }
+                    },
+                    Some(c) => {
+                        if other_cost < *c.ref0() {
+                            match other.best_first_search(*c.ref0(), bv_cost, leaf_cost) {
+                                None         => Some(c),
+                                Some(cother) => Some(cother) // cother is necessarily better.
+                            }
+                        }
+                        else {
+                            Some(c)
+                        }
+                    }
+                }
+            },
+            Leaf(_, ref b) => {
+                match (*leaf_cost)(b) {
+                    None         => None,
+                    Some((c, d)) => {
+                        if c < upper_bound {
+                            Some((c, d, b))
+                        }
+                        else {
+                            None
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<B, BV> BVT<B, BV> {
+    /// Traverses this tree and another one simultaneously.
+    ///
+    /// # Arguments:
+    /// * `other` - the other tree.
+    /// * `bv_test` - returns `false` if no leaf of the first bounding volume can interfere with a
+    /// leaf of the second. Such pairs of subtrees are not traversed any further.
+    /// * `leaf_pair` - called on each pair of leaves whose bounding volumes pass `bv_test`.
+    pub fn visit_bvtt<B2, BV2>(&self,
+                               other:     &BVT<B2, BV2>,
+                               bv_test:   &mut |&BV, &BV2| -> bool,
+                               leaf_pair: &mut |&B, &B2| -> ()) {
+        match (&self.tree, &other.tree) {
+            (&Some(ref n1), &Some(ref n2)) => n1.visit_bvtt(n2, bv_test, leaf_pair),
+            _                              => { }
+        }
+    }
+}
+
+impl<B, BV> BVTNode<B, BV> {
+    fn visit_bvtt<B2, BV2>(&self,
+                           other:     &BVTNode<B2, BV2>,
+                           bv_test:   &mut |&BV, &BV2| -> bool,
+                           leaf_pair: &mut |&B, &B2| -> ()) {
+        if !(*bv_test)(self.bounding_volume(), other.bounding_volume()) {
+            return;
+        }
+
+        match (self, other) {
+            (&Leaf(_, ref b1), &Leaf(_, ref b2)) => (*leaf_pair)(b1, b2),
+            (&Internal(_, ref left, ref right), &Leaf(_, _)) => {
+                left.visit_bvtt(other, bv_test, leaf_pair);
+                right.visit_bvtt(other, bv_test, leaf_pair);
+            },
+            (&Leaf(_, _), &Internal(_, ref left, ref right)) => {
+                self.visit_bvtt(&**left, bv_test, leaf_pair);
+                self.visit_bvtt(&**right, bv_test, leaf_pair);
+            },
+            (&Internal(_, ref left1, ref right1), &Internal(_, ref left2, ref right2)) => {
+                left1.visit_bvtt(&**left2, bv_test, leaf_pair);
+                left1.visit_bvtt(&**right2, bv_test, leaf_pair);
+                right1.visit_bvtt(&**left2, bv_test, leaf_pair);
+                right1.visit_bvtt(&**right2, bv_test, leaf_pair);
+            }
+        }
+    }
+}
+
 /// Construction function for a kdree.
 ///
 /// Use this as a parameter of `new_with_partitioner`.
@@ -308,3 +437,101 @@ fn __new_with_partitioner<B, BV>(depth:       uint,
         }
     }
 }
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::Vec3;
+    use bounding_volume::{BoundingVolume, AABB};
+    use partitioning::BVT;
+
+    fn unit_box(x: f64) -> AABB {
+        AABB::new(Vec3::new(x - 0.5, -0.5, -0.5), Vec3::new(x + 0.5, 0.5, 0.5))
+    }
+
+    // Unit boxes centered at `x = 0, 3, 6, 9, 12`.
+    fn aligned_boxes() -> BVT<uint, AABB> {
+        let mut leaves = Vec::new();
+
+        for i in range(0u, 5) {
+            leaves.push((i, unit_box((i as f64) * 3.0)));
+        }
+
+        BVT::new_kdtree(leaves)
+    }
+
+    fn gap_to(aabb: &AABB, x: f64) -> f64 {
+        if x < aabb.mins().x {
+            aabb.mins().x - x
+        }
+        else if x > aabb.maxs().x {
+            x - aabb.maxs().x
+        }
+        else {
+            0.0
+        }
+    }
+
+    #[test]
+    fn test_best_first_search() {
+        let bvt = aligned_boxes();
+        let pt  = 7.2f64;
+
+        let best = bvt.best_first_search(
+            &mut |aabb: &AABB| gap_to(aabb, pt),
+            &mut |i: &uint| Some(((*i as f64 * 3.0 - pt).abs(), *i)));
+
+        match best {
+            Some((cost, i, _)) => {
+                assert!(i == 2);
+                assert!((cost - 1.2).abs() < 1.0e-10);
+            },
+            None => fail!("The search should find a leaf.")
+        }
+    }
+
+    #[test]
+    fn test_best_first_search_ignored_leaf() {
+        let bvt = aligned_boxes();
+        let pt  = 7.2f64;
+
+        let best = bvt.best_first_search(
+            &mut |aabb: &AABB| gap_to(aabb, pt),
+            &mut |i: &uint| {
+                if *i == 2 {
+                    None
+                }
+                else {
+                    Some(((*i as f64 * 3.0 - pt).abs(), *i))
+                }
+            });
+
+        match best {
+            Some((_, i, _)) => assert!(i == 3),
+            None            => fail!("The search should find a leaf.")
+        }
+    }
+
+    #[test]
+    fn test_best_first_search_empty() {
+        let bvt: BVT<uint, AABB> = BVT::new_kdtree(Vec::new());
+
+        let best = bvt.best_first_search(&mut |_: &AABB| 0.0f64, &mut |i: &uint| Some((0.0f64, *i)));
+
+        assert!(best.is_none());
+    }
+
+    #[test]
+    fn test_visit_bvtt() {
+        let bvt1 = aligned_boxes();
+        let bvt2 = BVT::new_kdtree(vec!((0u, unit_box(0.8)), (1u, unit_box(6.2)), (2u, unit_box(20.0))));
+        let mut pairs = Vec::new();
+
+        bvt1.visit_bvtt(&bvt2,
+                        &mut |bv1: &AABB, bv2: &AABB| bv1.intersects(bv2),
+                        &mut |i: &uint, j: &uint| pairs.push((*i, *j)));
+
+        pairs.sort();
+
+        assert!(pairs == vec!((0u, 0u), (2u, 1u)));
+    }
+}
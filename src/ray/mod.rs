@@ -7,6 +7,7 @@ pub use ray::ray::{Ray, RayCast, RayIntersection};
 pub use ray::ray_plane::plane_toi_with_ray;
 pub use ray::ray_implicit::implicit_toi_and_normal_with_ray;
 pub use ray::ray_ball::ball_toi_with_ray;
+pub use ray::ray_bezier_curve::bezier_curve_ray_intersection;
 
 #[cfg(dim3)]
 pub use ray::ray_triangle::triangle_ray_intersection;
@@ -21,6 +22,8 @@ mod ray_implicit;
 mod ray_triangle;
 mod ray_concave;
 mod ray_mesh;
+#[cfg(dim3)]
+mod ray_tet_mesh;
 mod ray_bvt;
 mod ray_bezier_surface;
 mod ray_bezier_curve;
@@ -1,9 +1,156 @@
+use std::num::Zero;
+use nalgebra::na::{Vec2, Mat2, Inv, Norm};
+use nalgebra::na;
 use ray::{Ray, RayCast, RayIntersection};
+use ray;
+use bounding_volume::HasBoundingSphere;
 use geom::BezierCurve;
+use math::{Scalar, Vect};
+
+#[cfg(dim3)]
+use nalgebra::na::Vec3;
+
+// Subdivision depth of the curve before the Newton refinement.
+static MAX_DEPTH: uint = 8;
+// Number of Newton iterations.
+static NITER:     uint = 10;
 
 impl RayCast for BezierCurve {
-    fn toi_and_normal_with_ray(&self, _: &Ray, _: bool) -> Option<RayIntersection> {
-        // XXX: not yet implemented
-        None
+    fn toi_and_normal_with_ray(&self, ray: &Ray, _: bool) -> Option<RayIntersection> {
+        bezier_curve_ray_intersection(self, ray).map(|(inter, _)| inter)
+    }
+
+    #[cfg(dim3)]
+    fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray, _: bool) -> Option<RayIntersection> {
+        bezier_curve_ray_intersection(self, ray).map(|(inter, t)|
+            RayIntersection::new_with_uvs(inter.toi, inter.normal, Some(Vec3::new(t, na::zero(), na::zero())))
+        )
+    }
+}
+
+/// Computes the first intersection between a ray and a bezier curve.
+///
+/// Returns the intersection and the curve parameter of the intersection point.
+pub fn bezier_curve_ray_intersection(c: &BezierCurve, ray: &Ray) -> Option<(RayIntersection, Scalar)> {
+    let mut best = None;
+
+    do_bezier_curve_ray_intersection(c, ray, (na::zero(), na::one()), MAX_DEPTH, &mut best);
+
+    best.map(|(toi, normal, t)| (RayIntersection::new(toi, normal), t))
+}
+
+// `range` is the parametric domain of `c` on the original curve.
+fn do_bezier_curve_ray_intersection(c:         &BezierCurve,
+                                    ray:       &Ray,
+                                    range:     (Scalar, Scalar),
+                                    max_depth: uint,
+                                    best:      &mut Option<(Scalar, Vect, Scalar)>) {
+    // Bounding volume culling.
+    let bs = c.bounding_sphere(&na::one());
+
+    match ray::ball_toi_with_ray(bs.center().clone(), bs.radius(), ray, true).val1() {
+        None      => return,
+        Some(toi) => {
+            match *best {
+                Some((ref best_toi, _, _)) if *best_toi < toi => return,
+                _ => { }
+            }
+        }
+    }
+
+    let (t0, t1) = range;
+
+    if max_depth == 0 {
+        match newton(c, ray) {
+            Some((toi, normal, lt)) => {
+                let is_better = match *best {
+                    Some((ref best_toi, _, _)) => toi < *best_toi,
+                    None                       => true
+                };
+
+                if is_better {
+                    *best = Some((toi, normal, t0 + (t1 - t0) * lt));
+                }
+            },
+            None => { }
+        }
     }
+    else {
+        // subdivide
+        let _0_5: Scalar = na::cast(0.5);
+        let tmid          = (t0 + t1) * _0_5;
+        let mut left      = BezierCurve::new_with_degree(c.degree());
+        let mut right     = BezierCurve::new_with_degree(c.degree());
+
+        c.subdivide_at(&_0_5, &mut left, &mut right);
+
+        do_bezier_curve_ray_intersection(&left,  ray, (t0, tmid), max_depth - 1, best);
+        do_bezier_curve_ray_intersection(&right, ray, (tmid, t1), max_depth - 1, best);
+    }
+}
+
+// Solves `c(s) = ray.orig + ray.dir * t` with the Gauss-Newton method, starting from the
+// parametric center of `c`.
+//
+// Returns `None` if the method does not converge to a solution inside of the parametric domain.
+fn newton(c: &BezierCurve, ray: &Ray) -> Option<(Scalar, Vect, Scalar)> {
+    let mut diff = BezierCurve::new_with_degree(c.degree() - 1);
+
+    c.diff(&mut diff);
+
+    let mut cache = BezierCurve::new_evaluation_cache();
+    let center    = c.at(&na::cast(0.5), &mut cache);
+
+    // unknowns: (s, t)
+    let mut x: Vec2<Scalar> = Vec2::new(na::cast(0.5), na::dot(&(center - ray.orig), &ray.dir));
+
+    for _ in range(0, NITER) {
+        let f  = c.at(&x.x, &mut cache) - (ray.orig + ray.dir * x.y);
+        let ds = diff.at(&x.x, &mut cache);
+        let dt = -ray.dir;
+
+        // Normal equations: the jacobian `J = [ ds, dt ]` is not square if the dimension is not 2.
+        let mut inv_jtj = Mat2::new(
+            na::dot(&ds, &ds), na::dot(&ds, &dt),
+            na::dot(&dt, &ds), na::dot(&dt, &dt)
+        );
+        let jtf = Vec2::new(na::dot(&ds, &f), na::dot(&dt, &f));
+
+        if !inv_jtj.inv() {
+            return None;
+        }
+
+        x = x - inv_jtj * jtf;
+    }
+
+    let eps: Scalar = na::cast(1.0e-5);
+    let _1:  Scalar = na::one();
+
+    if x.x < -eps || x.x > _1 + eps || x.y < na::zero() {
+        return None;
+    }
+
+    let pt = c.at(&x.x, &mut cache);
+
+    if na::sqnorm(&(pt - (ray.orig + ray.dir * x.y))) > eps * eps {
+        // this is not an intersection.
+        return None;
+    }
+
+    // The normal is the component of the ray direction orthogonal to the tangent.
+    let mut tangent = diff.at(&x.x, &mut cache);
+
+    if tangent.normalize().is_zero() {
+        return None;
+    }
+
+    let mut normal = -ray.dir;
+
+    normal = normal - tangent * na::dot(&normal, &tangent);
+
+    if normal.normalize().is_zero() {
+        return None;
+    }
+
+    Some((x.y, normal, x.x))
 }
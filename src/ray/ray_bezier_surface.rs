@@ -1,9 +1,257 @@
+use std::num::Zero;
+use nalgebra::na::{Vec3, Mat3, Inv, Norm};
+use nalgebra::na;
 use ray::{Ray, RayCast, RayIntersection};
+use ray;
+use bounding_volume::HasBoundingSphere;
 use geom::BezierSurface;
+use math::{Scalar, Vect};
+
+// Subdivision depth of the surface before the Newton refinement.
+static MAX_DEPTH: uint = 10;
+// Number of Newton iterations.
+static NITER:     uint = 10;
 
 impl RayCast for BezierSurface {
-    fn toi_and_normal_with_ray(&self, _: &Ray, _: bool) -> Option<RayIntersection> {
-        // XXX: not yet implemented
-        None
+    fn toi_and_normal_with_ray(&self, ray: &Ray, _: bool) -> Option<RayIntersection> {
+        surface_ray_intersection(self, ray).map(|(toi, normal, _, _)| RayIntersection::new(toi, normal))
+    }
+
+    #[cfg(dim3)]
+    fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray, _: bool) -> Option<RayIntersection> {
+        surface_ray_intersection(self, ray).map(|(toi, normal, u, v)|
+            RayIntersection::new_with_uvs(toi, normal, Some(Vec3::new(u, v, na::zero())))
+        )
+    }
+}
+
+// Computes the first intersection between a ray and a bezier surface.
+//
+// Returns the time of impact, the normal, and the `(u, v)` parameters of the intersection point.
+fn surface_ray_intersection(b: &BezierSurface, ray: &Ray) -> Option<(Scalar, Vect, Scalar, Scalar)> {
+    let mut best = None;
+    let scale    = b.bounding_sphere(&na::one()).radius();
+
+    do_surface_ray_intersection(b, ray, &scale, (na::zero(), na::one()), (na::zero(), na::one()),
+                                MAX_DEPTH, &mut best);
+
+    best
+}
+
+// `urange` and `vrange` are the parametric domain of `b` on the original surface, and `scale` is
+// the size of the original surface.
+fn do_surface_ray_intersection(b:         &BezierSurface,
+                               ray:       &Ray,
+                               scale:     &Scalar,
+                               urange:    (Scalar, Scalar),
+                               vrange:    (Scalar, Scalar),
+                               max_depth: uint,
+                               best:      &mut Option<(Scalar, Vect, Scalar, Scalar)>) {
+    // Bounding volume culling.
+    let bs = b.bounding_sphere(&na::one());
+
+    match ray::ball_toi_with_ray(bs.center().clone(), bs.radius(), ray, true).val1() {
+        None      => return,
+        Some(toi) => {
+            match *best {
+                Some((ref best_toi, _, _, _)) if *best_toi < toi => return,
+                _ => { }
+            }
+        }
+    }
+
+    if max_depth == 0 {
+        match newton(b, ray, scale) {
+            Some((toi, normal, lu, lv)) => {
+                let is_better = match *best {
+                    Some((ref best_toi, _, _, _)) => toi < *best_toi,
+                    None                          => true
+                };
+
+                if is_better {
+                    let (u0, u1) = urange;
+                    let (v0, v1) = vrange;
+
+                    *best = Some((toi, normal, u0 + (u1 - u0) * lu, v0 + (v1 - v0) * lv));
+                }
+            },
+            None => { }
+        }
+    }
+    else {
+        // subdivide
+        let _0_5: Scalar = na::cast(0.5);
+        let mut left  = BezierSurface::new_with_degrees(b.degree_u(), b.degree_v());
+        let mut right = BezierSurface::new_with_degrees(b.degree_u(), b.degree_v());
+
+        if max_depth % 2 == 0 {
+            let (u0, u1) = urange;
+            let umid     = (u0 + u1) * _0_5;
+
+            b.subdivide_u(&_0_5, &mut left, &mut right);
+
+            do_surface_ray_intersection(&left,  ray, scale, (u0, umid), vrange, max_depth - 1, best);
+            do_surface_ray_intersection(&right, ray, scale, (umid, u1), vrange, max_depth - 1, best);
+        }
+        else {
+            let (v0, v1) = vrange;
+            let vmid     = (v0 + v1) * _0_5;
+
+            b.subdivide_v(&_0_5, &mut left, &mut right);
+
+            do_surface_ray_intersection(&left,  ray, scale, urange, (v0, vmid), max_depth - 1, best);
+            do_surface_ray_intersection(&right, ray, scale, urange, (vmid, v1), max_depth - 1, best);
+        }
+    }
+}
+
+// Solves `b(u, v) = ray.orig + ray.dir * t` with the Gauss-Newton method, starting from the
+// parametric center of `b`.
+//
+// Returns `None` if the method does not converge to a solution inside of the parametric domain.
+// The solution is accepted if its distance to the ray is small wrt. `scale`.
+fn newton(b: &BezierSurface, ray: &Ray, scale: &Scalar) -> Option<(Scalar, Vect, Scalar, Scalar)> {
+    let mut diff_u = BezierSurface::new_with_degrees(b.degree_u() - 1, b.degree_v());
+    let mut diff_v = BezierSurface::new_with_degrees(b.degree_u(),     b.degree_v() - 1);
+
+    b.diff_u(&mut diff_u);
+    b.diff_v(&mut diff_v);
+
+    let sqlen = na::sqnorm(&ray.dir);
+
+    if sqlen.is_zero() {
+        return None;
+    }
+
+    let mut cache = BezierSurface::new_evaluation_cache();
+    let center    = b.at(&na::cast(0.5), &na::cast(0.5), &mut cache);
+
+    // unknowns: (u, v, t). The ray direction is not necessarily normalized.
+    let t0 = na::dot(&(center - ray.orig), &ray.dir) / sqlen;
+    let mut x: Vec3<Scalar> = Vec3::new(na::cast(0.5), na::cast(0.5), t0);
+
+    for _ in range(0, NITER) {
+        let f  = b.at(&x.x, &x.y, &mut cache) - (ray.orig + ray.dir * x.z);
+        let du = diff_u.at(&x.x, &x.y, &mut cache);
+        let dv = diff_v.at(&x.x, &x.y, &mut cache);
+        let dt = -ray.dir;
+
+        // Normal equations: the jacobian `J = [ du, dv, dt ]` is not square if the dimension is
+        // not 3.
+        let mut inv_jtj = Mat3::new(
+            na::dot(&du, &du), na::dot(&du, &dv), na::dot(&du, &dt),
+            na::dot(&dv, &du), na::dot(&dv, &dv), na::dot(&dv, &dt),
+            na::dot(&dt, &du), na::dot(&dt, &dv), na::dot(&dt, &dt)
+        );
+        let jtf = Vec3::new(na::dot(&du, &f), na::dot(&dv, &f), na::dot(&dt, &f));
+
+        if !inv_jtj.inv() {
+            return None;
+        }
+
+        x = x - inv_jtj * jtf;
+    }
+
+    let eps: Scalar = na::cast(1.0e-5);
+    let _1:  Scalar = na::one();
+
+    if x.x < -eps || x.y < -eps || x.x > _1 + eps || x.y > _1 + eps || x.z < na::zero() {
+        return None;
+    }
+
+    let pt  = b.at(&x.x, &x.y, &mut cache);
+    let tol = eps * *scale;
+
+    if na::sqnorm(&(pt - (ray.orig + ray.dir * x.z))) > tol * tol {
+        // this is not an intersection.
+        return None;
+    }
+
+    // The normal is the component of the ray direction orthogonal to the tangent plane.
+    let mut du = diff_u.at(&x.x, &x.y, &mut cache);
+    let mut dv = diff_v.at(&x.x, &x.y, &mut cache);
+
+    if du.normalize().is_zero() {
+        return None;
+    }
+
+    dv = dv - du * na::dot(&dv, &du);
+
+    if dv.normalize().is_zero() {
+        return None;
+    }
+
+    let mut normal = -ray.dir;
+
+    normal = normal - du * na::dot(&normal, &du) - dv * na::dot(&normal, &dv);
+
+    if normal.normalize().is_zero() {
+        return None;
+    }
+
+    Some((x.z, normal, x.x, x.y))
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::Vec3;
+    use nalgebra::na;
+    use ray::{Ray, RayCast};
+    use geom::BezierSurface;
+    use test_utils::flat_surface;
+
+    // The surface `z = 4u(1 - u) * 4v(1 - v) / 4` on the square `[0, 2]²`, with `x = 2u` and
+    // `y = 2v`.
+    fn curved_surface() -> BezierSurface {
+        let mut pts = Vec::new();
+
+        for i in range(0u, 3) {
+            for j in range(0u, 3) {
+                let z = if i == 1 && j == 1 { 1.0 } else { 0.0 };
+
+                pts.push(Vec3::new(i as f64, j as f64, z));
+            }
+        }
+
+        BezierSurface::new(pts, 3, 3)
+    }
+
+    #[test]
+    fn test_ray_bezier_surface() {
+        let s   = flat_surface();
+        let ray = Ray::new(Vec3::new(1.0, 1.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let inter = s.toi_and_normal_and_uv_with_ray(&ray, true).expect("The ray should hit.");
+
+        assert!(na::approx_eq_eps(&inter.toi, &5.0, &1.0e-5));
+        assert!(na::approx_eq_eps(&inter.normal, &Vec3::new(0.0, 0.0, 1.0), &1.0e-5));
+        assert!(na::approx_eq_eps(&inter.uvs.unwrap(), &Vec3::new(0.5, 0.5, 0.0), &1.0e-5));
+
+        let miss = Ray::new(Vec3::new(3.0, 1.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(s.toi_with_ray(&miss, true).is_none());
+    }
+
+    #[test]
+    fn test_ray_curved_bezier_surface() {
+        let s   = curved_surface();
+        // Hits the point of parameters (0.3, 0.7), at the height 0.42 * 0.42. The direction is not
+        // normalized.
+        let ray = Ray::new(Vec3::new(0.6, 1.4, 5.0), Vec3::new(0.0, 0.0, -2.0));
+
+        let inter = s.toi_and_normal_and_uv_with_ray(&ray, true).expect("The ray should hit.");
+
+        assert!(na::approx_eq_eps(&inter.toi, &2.4118, &1.0e-5));
+        assert!(na::approx_eq_eps(&inter.normal, &Vec3::new(-0.16345011, 0.16345011, 0.97291733), &1.0e-5));
+        assert!(na::approx_eq_eps(&inter.uvs.unwrap(), &Vec3::new(0.3, 0.7, 0.0), &1.0e-5));
+
+        // Same point seen from below, with a slanted ray.
+        let orig  = Vec3::new(0.6 - 1.0, 1.4 - 0.5, 0.1764 - 3.0);
+        let ray   = Ray::new(orig, Vec3::new(1.0, 0.5, 3.0));
+        let inter = s.toi_and_normal_and_uv_with_ray(&ray, true).expect("The ray should hit.");
+
+        assert!(na::approx_eq_eps(&inter.toi, &1.0, &1.0e-5));
+        assert!(na::approx_eq_eps(&inter.normal, &-Vec3::new(-0.16345011, 0.16345011, 0.97291733), &1.0e-5));
+        assert!(na::approx_eq_eps(&inter.uvs.unwrap(), &Vec3::new(0.3, 0.7, 0.0), &1.0e-5));
     }
 }
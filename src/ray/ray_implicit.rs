@@ -3,7 +3,7 @@ use nalgebra::na::{Identity, Translation, Indexable, Norm};
 use nalgebra::na;
 use narrow::algorithm::simplex::Simplex;
 use narrow::algorithm::johnson_simplex::JohnsonSimplex;
-use geom::{Cylinder, Cone, Capsule, MinkowskiSum, Convex, Segment};
+use geom::{Cylinder, Cone, Capsule, MinkowskiSum, Convex, Segment, Tetrahedron};
 use implicit::Implicit;
 use ray::{Ray, RayCast, RayIntersection};
 use ray;
@@ -163,6 +163,12 @@ impl RayCast for Segment {
     }
 }
 
+impl RayCast for Tetrahedron {
+    fn toi_and_normal_with_ray(&self, ray: &Ray, solid: bool) -> Option<RayIntersection> {
+        implicit_toi_and_normal_with_ray(&Identity::new(), self, &mut JohnsonSimplex::<Vect>::new_w_tls(), ray, solid)
+    }
+}
+
 impl<'a, G1: Implicit<Vect, Matrix>, G2: Implicit<Vect, Matrix>>
 RayCast for MinkowskiSum<'a, G1, G2> {
     fn toi_and_normal_with_ray(&self, ray: &Ray, solid: bool) -> Option<RayIntersection> {
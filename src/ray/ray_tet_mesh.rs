@@ -0,0 +1,21 @@
+use ray::{Ray, RayCast, RayIntersection};
+use geom::TetMesh;
+use math::Scalar;
+
+// Each tetrahedron is solid: a ray starting inside of the mesh hits it at time 0.
+impl RayCast for TetMesh {
+    fn toi_with_ray(&self, ray: &Ray, _: bool) -> Option<Scalar> {
+        self.bvt().cast_ray(
+                ray,
+                &mut |b, r| self.element_at(*b).toi_with_ray(r, true).map(|t| (t.clone(), t))
+            ).map(|(_, res, _)| res)
+    }
+
+    fn toi_and_normal_with_ray(&self, ray: &Ray, _: bool) -> Option<RayIntersection> {
+        self.bvt().cast_ray(
+            ray,
+            &mut |b, r| self.element_at(*b).toi_and_normal_with_ray(r, true).map(
+                |inter| (inter.toi.clone(), inter))).map(
+                    |(_, res, _)| res)
+    }
+}
@@ -0,0 +1,59 @@
+//! Fixtures shared by the tests of several modules.
+
+use nalgebra::na::{Vec3, Iso3};
+use nalgebra::na;
+use geom::{AnnotatedPoint, Geom, Ball, Compound, BezierSurface};
+use implicit::{Implicit, PreferedSamplingDirections};
+use contact::Contact;
+use narrow::algorithm::johnson_simplex::JohnsonSimplex;
+use narrow::algorithm::gjk::Projection;
+use narrow::collide;
+use math::{Vect, Matrix};
+
+/// A transformation without rotation, with the translation `(x, y, z)`.
+pub fn translation(x: f64, y: f64, z: f64) -> Iso3<f64> {
+    na::append_translation(&na::one::<Iso3<f64>>(), &Vec3::new(x, y, z))
+}
+
+/// Two unit balls centered at `(-2, 0, 0)` and `(2, 0, 0)`.
+pub fn two_balls() -> Compound {
+    Compound::new(vec!(
+        (translation(-2.0, 0.0, 0.0), box Ball::new(1.0f64) as Box<Geom:Send>),
+        (translation(2.0, 0.0, 0.0),  box Ball::new(1.0f64) as Box<Geom:Send>)
+    ))
+}
+
+/// A flat bezier surface covering the square `[0, 2] x [0, 2]` of the plane `z = 0`.
+pub fn flat_surface() -> BezierSurface {
+    let mut pts = Vec::new();
+
+    for i in range(0u, 3) {
+        for j in range(0u, 3) {
+            pts.push(Vec3::new(i as f64, j as f64, 0.0));
+        }
+    }
+
+    BezierSurface::new(pts, 3, 3)
+}
+
+/// Checks that `contact` has the depth and the normal of the contact computed by GJK between two
+/// geometries, or that there is no contact if GJK does not find any.
+pub fn assert_same_contact_as_gjk<G1: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>,
+                                  G2: Implicit<Vect, Matrix> + PreferedSamplingDirections<Vect, Matrix>>(
+                                  m1:      &Matrix,
+                                  g1:      &G1,
+                                  m2:      &Matrix,
+                                  g2:      &G2,
+                                  contact: Option<Contact>) {
+    let mut simplex: JohnsonSimplex<AnnotatedPoint> = JohnsonSimplex::new_w_tls();
+
+    match collide::implicit_implicit(m1, g1, m2, g2, &na::zero(), &mut simplex, None) {
+        Projection(gjk) => {
+            let contact = contact.expect("GJK found a contact.");
+
+            assert!(na::approx_eq_eps(&contact.depth, &gjk.depth, &1.0e-5));
+            assert!(na::approx_eq_eps(&contact.normal, &gjk.normal, &1.0e-5));
+        },
+        _ => assert!(contact.is_none())
+    }
+}
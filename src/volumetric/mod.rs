@@ -10,6 +10,8 @@ pub use volumetric::volumetric_cone::cone_volume;
 pub use volumetric::volumetric_capsule::capsule_volume;
 #[cfg(dim3)]
 pub use volumetric::volumetric_cylinder::cylinder_volume;
+#[cfg(dim3)]
+pub use volumetric::volumetric_tetrahedron::tetrahedron_volume;
 
 #[cfg(dim2)]
 pub use volumetric::volumetric_cone::cone_volume;
@@ -28,7 +30,10 @@ mod volumetric_compound;
 mod volumetric_convex;
 mod volumetric_plane;
 mod volumetric_mesh;
+#[cfg(dim3)]
+mod volumetric_tet_mesh;
 mod volumetric_triangle;
+mod volumetric_tetrahedron;
 mod volumetric_segment;
 mod volumetric_bezier_surface;
 mod volumetric_bezier_curve;
@@ -0,0 +1,35 @@
+use nalgebra::na;
+use geom::TetMesh;
+use volumetric::{Volumetric, InertiaTensor};
+use math::{Scalar, Vect, AngularInertia};
+
+impl Volumetric for TetMesh {
+    fn mass_properties(&self, density: &Scalar) -> (Scalar, Vect, AngularInertia) {
+        let mut mtot: Scalar = na::zero();
+        let mut ctot: Vect   = na::zero();
+        let mut parts        = Vec::with_capacity(self.indices().len() / 4);
+
+        for i in range(0u, self.indices().len() / 4) {
+            let (mpart, cpart, ipart) = self.element_at(i).mass_properties(density);
+
+            mtot = mtot + mpart;
+            ctot = ctot + cpart * mpart;
+            parts.push((mpart, cpart, ipart));
+        }
+
+        if mtot == na::zero() {
+            return (na::zero(), na::zero(), na::zero());
+        }
+
+        ctot = ctot / mtot;
+
+        // The inertia of each tetrahedron is expressed wrt. its own center of mass.
+        let mut itot: AngularInertia = na::zero();
+
+        for &(ref mpart, ref cpart, ref ipart) in parts.iter() {
+            itot = itot + ipart.to_relative_wrt_point(mpart, &(*cpart - ctot));
+        }
+
+        (mtot, ctot, itot)
+    }
+}
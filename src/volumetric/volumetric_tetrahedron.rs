@@ -0,0 +1,114 @@
+use nalgebra::na;
+use geom::Tetrahedron;
+use volumetric::Volumetric;
+use math::{Scalar, Vect, AngularInertia};
+
+#[cfg(dim3)]
+use nalgebra::na::Indexable;
+
+/// Computes the volume of a tetrahedron.
+#[cfg(dim3)]
+#[inline]
+pub fn tetrahedron_volume(a: &Vect, b: &Vect, c: &Vect, d: &Vect) -> Scalar {
+    na::dot(&(*b - *a), &na::cross(&(*c - *a), &(*d - *a))).abs() / na::cast(6.0)
+}
+
+#[cfg(dim3)]
+impl Volumetric for Tetrahedron {
+    fn mass_properties(&self, density: &Scalar) -> (Scalar, Vect, AngularInertia) {
+        // NOTE: the margin is ignored.
+        let mass   = tetrahedron_volume(self.a(), self.b(), self.c(), self.d()) * *density;
+        let center = (*self.a() + *self.b() + *self.c() + *self.d()) / na::cast::<f32, Scalar>(4.0);
+
+        // The covariance of a tetrahedron wrt. its center of mass is `mass / 20 * Σ pi * pi^t`
+        // where the `pi` are its vertices relative to its center of mass.
+        let pts = [ *self.a() - center, *self.b() - center, *self.c() - center, *self.d() - center ];
+        let mut cov: AngularInertia = na::zero();
+
+        for p in pts.iter() {
+            for i in range(0u, 3) {
+                for j in range(0u, 3) {
+                    cov.set((i, j), cov.at((i, j)) + p.at(i) * p.at(j));
+                }
+            }
+        }
+
+        let _i20: Scalar = na::cast(1.0 / 20.0);
+        let w     = mass * _i20;
+        let trace = cov.at((0, 0)) + cov.at((1, 1)) + cov.at((2, 2));
+
+        let mut res: AngularInertia = na::zero();
+
+        for i in range(0u, 3) {
+            for j in range(0u, 3) {
+                let diag = if i == j { trace } else { na::zero() };
+                res.set((i, j), w * (diag - cov.at((i, j))));
+            }
+        }
+
+        (mass, center, res)
+    }
+}
+
+#[cfg(not(dim3))]
+impl Volumetric for Tetrahedron {
+    #[inline]
+    fn mass_properties(&self, _: &Scalar) -> (Scalar, Vect, AngularInertia) {
+        // In 4d, a tetrahedron is a boundary element: it has no volume.
+        assert!(na::dim::<Vect>() > 2, "A tetrahedron cannot be created in 2d.");
+        (na::zero(), na::zero(), na::zero())
+    }
+}
+
+#[cfg(dim3, f64, test)]
+mod test {
+    use nalgebra::na::{Vec3, Indexable};
+    use nalgebra::na;
+    use geom::Tetrahedron;
+    use volumetric::Volumetric;
+
+    #[test]
+    fn test_tetrahedron_mass_properties() {
+        let t = Tetrahedron::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0),
+                                 Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let (mass, center, inertia) = t.mass_properties(&6.0);
+
+        assert!(na::approx_eq(&mass, &1.0));
+        assert!(na::approx_eq(&center, &Vec3::new(0.25, 0.25, 0.25)));
+
+        // About the origin, the covariance of the unit corner tetrahedron is `mass / 10` on the
+        // diagonal and `mass / 20` off the diagonal. It is shifted to the center of mass with the
+        // parallel axis theorem: `cov - mass * center * center^t`.
+        let cov_diag = mass / 10.0 - mass * 0.25 * 0.25;
+        let cov_off  = mass / 20.0 - mass * 0.25 * 0.25;
+
+        for i in range(0u, 3) {
+            for j in range(0u, 3) {
+                let expected = if i == j { 2.0 * cov_diag } else { -cov_off };
+
+                assert!(na::approx_eq(&inertia.at((i, j)), &expected));
+            }
+        }
+
+        // i.e. 3 / 40 on the diagonal and 1 / 80 off the diagonal.
+        assert!(na::approx_eq(&inertia.at((0, 0)), &(3.0 / 40.0)));
+        assert!(na::approx_eq(&inertia.at((0, 1)), &(1.0 / 80.0)));
+    }
+
+    #[test]
+    fn test_tetrahedron_mass_properties_translated() {
+        let shift = Vec3::new(1.0, -2.0, 3.0);
+        let t1    = Tetrahedron::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0),
+                                     Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 3.0));
+        let t2    = Tetrahedron::new(*t1.a() + shift, *t1.b() + shift, *t1.c() + shift, *t1.d() + shift);
+
+        let (m1, c1, i1) = t1.mass_properties(&1.0);
+        let (m2, c2, i2) = t2.mass_properties(&1.0);
+
+        // The volume is `2 * 1 * 3 / 6` and the inertia is expressed wrt. the center of mass.
+        assert!(na::approx_eq(&m1, &1.0));
+        assert!(na::approx_eq(&m1, &m2));
+        assert!(na::approx_eq(&(c1 + shift), &c2));
+        assert!(na::approx_eq(&i1, &i2));
+    }
+}